@@ -0,0 +1,48 @@
+package main
+
+import (
+	"fmt"
+	"io/ioutil"
+	"path/filepath"
+	"strings"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/spf13/cobra"
+)
+
+func newAsmCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "asm",
+		Short:   "Assemble a CHIP-8 ROM",
+		Example: "$ ch8 asm -o game.ch8 game.asm",
+		Args:    cobra.ExactArgs(1),
+		RunE: func(cmd *cobra.Command, args []string) error {
+			program, err := ch8.Assemble(args[0])
+			if err != nil {
+				return err
+			}
+
+			// The ROM is named after the source by default
+			output, _ := cmd.Flags().GetString("output")
+			if output == "" {
+				output = strings.TrimSuffix(args[0], filepath.Ext(args[0])) + ".ch8"
+			}
+
+			if err := ioutil.WriteFile(output, program, 0644); err != nil {
+				return err
+			}
+
+			fmt.Println("assembled", len(program), "bytes to", output)
+			return nil
+		},
+	}
+
+	cmd.Flags().StringP(
+		"output",
+		"o",
+		"",
+		"set the path of the ROM (default the source with a .ch8 extension)",
+	)
+
+	return cmd
+}
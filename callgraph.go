@@ -0,0 +1,41 @@
+package main
+
+import (
+	"os"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newCallGraphCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "callgraph",
+		Short:   "Graph the subroutine calls of a CHIP-8 ROM in DOT format",
+		Example: "$ ch8 callgraph roms/games/Pong.ch8 | dot -Tsvg > Pong.svg",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			data, err := chip8.ReadROM(args[0])
+			if err != nil {
+				return err
+			}
+
+			var cov *chip8.Coverage
+			if path, _ := cmd.Flags().GetString("coverage"); path != "" {
+				if cov, err = chip8.LoadCoverage(path); err != nil {
+					return err
+				}
+			}
+
+			return ch8.BuildCallGraph(data).WriteDOT(os.Stdout, cov)
+		},
+	}
+
+	cmd.Flags().String(
+		"coverage",
+		"",
+		"dash the subroutines and calls never executed in a session recorded with --coverage",
+	)
+
+	return cmd
+}
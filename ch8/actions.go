@@ -0,0 +1,191 @@
+package ch8
+
+import (
+	"fmt"
+	"sync/atomic"
+
+	"github.com/hajimehoshi/ebiten/v2"
+)
+
+//=====================================================================
+// Actions
+//=====================================================================
+
+const (
+	// DefaultTurbo is the speed multiplier of the virtual machine
+	// while turbo is on.
+	DefaultTurbo = 4.0
+
+	// speedStep is how much the speed multiplier of the virtual
+	// machine changes when it is made faster or slower.
+	speedStep = 0.25
+)
+
+// action is something the emulator can do at the press of a hotkey.
+type action struct {
+	name        string
+	description string
+	run         func(emu *Emulator)
+}
+
+// actions are the actions of the emulator, in the order they are
+// listed.
+var actions = []action{
+	{"menu", "Open pause menu", (*Emulator).openPauseMenu},
+	{"commands", "Search actions", (*Emulator).openCommandPalette},
+	{"play", "Resume emulation", (*Emulator).play},
+	{"pause", "Pause emulation", (*Emulator).pause},
+	{"reset", "Reset emulation", (*Emulator).reset},
+	{"turbo", "Toggle turbo", (*Emulator).toggleTurbo},
+	{"faster", "Increase speed", (*Emulator).faster},
+	{"slower", "Decrease speed", (*Emulator).slower},
+	{"save-state", "Save state", (*Emulator).requestSaveState},
+	{"load-state", "Load state", (*Emulator).requestLoadState},
+	{"palette", "Cycle palette", (*Emulator).cyclePalette},
+	{"keymap", "Cycle keymap", (*Emulator).cycleKeymap},
+	{"hud", "Toggle HUD", (*Emulator).toggleHUD},
+	{"timing", "Toggle frame timing", (*Emulator).toggleFrameGraph},
+	{"keypad", "Toggle keypad", (*Emulator).toggleKeypad},
+	{"registers", "Toggle registers", (*Emulator).toggleRegisters},
+	{"grid", "Toggle grid", (*Emulator).cycleGrid},
+	{"rotate", "Rotate screen", (*Emulator).rotate},
+	{"rebind", "Rebind keys", (*Emulator).rebindKeys},
+	{"macro", "Record macro", (*Emulator).toggleMacro},
+	{"fullscreen", "Toggle fullscreen", (*Emulator).toggleFullscreen},
+	{"gif", "Record GIF", (*Emulator).toggleGIF},
+	{"video", "Record video", (*Emulator).toggleVideo},
+	{"screenshot", "Take screenshot", (*Emulator).screenshot},
+	{"dump-memory", "Dump memory", (*Emulator).requestMemoryDump},
+	{"bug-report", "Save bug report", (*Emulator).requestBugReport},
+}
+
+// DefaultHotkeys returns the keys bound to the actions of the emulator
+// by default, by action to key name.
+func DefaultHotkeys() map[string]string {
+	return map[string]string{
+		"menu":        "Escape",
+		"play":        "LeftBracket",
+		"pause":       "RightBracket",
+		"reset":       "Backslash",
+		"turbo":       "Tab",
+		"faster":      "Equal",
+		"slower":      "Minus",
+		"save-state":  "F5",
+		"load-state":  "F7",
+		"palette":     "P",
+		"keymap":      "K",
+		"hud":         "F1",
+		"timing":      "T",
+		"keypad":      "F2",
+		"registers":   "F8",
+		"grid":        "F3",
+		"rotate":      "F4",
+		"rebind":      "F6",
+		"macro":       "Home",
+		"fullscreen":  "F11",
+		"gif":         "F9",
+		"video":       "F10",
+		"screenshot":  "F12",
+		"dump-memory": "M",
+		"bug-report":  "End",
+	}
+}
+
+// findAction returns the action with the given name, or nil if there
+// is no such action.
+func findAction(name string) *action {
+	for i := range actions {
+		if actions[i].name == name {
+			return &actions[i]
+		}
+	}
+	return nil
+}
+
+// parseHotkeys binds keys to actions from the default hotkeys and the
+// given bindings of action to key name. An action is unbound if its key
+// name is empty.
+func parseHotkeys(bindings map[string]string) (map[ebiten.Key]*action, error) {
+	names := DefaultHotkeys()
+	for name, key := range bindings {
+		names[name] = key
+	}
+
+	hotkeys := map[ebiten.Key]*action{}
+
+	for name, keyName := range names {
+		a := findAction(name)
+		if a == nil {
+			return nil, fmt.Errorf("unknown action: %s", name)
+		} else if keyName == "" {
+			continue
+		}
+
+		key, err := ParseKey(keyName)
+		if err != nil {
+			return nil, err
+		}
+
+		if other, ok := hotkeys[key]; ok {
+			return nil, fmt.Errorf(
+				"%s is bound to both %s and %s", keyName, other.name, name,
+			)
+		}
+		hotkeys[key] = a
+	}
+
+	return hotkeys, nil
+}
+
+func (emu *Emulator) play() {
+	emu.vmChan <- playEvent
+	emu.paused = false
+}
+
+func (emu *Emulator) pause() {
+	emu.vmChan <- pauseEvent
+	emu.paused = true
+}
+
+func (emu *Emulator) reset() {
+	emu.vmChan <- resetEvent
+}
+
+func (emu *Emulator) requestSaveState() {
+	emu.vmChan <- saveStateEvent
+}
+
+func (emu *Emulator) requestLoadState() {
+	emu.vmChan <- loadStateEvent
+}
+
+func (emu *Emulator) toggleTurbo() {
+	if atomic.LoadInt32(&emu.turbo) == 1 {
+		atomic.StoreInt32(&emu.turbo, 0)
+	} else {
+		atomic.StoreInt32(&emu.turbo, 1)
+	}
+}
+
+func (emu *Emulator) faster() {
+	emu.setSpeed(emu.cfg.Speed + speedStep)
+}
+
+func (emu *Emulator) slower() {
+	if emu.cfg.Speed > speedStep {
+		emu.setSpeed(emu.cfg.Speed - speedStep)
+	}
+}
+
+// setSpeed changes the speed multiplier of the virtual machine, which
+// picks up the change from the configuration once it is told to.
+func (emu *Emulator) setSpeed(speed float64) {
+	emu.cfg.Speed = speed
+	emu.vmChan <- speedEvent
+	emuLog.Infof("speed: %gx", speed)
+}
+
+func (emu *Emulator) toggleFullscreen() {
+	ebiten.SetFullscreen(!ebiten.IsFullscreen())
+	emu.redraw = true
+}
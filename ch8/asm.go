@@ -0,0 +1,585 @@
+package ch8
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Assembler
+//=====================================================================
+
+// maxAssemblyDepth is how deeply includes, macros, and constants may
+// be nested, which stops them from expanding forever.
+const maxAssemblyDepth = 16
+
+// statement is a line of assembly, after includes and macros are
+// expanded.
+type statement struct {
+	pos   string
+	label string
+	op    string
+	args  []string
+}
+
+// sourceLine is a line of an assembly file.
+type sourceLine struct {
+	pos  string
+	text string
+}
+
+// macro is a named block of lines, whose parameters are replaced by
+// the arguments it is used with.
+type macro struct {
+	params []string
+	body   []sourceLine
+}
+
+// assembler assembles a program from assembly files.
+type assembler struct {
+	statements []statement
+	constants  map[string]string
+	labels     map[string]uint
+	macros     map[string]*macro
+
+	// The macro being defined, if any
+	defining     *macro
+	definingName string
+}
+
+// Assemble assembles the program of an assembly file, written with the
+// mnemonics of the disassembler, such as "LD V1, 0x2A", along with:
+//
+//	label:                 a label for the address of the next line
+//	NAME EQU 0x10          a constant
+//	DB 0x18, 0x3C          bytes of data
+//	DW 0x1234              words of data
+//	INCLUDE "sprites.asm"  the lines of another file, relative to this one
+//	MACRO name a, b        a macro with parameters, used as "name V1, 2",
+//	ENDM                   ending at ENDM
+//
+// Values may be numbers, labels, or constants, added to or subtracted
+// from one another, and comments start with a semicolon.
+func Assemble(path string) ([]byte, error) {
+	a := &assembler{
+		constants: map[string]string{},
+		labels:    map[string]uint{},
+		macros:    map[string]*macro{},
+	}
+
+	if err := a.include(path, 0); err != nil {
+		return nil, err
+	}
+	if a.defining != nil {
+		return nil, fmt.Errorf("%s: macro %s is missing ENDM", path, a.definingName)
+	}
+
+	// The size of every statement is known before any value is, so the
+	// addresses of the labels are found first
+	addr := uint(chip8.ProgramStartAddress)
+	for _, st := range a.statements {
+		if st.label != "" {
+			if _, ok := a.labels[st.label]; ok {
+				return nil, fmt.Errorf("%s: label %s is already defined", st.pos, st.label)
+			}
+			a.labels[st.label] = addr
+		}
+		addr += st.size()
+	}
+	if addr > chip8.MemorySize {
+		return nil, fmt.Errorf("%s: the program is too large", path)
+	}
+
+	program := make([]byte, 0, addr-chip8.ProgramStartAddress)
+	for _, st := range a.statements {
+		if st.op == "" {
+			continue
+		}
+
+		b, err := a.encode(st)
+		if err != nil {
+			return nil, fmt.Errorf("%s: %v", st.pos, err)
+		}
+		program = append(program, b...)
+	}
+
+	return program, nil
+}
+
+// include reads the lines of an assembly file.
+func (a *assembler) include(path string, depth int) error {
+	if depth > maxAssemblyDepth {
+		return fmt.Errorf("%s: includes are nested too deeply", path)
+	}
+
+	file, err := os.Open(path)
+	if err != nil {
+		return err
+	}
+	defer file.Close()
+
+	scanner := bufio.NewScanner(file)
+	for n := 1; scanner.Scan(); n++ {
+		pos := fmt.Sprintf("%s:%d", path, n)
+		if err := a.line(sourceLine{pos, scanner.Text()}, filepath.Dir(path), depth); err != nil {
+			return err
+		}
+	}
+
+	return scanner.Err()
+}
+
+// line parses a line of assembly, which may define or use a macro,
+// define a constant, or include another file.
+func (a *assembler) line(src sourceLine, dir string, depth int) error {
+	text := src.text
+	if i := strings.IndexByte(text, ';'); i >= 0 {
+		text = text[:i]
+	}
+	text = strings.TrimSpace(text)
+
+	// Lines of a macro are kept as they are until it ends
+	if a.defining != nil {
+		if strings.EqualFold(text, "ENDM") {
+			a.macros[a.definingName] = a.defining
+			a.defining = nil
+		} else {
+			a.defining.body = append(a.defining.body, src)
+		}
+		return nil
+	}
+
+	var label string
+	if i := strings.IndexByte(text, ':'); i >= 0 && !strings.ContainsAny(text[:i], " \t") {
+		label, text = text[:i], strings.TrimSpace(text[i+1:])
+	}
+
+	fields := strings.Fields(text)
+	if len(fields) == 0 {
+		if label != "" {
+			a.statements = append(a.statements, statement{pos: src.pos, label: label})
+		}
+		return nil
+	}
+
+	// Constants are written as NAME EQU value
+	if len(fields) >= 3 && strings.EqualFold(fields[1], "EQU") {
+		if _, ok := a.constants[fields[0]]; ok {
+			return fmt.Errorf("%s: constant %s is already defined", src.pos, fields[0])
+		}
+		a.constants[fields[0]] = strings.Join(fields[2:], " ")
+		return nil
+	}
+
+	op := strings.ToUpper(fields[0])
+	rest := strings.TrimSpace(text[len(fields[0]):])
+
+	var args []string
+	if rest != "" {
+		for _, arg := range strings.Split(rest, ",") {
+			args = append(args, strings.TrimSpace(arg))
+		}
+	}
+
+	switch op {
+	case "INCLUDE":
+		if len(args) != 1 {
+			return fmt.Errorf("%s: INCLUDE takes the path of a file", src.pos)
+		}
+		path := strings.Trim(args[0], `"`)
+		if !filepath.IsAbs(path) {
+			path = filepath.Join(dir, path)
+		}
+		return a.include(path, depth+1)
+
+	case "MACRO":
+		if len(fields) < 2 {
+			return fmt.Errorf("%s: MACRO takes a name", src.pos)
+		}
+		name := strings.ToUpper(fields[1])
+		m := &macro{}
+		if params := strings.TrimSpace(rest[len(fields[1]):]); params != "" {
+			for _, p := range strings.Split(params, ",") {
+				if p = strings.TrimSpace(p); p == "" {
+					return fmt.Errorf("%s: macro %s has an empty parameter", src.pos, name)
+				}
+				m.params = append(m.params, p)
+			}
+		}
+		a.defining, a.definingName = m, name
+		return nil
+
+	case "ENDM":
+		return fmt.Errorf("%s: ENDM without MACRO", src.pos)
+	}
+
+	if m, ok := a.macros[op]; ok {
+		if label != "" {
+			a.statements = append(a.statements, statement{pos: src.pos, label: label})
+		}
+		return a.expand(m, op, args, src.pos, dir, depth)
+	}
+
+	a.statements = append(a.statements, statement{src.pos, label, op, args})
+	return nil
+}
+
+// expand replaces the parameters of a macro with the given arguments
+// and parses its lines.
+func (a *assembler) expand(m *macro, name string, args []string, pos, dir string, depth int) error {
+	if depth > maxAssemblyDepth {
+		return fmt.Errorf("%s: macros are nested too deeply", pos)
+	}
+	if len(args) != len(m.params) {
+		return fmt.Errorf("%s: macro %s takes %d arguments", pos, name, len(m.params))
+	}
+
+	for _, src := range m.body {
+		text := src.text
+		for i, p := range m.params {
+			text = replaceWord(text, p, args[i])
+		}
+
+		line := sourceLine{fmt.Sprintf("%s (%s from %s)", src.pos, name, pos), text}
+		if err := a.line(line, dir, depth+1); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// replaceWord replaces the whole words of s which equal old.
+func replaceWord(s, old, repl string) string {
+	isWord := func(c byte) bool {
+		return c == '_' || (c >= '0' && c <= '9') || (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z')
+	}
+
+	var b strings.Builder
+	for i := 0; i < len(s); {
+		if strings.HasPrefix(s[i:], old) &&
+			(i == 0 || !isWord(s[i-1])) &&
+			(i+len(old) == len(s) || !isWord(s[i+len(old)])) {
+			b.WriteString(repl)
+			i += len(old)
+			continue
+		}
+		b.WriteByte(s[i])
+		i++
+	}
+	return b.String()
+}
+
+// size returns the number of bytes the statement assembles into.
+func (st statement) size() uint {
+	switch st.op {
+	case "":
+		return 0
+	case "DB":
+		return uint(len(st.args))
+	case "DW":
+		return 2 * uint(len(st.args))
+	}
+	return 2
+}
+
+// value evaluates a value: numbers, labels, and constants, added to or
+// subtracted from one another.
+func (a *assembler) value(expr string, depth int) (int, error) {
+	if depth > maxAssemblyDepth {
+		return 0, fmt.Errorf("constants are nested too deeply: %s", expr)
+	}
+
+	expr = strings.ReplaceAll(expr, " ", "")
+	if expr == "" {
+		return 0, fmt.Errorf("missing value")
+	}
+
+	total, sign := 0, 1
+	for len(expr) > 0 {
+		switch expr[0] {
+		case '+':
+			expr = expr[1:]
+			continue
+		case '-':
+			sign = -sign
+			expr = expr[1:]
+			continue
+		}
+
+		end := strings.IndexAny(expr, "+-")
+		if end < 0 {
+			end = len(expr)
+		}
+		term := expr[:end]
+		expr = expr[end:]
+
+		v, err := a.term(term, depth)
+		if err != nil {
+			return 0, err
+		}
+		total += sign * v
+		sign = 1
+	}
+
+	return total, nil
+}
+
+// term evaluates a number, label, or constant.
+func (a *assembler) term(term string, depth int) (int, error) {
+	if n, err := strconv.ParseInt(term, 0, 32); err == nil {
+		return int(n), nil
+	}
+	if addr, ok := a.labels[term]; ok {
+		return int(addr), nil
+	}
+	if expr, ok := a.constants[term]; ok {
+		return a.value(expr, depth+1)
+	}
+	return 0, fmt.Errorf("unknown value: %s", term)
+}
+
+// bounded evaluates a value which must lie within [min, max].
+func (a *assembler) bounded(expr string, min, max int) (uint, error) {
+	v, err := a.value(expr, 0)
+	if err != nil {
+		return 0, err
+	}
+	if v < min || v > max {
+		return 0, fmt.Errorf("value out of range: %s", expr)
+	}
+	return uint(v), nil
+}
+
+// register parses a register, such as V1.
+func register(arg string) (uint, bool) {
+	if len(arg) != 2 || (arg[0] != 'V' && arg[0] != 'v') {
+		return 0, false
+	}
+	v, err := strconv.ParseUint(arg[1:], 16, 8)
+	return uint(v), err == nil
+}
+
+// encode assembles a statement into bytes.
+func (a *assembler) encode(st statement) ([]byte, error) {
+	args := st.args
+
+	switch st.op {
+	case "DB":
+		b := make([]byte, len(args))
+		for i, arg := range args {
+			v, err := a.bounded(arg, -0x80, 0xff)
+			if err != nil {
+				return nil, err
+			}
+			b[i] = byte(v)
+		}
+		return b, nil
+	case "DW":
+		b := make([]byte, 0, 2*len(args))
+		for _, arg := range args {
+			v, err := a.bounded(arg, 0, 0xffff)
+			if err != nil {
+				return nil, err
+			}
+			b = append(b, byte(v>>8), byte(v))
+		}
+		return b, nil
+	}
+
+	opcode, err := a.instruction(st.op, args)
+	if err != nil {
+		return nil, err
+	}
+	return []byte{byte(opcode >> 8), byte(opcode)}, nil
+}
+
+// mnemonics are the mnemonics of the instructions.
+var mnemonics = map[string]bool{
+	"CLS": true, "RET": true, "EXIT": true, "LOW": true, "HIGH": true,
+	"JP": true, "CALL": true, "SE": true, "SNE": true, "LD": true,
+	"ADD": true, "OR": true, "AND": true, "XOR": true, "SUB": true,
+	"SHR": true, "SUBN": true, "SHL": true, "RND": true, "DRW": true,
+	"SKP": true, "SKNP": true, "SCD": true, "SCR": true, "SCL": true,
+}
+
+// instruction assembles an instruction into its opcode.
+func (a *assembler) instruction(op string, args []string) (uint, error) {
+	if !mnemonics[op] {
+		return 0, fmt.Errorf("unknown instruction: %s", op)
+	}
+
+	arg := func(i int) string {
+		if i < len(args) {
+			return strings.ToUpper(args[i])
+		}
+		return ""
+	}
+	addr := func(i int) (uint, error) {
+		return a.bounded(args[i], 0, 0xfff)
+	}
+	kk := func(i int) (uint, error) {
+		v, err := a.bounded(args[i], -0x80, 0xff)
+		return v & 0xff, err
+	}
+
+	x, xok := register(arg(0))
+	y, yok := register(arg(1))
+	invalid := fmt.Errorf("invalid operands: %s %s", op, strings.Join(args, ", "))
+
+	switch {
+	case len(args) == 0:
+		switch op {
+		case "CLS":
+			return 0x00e0, nil
+		case "RET":
+			return 0x00ee, nil
+		case "EXIT":
+			return 0x00fd, nil
+		case "LOW":
+			return 0x00fe, nil
+		case "HIGH":
+			return 0x00ff, nil
+		case "SCR":
+			return 0x00fb, nil
+		case "SCL":
+			return 0x00fc, nil
+		}
+
+	case len(args) == 1:
+		switch op {
+		case "JP":
+			nnn, err := addr(0)
+			return 0x1000 | nnn, err
+		case "CALL":
+			nnn, err := addr(0)
+			return 0x2000 | nnn, err
+		case "SCD":
+			n, err := a.bounded(args[0], 0, 0xf)
+			return 0x00c0 | n, err
+		}
+		if !xok {
+			return 0, invalid
+		}
+		switch op {
+		case "SHR":
+			return 0x8006 | x<<8 | x<<4, nil
+		case "SHL":
+			return 0x800e | x<<8 | x<<4, nil
+		case "SKP":
+			return 0xe09e | x<<8, nil
+		case "SKNP":
+			return 0xe0a1 | x<<8, nil
+		}
+
+	case len(args) == 2:
+		switch {
+		case op == "JP" && arg(0) == "V0":
+			nnn, err := addr(1)
+			return 0xb000 | nnn, err
+		case op == "LD" && arg(0) == "I":
+			nnn, err := addr(1)
+			return 0xa000 | nnn, err
+		case op == "ADD" && arg(0) == "I" && yok:
+			return 0xf01e | y<<8, nil
+		case op == "LD" && yok:
+			switch arg(0) {
+			case "DT":
+				return 0xf015 | y<<8, nil
+			case "ST":
+				return 0xf018 | y<<8, nil
+			case "F":
+				return 0xf029 | y<<8, nil
+			case "HF":
+				return 0xf030 | y<<8, nil
+			case "R":
+				if y >= chip8.NumberOfFlags {
+					return 0, invalid
+				}
+				return 0xf075 | y<<8, nil
+			case "B":
+				return 0xf033 | y<<8, nil
+			case "[I]":
+				return 0xf055 | y<<8, nil
+			}
+		}
+		if !xok {
+			return 0, invalid
+		}
+
+		// Instructions of two registers
+		if yok {
+			switch op {
+			case "SE":
+				return 0x5000 | x<<8 | y<<4, nil
+			case "SNE":
+				return 0x9000 | x<<8 | y<<4, nil
+			case "LD":
+				return 0x8000 | x<<8 | y<<4, nil
+			case "OR":
+				return 0x8001 | x<<8 | y<<4, nil
+			case "AND":
+				return 0x8002 | x<<8 | y<<4, nil
+			case "XOR":
+				return 0x8003 | x<<8 | y<<4, nil
+			case "ADD":
+				return 0x8004 | x<<8 | y<<4, nil
+			case "SUB":
+				return 0x8005 | x<<8 | y<<4, nil
+			case "SHR":
+				return 0x8006 | x<<8 | y<<4, nil
+			case "SUBN":
+				return 0x8007 | x<<8 | y<<4, nil
+			case "SHL":
+				return 0x800e | x<<8 | y<<4, nil
+			}
+			return 0, invalid
+		}
+
+		if op == "LD" {
+			switch arg(1) {
+			case "DT":
+				return 0xf007 | x<<8, nil
+			case "K":
+				return 0xf00a | x<<8, nil
+			case "[I]":
+				return 0xf065 | x<<8, nil
+			case "R":
+				if x >= chip8.NumberOfFlags {
+					return 0, invalid
+				}
+				return 0xf085 | x<<8, nil
+			}
+		}
+
+		// Instructions of a register and a byte
+		var base uint
+		switch op {
+		case "SE":
+			base = 0x3000
+		case "SNE":
+			base = 0x4000
+		case "LD":
+			base = 0x6000
+		case "ADD":
+			base = 0x7000
+		case "RND":
+			base = 0xc000
+		default:
+			return 0, invalid
+		}
+		v, err := kk(1)
+		return base | x<<8 | v, err
+
+	case len(args) == 3 && op == "DRW" && xok && yok:
+		n, err := a.bounded(args[2], 0, 0xf)
+		return 0xd000 | x<<8 | y<<4 | n, err
+	}
+
+	return 0, invalid
+}
@@ -0,0 +1,163 @@
+package ch8
+
+import (
+	"bytes"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// assembleSource assembles the program of an assembly file with the
+// given lines.
+func assembleSource(t *testing.T, lines ...string) []byte {
+	path := filepath.Join(t.TempDir(), "test.asm")
+	if err := ioutil.WriteFile(path, []byte(strings.Join(lines, "\n")), 0644); err != nil {
+		t.Fatal(err)
+	}
+
+	program, err := Assemble(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	return program
+}
+
+func TestAssembleRoundTrip(t *testing.T) {
+	tests := []struct {
+		source string
+		opcode uint
+	}{
+		{"CLS", 0x00e0},
+		{"RET", 0x00ee},
+		{"SCD 4", 0x00c4},
+		{"SCR", 0x00fb},
+		{"SCL", 0x00fc},
+		{"EXIT", 0x00fd},
+		{"LOW", 0x00fe},
+		{"HIGH", 0x00ff},
+		{"JP 0x2A0", 0x12a0},
+		{"CALL 0x300", 0x2300},
+		{"SE V1, 0x2A", 0x312a},
+		{"SNE V1, 0x2A", 0x412a},
+		{"SE V1, V2", 0x5120},
+		{"LD V1, 0x2A", 0x612a},
+		{"ADD V1, 0x2A", 0x712a},
+		{"LD V1, V2", 0x8120},
+		{"OR V1, V2", 0x8121},
+		{"AND V1, V2", 0x8122},
+		{"XOR V1, V2", 0x8123},
+		{"ADD V1, V2", 0x8124},
+		{"SUB V1, V2", 0x8125},
+		{"SHR V1", 0x8116},
+		{"SUBN V1, V2", 0x8127},
+		{"SHL V1", 0x811e},
+		{"SNE V1, V2", 0x9120},
+		{"LD I, 0x300", 0xa300},
+		{"JP V0, 0x300", 0xb300},
+		{"RND V1, 0x0F", 0xc10f},
+		{"DRW V1, V2, 5", 0xd125},
+		{"DRW V1, V2, 0", 0xd120},
+		{"SKP V1", 0xe19e},
+		{"SKNP V1", 0xe1a1},
+		{"LD V1, DT", 0xf107},
+		{"LD V1, K", 0xf10a},
+		{"LD DT, V1", 0xf115},
+		{"LD ST, V1", 0xf118},
+		{"ADD I, V1", 0xf11e},
+		{"LD F, V1", 0xf129},
+		{"LD HF, V1", 0xf130},
+		{"LD B, V1", 0xf133},
+		{"LD [I], V1", 0xf155},
+		{"LD V1, [I]", 0xf165},
+		{"LD R, V1", 0xf175},
+		{"LD V1, R", 0xf185},
+		{"DW 0x5121", 0x5121},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.source, func(t *testing.T) {
+			program := assembleSource(t, tt.source)
+			want := []byte{byte(tt.opcode >> 8), byte(tt.opcode)}
+			if !bytes.Equal(program, want) {
+				t.Fatalf("Assemble(%q) = % X, want % X", tt.source, program, want)
+			}
+
+			if got := Decode(tt.opcode).String(); got != tt.source {
+				t.Errorf("Decode(%.4X) = %q, want %q", tt.opcode, got, tt.source)
+			}
+		})
+	}
+}
+
+func TestAssembleLabelsAndMacros(t *testing.T) {
+	program := assembleSource(t,
+		"SPRITE EQU 0x300",
+		"MACRO clear reg",
+		"  LD reg, 0",
+		"ENDM",
+		"start:",
+		"  clear V3",
+		"  LD I, SPRITE + 2",
+		"  JP start ; loop forever",
+		"  DB 0x18, -1",
+	)
+
+	want := []byte{0x63, 0x00, 0xa3, 0x02, 0x12, 0x00, 0x18, 0xff}
+	if !bytes.Equal(program, want) {
+		t.Errorf("Assemble() = % X, want % X", program, want)
+	}
+}
+
+func TestAssembleInclude(t *testing.T) {
+	dir := t.TempDir()
+	files := map[string]string{
+		"main.asm":       "INCLUDE \"lib/defs.asm\"\nLD I, sprite\nDRW V0, V1, HEIGHT\nINCLUDE \"lib/sprite.asm\"\n",
+		"lib/defs.asm":   "HEIGHT EQU 2\n",
+		"lib/sprite.asm": "sprite: DB 0x18, 0x3C\n",
+		"loop/a.asm":     "INCLUDE \"b.asm\"\n",
+		"loop/b.asm":     "INCLUDE \"a.asm\"\n",
+	}
+	for name, text := range files {
+		path := filepath.Join(dir, name)
+		if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+			t.Fatal(err)
+		}
+		if err := ioutil.WriteFile(path, []byte(text), 0644); err != nil {
+			t.Fatal(err)
+		}
+	}
+
+	program, err := Assemble(filepath.Join(dir, "main.asm"))
+	if err != nil {
+		t.Fatal(err)
+	}
+	want := []byte{0xa2, 0x04, 0xd0, 0x12, 0x18, 0x3c}
+	if !bytes.Equal(program, want) {
+		t.Errorf("Assemble() = % X, want % X", program, want)
+	}
+
+	if _, err := Assemble(filepath.Join(dir, "loop", "a.asm")); err == nil {
+		t.Error("Assemble() accepted files which include each other")
+	}
+}
+
+func TestDisassembleListing(t *testing.T) {
+	program := []byte{0x61, 0x2a, 0x81, 0x24, 0x12, 0x00, 0xff}
+
+	var buf bytes.Buffer
+	if err := Disassemble(&buf, program, DisasmOptions{Pseudocode: true}); err != nil {
+		t.Fatal(err)
+	}
+
+	want := []string{
+		"0x200  612A  LD V1, 0x2A     ; V1 = 0x2A",
+		"0x202  8124  ADD V1, V2      ; V1 += V2; VF = carry",
+		"0x204  1200  JP 0x200        ; goto 0x200",
+		"0x206  FF00  DW 0xFF00",
+	}
+	if got := buf.String(); got != strings.Join(want, "\n")+"\n" {
+		t.Errorf("Disassemble() =\n%swant\n%s", got, strings.Join(want, "\n"))
+	}
+}
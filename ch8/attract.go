@@ -0,0 +1,129 @@
+package ch8
+
+import (
+	"io/ioutil"
+	"math/rand"
+	"path/filepath"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Attract Mode
+//=====================================================================
+
+const (
+	// attractPressFrames is the longest number of frames a random key
+	// is held in attract mode.
+	attractPressFrames = 20
+
+	// attractIdleFrames is the longest number of frames between random
+	// key presses in attract mode.
+	attractIdleFrames = 30
+)
+
+// attractMode runs the ROMs of a directory one after another for a
+// while each, cycling forever, for kiosks and exhibitions. It is also
+// an input source pressing random keys, so the games play themselves.
+type attractMode struct {
+	roms     []string
+	current  int
+	duration time.Duration
+	started  time.Time
+	demo     bool
+	rng      *rand.Rand
+	key      int
+	frames   int
+}
+
+// newAttractMode lists the ROMs (*.ch8) of a directory, in the order
+// they are run.
+func newAttractMode(dir string, duration time.Duration, input string) (*attractMode, error) {
+	roms, err := chip8.ListROMs(dir)
+	if err != nil {
+		return nil, err
+	}
+
+	return &attractMode{
+		roms:     roms,
+		duration: duration,
+		started:  time.Now(),
+		demo:     input == AttractInputRandom,
+		rng:      rand.New(rand.NewSource(time.Now().UnixNano())),
+	}, nil
+}
+
+// update switches to the next ROM once the current one has run for
+// long enough.
+func (a *attractMode) update(emu *Emulator) {
+	if time.Since(a.started) < a.duration {
+		return
+	}
+	a.started = time.Now()
+
+	// ROMs which can't be read are skipped until the next round
+	for tries := 0; tries < len(a.roms); tries++ {
+		a.current = (a.current + 1) % len(a.roms)
+		path := a.roms[a.current]
+
+		data, err := ioutil.ReadFile(path)
+		if err != nil {
+			emuLog.Error(err)
+			continue
+		}
+
+		emu.switchROM(filepath.Base(path), data)
+		emu.updateTitle()
+		return
+	}
+}
+
+// Press holds a random key for a few frames at a time, with a few idle
+// frames in between.
+func (a *attractMode) Press(keys *[chip8.NumberOfKeys]bool) {
+	if !a.demo {
+		return
+	}
+
+	if a.frames > 0 {
+		a.frames--
+		if a.key >= 0 {
+			keys[a.key] = true
+		}
+		return
+	}
+
+	// Alternate between holding a key and resting
+	if a.key >= 0 {
+		a.key = -1
+		a.frames = 1 + a.rng.Intn(attractIdleFrames)
+	} else {
+		a.key = a.rng.Intn(chip8.NumberOfKeys)
+		a.frames = 1 + a.rng.Intn(attractPressFrames)
+	}
+}
+
+// Attract runs the ROMs (*.ch8) of a directory one after another in
+// attract mode, starting with the first one, for as long as the
+// configuration gives each of them.
+func (emu *Emulator) Attract(dir string) error {
+	a, err := newAttractMode(dir, emu.cfg.Attract, emu.cfg.AttractInput)
+	if err != nil {
+		return err
+	}
+
+	data, err := ioutil.ReadFile(a.roms[0])
+	if err != nil {
+		return err
+	}
+	if err := emu.LoadBytes(data); err != nil {
+		return err
+	}
+	emu.cfg.ROM = filepath.Base(a.roms[0])
+
+	emu.attract = a
+	emu.AddInput(a)
+	emu.updateTitle()
+	return nil
+}
@@ -0,0 +1,364 @@
+package ch8
+
+import (
+	"fmt"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/hajimehoshi/ebiten/v2/audio"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Beeper
+//=====================================================================
+
+// Beeper is the audio backend of the CHIP-8 beeper.
+type Beeper interface {
+	// SetBeeping turns the tone of the beeper on or off. It is called
+	// once every time the timers are updated.
+	SetBeeping(on bool)
+
+	// Close releases the resources held by the beeper.
+	Close() error
+}
+
+// GatedBeeper is a beeper which also follows the sound timer between
+// updates of the timers, so its tone starts and stops at the
+// instruction which changed the timer rather than at the next update.
+type GatedBeeper interface {
+	Beeper
+
+	// SetGate turns the tone of the beeper on or off as soon as the
+	// sound timer starts or stops. It is safe to call from any
+	// goroutine.
+	SetGate(on bool)
+}
+
+// audioBeeper is a beeper which plays its tone through Ebiten's audio
+// player.
+type audioBeeper struct {
+	player *audio.Player
+	tone   *stream
+}
+
+var (
+	audioMutex   sync.Mutex
+	audioContext *audio.Context
+)
+
+// sharedAudioContext returns the audio context of the process, creating
+// it at the given sample rate the first time. Ebiten allows only one
+// audio context per process, so every beeper shares it.
+func sharedAudioContext(sampleRate int) (*audio.Context, error) {
+	audioMutex.Lock()
+	defer audioMutex.Unlock()
+
+	if audioContext == nil {
+		audioContext = audio.NewContext(sampleRate)
+	} else if audioContext.SampleRate() != sampleRate {
+		return nil, fmt.Errorf("the audio device is already open at %d Hz", audioContext.SampleRate())
+	}
+	return audioContext, nil
+}
+
+// NewAudioBeeper creates a beeper which plays sound through the audio
+// device of the system. Beepers may be created any number of times, but
+// all of them must use the same sample rate.
+func NewAudioBeeper(cfg Config) (Beeper, error) {
+	context, err := sharedAudioContext(cfg.SampleRate)
+	if err != nil {
+		return nil, err
+	}
+
+	tone := newStream(chip8.DefaultFrequency, cfg.SampleRate, cfg.AudioBuffer)
+	player, err := audio.NewPlayer(context, tone)
+	if err != nil {
+		return nil, err
+	}
+
+	player.SetVolume(cfg.Volume)
+	player.Play()
+
+	return &audioBeeper{player, tone}, nil
+}
+
+// SetBeeping turns the tone of the beeper on or off.
+func (b *audioBeeper) SetBeeping(on bool) {
+	b.tone.SetGate(on)
+}
+
+// SetGate turns the tone of the beeper on or off.
+func (b *audioBeeper) SetGate(on bool) {
+	b.tone.SetGate(on)
+}
+
+// Close stops the audio player.
+func (b *audioBeeper) Close() error {
+	return b.player.Close()
+}
+
+// lazyBeeper opens the audio device the first time the beeper sounds,
+// so the emulator starts even where there is no audio device, such as
+// in containers. If the device can't be opened, the beeper stays silent
+// and the emulator falls back to the visual bell.
+type lazyBeeper struct {
+	cfg    Config
+	mutex  sync.Mutex
+	beeper Beeper
+	failed int32
+}
+
+func newLazyBeeper(cfg Config) *lazyBeeper {
+	return &lazyBeeper{cfg: cfg}
+}
+
+// open opens the audio device, recovering from the panics of an audio
+// driver which fails to initialize.
+func (b *lazyBeeper) open() (beeper Beeper, err error) {
+	defer func() {
+		if r := recover(); r != nil {
+			err = fmt.Errorf("audio: %v", r)
+		}
+	}()
+	return NewAudioBeeper(b.cfg)
+}
+
+// SetBeeping turns the tone of the beeper on or off, opening the audio
+// device when the tone first turns on.
+func (b *lazyBeeper) SetBeeping(on bool) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	if b.ready(on) {
+		b.beeper.SetBeeping(on)
+	}
+}
+
+// SetGate turns the tone of the beeper on or off as soon as the sound
+// timer changes, opening the audio device when the tone first turns on.
+func (b *lazyBeeper) SetGate(on bool) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	if !b.ready(on) {
+		return
+	}
+	if beeper, ok := b.beeper.(GatedBeeper); ok {
+		beeper.SetGate(on)
+	}
+}
+
+// ready opens the audio device if the tone turns on for the first time,
+// and reports whether the device is open. The mutex must be held.
+func (b *lazyBeeper) ready(on bool) bool {
+	if b.beeper == nil && on && !b.unavailable() {
+		beeper, err := b.open()
+		if err != nil {
+			emuLog.Warn("audio is unavailable, using the visual bell:", err)
+			atomic.StoreInt32(&b.failed, 1)
+			return false
+		}
+		b.beeper = beeper
+	}
+
+	return b.beeper != nil
+}
+
+// unavailable reports whether the audio device failed to open.
+func (b *lazyBeeper) unavailable() bool {
+	return atomic.LoadInt32(&b.failed) == 1
+}
+
+// Close closes the audio device if it was opened.
+func (b *lazyBeeper) Close() error {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	if b.beeper == nil {
+		return nil
+	}
+	return b.beeper.Close()
+}
+
+// BeepEvent is a change in the state of the beeper.
+type BeepEvent struct {
+	// Tick is the number of timer updates that happened before the
+	// change.
+	Tick int
+
+	// On is whether the beeper started or stopped.
+	On bool
+}
+
+// NullBeeper is a silent beeper for tests and headless runs. It
+// records every time the beeper starts or stops.
+type NullBeeper struct {
+	mutex  sync.Mutex
+	ticks  int
+	on     bool
+	events []BeepEvent
+}
+
+// SetBeeping records whether the tone of the beeper changed.
+func (b *NullBeeper) SetBeeping(on bool) {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	if on != b.on {
+		b.events = append(b.events, BeepEvent{b.ticks, on})
+		b.on = on
+	}
+	b.ticks++
+}
+
+// Close does nothing.
+func (b *NullBeeper) Close() error {
+	return nil
+}
+
+// Events returns the recorded starts and stops of the beeper.
+func (b *NullBeeper) Events() []BeepEvent {
+	b.mutex.Lock()
+	defer b.mutex.Unlock()
+
+	return append([]BeepEvent(nil), b.events...)
+}
+
+//=====================================================================
+// Audio
+//=====================================================================
+
+// This struct is adapted from Ebiten's example code:
+// <https://ebiten.org/examples/sinewave.html>
+//
+// Rather than pausing the audio player, the stream is played
+// continuously and the tone is gated on and off. The amount of audio
+// synthesized per read is bounded by the buffer size, which controls
+// how quickly a change of the sound timer is heard.
+//
+// Every change of the gate is timed, and the samples of each read are
+// spread evenly over the time since the last read, so the tone starts
+// and stops at the sample where the sound timer changed, as in
+// chip8.Core.Audio, rather than at the start of a read. The tone plays
+// for at least one update of the timers, so a beep of a single tick is
+// still heard.
+type stream struct {
+	mutex      sync.Mutex
+	changes    []gateChange
+	gate       bool
+	on         bool
+	onAt       time.Time
+	lastRead   time.Time
+	minBeep    time.Duration
+	sampleRate int
+	bufferSize int
+	remaining  []byte
+	oscillator chip8.Oscillator
+}
+
+// gateChange is a change of the gate of the tone, and the time at which
+// it is heard.
+type gateChange struct {
+	at time.Time
+	on bool
+}
+
+// newStream creates a beeper stream which synthesizes at most buffer
+// worth of audio at a time.
+func newStream(frequency, sampleRate int, buffer time.Duration) *stream {
+	size := 4 * int(int64(sampleRate)*int64(buffer)/int64(time.Second))
+	if size < 4 {
+		size = 4
+	}
+
+	return &stream{
+		minBeep:    DefaultHzIO,
+		sampleRate: sampleRate,
+		bufferSize: size,
+		oscillator: chip8.NewOscillator(frequency, sampleRate),
+	}
+}
+
+// SetGate turns the tone of the stream on or off.
+func (s *stream) SetGate(on bool) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if on == s.gate {
+		return
+	}
+	s.gate = on
+
+	at := time.Now()
+	if !on && at.Before(s.onAt.Add(s.minBeep)) {
+		at = s.onAt.Add(s.minBeep)
+	}
+
+	// A beep which was stretched delays the changes after it
+	if n := len(s.changes); n > 0 && at.Before(s.changes[n-1].at) {
+		at = s.changes[n-1].at
+	}
+	if on {
+		s.onAt = at
+	}
+
+	s.changes = append(s.changes, gateChange{at, on})
+}
+
+// Read fills the byte stream with sine wave samples.
+func (s *stream) Read(buf []byte) (int, error) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if len(s.remaining) > 0 {
+		n := copy(buf, s.remaining)
+		s.remaining = s.remaining[n:]
+		return n, nil
+	}
+
+	if len(buf) > s.bufferSize {
+		buf = buf[:s.bufferSize]
+	}
+
+	var origBuf []byte
+	if len(buf)%4 > 0 {
+		origBuf = buf
+		buf = make([]byte, len(origBuf)+4-len(origBuf)%4)
+	}
+
+	n := len(buf) / 4
+	now := time.Now()
+	if s.lastRead.IsZero() {
+		s.lastRead = now.Add(-time.Duration(n) * time.Second / time.Duration(s.sampleRate))
+	}
+	span := now.Sub(s.lastRead)
+
+	for i := 0; i < n; i++ {
+		t := s.lastRead.Add(span * time.Duration(i) / time.Duration(n))
+		for len(s.changes) > 0 && !s.changes[0].at.After(t) {
+			s.on = s.changes[0].on
+			s.changes = s.changes[1:]
+		}
+
+		b := s.oscillator.Next(s.on)
+		buf[4*i] = byte(b)
+		buf[4*i+1] = byte(b >> 8)
+		buf[4*i+2] = byte(b)
+		buf[4*i+3] = byte(b >> 8)
+	}
+	s.lastRead = now
+
+	if origBuf != nil {
+		n := copy(origBuf, buf)
+		s.remaining = buf[n:]
+		return n, nil
+	}
+	return len(buf), nil
+}
+
+// Close closes the bye stream.
+func (s *stream) Close() error {
+	return nil
+}
@@ -0,0 +1,138 @@
+package ch8
+
+import (
+	"archive/zip"
+	"bytes"
+	"crypto/sha1"
+	"encoding/hex"
+	"encoding/json"
+	"image/png"
+	"os"
+	"runtime"
+	"strings"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Bug Reports
+//=====================================================================
+
+// bugReport is the summary of a bug report, which tells which ROM was
+// running and how the emulator was set up.
+type bugReport struct {
+	ROM     string       `json:"rom"`
+	SHA1    string       `json:"sha1"`
+	Machine string       `json:"machine"`
+	Quirks  chip8.Quirks `json:"quirks"`
+	Speed   float64      `json:"speed"`
+	Cycles  uint64       `json:"cycles"`
+	Time    time.Time    `json:"time"`
+	OS      string       `json:"os"`
+	Arch    string       `json:"arch"`
+	Go      string       `json:"go"`
+}
+
+// hashROM returns the SHA-1 hash of a ROM in hexadecimal, which tells
+// apart the many versions of the same game.
+func hashROM(data []byte) string {
+	sum := sha1.Sum(data)
+	return hex.EncodeToString(sum[:])
+}
+
+// saveBugReport packages everything needed to reproduce a problem into
+// a zip file of the crash directory, to attach to an issue: a summary
+// of the ROM and emulator, the configuration, a save state, a
+// screenshot, and the last instructions run. It must be called from
+// the goroutine running the virtual machine.
+func (emu *Emulator) saveBugReport() {
+	dir := emu.cfg.CrashDir
+	if dir == "" {
+		dir = DefaultCrashDir
+	}
+	if err := os.MkdirAll(dir, 0755); err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	path := timestampedPath(dir, emu.cfg.ROM, ".zip")
+	if err := emu.writeBugReport(path); err != nil {
+		emuLog.Error(err)
+		return
+	}
+	emuLog.Info("saved a bug report to", path)
+}
+
+func (emu *Emulator) writeBugReport(path string) error {
+	summary, err := json.MarshalIndent(bugReport{
+		ROM:     emu.cfg.ROM,
+		SHA1:    emu.romHash,
+		Machine: emu.cfg.Machine,
+		Quirks:  emu.vm.Quirks,
+		Speed:   emu.cfg.Speed,
+		Cycles:  emu.vm.Cycles(),
+		Time:    time.Now(),
+		OS:      runtime.GOOS,
+		Arch:    runtime.GOARCH,
+		Go:      runtime.Version(),
+	}, "", "  ")
+	if err != nil {
+		return err
+	}
+
+	config, err := json.MarshalIndent(emu.cfg, "", "  ")
+	if err != nil {
+		return err
+	}
+
+	var state bytes.Buffer
+	if err := chip8.WriteSnapshot(&state, emu.vm.Snapshot()); err != nil {
+		return err
+	}
+
+	var screen bytes.Buffer
+	if err := png.Encode(&screen, RenderImage(emu.vm, emu.palettes[emu.palette], emu.cfg.Scale)); err != nil {
+		return err
+	}
+
+	trace := strings.Join(emu.history.lines(), "\n") + "\n"
+
+	files := []struct {
+		name string
+		data []byte
+	}{
+		{"report.json", summary},
+		{"config.json", config},
+		{"rom.state", state.Bytes()},
+		{"screen.png", screen.Bytes()},
+		{"trace.txt", []byte(trace)},
+	}
+
+	file, err := os.Create(path)
+	if err != nil {
+		return err
+	}
+
+	zw := zip.NewWriter(file)
+	for _, f := range files {
+		w, err := zw.Create(f.name)
+		if err == nil {
+			_, err = w.Write(f.data)
+		}
+		if err != nil {
+			file.Close()
+			return err
+		}
+	}
+
+	if err := zw.Close(); err != nil {
+		file.Close()
+		return err
+	}
+	return file.Close()
+}
+
+func (emu *Emulator) requestBugReport() {
+	emu.vmChan <- bugReportEvent
+}
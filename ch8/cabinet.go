@@ -0,0 +1,105 @@
+package ch8
+
+import (
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Cabinet
+//=====================================================================
+
+// Cabinet runs a CHIP-8 virtual machine inside another Ebiten game,
+// such as on the screen of an arcade cabinet within its scene.
+//
+// The game calls Update once per tick and draws the image of the
+// display wherever it likes. Keys are taken from the input sources of
+// the cabinet, and the audio of the beeper is left to the game (see
+// Core.Audio).
+type Cabinet struct {
+	core     *chip8.Core
+	palette  chip8.Palette
+	renderer *renderer
+	inputs   []InputSource
+	frame    *ebiten.Image
+}
+
+// NewCabinet creates a cabinet using the speed, palette, and renderer
+// settings of the configuration.
+func NewCabinet(cfg Config) (*Cabinet, error) {
+	// The configuration may not have been resolved by the caller
+	cfg.Resolve()
+
+	palettes, err := cfg.Palettes()
+	if err != nil {
+		return nil, err
+	}
+
+	palette := chip8.FindPalette(palettes, cfg.Palette)
+	if palette < 0 {
+		palette = 0
+	}
+
+	core := chip8.NewCore(cfg.MachineSpec(), cfg.SampleRate)
+	core.Speed = cfg.Speed
+	core.VM.Quirks = cfg.MachineQuirks()
+	core.TimerRate = cfg.TimerRate
+
+	return &Cabinet{
+		core:     core,
+		palette:  palettes[palette],
+		renderer: newRenderer(cfg),
+	}, nil
+}
+
+// Core returns the core running the virtual machine of the cabinet.
+func (c *Cabinet) Core() *chip8.Core {
+	return c.core
+}
+
+// AddInput adds a source of key presses to the cabinet.
+func (c *Cabinet) AddInput(src InputSource) {
+	c.inputs = append(c.inputs, src)
+}
+
+// LoadROM loads a CHIP-8 ROM into the virtual machine.
+func (c *Cabinet) LoadROM(path string) error {
+	return c.core.VM.LoadROM(path)
+}
+
+// LoadBytes loads the bytes of a CHIP-8 ROM into the virtual machine.
+func (c *Cabinet) LoadBytes(data []byte) error {
+	return c.core.VM.LoadBytes(data)
+}
+
+// Update presses the keys held by the input sources and runs a single
+// frame of the virtual machine.
+func (c *Cabinet) Update() error {
+	var keys [chip8.NumberOfKeys]bool
+	for _, src := range c.inputs {
+		src.Press(&keys)
+	}
+	c.core.VM.Keys = keys
+
+	if err := c.core.RunFrame(); err != nil {
+		return err
+	}
+
+	// Fading pixels change even when the display doesn't
+	vm := c.core.VM
+	if vm.DisplayChanged() || c.renderer.isFading() || c.frame == nil {
+		c.frame = c.renderer.render(vm, c.palette.Foreground(), c.palette.Background())
+	}
+
+	return nil
+}
+
+// Image returns the image of the display, with one pixel per pixel of
+// the display. It is updated in place, so it can be kept by the game
+// until the resolution of the display changes.
+func (c *Cabinet) Image() *ebiten.Image {
+	if c.frame == nil {
+		c.frame = c.renderer.render(c.core.VM, c.palette.Foreground(), c.palette.Background())
+	}
+	return c.frame
+}
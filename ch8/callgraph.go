@@ -0,0 +1,153 @@
+package ch8
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"sort"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Call Graph
+//=====================================================================
+
+// CallSite is a call from a subroutine to another, made by the
+// instruction at an address.
+type CallSite struct {
+	Addr   uint
+	Callee uint
+}
+
+// CallGraph is the subroutines of a program, by the address where they
+// start, along with the calls each of them makes. The program itself
+// is the subroutine starting at ProgramStartAddress.
+type CallGraph map[uint][]CallSite
+
+// BuildCallGraph finds the subroutines of a program and the calls
+// between them by following the control flow of each subroutine from
+// where it starts to where it returns, without entering the
+// subroutines it calls. Subroutines only called through computed jumps
+// (BNNN) aren't found.
+func BuildCallGraph(program []byte) CallGraph {
+	g := CallGraph{}
+
+	entries := []uint{chip8.ProgramStartAddress}
+	for len(entries) > 0 {
+		entry := entries[0]
+		entries = entries[1:]
+		if _, ok := g[entry]; ok {
+			continue
+		}
+		g[entry] = []CallSite{}
+
+		visited := map[uint]bool{}
+		pending := []uint{entry}
+		for len(pending) > 0 {
+			addr := pending[len(pending)-1]
+			pending = pending[:len(pending)-1]
+
+			i := int(addr) - chip8.ProgramStartAddress
+			if i < 0 || i+1 >= len(program) || visited[addr] {
+				continue
+			}
+			visited[addr] = true
+
+			in := Decode(uint(program[i])<<8 | uint(program[i+1]))
+			if !in.Valid() {
+				continue
+			}
+
+			next := addr + 2
+			switch {
+			case in.Opcode == 0x00ee || in.Opcode == 0x00fd:
+				// The subroutine returns here
+			case in.Opcode>>12 == 0x1, in.Opcode>>12 == 0xb:
+				pending = append(pending, in.NNN)
+			case in.Opcode>>12 == 0x2:
+				g[entry] = append(g[entry], CallSite{addr, in.NNN})
+				entries = append(entries, in.NNN)
+				pending = append(pending, next)
+			case in.skips():
+				pending = append(pending, next, next+2)
+			default:
+				pending = append(pending, next)
+			}
+		}
+
+		sort.Slice(g[entry], func(i, j int) bool {
+			return g[entry][i].Addr < g[entry][j].Addr
+		})
+	}
+
+	return g
+}
+
+// WriteDOT writes the call graph as a Graphviz graph, with a node for
+// each subroutine and an edge for each subroutine it calls, labeled
+// with the number of calls if it is called more than once.
+//
+// Given the coverage of a session, the subroutines which never ran and
+// the calls which were never made are drawn dashed.
+func (g CallGraph) WriteDOT(w io.Writer, cov *chip8.Coverage) error {
+	bw := bufio.NewWriter(w)
+	bw.WriteString("digraph calls {\n")
+	bw.WriteString("\tnode [shape=box, fontname=monospace];\n")
+
+	entries := make([]uint, 0, len(g))
+	for entry := range g {
+		entries = append(entries, entry)
+	}
+	sort.Slice(entries, func(i, j int) bool { return entries[i] < entries[j] })
+
+	name := func(addr uint) string {
+		if addr == chip8.ProgramStartAddress {
+			return "main"
+		}
+		return labelName(addr, labelCall)
+	}
+
+	for _, entry := range entries {
+		style := ""
+		if cov != nil && !cov[entry] {
+			style = ", style=dashed"
+		}
+		fmt.Fprintf(bw, "\t%q [label=\"%s\\n0x%.3X\"%s];\n", name(entry), name(entry), entry, style)
+	}
+
+	for _, entry := range entries {
+		// Calls to the same subroutine are drawn as a single edge,
+		// which is dashed if none of them were made
+		var callees []uint
+		count := map[uint]int{}
+		made := map[uint]bool{}
+		for _, call := range g[entry] {
+			if count[call.Callee] == 0 {
+				callees = append(callees, call.Callee)
+			}
+			count[call.Callee]++
+			made[call.Callee] = made[call.Callee] || cov == nil || cov[call.Addr]
+		}
+
+		for _, callee := range callees {
+			var attrs []string
+			if count[callee] > 1 {
+				attrs = append(attrs, fmt.Sprintf("label=\"%d\"", count[callee]))
+			}
+			if !made[callee] {
+				attrs = append(attrs, "style=dashed")
+			}
+
+			fmt.Fprintf(bw, "\t%q -> %q", name(entry), name(callee))
+			if len(attrs) > 0 {
+				fmt.Fprintf(bw, " [%s]", strings.Join(attrs, ", "))
+			}
+			bw.WriteString(";\n")
+		}
+	}
+
+	bw.WriteString("}\n")
+	return bw.Flush()
+}
@@ -0,0 +1,201 @@
+package ch8
+
+import (
+	"image"
+	"image/color"
+	"path/filepath"
+	"sort"
+	"strings"
+	"unicode"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Command Palette
+//=====================================================================
+
+const (
+	// commandPaletteWidth is the width (in characters) of the command
+	// palette.
+	commandPaletteWidth = 48
+
+	// maxCommandMatches is the number of matching commands listed by
+	// the command palette.
+	maxCommandMatches = 12
+)
+
+// commandItems returns the commands of the command palette: every
+// action of the emulator, along with opening the recently opened ROMs
+// and the built-in ROMs.
+func (emu *Emulator) commandItems() []menuItem {
+	keys := emu.hotkeyNames()
+
+	var items []menuItem
+	for _, a := range actions {
+		if a.name != "commands" {
+			items = append(items, menuItem{a.description, keys[a.name], a.run})
+		}
+	}
+
+	for _, path := range emu.cfg.RecentROMs {
+		path := path
+		items = append(items, menuItem{
+			label: "Open " + filepath.Base(path),
+			run:   func(emu *Emulator) { emu.openROM(path) },
+		})
+	}
+
+	for _, path := range chip8.BuiltinROMs() {
+		name := chip8.ROMName(path)
+		data, _ := chip8.ReadROM(path)
+		items = append(items, menuItem{
+			label: "Open " + name + " (built-in)",
+			run:   func(emu *Emulator) { emu.switchROM(name, append([]byte(nil), data...)) },
+		})
+	}
+	return items
+}
+
+// fuzzyMatch reports whether the characters of a query appear in order
+// within a label, ignoring case, along with a score which is higher
+// when the characters are consecutive or start words of the label.
+func fuzzyMatch(query, label string) (int, bool) {
+	q := []rune(strings.ToLower(query))
+	l := []rune(strings.ToLower(label))
+
+	score, i, last := 0, 0, -2
+	for j := 0; j < len(l) && i < len(q); j++ {
+		if l[j] != q[i] {
+			continue
+		}
+
+		score++
+		if j == last+1 {
+			score += 2
+		}
+		if j == 0 || l[j-1] == ' ' {
+			score += 3
+		}
+		i, last = i+1, j
+	}
+	return score, i == len(q)
+}
+
+// commandPalette is an overlay listing the actions of the emulator
+// which match a search typed on the keyboard, so that every action can
+// be found without knowing its hotkey. It takes every key of the
+// keyboard while it is open.
+type commandPalette struct {
+	active   bool
+	query    string
+	selected int
+}
+
+// matches returns the commands which match the query, best first.
+func (c *commandPalette) matches(emu *Emulator) []menuItem {
+	type match struct {
+		item  menuItem
+		score int
+	}
+
+	var ms []match
+	for _, item := range emu.commandItems() {
+		if score, ok := fuzzyMatch(c.query, item.label); ok {
+			ms = append(ms, match{item, score})
+		}
+	}
+	sort.SliceStable(ms, func(i, j int) bool {
+		return ms[i].score > ms[j].score
+	})
+
+	items := make([]menuItem, 0, len(ms))
+	for _, m := range ms {
+		if len(items) == maxCommandMatches {
+			break
+		}
+		items = append(items, m.item)
+	}
+	return items
+}
+
+// update edits the query, moves the selection, and runs the command
+// which is chosen, closing the palette.
+func (c *commandPalette) update(emu *Emulator) {
+	if inpututil.IsKeyJustPressed(ebiten.KeyEscape) {
+		c.active = false
+		emu.redraw = true
+		return
+	}
+
+	for _, r := range ebiten.InputChars() {
+		if unicode.IsPrint(r) {
+			c.query += string(r)
+			c.selected = 0
+		}
+	}
+	if inpututil.IsKeyJustPressed(ebiten.KeyBackspace) && c.query != "" {
+		q := []rune(c.query)
+		c.query = string(q[:len(q)-1])
+		c.selected = 0
+	}
+
+	matches := c.matches(emu)
+	if len(matches) == 0 {
+		return
+	}
+
+	switch {
+	case inpututil.IsKeyJustPressed(ebiten.KeyUp):
+		c.selected = (c.selected + len(matches) - 1) % len(matches)
+	case inpututil.IsKeyJustPressed(ebiten.KeyDown):
+		c.selected = (c.selected + 1) % len(matches)
+	case inpututil.IsKeyJustPressed(ebiten.KeyEnter):
+		c.active = false
+		emu.redraw = true
+		matches[c.selected%len(matches)].run(emu)
+	}
+}
+
+// draw shows the query and the matching commands near the top of the
+// screen, highlighting the selected command.
+func (c *commandPalette) draw(screen *ebiten.Image, emu *Emulator) {
+	if !c.active {
+		return
+	}
+
+	background := color.RGBA{0x20, 0x20, 0x20, 0xe0}
+	highlight := color.RGBA{0x40, 0x40, 0x80, 0xff}
+
+	matches := c.matches(emu)
+	width := commandPaletteWidth * menuCharWidth
+	x := (screen.Bounds().Dx() - width) / 2
+	y := emu.menu.height() + menuBarHeight
+
+	box := image.Rect(x, y, x+width, y+(len(matches)+1)*menuBarHeight)
+	drawRect(screen, box, background)
+	ebitenutil.DebugPrintAt(screen, "> "+c.query+"_", x+menuCharWidth, y)
+
+	for i, item := range matches {
+		row := y + (i+1)*menuBarHeight
+		if i == c.selected {
+			drawRect(screen, image.Rect(x, row, x+width, row+menuBarHeight), highlight)
+		}
+
+		// Hotkeys are right-aligned
+		label := item.label
+		if item.hotkey != "" {
+			gap := commandPaletteWidth - 2 - len(item.label) - len(item.hotkey)
+			label += strings.Repeat(" ", gap) + item.hotkey
+		}
+		ebitenutil.DebugPrintAt(screen, label, x+menuCharWidth, row)
+	}
+}
+
+func (emu *Emulator) openCommandPalette() {
+	emu.cmdPalette = commandPalette{active: true}
+	emu.redraw = true
+}
@@ -0,0 +1,90 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+	"sync/atomic"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Quirk Comparison
+//=====================================================================
+
+// comparison runs the ROM on a second virtual machine with another
+// quirk profile, in lockstep with the first one and with the same keys,
+// so the two can be watched side by side to see how the quirks change
+// the behavior of the program.
+type comparison struct {
+	vm       *chip8.VirtualMachine
+	renderer *renderer
+	name     string
+	stopped  int32
+}
+
+func newComparison(cfg Config) *comparison {
+	vm := chip8.NewMachine(cfg.MachineSpec())
+	vm.Quirks = chip8.QuirkProfiles[chip8.FindQuirks(cfg.CompareQuirks)].Quirks
+
+	return &comparison{
+		vm:       vm,
+		renderer: newRenderer(cfg),
+		name:     cfg.CompareQuirks,
+	}
+}
+
+// runCycle runs a single CPU cycle of the compared virtual machine.
+func (c *comparison) runCycle() {
+	if err := c.vm.RunCycle(); err != nil {
+		emuLog.Errorf("%s: %v", c.name, err)
+	}
+}
+
+// stop stops comparing the quirks for the rest of the session, such as
+// when the compared virtual machine can't load the ROM.
+func (c *comparison) stop(err error) {
+	emuLog.Errorf("%s: %v", c.name, err)
+	emuLog.Warn("no longer comparing quirks with", c.name)
+	atomic.StoreInt32(&c.stopped, 1)
+}
+
+// comparing reports whether the ROM is being compared with another
+// quirk profile.
+func (emu *Emulator) comparing() bool {
+	return emu.compare != nil && atomic.LoadInt32(&emu.compare.stopped) == 0
+}
+
+// split splits the view of the display into halves, the left one for
+// the virtual machine of the emulator and the right one for the
+// compared virtual machine.
+func (c *comparison) split(view image.Rectangle) (image.Rectangle, image.Rectangle) {
+	mid := view.Min.X + view.Dx()/2
+	left := image.Rect(view.Min.X, view.Min.Y, mid, view.Max.Y)
+	right := image.Rect(mid, view.Min.Y, view.Max.X, view.Max.Y)
+	return left, right
+}
+
+// draw draws the display of the compared virtual machine within its
+// half of the screen, and labels both halves with their quirk profiles.
+func (c *comparison) draw(screen *ebiten.Image, emu *Emulator, left, right image.Rectangle, fg, bg color.Color) {
+	display := c.vm.Display.Size()
+	rotated := rotateSize(display, emu.cfg.Rotation)
+	scale, offset := fitDisplay(right.Size(), rotated)
+	offset = offset.Add(right.Min)
+	area := image.Rectangle{Min: offset, Max: offset.Add(rotated.Mul(scale))}
+
+	op := &ebiten.DrawImageOptions{}
+	op.GeoM.Scale(float64(scale), float64(scale))
+	rotateGeoM(&op.GeoM, display.Mul(scale), emu.cfg.Rotation)
+	op.GeoM.Translate(float64(offset.X), float64(offset.Y))
+
+	screen.DrawImage(c.renderer.render(c.vm, fg, bg), op)
+	emu.filter.apply(screen, area)
+
+	ebitenutil.DebugPrintAt(screen, fmt.Sprintf("quirks: %s", emu.cfg.Quirks), left.Min.X+4, left.Min.Y)
+	ebitenutil.DebugPrintAt(screen, fmt.Sprintf("quirks: %s", c.name), right.Min.X+4, right.Min.Y)
+}
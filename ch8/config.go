@@ -0,0 +1,769 @@
+package ch8
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Constants
+//=====================================================================
+
+const (
+	// DefaultScale is the default scale factor of the CHIP-8 screen.
+	DefaultScale = 10
+
+	// DefaultVolume is the default volume of the CHIP-8 beeper.
+	//
+	// The volume ranges within [0.0, 1.0].
+	DefaultVolume = 0.5
+
+	// DefaultAudioBuffer is the default amount of audio the CHIP-8
+	// beeper synthesizes ahead of playback.
+	DefaultAudioBuffer = 20 * time.Millisecond
+
+	// DefaultHzI is the default speed (in hertz) in which to update
+	// the IO timers and audio.
+	DefaultHzIO = 16 * time.Millisecond
+)
+
+//=====================================================================
+// Settings
+//=====================================================================
+
+const (
+	// DefaultScreenshotDir is the default directory where screenshots
+	// are saved.
+	DefaultScreenshotDir = "screenshots"
+
+	// DefaultCrashDir is the default directory where crash reports are
+	// saved.
+	DefaultCrashDir = "crashes"
+
+	// DefaultEducationRate is the default number of instructions run
+	// per second in education mode.
+	DefaultEducationRate = 2.0
+
+	// DefaultGamepadThreshold is the default distance an axis of a
+	// gamepad must be pushed from its center to press a key.
+	DefaultGamepadThreshold = 0.5
+
+	// DefaultGIFDecimation is the default number of frames per
+	// captured frame of a GIF recording.
+	//
+	// Many GIF viewers slow down GIFs with frame delays below 2/100ths
+	// of a second, so recording every frame at 60 FPS is not
+	// recommended.
+	DefaultGIFDecimation = 2
+
+	// DefaultKeypadCellSize is the default size (in pixels) of a key
+	// of the keypad overlay.
+	DefaultKeypadCellSize = 24
+
+	// MinKeypadCellSize is the smallest size (in pixels) of a key of
+	// the keypad overlay, which fits the digit of the key.
+	MinKeypadCellSize = 12
+)
+
+const (
+	// FilterNone draws the display as is.
+	FilterNone = "none"
+
+	// FilterScanlines darkens every other line of the screen.
+	FilterScanlines = "scanlines"
+
+	// FilterCRT darkens every other line of the screen and the edges
+	// of the display, like an old CRT monitor.
+	FilterCRT = "crt"
+)
+
+const (
+	// RendererAccelerated streams the display to the GPU as a texture
+	// once per frame.
+	RendererAccelerated = "accelerated"
+
+	// RendererSoftware plots the lit pixels of the display onto the
+	// texture one at a time, for graphics drivers which misbehave with
+	// whole texture uploads. It is still drawn by the GPU, and is never
+	// chosen automatically.
+	RendererSoftware = "software"
+)
+
+// Corners of the screen where the keypad overlay can be shown.
+const (
+	CornerTopLeft     = "top-left"
+	CornerTopRight    = "top-right"
+	CornerBottomLeft  = "bottom-left"
+	CornerBottomRight = "bottom-right"
+)
+
+const (
+	// AttractInputRandom presses random keys of the keypad in attract
+	// mode, which starts most games and keeps them moving.
+	AttractInputRandom = "random"
+
+	// AttractInputNone presses no keys in attract mode.
+	AttractInputNone = "none"
+)
+
+//=====================================================================
+// Configuration
+//=====================================================================
+
+// Config is the configuration of the CHIP-8 emulator.
+//
+// The configuration can be stored as a JSON file, which is layered
+// below the flags of the CLI.
+type Config struct {
+	// Config is the configuration of the virtual machine, whose
+	// settings are kept in the same file.
+	chip8.Config
+
+	// Scale is the scale factor of the CHIP-8 screen.
+	Scale int `json:"scale"`
+
+	// Palette is the name of the color scheme of the CHIP-8 screen.
+	Palette string `json:"palette"`
+
+	// Colors are the colors of the custom palette, one for each
+	// combination of bit planes, written in hexadecimal.
+	Colors []string `json:"colors,omitempty"`
+
+	// Decay is the number of frames a pixel takes to fade out after it
+	// is turned off, which reduces the flicker of sprites. Pixels turn
+	// off immediately if the decay is zero.
+	Decay int `json:"decay"`
+
+	// Rotation is the angle (in degrees clockwise) the CHIP-8 screen
+	// is rotated by, either 0, 90, 180, or 270.
+	Rotation int `json:"rotation"`
+
+	// Border is the thickness (in pixels) of the margin around the
+	// CHIP-8 screen, where the visual bell flashes.
+	Border int `json:"border"`
+
+	// BorderColor is the color of the margin around the CHIP-8 screen,
+	// written in hexadecimal. The background of the palette is used if
+	// the color is empty.
+	BorderColor string `json:"borderColor,omitempty"`
+
+	// Keymap is the name of the keymap of the CHIP-8 keypad.
+	Keymap string `json:"keymap"`
+
+	// Keys binds keys of the keyboard to keys of the CHIP-8 keypad for
+	// the custom keymap, by name (e.g. "Q" or "Space") to hexadecimal
+	// digit.
+	Keys map[string]string `json:"keys,omitempty"`
+
+	// KeyLabels matches keys of the keyboard by their label on the
+	// keyboard layout rather than their position, so the literal
+	// keymap follows the letters printed on the keys.
+	KeyLabels bool `json:"keyLabels,omitempty"`
+
+	// Gamepad binds buttons and axes of gamepads to keys of the CHIP-8
+	// keypad, by name (e.g. "button0" or "axis1-") to hexadecimal digit.
+	Gamepad map[string]string `json:"gamepad,omitempty"`
+
+	// GamepadThreshold is the distance an axis of a gamepad must be
+	// pushed from its center to press a key, within (0.0, 1.0].
+	GamepadThreshold float64 `json:"gamepadThreshold"`
+
+	// Hotkeys binds keys of the keyboard to the actions of the
+	// emulator, by action (e.g. "pause") to key name (e.g. "F8"). An
+	// empty key name unbinds the action, and actions which aren't
+	// listed keep their default hotkeys.
+	Hotkeys map[string]string `json:"hotkeys,omitempty"`
+
+	// Macros binds keys of the keyboard to short sequences of keypad
+	// input, by key name (e.g. "Space") to the keys held in each frame,
+	// such as "5 . 5 . 5 .".
+	Macros map[string]string `json:"macros,omitempty"`
+
+	// TouchKeypad shows a keypad beside the CHIP-8 screen, which can be
+	// tapped or clicked. The keypad is always shown once the screen is
+	// touched.
+	TouchKeypad bool `json:"touchKeypad"`
+
+	// MenuBar shows a bar of menus listing the actions of the emulator
+	// above the CHIP-8 screen.
+	MenuBar bool `json:"menuBar"`
+
+	// Education explains each instruction the virtual machine executes
+	// in plain English, along with the registers it changed, and runs
+	// the virtual machine slowly enough to follow along.
+	Education bool `json:"education"`
+
+	// EducationRate is the number of instructions run per second in
+	// education mode, before the speed multiplier.
+	EducationRate float64 `json:"educationRate"`
+
+	// Registers shows an overlay of the registers and stack of the
+	// virtual machine beside the CHIP-8 screen.
+	Registers bool `json:"registers"`
+
+	// InputDisplay shows the keys of the CHIP-8 keypad which are held
+	// in a corner of the screen from the start, either CornerTopLeft,
+	// CornerTopRight, CornerBottomLeft, or CornerBottomRight. The
+	// keypad overlay starts hidden if it is empty.
+	InputDisplay string `json:"inputDisplay,omitempty"`
+
+	// InputDisplaySize is the size (in pixels) of a key of the keypad
+	// overlay, at least MinKeypadCellSize.
+	InputDisplaySize int `json:"inputDisplaySize"`
+
+	// PauseOnFocusLoss pauses and mutes the emulator while its window
+	// isn't focused, and resumes it when the window is focused again.
+	PauseOnFocusLoss bool `json:"pauseOnFocusLoss"`
+
+	// RecentROMs are the paths of the most recently opened ROMs, which
+	// are listed in the menu bar.
+	RecentROMs []string `json:"recentROMs,omitempty"`
+
+	// Filter is the retro effect drawn over the CHIP-8 screen, either
+	// FilterNone, FilterScanlines, or FilterCRT.
+	Filter string `json:"filter"`
+
+	// Renderer is how the CHIP-8 screen is drawn, either
+	// RendererAccelerated or RendererSoftware.
+	Renderer string `json:"renderer"`
+
+	// Volume is the volume of the CHIP-8 beeper.
+	//
+	// The volume ranges within [0.0, 1.0].
+	Volume float64 `json:"volume"`
+
+	// SampleRate is the sample rate (in hertz) of the CHIP-8 beeper.
+	SampleRate int `json:"sampleRate"`
+
+	// AudioBuffer is the amount of audio the beeper synthesizes ahead
+	// of playback. Smaller buffers make short beeps line up with the
+	// sound timer, while larger buffers are less prone to stutter.
+	AudioBuffer time.Duration `json:"audioBuffer"`
+
+	// NoAudio disables audio output entirely, which is useful for
+	// headless runs and systems without an audio device.
+	NoAudio bool `json:"noAudio"`
+
+	// VisualBell inverts the colors of the screen while the sound
+	// timer is active. The visual bell is always used when the beeper
+	// is muted or audio is unavailable.
+	VisualBell bool `json:"visualBell"`
+
+	// SafeMode makes flicker-heavy ROMs safer to watch for people with
+	// photosensitivity: pixels fade out slowly, the palette has less
+	// contrast, and the screen flashes at most 3 times per second.
+	SafeMode bool `json:"safeMode"`
+
+	// ScreenshotDir is the directory where screenshots are saved.
+	ScreenshotDir string `json:"screenshotDir"`
+
+	// CrashDir is the directory where reports are saved when the
+	// virtual machine crashes. No reports are saved if it is empty.
+	CrashDir string `json:"crashDir"`
+
+	// ScreenshotScale is the scale factor of screenshots, where 1 saves
+	// screenshots at the native resolution of the CHIP-8 screen.
+	ScreenshotScale int `json:"screenshotScale"`
+
+	// GIFScale is the scale factor of GIF recordings.
+	GIFScale int `json:"gifScale"`
+
+	// GIFDecimation is the number of frames per captured frame of GIF
+	// recordings, where 1 captures every frame.
+	GIFDecimation int `json:"gifDecimation"`
+
+	// StateDir is the directory where save states are saved.
+	StateDir string `json:"stateDir"`
+
+	// SaveRAM is a range of memory, such as "0xF00-0xFFF", which is
+	// kept in a file for each ROM in StateDir, so programs can keep
+	// high scores or saved games between runs. No memory is kept if
+	// the range is empty.
+	SaveRAM string `json:"saveRAM"`
+
+	// FFmpeg is the ffmpeg executable used to record videos.
+	FFmpeg string `json:"ffmpeg"`
+
+	// VideoFormat is the file extension of video recordings, such as
+	// "mp4" or "webm", from which ffmpeg picks the codecs.
+	VideoFormat string `json:"videoFormat"`
+
+	// VideoScale is the scale factor of video recordings.
+	VideoScale int `json:"videoScale"`
+
+	// WAVPath is the path of a WAV file to record the beeper audio
+	// to. Nothing is recorded if the path is empty.
+	WAVPath string `json:"-"`
+
+	// Stream is the address (e.g. ":8080") where the display is
+	// streamed to web browsers, which can also press keys. Nothing is
+	// streamed if the address is empty.
+	Stream string `json:"-"`
+
+	// Control is the address (e.g. "localhost:8081") where an HTTP API
+	// to control the emulator is served. Nothing is served if the
+	// address is empty.
+	Control string `json:"-"`
+
+	// InputSocket is the address of a socket taking keypad events,
+	// either a path prefixed with "unix:" or a TCP address (e.g.
+	// "localhost:9000"). No socket is opened if the address is empty.
+	InputSocket string `json:"-"`
+
+	// CompareQuirks is the name of a quirk profile to compare with,
+	// by running the ROM with both profiles side by side. Nothing is
+	// compared if the name is empty.
+	CompareQuirks string `json:"-"`
+
+	// CoveragePath is the path of a file where the addresses of the
+	// instructions executed during the session are written on exit. No
+	// coverage is recorded if the path is empty.
+	CoveragePath string `json:"-"`
+
+	// TracePath is the path of a file where a timeline of the session
+	// is written in the Chrome trace event format. No timeline is
+	// written if the path is empty.
+	TracePath string `json:"-"`
+
+	// PauseAtStart pauses the emulator before the first instruction of
+	// the ROM runs, so that it can be inspected or debugged from the
+	// start.
+	PauseAtStart bool `json:"-"`
+
+	// Debug restores the breakpoints, watchpoints, and watches of the
+	// last debugging session of the ROM, which are saved on exit.
+	Debug bool `json:"-"`
+
+	// Breakpoints are the addresses of instructions, such as "0x2A4",
+	// which pause the emulator before they run.
+	Breakpoints []string `json:"-"`
+
+	// Watchpoints are the addresses of memory which pause the emulator
+	// once an instruction changes them.
+	Watchpoints []string `json:"-"`
+
+	// Watches are expressions, such as "V3" or "[I]", whose values are
+	// logged whenever the emulator pauses at a breakpoint or watchpoint.
+	Watches []string `json:"-"`
+
+	// TASPath is the path of the movie of tool-assisted mode, which runs
+	// the emulator frame by frame with the input of the movie. The
+	// movie is recorded anew if the file doesn't exist.
+	TASPath string `json:"-"`
+
+	// Attract is how long each ROM of a directory runs in attract
+	// mode, which cycles through the ROMs forever. Attract mode is off
+	// if the duration is zero.
+	Attract time.Duration `json:"-"`
+
+	// AttractInput is the input of attract mode, either
+	// AttractInputRandom or AttractInputNone.
+	AttractInput string `json:"-"`
+
+	// NetplayHost is the address (e.g. ":7000") where a game of
+	// netplay is hosted for another player to join.
+	NetplayHost string `json:"-"`
+
+	// NetplayJoin is the address (e.g. "example.com:7000") of a game
+	// of netplay to join.
+	NetplayJoin string `json:"-"`
+
+	// LogLevel is the level of the emulator's logs, followed by the
+	// levels of individual modules, such as "info,netplay=debug".
+	LogLevel string `json:"logLevel"`
+
+	// ROMs are the settings of individual ROMs, keyed by the file name
+	// of the ROM.
+	ROMs map[string]ROMConfig `json:"roms,omitempty"`
+
+	// Path is the path of the configuration file, where settings
+	// changed while the emulator runs are saved.
+	Path string `json:"-"`
+
+	// ROM is the file name of the ROM being run.
+	ROM string `json:"-"`
+}
+
+// ROMConfig is the configuration of an individual ROM, which takes
+// precedence over the rest of the configuration file.
+type ROMConfig struct {
+	// Quirks is the name of the quirk profile of the virtual machine.
+	Quirks string `json:"quirks,omitempty"`
+
+	// Palette is the name of the color scheme of the CHIP-8 screen.
+	Palette string `json:"palette,omitempty"`
+
+	// Colors are the colors of the custom palette, one for each
+	// combination of bit planes, written in hexadecimal.
+	Colors []string `json:"colors,omitempty"`
+
+	// Keymap is the name of the keymap of the CHIP-8 keypad.
+	Keymap string `json:"keymap,omitempty"`
+
+	// Keys binds keys of the keyboard to keys of the CHIP-8 keypad for
+	// the custom keymap.
+	Keys map[string]string `json:"keys,omitempty"`
+
+	// Gamepad binds buttons and axes of gamepads to keys of the CHIP-8
+	// keypad.
+	Gamepad map[string]string `json:"gamepad,omitempty"`
+}
+
+// DefaultConfig returns the default configuration of the emulator.
+func DefaultConfig() Config {
+	return Config{
+		Config:      chip8.DefaultConfig(),
+		Scale:       DefaultScale,
+		Palette:     chip8.DefaultPalette,
+		Keymap:      chip8.DefaultKeymap,
+		Filter:      FilterNone,
+		Renderer:    RendererAccelerated,
+		Volume:      DefaultVolume,
+		SampleRate:  chip8.DefaultSampleRate,
+		AudioBuffer: DefaultAudioBuffer,
+		LogLevel:    chip8.DefaultLogLevel,
+
+		EducationRate:    DefaultEducationRate,
+		GamepadThreshold: DefaultGamepadThreshold,
+		InputDisplaySize: DefaultKeypadCellSize,
+
+		ScreenshotDir:   DefaultScreenshotDir,
+		CrashDir:        DefaultCrashDir,
+		ScreenshotScale: 1,
+		GIFScale:        2,
+		GIFDecimation:   DefaultGIFDecimation,
+		StateDir:        chip8.DefaultStateDir,
+		FFmpeg:          "ffmpeg",
+		VideoFormat:     "mp4",
+		VideoScale:      DefaultScale,
+	}
+}
+
+// LoadConfig reads the configuration file at path on top of the
+// default configuration.
+//
+// The default configuration is returned if the file does not exist.
+func LoadConfig(path string) (Config, error) {
+	cfg := DefaultConfig()
+	cfg.Path = path
+
+	err := chip8.ReadConfig(path, &cfg)
+	return cfg, err
+}
+
+// UpdateConfig applies a change to the configuration file at path,
+// creating the file if it does not exist.
+//
+// Only the change is written, so settings overridden by the CLI are
+// not saved to the file.
+func UpdateConfig(path string, update func(cfg *Config)) error {
+	if path == "" {
+		return errors.New("no config file to save to")
+	}
+
+	cfg, err := LoadConfig(path)
+	if err != nil {
+		return err
+	}
+
+	update(&cfg)
+
+	data, err := json.MarshalIndent(cfg, "", "  ")
+	if err != nil {
+		return err
+	}
+
+	if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+		return err
+	}
+
+	return ioutil.WriteFile(path, append(data, '\n'), 0644)
+}
+
+// UseROM applies the settings of a ROM from the configuration file.
+func (cfg *Config) UseROM(name string) {
+	cfg.ROM = name
+
+	rom, ok := cfg.ROMs[name]
+	if !ok {
+		return
+	}
+
+	if rom.Quirks != "" {
+		cfg.Quirks = rom.Quirks
+	}
+	if rom.Palette != "" {
+		cfg.Palette = rom.Palette
+	}
+	if len(rom.Colors) > 0 {
+		cfg.Colors = rom.Colors
+	}
+	if rom.Keymap != "" {
+		cfg.Keymap = rom.Keymap
+	}
+	if len(rom.Keys) > 0 {
+		cfg.Keys = rom.Keys
+	}
+	if len(rom.Gamepad) > 0 {
+		cfg.Gamepad = rom.Gamepad
+	}
+}
+
+// UseEnv applies the settings given by environment variables, such as
+// CHIP8_SCALE=12, which take precedence over the configuration file.
+func (cfg *Config) UseEnv() error {
+	if err := cfg.Config.UseEnv(); err != nil {
+		return err
+	}
+
+	return chip8.ReadEnv(map[string]interface{}{
+		"SCALE":     &cfg.Scale,
+		"PALETTE":   &cfg.Palette,
+		"KEYMAP":    &cfg.Keymap,
+		"FILTER":    &cfg.Filter,
+		"VOLUME":    &cfg.Volume,
+		"NO_AUDIO":  &cfg.NoAudio,
+		"SAFE_MODE": &cfg.SafeMode,
+		"LOG_LEVEL": &cfg.LogLevel,
+	})
+}
+
+// Keymaps returns the keymaps available to the emulator, which
+// includes the custom keymap if any keys are bound.
+func (cfg *Config) Keymaps() ([]chip8.Keymap, error) {
+	return chip8.AvailableKeymaps(cfg.Keys)
+}
+
+// Palettes returns the palettes available to the emulator, which
+// includes the custom palette if any colors are configured.
+func (cfg *Config) Palettes() ([]chip8.Palette, error) {
+	return chip8.AvailablePalettes(cfg.Colors)
+}
+
+// Validate checks that the configuration is usable, including the
+// bindings of the keyboard and gamepads, and the debugging session.
+func (cfg *Config) Validate() error {
+	if err := cfg.Config.Validate(); err != nil {
+		return err
+	}
+
+	if cfg.Scale < 1 {
+		return errors.New("scale factor must be positive")
+	}
+
+	if cfg.CompareQuirks != "" && chip8.FindQuirks(cfg.CompareQuirks) < 0 {
+		return fmt.Errorf("unknown quirks: %s", cfg.CompareQuirks)
+	}
+
+	if cfg.EducationRate <= 0.0 {
+		return errors.New("education rate must be positive")
+	}
+
+	if cfg.Attract < 0 {
+		return errors.New("attract duration must not be negative")
+	}
+
+	switch cfg.AttractInput {
+	case AttractInputRandom, AttractInputNone, "":
+	default:
+		return fmt.Errorf("unknown attract input: %s", cfg.AttractInput)
+	}
+
+	if cfg.SaveRAM != "" {
+		spec := cfg.MachineSpec()
+		r, err := chip8.ParseAddressRange(cfg.SaveRAM)
+		if err != nil {
+			return err
+		} else if r.Start < spec.LoadAddress || r.End > spec.MemorySize {
+			return errors.New("persistent memory must be within program memory")
+		}
+	}
+
+	if cfg.NetplayHost != "" && cfg.NetplayJoin != "" {
+		return errors.New("netplay can't both host and join a game")
+	}
+	if cfg.TASPath != "" && (cfg.NetplayHost != "" || cfg.NetplayJoin != "") {
+		return errors.New("tool-assisted mode isn't available during netplay")
+	}
+
+	palettes, err := cfg.Palettes()
+	if err != nil {
+		return err
+	} else if chip8.FindPalette(palettes, cfg.Palette) < 0 {
+		return fmt.Errorf("unknown palette: %s", cfg.Palette)
+	}
+
+	if cfg.Decay < 0 {
+		return errors.New("decay must not be negative")
+	}
+
+	switch cfg.Rotation {
+	case 0, 90, 180, 270:
+	default:
+		return fmt.Errorf("invalid rotation: %d", cfg.Rotation)
+	}
+
+	if cfg.Border < 0 {
+		return errors.New("border must not be negative")
+	}
+
+	switch cfg.InputDisplay {
+	case "", CornerTopLeft, CornerTopRight, CornerBottomLeft, CornerBottomRight:
+	default:
+		return fmt.Errorf("invalid input display corner: %s", cfg.InputDisplay)
+	}
+
+	if cfg.InputDisplaySize < MinKeypadCellSize {
+		return fmt.Errorf("input display size must be at least %d", MinKeypadCellSize)
+	}
+
+	if cfg.BorderColor != "" {
+		if _, err := chip8.ParseColor(cfg.BorderColor); err != nil {
+			return err
+		}
+	}
+
+	switch cfg.Filter {
+	case FilterNone, FilterScanlines, FilterCRT:
+	default:
+		return fmt.Errorf("unknown filter: %s", cfg.Filter)
+	}
+
+	switch cfg.Renderer {
+	case RendererAccelerated, RendererSoftware:
+	default:
+		return fmt.Errorf("unknown renderer: %s", cfg.Renderer)
+	}
+
+	if cfg.Volume < 0.0 || cfg.Volume > 1.0 {
+		return errors.New("volume must be between [0, 1]")
+	}
+
+	if cfg.SampleRate < chip8.DefaultFrequency*2 {
+		return errors.New("sample rate is too low for the beeper")
+	}
+
+	if cfg.AudioBuffer <= 0 {
+		return errors.New("audio buffer must be positive")
+	}
+
+	if cfg.ScreenshotScale < 1 {
+		return errors.New("screenshot scale must be positive")
+	}
+
+	if cfg.GIFScale < 1 {
+		return errors.New("GIF scale must be positive")
+	}
+
+	if cfg.GIFDecimation < 1 {
+		return errors.New("GIF decimation must be positive")
+	}
+
+	if cfg.VideoScale < 1 {
+		return errors.New("video scale must be positive")
+	}
+
+	if err := chip8.CheckLogLevel(cfg.LogLevel); err != nil {
+		return err
+	}
+
+	if _, err := ParseDebugSession(cfg.Breakpoints, cfg.Watchpoints, cfg.Watches); err != nil {
+		return err
+	}
+
+	keymaps, err := cfg.Keymaps()
+	if err != nil {
+		return err
+	}
+	i := chip8.FindKeymap(keymaps, cfg.Keymap)
+	if i < 0 {
+		return fmt.Errorf("unknown keymap: %s", cfg.Keymap)
+	}
+	keymap := keymaps[i]
+	for name := range keymap.Keys {
+		if _, err := ParseKey(name); err != nil {
+			return err
+		}
+	}
+	keys := keymapKeys(keymap)
+
+	// Hotkeys must not take keys away from the keypad
+	hotkeys, err := parseHotkeys(cfg.Hotkeys)
+	if err != nil {
+		return err
+	}
+	for key := range keys {
+		if a, ok := hotkeys[key]; ok {
+			return fmt.Errorf(
+				"hotkey %s of %s is bound to the keypad", key, a.name,
+			)
+		}
+	}
+
+	// Macros must not take keys away from the keypad or hotkeys
+	macros, err := parseMacros(cfg.Macros)
+	if err != nil {
+		return err
+	}
+	for key := range macros {
+		if _, ok := keys[key]; ok {
+			return fmt.Errorf("macro key %s is bound to the keypad", key)
+		}
+		if a, ok := hotkeys[key]; ok {
+			return fmt.Errorf("macro key %s is the hotkey to %s", key, a.name)
+		}
+	}
+
+	if cfg.GamepadThreshold <= 0.0 || cfg.GamepadThreshold > 1.0 {
+		return errors.New("gamepad threshold must be between (0, 1]")
+	}
+
+	if _, err := newGamepadMap(cfg.Gamepad, cfg.GamepadThreshold); err != nil {
+		return err
+	}
+
+	return nil
+}
+
+// MarshalJSON encodes the configuration, writing durations in a
+// human-readable form such as "20ms".
+func (cfg Config) MarshalJSON() ([]byte, error) {
+	type config Config
+
+	return json.Marshal(struct {
+		config
+		AudioBuffer string `json:"audioBuffer"`
+	}{config(cfg), cfg.AudioBuffer.String()})
+}
+
+// UnmarshalJSON decodes the configuration, reading durations in a
+// human-readable form such as "20ms".
+func (cfg *Config) UnmarshalJSON(data []byte) error {
+	type config Config
+
+	aux := struct {
+		*config
+		AudioBuffer string `json:"audioBuffer"`
+	}{config: (*config)(cfg)}
+
+	if err := json.Unmarshal(data, &aux); err != nil {
+		return err
+	}
+
+	if aux.AudioBuffer != "" {
+		d, err := time.ParseDuration(aux.AudioBuffer)
+		if err != nil {
+			return err
+		}
+		cfg.AudioBuffer = d
+	}
+
+	return nil
+}
@@ -0,0 +1,382 @@
+package ch8
+
+import (
+	"encoding/json"
+	"image/png"
+	"io/ioutil"
+	"net"
+	"net/http"
+	"strconv"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Control API
+//=====================================================================
+
+// controlState is the state of the virtual machine returned by the
+// control API.
+type controlState struct {
+	ROM     string        `json:"rom"`
+	Paused  bool          `json:"paused"`
+	Speed   float64       `json:"speed"`
+	Cycles  uint64        `json:"cycles"`
+	PC      uint          `json:"pc"`
+	I       uint          `json:"i"`
+	SP      uint          `json:"sp"`
+	DT      uint          `json:"dt"`
+	ST      uint          `json:"st"`
+	V       []uint        `json:"v"`
+	Stack   []uint        `json:"stack"`
+	Width   int           `json:"width"`
+	Height  int           `json:"height"`
+	Waiting bool          `json:"waiting"`
+	Metrics chip8.Metrics `json:"metrics"`
+}
+
+// searchState is the state of the memory search returned by the
+// control API.
+type searchState struct {
+	Count      int            `json:"count"`
+	Candidates []SearchResult `json:"candidates"`
+}
+
+// startControlServer starts serving an HTTP API to control the
+// emulator at the given address, such as "localhost:8081":
+//
+//	GET  /state             registers, timers, and metrics as JSON
+//	GET  /memory?addr&size  bytes of memory, raw or as a hex dump
+//	GET  /screenshot?scale  the display as a PNG image
+//	POST /rom?name          load the ROM in the body of the request
+//	POST /actions/<name>    run an action, such as pause or reset
+//	GET  /debugger          breakpoints, watchpoints, and watches as JSON
+//	PUT  /debugger          replace the breakpoints, watchpoints, and watches
+//	POST /debugger/run?addr resume until an address is reached
+//	POST /search?filter     start a memory search, or filter its candidates
+//	GET  /search?limit      the candidates of the memory search as JSON
+//
+// Requests are carried out by the goroutine running the emulator, one
+// frame at a time.
+func (emu *Emulator) startControlServer(addr string) error {
+	listener, err := net.Listen("tcp", addr)
+	if err != nil {
+		return err
+	}
+
+	mux := http.NewServeMux()
+	mux.HandleFunc("/state", emu.serveState)
+	mux.HandleFunc("/memory", emu.serveMemory)
+	mux.HandleFunc("/screenshot", emu.serveScreenshot)
+	mux.HandleFunc("/rom", emu.serveROM)
+	mux.HandleFunc("/actions/", emu.serveAction)
+	mux.HandleFunc("/debugger", emu.serveDebugger)
+	mux.HandleFunc("/debugger/run", emu.serveRunTo)
+	mux.HandleFunc("/search", emu.serveSearch)
+
+	go func() {
+		if err := http.Serve(listener, mux); err != nil {
+			controlLog.Error(err)
+		}
+	}()
+
+	controlLog.Info("control API on", listener.Addr())
+	return nil
+}
+
+// do runs a function on the goroutine running the emulator during its
+// next update, and waits for it to finish.
+func (emu *Emulator) do(f func()) {
+	done := make(chan struct{})
+	emu.commands <- func() {
+		f()
+		close(done)
+	}
+	<-done
+}
+
+// runCommands runs the functions queued by do.
+func (emu *Emulator) runCommands() {
+	for {
+		select {
+		case f := <-emu.commands:
+			f()
+			emu.updateTitle()
+		default:
+			return
+		}
+	}
+}
+
+func (emu *Emulator) serveState(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	s := emu.snapshotVM()
+
+	var state controlState
+	emu.do(func() {
+		state = emu.vmState(s)
+	})
+
+	w.Header().Set("Content-Type", "application/json")
+	json.NewEncoder(w).Encode(state)
+}
+
+// snapshotVM takes a snapshot of the virtual machine on the goroutine
+// running it, so the snapshot never sees an instruction half executed.
+// It must not be called from the goroutine updating the emulator.
+func (emu *Emulator) snapshotVM() chip8.Snapshot {
+	// During netplay the virtual machine runs on the goroutine updating
+	// the emulator, which may itself be waiting to send an event
+	if emu.netplay != nil {
+		var s chip8.Snapshot
+		emu.do(func() {
+			s = emu.vm.Snapshot()
+		})
+		return s
+	}
+
+	emu.vmChan <- snapshotEvent
+	return <-emu.snapshots
+}
+
+// vmState captures the state of the virtual machine from a snapshot. It
+// must be called from the goroutine updating the emulator.
+func (emu *Emulator) vmState(s chip8.Snapshot) controlState {
+	sp := s.SP
+	if sp > chip8.MaxStackDepth {
+		sp = chip8.MaxStackDepth
+	}
+
+	return controlState{
+		ROM:     emu.cfg.ROM,
+		Paused:  emu.paused,
+		Speed:   emu.cfg.Speed,
+		Cycles:  s.Cycles,
+		PC:      s.PC,
+		I:       s.I,
+		SP:      s.SP,
+		DT:      s.DT,
+		ST:      s.ST,
+		V:       append([]uint(nil), s.V[:]...),
+		Stack:   append([]uint(nil), s.Stack[:sp]...),
+		Width:   s.Display.Width,
+		Height:  s.Display.Height,
+		Waiting: emu.vm.WaitingForKey(),
+		Metrics: emu.vm.Metrics(),
+	}
+}
+
+func (emu *Emulator) serveMemory(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	addr, err := queryInt(r, "addr", 0)
+	if err != nil || addr < 0 || addr >= chip8.MemorySize {
+		http.Error(w, "invalid addr", http.StatusBadRequest)
+		return
+	}
+	size, err := queryInt(r, "size", chip8.MemorySize-addr)
+	if err != nil || size < 0 {
+		http.Error(w, "invalid size", http.StatusBadRequest)
+		return
+	}
+
+	format := r.URL.Query().Get("format")
+	switch format {
+	case "", chip8.DumpBinary:
+		format = chip8.DumpBinary
+		w.Header().Set("Content-Type", "application/octet-stream")
+	case chip8.DumpHex:
+		w.Header().Set("Content-Type", "text/plain; charset=utf-8")
+	default:
+		http.Error(w, "invalid format", http.StatusBadRequest)
+		return
+	}
+
+	// The range is read from a snapshot, outside of the emulator
+	vm := &chip8.VirtualMachine{Memory: emu.snapshotVM().Memory}
+	data, _ := vm.MemoryRange(addr, size)
+
+	chip8.DumpMemory(w, data, addr, format)
+}
+
+func (emu *Emulator) serveScreenshot(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodGet {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	scale, err := queryInt(r, "scale", emu.cfg.ScreenshotScale)
+	if err != nil || scale < 1 {
+		http.Error(w, "invalid scale", http.StatusBadRequest)
+		return
+	}
+
+	var palette chip8.Palette
+	emu.do(func() {
+		palette = emu.palettes[emu.palette]
+	})
+
+	// The image is rendered from a snapshot, outside of the emulator
+	vm := &chip8.VirtualMachine{Display: emu.snapshotVM().Display}
+
+	w.Header().Set("Content-Type", "image/png")
+	png.Encode(w, RenderImage(vm, palette, scale))
+}
+
+func (emu *Emulator) serveROM(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	data, err := ioutil.ReadAll(http.MaxBytesReader(w, r.Body, chip8.ProgramMemorySize+1))
+	if err != nil || len(data) > chip8.ProgramMemorySize {
+		http.Error(w, "the ROM is too large", http.StatusRequestEntityTooLarge)
+		return
+	}
+
+	name := r.URL.Query().Get("name")
+	if name == "" {
+		name = "rom.ch8"
+	}
+
+	emu.do(func() {
+		emu.switchROM(name, data)
+		controlLog.Info("loaded", name, "from the control API")
+	})
+	w.WriteHeader(http.StatusNoContent)
+}
+
+func (emu *Emulator) serveAction(w http.ResponseWriter, r *http.Request) {
+	if r.Method != http.MethodPost {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	a := findAction(strings.TrimPrefix(r.URL.Path, "/actions/"))
+	if a == nil {
+		http.NotFound(w, r)
+		return
+	}
+
+	emu.do(func() { a.run(emu) })
+	w.WriteHeader(http.StatusNoContent)
+}
+
+func (emu *Emulator) serveDebugger(w http.ResponseWriter, r *http.Request) {
+	if emu.debugger == nil {
+		http.Error(w, "the debugger is off", http.StatusNotFound)
+		return
+	}
+
+	switch r.Method {
+	case http.MethodGet:
+		w.Header().Set("Content-Type", "application/json")
+		json.NewEncoder(w).Encode(emu.debugger.getSession())
+	case http.MethodPut:
+		var s DebugSession
+		if err := json.NewDecoder(r.Body).Decode(&s); err != nil {
+			http.Error(w, "invalid session", http.StatusBadRequest)
+			return
+		}
+		if err := s.validate(); err != nil {
+			http.Error(w, err.Error(), http.StatusBadRequest)
+			return
+		}
+
+		emu.debugger.setSession(s)
+		emu.saveDebugSession()
+		w.WriteHeader(http.StatusNoContent)
+	default:
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+	}
+}
+
+func (emu *Emulator) serveRunTo(w http.ResponseWriter, r *http.Request) {
+	if emu.debugger == nil {
+		http.Error(w, "the debugger is off", http.StatusNotFound)
+		return
+	}
+	if r.Method != http.MethodPost {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	addr, err := queryInt(r, "addr", -1)
+	if err != nil || addr < 0 || addr >= chip8.MemorySize {
+		http.Error(w, "invalid addr", http.StatusBadRequest)
+		return
+	}
+
+	emu.debugger.runToAddr(uint(addr))
+	emu.do(emu.play)
+	w.WriteHeader(http.StatusNoContent)
+}
+
+func (emu *Emulator) serveSearch(w http.ResponseWriter, r *http.Request) {
+	limit, err := queryInt(r, "limit", 100)
+	if err != nil || limit < 0 {
+		http.Error(w, "invalid limit", http.StatusBadRequest)
+		return
+	}
+
+	// Without a filter, a new search starts from the memory as it is
+	if r.Method == http.MethodPost {
+		filter := r.URL.Query().Get("filter")
+		value, err := queryInt(r, "value", 0)
+		if err != nil || value < 0 {
+			http.Error(w, "invalid value", http.StatusBadRequest)
+			return
+		}
+
+		memory := emu.snapshotVM().Memory
+		emu.do(func() {
+			if filter == "" {
+				emu.search = NewMemorySearch(memory)
+			} else if emu.search != nil {
+				err = emu.search.Filter(memory, filter, uint(value))
+			}
+		})
+		if err != nil {
+			http.Error(w, err.Error(), http.StatusBadRequest)
+			return
+		}
+	} else if r.Method != http.MethodGet {
+		http.Error(w, "method not allowed", http.StatusMethodNotAllowed)
+		return
+	}
+
+	var state *searchState
+	emu.do(func() {
+		if emu.search != nil {
+			state = &searchState{emu.search.Count(), emu.search.Results(limit)}
+		}
+	})
+	if state == nil {
+		http.Error(w, "no memory search was started", http.StatusNotFound)
+		return
+	}
+
+	w.Header().Set("Content-Type", "application/json")
+	json.NewEncoder(w).Encode(state)
+}
+
+// queryInt parses an integer parameter of a request, which may be
+// written in hexadecimal (e.g. 0x200), or returns def if it is missing.
+func queryInt(r *http.Request, name string, def int) (int, error) {
+	s := r.URL.Query().Get(name)
+	if s == "" {
+		return def, nil
+	}
+
+	v, err := strconv.ParseInt(s, 0, 32)
+	return int(v), err
+}
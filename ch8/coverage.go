@@ -0,0 +1,23 @@
+package ch8
+
+import "github.com/kevhlee/chip8/chip8"
+
+//=====================================================================
+// Coverage
+//=====================================================================
+
+// Unreachable returns the ranges of a program which its control flow
+// never reaches from its start. They are data, dead code, or code only
+// reached through computed jumps (BNNN).
+func Unreachable(program []byte) []chip8.AddressRange {
+	flow := traceControlFlow(program)
+
+	// The instructions reached are as good as executed
+	var cov chip8.Coverage
+	for i, start := range flow.starts {
+		if start {
+			cov[chip8.ProgramStartAddress+i] = true
+		}
+	}
+	return cov.Unexecuted(program)
+}
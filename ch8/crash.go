@@ -0,0 +1,130 @@
+package ch8
+
+import (
+	"encoding/json"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync/atomic"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Crash Reports
+//=====================================================================
+
+const (
+	// crashTraceLength is the number of instructions listed in a crash
+	// report, leading up to the crash.
+	crashTraceLength = 64
+)
+
+// tracedInstruction is an instruction run by the virtual machine.
+type tracedInstruction struct {
+	addr   uint
+	opcode uint
+}
+
+// instructionTrace keeps the last instructions run by the virtual
+// machine. It is only used from the goroutine running the virtual
+// machine.
+type instructionTrace struct {
+	ins   [crashTraceLength]tracedInstruction
+	next  int
+	count int
+}
+
+func (t *instructionTrace) record(addr, opcode uint) {
+	t.ins[t.next] = tracedInstruction{addr, opcode}
+	t.next = (t.next + 1) % crashTraceLength
+	if t.count < crashTraceLength {
+		t.count++
+	}
+}
+
+// lines lists the instructions, oldest first, as in a disassembly.
+func (t *instructionTrace) lines() []string {
+	lines := make([]string, 0, t.count)
+	for i := crashTraceLength - t.count; i < crashTraceLength; i++ {
+		in := t.ins[(t.next+i)%crashTraceLength]
+		lines = append(lines, fmt.Sprintf("0x%.3X  %.4X  %s", in.addr, in.opcode, Decode(in.opcode)))
+	}
+	return lines
+}
+
+// vmCrash is an error which halted the virtual machine, along with the
+// instructions which led up to it.
+type vmCrash struct {
+	err   error
+	trace []string
+}
+
+// crashState is the state of the virtual machine written to a crash
+// report.
+type crashState struct {
+	Error string `json:"error"`
+	controlState
+}
+
+// crashVM halts the virtual machine on an error, and hands the crash
+// over to the goroutine updating the emulator, which reports it. It
+// must be called from the goroutine running the virtual machine.
+func (emu *Emulator) crashVM(err error) {
+	emu.crash = &vmCrash{err, emu.history.lines()}
+	atomic.StoreInt32(&emu.crashed, 1)
+}
+
+// reportCrash writes a report of a crash to a folder of the crash
+// directory named after the ROM and the time: a screenshot of the
+// display, the state of the virtual machine as JSON, a hex dump of its
+// memory, and the instructions which led up to the crash.
+func (emu *Emulator) reportCrash(crash *vmCrash) {
+	if emu.cfg.CrashDir == "" {
+		return
+	}
+
+	dir := timestampedPath(emu.cfg.CrashDir, emu.cfg.ROM, "")
+	if err := emu.writeCrashReport(dir, crash); err != nil {
+		emuLog.Error(err)
+		return
+	}
+	emuLog.Info("wrote a crash report to", dir)
+}
+
+func (emu *Emulator) writeCrashReport(dir string, crash *vmCrash) error {
+	if err := os.MkdirAll(dir, 0755); err != nil {
+		return err
+	}
+
+	img := RenderImage(emu.vm, emu.palettes[emu.palette], emu.cfg.Scale)
+	if err := SavePNG(filepath.Join(dir, "screen.png"), img); err != nil {
+		return err
+	}
+
+	state, err := json.MarshalIndent(crashState{crash.err.Error(), emu.vmState(emu.vm.Snapshot())}, "", "  ")
+	if err != nil {
+		return err
+	}
+	if err := ioutil.WriteFile(filepath.Join(dir, "state.json"), state, 0644); err != nil {
+		return err
+	}
+
+	file, err := os.Create(filepath.Join(dir, "memory.hex"))
+	if err != nil {
+		return err
+	}
+	data, _ := emu.vm.MemoryRange(0, chip8.MemorySize)
+	err = chip8.DumpMemory(file, data, 0, chip8.DumpHex)
+	if cerr := file.Close(); err == nil {
+		err = cerr
+	}
+	if err != nil {
+		return err
+	}
+
+	trace := strings.Join(crash.trace, "\n") + "\n"
+	return ioutil.WriteFile(filepath.Join(dir, "trace.txt"), []byte(trace), 0644)
+}
@@ -0,0 +1,332 @@
+package ch8
+
+import (
+	"encoding/json"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"sync"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Debugger
+//=====================================================================
+
+// DebugSession is the state of the debugger for a ROM, which is kept in
+// a file beside its save states so that it survives restarts.
+type DebugSession struct {
+	// Breakpoints are the addresses of instructions which pause the
+	// emulator before they run.
+	Breakpoints []uint `json:"breakpoints"`
+
+	// Watchpoints are the addresses of memory which pause the emulator
+	// once an instruction changes them.
+	Watchpoints []uint `json:"watchpoints"`
+
+	// Watches are expressions, such as "V3" or "[I]", whose values are
+	// logged whenever the emulator pauses at a breakpoint or watchpoint.
+	Watches []string `json:"watches"`
+}
+
+// ParseDebugSession parses the addresses of breakpoints and watchpoints,
+// such as "0x2A4", along with watch expressions.
+func ParseDebugSession(breakpoints, watchpoints, watches []string) (DebugSession, error) {
+	var s DebugSession
+
+	for _, b := range breakpoints {
+		addr, err := strconv.ParseUint(b, 0, 16)
+		if err != nil || addr >= chip8.MemorySize {
+			return s, fmt.Errorf("invalid breakpoint: %s", b)
+		}
+		s.Breakpoints = append(s.Breakpoints, uint(addr))
+	}
+
+	for _, w := range watchpoints {
+		addr, err := strconv.ParseUint(w, 0, 16)
+		if err != nil || addr >= chip8.MemorySize {
+			return s, fmt.Errorf("invalid watchpoint: %s", w)
+		}
+		s.Watchpoints = append(s.Watchpoints, uint(addr))
+	}
+
+	s.Watches = watches
+	return s, s.validate()
+}
+
+// validate checks the addresses and watch expressions of the session.
+func (s *DebugSession) validate() error {
+	for _, addr := range s.Breakpoints {
+		if addr >= chip8.MemorySize {
+			return fmt.Errorf("invalid breakpoint: 0x%.3X", addr)
+		}
+	}
+	for _, addr := range s.Watchpoints {
+		if addr >= chip8.MemorySize {
+			return fmt.Errorf("invalid watchpoint: 0x%.3X", addr)
+		}
+	}
+	for _, w := range s.Watches {
+		if _, err := evalWatch(&chip8.VirtualMachine{}, w); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// merge adds the breakpoints, watchpoints, and watches of another
+// session which the session doesn't have yet.
+func (s *DebugSession) merge(other DebugSession) {
+	for _, addr := range other.Breakpoints {
+		if !hasAddr(s.Breakpoints, addr) {
+			s.Breakpoints = append(s.Breakpoints, addr)
+		}
+	}
+	for _, addr := range other.Watchpoints {
+		if !hasAddr(s.Watchpoints, addr) {
+			s.Watchpoints = append(s.Watchpoints, addr)
+		}
+	}
+	for _, w := range other.Watches {
+		found := false
+		for _, v := range s.Watches {
+			found = found || v == w
+		}
+		if !found {
+			s.Watches = append(s.Watches, w)
+		}
+	}
+}
+
+// hasAddr reports whether an address is in a list of addresses.
+func hasAddr(addrs []uint, addr uint) bool {
+	for _, a := range addrs {
+		if a == addr {
+			return true
+		}
+	}
+	return false
+}
+
+// evalWatch returns the value of a watch expression: a register (V0 to
+// VF, I, PC, SP, DT, or ST), a number, or the byte of memory at either
+// of them, such as "[0x300]" or "[I]".
+func evalWatch(vm *chip8.VirtualMachine, expr string) (uint, error) {
+	e := strings.ToUpper(strings.TrimSpace(expr))
+
+	if strings.HasPrefix(e, "[") && strings.HasSuffix(e, "]") {
+		addr, err := evalWatch(vm, e[1:len(e)-1])
+		if err != nil {
+			return 0, err
+		}
+		return vm.Memory[addr%chip8.MemorySize], nil
+	}
+
+	switch e {
+	case "I":
+		return vm.I, nil
+	case "PC":
+		return vm.PC, nil
+	case "SP":
+		return vm.SP, nil
+	case "DT":
+		return vm.DT, nil
+	case "ST":
+		return vm.ST, nil
+	}
+
+	if len(e) == 2 && e[0] == 'V' {
+		if x, err := strconv.ParseUint(e[1:], 16, 4); err == nil {
+			return vm.V[x], nil
+		}
+	}
+	if n, err := strconv.ParseUint(e, 0, 16); err == nil {
+		return uint(n), nil
+	}
+
+	return 0, fmt.Errorf("invalid watch expression: %s", expr)
+}
+
+// debugger pauses the virtual machine at breakpoints and watchpoints.
+// Its session is changed by the control API while the virtual machine
+// runs, so it is guarded by a mutex.
+type debugger struct {
+	mutex   sync.Mutex
+	session DebugSession
+	path    string
+
+	// flags are the breakpoints, watchpoints, and watches given on the
+	// command line, which are added to the session of every ROM
+	flags DebugSession
+
+	// values are the values of the watchpoints before the last
+	// instruction, which ran at lastPC
+	values map[uint]uint
+	lastPC uint
+
+	// A breakpoint is skipped once when resuming from it
+	resumed bool
+
+	// runTo is the address of a temporary breakpoint, which is removed
+	// once it is hit, or -1 if there is none
+	runTo int
+
+	// hit is set once the debugger pauses the virtual machine, for the
+	// emulator to show that it is paused
+	hit int32
+}
+
+func newDebugger(flags DebugSession) *debugger {
+	return &debugger{flags: flags, values: map[uint]uint{}, runTo: -1}
+}
+
+// breaks reports whether the virtual machine should pause before it
+// runs its next instruction, since the instruction is at a breakpoint,
+// or the last one changed a watchpoint. It must be called before every
+// cycle, from the goroutine running the virtual machine.
+func (d *debugger) breaks(vm *chip8.VirtualMachine) bool {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	hit := false
+	for _, addr := range d.session.Watchpoints {
+		old, ok := d.values[addr]
+		value := vm.Memory[addr%chip8.MemorySize]
+		d.values[addr] = value
+
+		if ok && value != old {
+			debugLog.Infof(
+				"watchpoint 0x%.3X changed from 0x%.2X to 0x%.2X at 0x%.3X",
+				addr, old, value, d.lastPC,
+			)
+			hit = true
+		}
+	}
+
+	if !d.resumed && hasAddr(d.session.Breakpoints, vm.PC) {
+		debugLog.Infof("breakpoint at 0x%.3X", vm.PC)
+		hit = true
+	} else if d.runTo >= 0 && vm.PC == uint(d.runTo) {
+		debugLog.Infof("ran to 0x%.3X", vm.PC)
+		hit = true
+	}
+	if hit {
+		d.runTo = -1
+	}
+	d.resumed = hit
+	d.lastPC = vm.PC
+
+	if hit {
+		for _, w := range d.session.Watches {
+			if value, err := evalWatch(vm, w); err != nil {
+				debugLog.Error(err)
+			} else {
+				debugLog.Infof("%s = 0x%.2X", w, value)
+			}
+		}
+	}
+	return hit
+}
+
+// runToAddr sets a temporary breakpoint at an address, which pauses
+// the virtual machine the next time it gets there, unless it pauses
+// elsewhere first.
+func (d *debugger) runToAddr(addr uint) {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	d.runTo = int(addr)
+}
+
+// getSession returns a copy of the session of the debugger.
+func (d *debugger) getSession() DebugSession {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	return DebugSession{
+		Breakpoints: append([]uint(nil), d.session.Breakpoints...),
+		Watchpoints: append([]uint(nil), d.session.Watchpoints...),
+		Watches:     append([]string(nil), d.session.Watches...),
+	}
+}
+
+// setSession replaces the session of the debugger.
+func (d *debugger) setSession(s DebugSession) {
+	d.mutex.Lock()
+	defer d.mutex.Unlock()
+
+	d.session = s
+	d.values = map[uint]uint{}
+}
+
+// debugPath returns the path of the debugger session of the running
+// ROM.
+func (emu *Emulator) debugPath() string {
+	return filepath.Join(emu.cfg.StateDir, emu.romName()+".debug.json")
+}
+
+// loadDebugSession restores the last debugger session of the running
+// ROM, along with the breakpoints, watchpoints, and watches given on
+// the command line.
+func (emu *Emulator) loadDebugSession() {
+	d := emu.debugger
+	if d == nil {
+		return
+	}
+
+	var s DebugSession
+	path := emu.debugPath()
+	data, err := ioutil.ReadFile(path)
+	if err == nil {
+		if err := json.Unmarshal(data, &s); err != nil {
+			debugLog.Error(err)
+			s = DebugSession{}
+		} else if err := s.validate(); err != nil {
+			debugLog.Error(err)
+			s = DebugSession{}
+		} else {
+			debugLog.Info("restored debugger session from", path)
+		}
+	} else if !os.IsNotExist(err) {
+		debugLog.Error(err)
+	}
+	s.merge(d.flags)
+
+	d.setSession(s)
+	d.mutex.Lock()
+	d.path = path
+	d.mutex.Unlock()
+}
+
+// saveDebugSession saves the debugger session of the running ROM.
+func (emu *Emulator) saveDebugSession() {
+	d := emu.debugger
+	if d == nil {
+		return
+	}
+
+	d.mutex.Lock()
+	path := d.path
+	d.mutex.Unlock()
+	if path == "" {
+		return
+	}
+
+	data, err := json.MarshalIndent(d.getSession(), "", "  ")
+	if err != nil {
+		debugLog.Error(err)
+		return
+	}
+
+	if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+		debugLog.Error(err)
+		return
+	}
+	if err := ioutil.WriteFile(path, data, 0644); err != nil {
+		debugLog.Error(err)
+	}
+}
@@ -0,0 +1,318 @@
+package ch8
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Decompiler
+//=====================================================================
+
+// Kinds of labels of a decompiled program, which tell what the label
+// points at.
+const (
+	labelJump = iota
+	labelCall
+	labelData
+)
+
+// dataLineSize is the number of bytes per line of a block of data in
+// decompiled source.
+const dataLineSize = 8
+
+// controlFlow is the result of tracing the control flow of a program:
+// the instructions it can reach from its start, and the addresses its
+// instructions refer to.
+type controlFlow struct {
+	program []byte
+
+	// starts marks the offsets where reached instructions start.
+	starts []bool
+
+	// targets are the addresses of memory which instructions jump to,
+	// call, or point I at, by the kind of label they need.
+	targets map[uint]int
+
+	// refs are the addresses of the instructions which refer to each
+	// target, in no particular order.
+	refs map[uint][]uint
+}
+
+// traceControlFlow follows every path of a program from its start,
+// through jumps, calls, and skips, to find which bytes of the program
+// are instructions. Bytes which are never reached are data, or code
+// only reached through computed jumps (BNNN), which are followed to
+// their base address only.
+func traceControlFlow(program []byte) *controlFlow {
+	flow := &controlFlow{
+		program: program,
+		starts:  make([]bool, len(program)),
+		targets: map[uint]int{},
+		refs:    map[uint][]uint{},
+	}
+
+	pending := []uint{chip8.ProgramStartAddress}
+	for len(pending) > 0 {
+		addr := pending[len(pending)-1]
+		pending = pending[:len(pending)-1]
+
+		i := int(addr) - chip8.ProgramStartAddress
+		if i < 0 || i+1 >= len(program) || flow.starts[i] {
+			continue
+		}
+
+		in := Decode(uint(program[i])<<8 | uint(program[i+1]))
+		if !in.Valid() {
+			continue
+		}
+		flow.starts[i] = true
+
+		switch in.Opcode >> 12 {
+		case 0x1, 0x2, 0xa, 0xb:
+			flow.refs[in.NNN] = append(flow.refs[in.NNN], addr)
+		}
+
+		next := addr + 2
+		switch {
+		case in.Opcode == 0x00ee || in.Opcode == 0x00fd:
+			// The path ends here
+		case in.Opcode>>12 == 0x1:
+			flow.targets[in.NNN] = labelJump
+			pending = append(pending, in.NNN)
+		case in.Opcode>>12 == 0x2:
+			flow.targets[in.NNN] = labelCall
+			pending = append(pending, in.NNN, next)
+		case in.Opcode>>12 == 0xb:
+			flow.targets[in.NNN] = labelJump
+			pending = append(pending, in.NNN)
+		case in.skips():
+			pending = append(pending, next, next+2)
+		case in.Opcode>>12 == 0xa:
+			if _, ok := flow.targets[in.NNN]; !ok {
+				flow.targets[in.NNN] = labelData
+			}
+			pending = append(pending, next)
+		default:
+			pending = append(pending, next)
+		}
+	}
+
+	return flow
+}
+
+// skips reports whether the instruction may skip the next one.
+func (in Instruction) skips() bool {
+	switch in.Opcode >> 12 {
+	case 0x3, 0x4, 0x5, 0x9:
+		return true
+	case 0xe:
+		return in.KK == 0x9e || in.KK == 0xa1
+	}
+	return false
+}
+
+// boundaries returns the offsets where the decompiled source can place
+// a label: the start of every instruction and every byte of data.
+func (flow *controlFlow) boundaries() []bool {
+	bounds := make([]bool, len(flow.program))
+	for i := 0; i < len(flow.program); {
+		bounds[i] = true
+		if flow.starts[i] {
+			i += 2
+		} else {
+			i++
+		}
+	}
+	return bounds
+}
+
+// Decompile writes a CHIP-8 program as Octo source, which compiles
+// back into the same bytes.
+//
+// Instructions reached from the start of the program are written as
+// Octo statements, and the rest of the program as blocks of data bytes.
+// The addresses which instructions refer to are given labels: main for
+// the start of the program, and names such as sub_2A4, label_2B0, and
+// data_2F0 for the targets of calls, jumps, and I.
+func Decompile(w io.Writer, program []byte) error {
+	flow := traceControlFlow(program)
+	bounds := flow.boundaries()
+
+	// Only addresses within the program, at a boundary, can be labeled
+	labels := map[uint]string{}
+	for addr, kind := range flow.targets {
+		i := int(addr) - chip8.ProgramStartAddress
+		if i < 0 || i >= len(program) || !bounds[i] {
+			continue
+		}
+		labels[addr] = labelName(addr, kind)
+	}
+	labels[chip8.ProgramStartAddress] = "main"
+
+	b := bufio.NewWriter(w)
+	var data []string
+
+	flush := func() {
+		if len(data) > 0 {
+			fmt.Fprintf(b, "\t%s\n", strings.Join(data, " "))
+			data = data[:0]
+		}
+	}
+
+	for i := 0; i < len(program); {
+		addr := uint(chip8.ProgramStartAddress + i)
+		if label, ok := labels[addr]; ok {
+			flush()
+			if i > 0 {
+				b.WriteByte('\n')
+			}
+			fmt.Fprintf(b, ": %s\n", label)
+		}
+
+		if flow.starts[i] {
+			flush()
+			in := Decode(uint(program[i])<<8 | uint(program[i+1]))
+			fmt.Fprintf(b, "\t%s\n", in.octo(labels))
+			i += 2
+			continue
+		}
+
+		data = append(data, fmt.Sprintf("0x%.2X", program[i]))
+		if len(data) == dataLineSize {
+			flush()
+		}
+		i++
+	}
+	flush()
+
+	return b.Flush()
+}
+
+// labelName returns the name of a label of the given kind.
+func labelName(addr uint, kind int) string {
+	switch kind {
+	case labelCall:
+		return fmt.Sprintf("sub_%.3X", addr)
+	case labelData:
+		return fmt.Sprintf("data_%.3X", addr)
+	}
+	return fmt.Sprintf("label_%.3X", addr)
+}
+
+// octo returns the instruction as an Octo statement, referring to the
+// addresses of memory by their labels where they have one.
+//
+// The conditions of Octo statements tell when the next statement runs,
+// so they are the opposite of the conditions of skip instructions.
+func (in Instruction) octo(labels map[uint]string) string {
+	x, y, n := in.X, in.Y, in.N
+	kk, nnn := in.KK, in.NNN
+
+	ref := func(addr uint) string {
+		if label, ok := labels[addr]; ok {
+			return label
+		}
+		return fmt.Sprintf("0x%.3X", addr)
+	}
+
+	switch in.Opcode >> 12 {
+	case 0x0:
+		if nnn&0xff0 == 0x0c0 {
+			return fmt.Sprintf("scroll-down %d", n)
+		}
+		switch nnn {
+		case 0x0e0:
+			return "clear"
+		case 0x0ee:
+			return "return"
+		case 0x0fb:
+			return "scroll-right"
+		case 0x0fc:
+			return "scroll-left"
+		case 0x0fd:
+			return "exit"
+		case 0x0fe:
+			return "lores"
+		case 0x0ff:
+			return "hires"
+		}
+	case 0x1:
+		return "jump " + ref(nnn)
+	case 0x2:
+		// Subroutines are called by their name
+		if label, ok := labels[nnn]; ok {
+			return label
+		}
+		return fmt.Sprintf(":call 0x%.3X", nnn)
+	case 0x3:
+		return fmt.Sprintf("if v%x != 0x%.2X then", x, kk)
+	case 0x4:
+		return fmt.Sprintf("if v%x == 0x%.2X then", x, kk)
+	case 0x5:
+		return fmt.Sprintf("if v%x != v%x then", x, y)
+	case 0x6:
+		return fmt.Sprintf("v%x := 0x%.2X", x, kk)
+	case 0x7:
+		return fmt.Sprintf("v%x += 0x%.2X", x, kk)
+	case 0x8:
+		ops := map[uint]string{
+			0x0: ":=", 0x1: "|=", 0x2: "&=", 0x3: "^=", 0x4: "+=",
+			0x5: "-=", 0x6: ">>=", 0x7: "=-", 0xe: "<<=",
+		}
+		return fmt.Sprintf("v%x %s v%x", x, ops[n], y)
+	case 0x9:
+		return fmt.Sprintf("if v%x == v%x then", x, y)
+	case 0xa:
+		return "i := " + ref(nnn)
+	case 0xb:
+		return "jump0 " + ref(nnn)
+	case 0xc:
+		return fmt.Sprintf("v%x := random 0x%.2X", x, kk)
+	case 0xd:
+		return fmt.Sprintf("sprite v%x v%x %d", x, y, n)
+	case 0xe:
+		if kk == 0x9e {
+			return fmt.Sprintf("if v%x -key then", x)
+		}
+		return fmt.Sprintf("if v%x key then", x)
+	case 0xf:
+		switch kk {
+		case 0x07:
+			return fmt.Sprintf("v%x := delay", x)
+		case 0x0a:
+			return fmt.Sprintf("v%x := key", x)
+		case 0x15:
+			return fmt.Sprintf("delay := v%x", x)
+		case 0x18:
+			return fmt.Sprintf("buzzer := v%x", x)
+		case 0x1e:
+			return fmt.Sprintf("i += v%x", x)
+		case 0x29:
+			return fmt.Sprintf("i := hex v%x", x)
+		case 0x30:
+			return fmt.Sprintf("i := bighex v%x", x)
+		case 0x33:
+			return fmt.Sprintf("bcd v%x", x)
+		case 0x55:
+			return fmt.Sprintf("save v%x", x)
+		case 0x65:
+			return fmt.Sprintf("load v%x", x)
+		case 0x75:
+			if x < chip8.NumberOfFlags {
+				return fmt.Sprintf("saveflags v%x", x)
+			}
+		case 0x85:
+			if x < chip8.NumberOfFlags {
+				return fmt.Sprintf("loadflags v%x", x)
+			}
+		}
+	}
+
+	// Only valid instructions are decompiled as statements
+	return fmt.Sprintf("0x%.2X 0x%.2X", in.Opcode>>8, in.KK)
+}
@@ -0,0 +1,61 @@
+package ch8
+
+import (
+	"fmt"
+	"io"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// ROM Diffs
+//=====================================================================
+
+// DiffROMs lists the instructions which differ between two versions of
+// a ROM, side by side with the disassembly of both, such as:
+//
+//	0x2A4  6A02  LD VA, 0x02            | 6A04  LD VA, 0x04
+//
+// Instructions past the end of the shorter ROM are shown as missing.
+// It returns the number of bytes which differ.
+func DiffROMs(w io.Writer, a, b []byte) (int, error) {
+	size := len(a)
+	if len(b) > size {
+		size = len(b)
+	}
+
+	diffs := 0
+	for i := 0; i < size; i += 2 {
+		changed := 0
+		for j := i; j < i+2 && j < size; j++ {
+			if j >= len(a) || j >= len(b) || a[j] != b[j] {
+				changed++
+			}
+		}
+		if changed == 0 {
+			continue
+		}
+		diffs += changed
+
+		line := fmt.Sprintf("0x%.3X  %-28s | %s", chip8.ProgramStartAddress+i, diffSide(a, i), diffSide(b, i))
+		if _, err := fmt.Fprintln(w, line); err != nil {
+			return diffs, err
+		}
+	}
+
+	return diffs, nil
+}
+
+// diffSide returns the opcode and mnemonic of the instruction at an
+// offset of a ROM, or dashes if the ROM ends before it.
+func diffSide(program []byte, i int) string {
+	if i >= len(program) {
+		return "----"
+	}
+
+	opcode := uint(program[i]) << 8
+	if i+1 < len(program) {
+		opcode |= uint(program[i+1])
+	}
+	return fmt.Sprintf("%.4X  %s", opcode, Decode(opcode))
+}
@@ -0,0 +1,455 @@
+package ch8
+
+import (
+	"fmt"
+	"io"
+	"sort"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Disassembly
+//=====================================================================
+
+// Instruction is a decoded CHIP-8 instruction.
+type Instruction struct {
+	Opcode uint
+	X      uint
+	Y      uint
+	N      uint
+	KK     uint
+	NNN    uint
+}
+
+// DisasmOptions are the options of a disassembly listing.
+type DisasmOptions struct {
+	// Pseudocode appends a pseudocode comment to each instruction,
+	// such as "; V1 |= V2".
+	Pseudocode bool
+
+	// XRefs adds a comment above each instruction which other
+	// instructions jump to, call, or point I at, listing where they
+	// are, such as "; xrefs: 0x204 (CALL), 0x2A0 (JP)".
+	XRefs bool
+
+	// Coverage lists the bytes which a session never executed as
+	// blocks of data, such as "DB 0xFF, 0x00", rather than as
+	// instructions.
+	Coverage *chip8.Coverage
+}
+
+// instructionText is the text which describes an instruction.
+type instructionText struct {
+	mnemonic    string
+	explanation string
+	pseudocode  string
+}
+
+// Decode decodes the fields of an opcode.
+func Decode(opcode uint) Instruction {
+	return Instruction{
+		Opcode: opcode,
+		X:      (opcode >> 8) & 0xf,
+		Y:      (opcode >> 4) & 0xf,
+		N:      opcode & 0xf,
+		KK:     opcode & 0xff,
+		NNN:    opcode & 0xfff,
+	}
+}
+
+// String returns the mnemonic of the instruction, such as "LD V1, 0x2A",
+// or "DW 0xABCD" if the opcode is not an instruction.
+func (in Instruction) String() string {
+	return in.describe().mnemonic
+}
+
+// Explain returns a plain-English explanation of the instruction, such
+// as "Set V1 to 0x2A".
+func (in Instruction) Explain() string {
+	return in.describe().explanation
+}
+
+// Pseudocode returns the instruction as a line of pseudocode, such as
+// "V1 |= V2".
+func (in Instruction) Pseudocode() string {
+	return in.describe().pseudocode
+}
+
+// Valid reports whether the opcode is an instruction of the virtual
+// machine.
+func (in Instruction) Valid() bool {
+	return in.describe().explanation != ""
+}
+
+func (in Instruction) describe() instructionText {
+	x, y, n := in.X, in.Y, in.N
+	kk, nnn := in.KK, in.NNN
+
+	switch in.Opcode >> 12 {
+	case 0x0:
+		if nnn&0xff0 == 0x0c0 {
+			return instructionText{
+				fmt.Sprintf("SCD %d", n),
+				fmt.Sprintf("Scroll the screen down by %d pixels", n),
+				fmt.Sprintf("scroll_down(%d)", n),
+			}
+		}
+		switch nnn {
+		case 0x0e0:
+			return instructionText{"CLS", "Clear the screen", "clear()"}
+		case 0x0ee:
+			return instructionText{"RET", "Return from the subroutine", "return"}
+		case 0x0fb:
+			return instructionText{"SCR", "Scroll the screen right by 4 pixels", "scroll_right(4)"}
+		case 0x0fc:
+			return instructionText{"SCL", "Scroll the screen left by 4 pixels", "scroll_left(4)"}
+		case 0x0fd:
+			return instructionText{"EXIT", "Exit the program with V0 as its exit code", "exit(V0)"}
+		case 0x0fe:
+			return instructionText{"LOW", "Switch to the 64x32 low resolution", "lores()"}
+		case 0x0ff:
+			return instructionText{"HIGH", "Switch to the 128x64 high resolution", "hires()"}
+		}
+	case 0x1:
+		return instructionText{
+			fmt.Sprintf("JP 0x%.3X", nnn),
+			fmt.Sprintf("Jump to 0x%.3X", nnn),
+			fmt.Sprintf("goto 0x%.3X", nnn),
+		}
+	case 0x2:
+		return instructionText{
+			fmt.Sprintf("CALL 0x%.3X", nnn),
+			fmt.Sprintf("Call the subroutine at 0x%.3X", nnn),
+			fmt.Sprintf("call 0x%.3X", nnn),
+		}
+	case 0x3:
+		return instructionText{
+			fmt.Sprintf("SE V%X, 0x%.2X", x, kk),
+			fmt.Sprintf("Skip the next instruction if V%X is 0x%.2X", x, kk),
+			fmt.Sprintf("if V%X == 0x%.2X then skip", x, kk),
+		}
+	case 0x4:
+		return instructionText{
+			fmt.Sprintf("SNE V%X, 0x%.2X", x, kk),
+			fmt.Sprintf("Skip the next instruction unless V%X is 0x%.2X", x, kk),
+			fmt.Sprintf("if V%X != 0x%.2X then skip", x, kk),
+		}
+	case 0x5:
+		if n == 0x0 {
+			return instructionText{
+				fmt.Sprintf("SE V%X, V%X", x, y),
+				fmt.Sprintf("Skip the next instruction if V%X equals V%X", x, y),
+				fmt.Sprintf("if V%X == V%X then skip", x, y),
+			}
+		}
+	case 0x6:
+		return instructionText{
+			fmt.Sprintf("LD V%X, 0x%.2X", x, kk),
+			fmt.Sprintf("Set V%X to 0x%.2X", x, kk),
+			fmt.Sprintf("V%X = 0x%.2X", x, kk),
+		}
+	case 0x7:
+		return instructionText{
+			fmt.Sprintf("ADD V%X, 0x%.2X", x, kk),
+			fmt.Sprintf("Add 0x%.2X to V%X", kk, x),
+			fmt.Sprintf("V%X += 0x%.2X", x, kk),
+		}
+	case 0x8:
+		switch n {
+		case 0x0:
+			return instructionText{
+				fmt.Sprintf("LD V%X, V%X", x, y),
+				fmt.Sprintf("Copy V%X into V%X", y, x),
+				fmt.Sprintf("V%X = V%X", x, y),
+			}
+		case 0x1:
+			return instructionText{
+				fmt.Sprintf("OR V%X, V%X", x, y),
+				fmt.Sprintf("Set V%X to V%X OR V%X", x, x, y),
+				fmt.Sprintf("V%X |= V%X", x, y),
+			}
+		case 0x2:
+			return instructionText{
+				fmt.Sprintf("AND V%X, V%X", x, y),
+				fmt.Sprintf("Set V%X to V%X AND V%X", x, x, y),
+				fmt.Sprintf("V%X &= V%X", x, y),
+			}
+		case 0x3:
+			return instructionText{
+				fmt.Sprintf("XOR V%X, V%X", x, y),
+				fmt.Sprintf("Set V%X to V%X XOR V%X", x, x, y),
+				fmt.Sprintf("V%X ^= V%X", x, y),
+			}
+		case 0x4:
+			return instructionText{
+				fmt.Sprintf("ADD V%X, V%X", x, y),
+				fmt.Sprintf("Add V%X to V%X, setting VF to 1 if it carries", y, x),
+				fmt.Sprintf("V%X += V%X; VF = carry", x, y),
+			}
+		case 0x5:
+			return instructionText{
+				fmt.Sprintf("SUB V%X, V%X", x, y),
+				fmt.Sprintf("Subtract V%X from V%X, setting VF to 0 if it borrows", y, x),
+				fmt.Sprintf("VF = V%X >= V%X; V%X -= V%X", x, y, x, y),
+			}
+		case 0x6:
+			return instructionText{
+				fmt.Sprintf("SHR V%X", x),
+				fmt.Sprintf("Shift V%X right by 1, moving the lowest bit into VF", x),
+				fmt.Sprintf("VF = V%X & 1; V%X >>= 1", x, x),
+			}
+		case 0x7:
+			return instructionText{
+				fmt.Sprintf("SUBN V%X, V%X", x, y),
+				fmt.Sprintf("Set V%X to V%X minus V%X, setting VF to 0 if it borrows", x, y, x),
+				fmt.Sprintf("VF = V%X >= V%X; V%X = V%X - V%X", y, x, x, y, x),
+			}
+		case 0xe:
+			return instructionText{
+				fmt.Sprintf("SHL V%X", x),
+				fmt.Sprintf("Shift V%X left by 1, moving the highest bit into VF", x),
+				fmt.Sprintf("VF = V%X >> 7; V%X <<= 1", x, x),
+			}
+		}
+	case 0x9:
+		if n == 0x0 {
+			return instructionText{
+				fmt.Sprintf("SNE V%X, V%X", x, y),
+				fmt.Sprintf("Skip the next instruction unless V%X equals V%X", x, y),
+				fmt.Sprintf("if V%X != V%X then skip", x, y),
+			}
+		}
+	case 0xa:
+		return instructionText{
+			fmt.Sprintf("LD I, 0x%.3X", nnn),
+			fmt.Sprintf("Point I at 0x%.3X", nnn),
+			fmt.Sprintf("I = 0x%.3X", nnn),
+		}
+	case 0xb:
+		return instructionText{
+			fmt.Sprintf("JP V0, 0x%.3X", nnn),
+			fmt.Sprintf("Jump to 0x%.3X plus V0", nnn),
+			fmt.Sprintf("goto 0x%.3X + V0", nnn),
+		}
+	case 0xc:
+		return instructionText{
+			fmt.Sprintf("RND V%X, 0x%.2X", x, kk),
+			fmt.Sprintf("Set V%X to a random number AND 0x%.2X", x, kk),
+			fmt.Sprintf("V%X = random() & 0x%.2X", x, kk),
+		}
+	case 0xd:
+		if n == 0x0 {
+			return instructionText{
+				fmt.Sprintf("DRW V%X, V%X, 0", x, y),
+				fmt.Sprintf(
+					"Draw the 16x16 sprite at I at (V%X, V%X), setting VF to 1 if it erases a pixel",
+					x, y,
+				),
+				fmt.Sprintf("draw16(V%X, V%X); VF = collision", x, y),
+			}
+		}
+		return instructionText{
+			fmt.Sprintf("DRW V%X, V%X, %d", x, y, n),
+			fmt.Sprintf(
+				"Draw the %d-row sprite at I at (V%X, V%X), setting VF to 1 if it erases a pixel",
+				n, x, y,
+			),
+			fmt.Sprintf("draw(V%X, V%X, %d); VF = collision", x, y, n),
+		}
+	case 0xe:
+		switch kk {
+		case 0x9e:
+			return instructionText{
+				fmt.Sprintf("SKP V%X", x),
+				fmt.Sprintf("Skip the next instruction if the key in V%X is held", x),
+				fmt.Sprintf("if key(V%X) then skip", x),
+			}
+		case 0xa1:
+			return instructionText{
+				fmt.Sprintf("SKNP V%X", x),
+				fmt.Sprintf("Skip the next instruction unless the key in V%X is held", x),
+				fmt.Sprintf("if !key(V%X) then skip", x),
+			}
+		}
+	case 0xf:
+		switch kk {
+		case 0x07:
+			return instructionText{
+				fmt.Sprintf("LD V%X, DT", x),
+				fmt.Sprintf("Copy the delay timer into V%X", x),
+				fmt.Sprintf("V%X = DT", x),
+			}
+		case 0x0a:
+			return instructionText{
+				fmt.Sprintf("LD V%X, K", x),
+				fmt.Sprintf("Wait for a key to be pressed, and store it in V%X", x),
+				fmt.Sprintf("V%X = wait_key()", x),
+			}
+		case 0x15:
+			return instructionText{
+				fmt.Sprintf("LD DT, V%X", x),
+				fmt.Sprintf("Set the delay timer to V%X", x),
+				fmt.Sprintf("DT = V%X", x),
+			}
+		case 0x18:
+			return instructionText{
+				fmt.Sprintf("LD ST, V%X", x),
+				fmt.Sprintf("Set the sound timer to V%X, beeping until it runs out", x),
+				fmt.Sprintf("ST = V%X", x),
+			}
+		case 0x1e:
+			return instructionText{
+				fmt.Sprintf("ADD I, V%X", x),
+				fmt.Sprintf("Add V%X to I", x),
+				fmt.Sprintf("I += V%X", x),
+			}
+		case 0x29:
+			return instructionText{
+				fmt.Sprintf("LD F, V%X", x),
+				fmt.Sprintf("Point I at the font sprite of the digit in V%X", x),
+				fmt.Sprintf("I = font(V%X)", x),
+			}
+		case 0x30:
+			return instructionText{
+				fmt.Sprintf("LD HF, V%X", x),
+				fmt.Sprintf("Point I at the large font sprite of the digit in V%X", x),
+				fmt.Sprintf("I = bigfont(V%X)", x),
+			}
+		case 0x33:
+			return instructionText{
+				fmt.Sprintf("LD B, V%X", x),
+				fmt.Sprintf("Store the 3 decimal digits of V%X at I, I+1, and I+2", x),
+				fmt.Sprintf("mem[I..I+2] = bcd(V%X)", x),
+			}
+		case 0x55:
+			return instructionText{
+				fmt.Sprintf("LD [I], V%X", x),
+				fmt.Sprintf("Store V0 to V%X in memory starting at I", x),
+				fmt.Sprintf("mem[I..I+%d] = V0..V%X", x, x),
+			}
+		case 0x65:
+			return instructionText{
+				fmt.Sprintf("LD V%X, [I]", x),
+				fmt.Sprintf("Load V0 to V%X from memory starting at I", x),
+				fmt.Sprintf("V0..V%X = mem[I..I+%d]", x, x),
+			}
+		case 0x75:
+			if x < chip8.NumberOfFlags {
+				return instructionText{
+					fmt.Sprintf("LD R, V%X", x),
+					fmt.Sprintf("Save V0 to V%X in the flag registers", x),
+					fmt.Sprintf("flags[0..%d] = V0..V%X", x, x),
+				}
+			}
+		case 0x85:
+			if x < chip8.NumberOfFlags {
+				return instructionText{
+					fmt.Sprintf("LD V%X, R", x),
+					fmt.Sprintf("Load V0 to V%X from the flag registers", x),
+					fmt.Sprintf("V0..V%X = flags[0..%d]", x, x),
+				}
+			}
+		}
+	}
+
+	return instructionText{mnemonic: fmt.Sprintf("DW 0x%.4X", in.Opcode)}
+}
+
+// Disassemble writes a listing of a CHIP-8 program, with the address,
+// opcode, and mnemonic of each instruction on a line of its own.
+//
+// The program is disassembled two bytes at a time from the start, so
+// data within the program is listed as if it were instructions, unless
+// the coverage of a session tells which bytes were executed.
+func Disassemble(w io.Writer, program []byte, opts DisasmOptions) error {
+	var flow *controlFlow
+	if opts.XRefs {
+		flow = traceControlFlow(program)
+	}
+
+	for i := 0; i < len(program); {
+		addr := uint(chip8.ProgramStartAddress + i)
+
+		// Bytes which were never executed are listed as data, up to the
+		// next instruction or cross-referenced address
+		if opts.Coverage != nil && !opts.Coverage[addr] {
+			n := 1
+			for n < dataLineSize && i+n < len(program) && !opts.Coverage[addr+uint(n)] {
+				if flow != nil && len(flow.refs[addr+uint(n)]) > 0 {
+					break
+				}
+				n++
+			}
+
+			if flow != nil {
+				if xrefs := flow.xrefs(addr); xrefs != "" {
+					if _, err := fmt.Fprintln(w, "; xrefs: "+xrefs); err != nil {
+						return err
+					}
+				}
+			}
+
+			hex := make([]string, n)
+			bytes := make([]string, n)
+			for k, b := range program[i : i+n] {
+				hex[k] = fmt.Sprintf("%.2X", b)
+				bytes[k] = fmt.Sprintf("0x%.2X", b)
+			}
+			line := fmt.Sprintf("0x%.3X  %s  DB %s", addr, strings.Join(hex, ""), strings.Join(bytes, ", "))
+			if _, err := fmt.Fprintln(w, line); err != nil {
+				return err
+			}
+
+			i += n
+			continue
+		}
+
+		opcode := uint(program[i]) << 8
+		if i+1 < len(program) {
+			opcode |= uint(program[i+1])
+		}
+
+		if flow != nil {
+			if xrefs := flow.xrefs(addr, addr+1); xrefs != "" {
+				if _, err := fmt.Fprintln(w, "; xrefs: "+xrefs); err != nil {
+					return err
+				}
+			}
+		}
+
+		in := Decode(opcode)
+		line := fmt.Sprintf("0x%.3X  %.4X  %s", addr, opcode, in)
+		if opts.Pseudocode && in.Valid() {
+			line = fmt.Sprintf("%-28s ; %s", line, in.Pseudocode())
+		}
+
+		if _, err := fmt.Fprintln(w, line); err != nil {
+			return err
+		}
+		i += 2
+	}
+
+	return nil
+}
+
+// xrefs lists the instructions which refer to any of the given
+// addresses, in order of address, along with their mnemonic, such as
+// "0x204 (CALL), 0x2A0 (JP)".
+func (flow *controlFlow) xrefs(addrs ...uint) string {
+	var from []uint
+	for _, addr := range addrs {
+		from = append(from, flow.refs[addr]...)
+	}
+	sort.Slice(from, func(i, j int) bool { return from[i] < from[j] })
+
+	parts := make([]string, len(from))
+	for k, addr := range from {
+		i := int(addr) - chip8.ProgramStartAddress
+		in := Decode(uint(flow.program[i])<<8 | uint(flow.program[i+1]))
+		mnemonic := strings.Fields(in.String())[0]
+		parts[k] = fmt.Sprintf("0x%.3X (%s)", addr, mnemonic)
+	}
+	return strings.Join(parts, ", ")
+}
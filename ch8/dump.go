@@ -0,0 +1,43 @@
+package ch8
+
+import (
+	"os"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Memory Dumps
+//=====================================================================
+
+// dumpMemory saves the whole memory of the virtual machine as a hex
+// dump next to the screenshots. It must be called from the goroutine
+// running the virtual machine.
+func (emu *Emulator) dumpMemory() {
+	if err := os.MkdirAll(emu.cfg.ScreenshotDir, 0755); err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	path := timestampedPath(emu.cfg.ScreenshotDir, emu.cfg.ROM, ".hex")
+	file, err := os.Create(path)
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	data, _ := emu.vm.MemoryRange(0, chip8.MemorySize)
+	err = chip8.DumpMemory(file, data, 0, chip8.DumpHex)
+	if cerr := file.Close(); err == nil {
+		err = cerr
+	}
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+	emuLog.Info("dumped memory to", path)
+}
+
+func (emu *Emulator) requestMemoryDump() {
+	emu.vmChan <- dumpMemoryEvent
+}
@@ -0,0 +1,171 @@
+package ch8
+
+import (
+	"fmt"
+	"image/color"
+	"strings"
+	"sync"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Education Mode
+//=====================================================================
+
+const (
+	// maxLessons is the number of executed instructions listed in
+	// education mode.
+	maxLessons = 6
+
+	// lessonLineHeight is the height (in pixels) of a line of debug
+	// text.
+	lessonLineHeight = 16
+)
+
+// lesson is an instruction executed in education mode, along with the
+// registers it changed.
+type lesson struct {
+	pc      uint
+	in      Instruction
+	changes []string
+	repeats int
+}
+
+// registers are the registers of the virtual machine compared before
+// and after each instruction in education mode.
+type registers struct {
+	V  [chip8.NumberOfRegisters]uint
+	I  uint
+	SP uint
+	DT uint
+	ST uint
+}
+
+func registersOf(vm *chip8.VirtualMachine) registers {
+	return registers{V: vm.V, I: vm.I, SP: vm.SP, DT: vm.DT, ST: vm.ST}
+}
+
+// education is a mode for students, which slows the virtual machine
+// down to a few instructions per second and explains each instruction
+// it executes in plain English, along with the registers it changed.
+type education struct {
+	mutex   sync.Mutex
+	lessons []lesson
+}
+
+// run runs a single CPU cycle of the virtual machine, and records the
+// instruction it executed.
+func (e *education) run(vm *chip8.VirtualMachine) error {
+	pc := vm.PC
+	in := Decode((vm.Memory[pc] << 8) | vm.Memory[pc+1])
+
+	before := registersOf(vm)
+	err := vm.RunCycle()
+	after := registersOf(vm)
+
+	var changes []string
+	for i := range before.V {
+		if before.V[i] != after.V[i] {
+			changes = append(changes, fmt.Sprintf("V%X: 0x%.2X -> 0x%.2X", i, before.V[i], after.V[i]))
+		}
+	}
+	if before.I != after.I {
+		changes = append(changes, fmt.Sprintf("I: 0x%.3X -> 0x%.3X", before.I, after.I))
+	}
+	if before.SP != after.SP {
+		changes = append(changes, fmt.Sprintf("SP: %d -> %d", before.SP, after.SP))
+	}
+
+	// The timers count down on their own, so they are only compared
+	// when the instruction sets them
+	if in.Opcode>>12 == 0xf && (in.KK == 0x15 || in.KK == 0x18) {
+		if before.DT != after.DT {
+			changes = append(changes, fmt.Sprintf("DT: 0x%.2X -> 0x%.2X", before.DT, after.DT))
+		}
+		if before.ST != after.ST {
+			changes = append(changes, fmt.Sprintf("ST: 0x%.2X -> 0x%.2X", before.ST, after.ST))
+		}
+	}
+
+	e.record(lesson{pc: pc, in: in, changes: changes})
+	return err
+}
+
+// record adds a lesson to the list, where an instruction which runs
+// over and over without changing anything (such as waiting for a key)
+// is only counted.
+func (e *education) record(l lesson) {
+	e.mutex.Lock()
+	defer e.mutex.Unlock()
+
+	if n := len(e.lessons); n > 0 {
+		last := &e.lessons[n-1]
+		if last.pc == l.pc && last.in == l.in && len(l.changes) == 0 && len(last.changes) == 0 {
+			last.repeats++
+			return
+		}
+	}
+
+	e.lessons = append(e.lessons, l)
+	if len(e.lessons) > maxLessons {
+		e.lessons = e.lessons[1:]
+	}
+}
+
+// draw draws the most recently executed instructions along the bottom
+// of the screen, with the newest one last.
+func (e *education) draw(screen *ebiten.Image) {
+	e.mutex.Lock()
+	lessons := append([]lesson(nil), e.lessons...)
+	e.mutex.Unlock()
+
+	if len(lessons) == 0 {
+		return
+	}
+
+	size := screen.Bounds().Size()
+	h := 2 * lessonLineHeight * len(lessons)
+	y := size.Y - h
+
+	ebitenutil.DrawRect(screen, 0, float64(y), float64(size.X), float64(h), color.RGBA{0, 0, 0, 0xc0})
+
+	for i, l := range lessons {
+		marker := " "
+		if i == len(lessons)-1 {
+			marker = ">"
+		}
+
+		first := fmt.Sprintf("%s 0x%.3X  %.4X  %-16s %s", marker, l.pc, l.in.Opcode, l.in, strings.Join(l.changes, ", "))
+		explanation := l.in.Explain()
+		if explanation == "" {
+			explanation = "Not an instruction"
+		}
+		if l.repeats > 0 {
+			explanation += fmt.Sprintf(" (x%d)", l.repeats+1)
+		}
+
+		ebitenutil.DebugPrintAt(screen, first, 0, y)
+		ebitenutil.DebugPrintAt(screen, "    "+explanation, 0, y+lessonLineHeight)
+		y += 2 * lessonLineHeight
+	}
+}
+
+// vmSpeed returns the speed multiplier of the virtual machine, which
+// education mode turns into a few instructions per second.
+func (emu *Emulator) vmSpeed() float64 {
+	if emu.education == nil {
+		return emu.cfg.Speed
+	}
+	return emu.cfg.Speed * emu.cfg.EducationRate * chip8.DefaultHzVM.Seconds()
+}
+
+// runCycle runs a single CPU cycle of the virtual machine.
+func (emu *Emulator) runCycle() error {
+	if emu.education == nil {
+		return emu.vm.RunCycle()
+	}
+	return emu.education.run(emu.vm)
+}
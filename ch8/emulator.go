@@ -2,211 +2,980 @@ package ch8
 
 import (
 	"fmt"
+	"image"
 	"image/color"
-	"log"
-	"math"
+	"os"
+	"strings"
+	"sync"
+	"sync/atomic"
 	"time"
 
 	"github.com/hajimehoshi/ebiten/v2"
-	"github.com/hajimehoshi/ebiten/v2/audio"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
 )
 
 //=====================================================================
-// Constants
+// Emulator
 //=====================================================================
 
 const (
-	// DefaultScale is the default scale factor of the CHIP-8 screen.
-	DefaultScale = 10
+	playEvent       = "play"
+	pauseEvent      = "pause"
+	resetEvent      = "reset"
+	saveStateEvent  = "save-state"
+	loadStateEvent  = "load-state"
+	loadROMEvent    = "load-rom"
+	speedEvent      = "speed"
+	quirksEvent     = "quirks"
+	dumpMemoryEvent = "dump-memory"
+	bugReportEvent  = "bug-report"
+	snapshotEvent   = "snapshot"
+)
 
-	// DefaultVolume is the default volume of the CHIP-8 beeper.
-	//
-	// The volume ranges within [0.0, 1.0].
-	DefaultVolume = 0.5
+// Emulator is the CHIP-8 emulator.
+type Emulator struct {
+	cfg        Config
+	vm         *chip8.VirtualMachine
+	beeper     Beeper
+	keymaps    []chip8.Keymap
+	keymap     int
+	keyLabels  *keyLabels
+	hotkeys    map[ebiten.Key]*action
+	gamepad    *gamepadMap
+	inputs     []InputSource
+	rebinder   rebinder
+	macros     macroPlayer
+	touch      touchKeypad
+	menu       menuBar
+	pauseMenu  pauseMenu
+	cmdPalette commandPalette
+	vmChan     chan string
+	snapshots  chan chip8.Snapshot
+	renderer   *renderer
+	filter     *filter
+	hud        hud
+	frameGraph *frameGraph
+	watchdog   stallWatchdog
+	keypad     keypadOverlay
+	registers  registerOverlay
+	grid       gridOverlay
+	palettes   []chip8.Palette
+	palette    int
+	visualBell bool
+	ringing    bool
+	redraw     bool
+	held       bool
+	frame      uint64
+	paused     bool
+	quit       bool
+	unfocused  bool
+	muted      int32
+	turbo      int32
+	crashed    int32
+	crash      *vmCrash
+	history    instructionTrace
+	title      string
+	screenSize image.Point
+	display    image.Point
+	nextROM    []byte
+	romHash    string
+	commands   chan func()
+	onFrame    []chip8.FrameFunc
+	keyMutex   sync.Mutex
+	frameKeys  [chip8.NumberOfKeys]bool
 
-	// DefaultFrequency is the default frequency of the CHIP-8 beeper.
-	DefaultFrequency = 440
+	wavMutex  sync.Mutex
+	wav       *wavRecorder
+	gif       *gifRecorder
+	video     *videoRecorder
+	trace     *traceRecorder
+	stream    *streamServer
+	netplay   *netplay
+	tas       *tasEditor
+	education *education
+	compare   *comparison
+	attract   *attractMode
+	save      *saveRAM
+	debugger  *debugger
+	search    *MemorySearch
+	safe      *flashLimiter
+}
 
-	// DefaultSampleRate is the default sample rate of the CHIP-8
-	// beeper.
-	DefaultSampleRate = 44100
+// NewEmulator creates a new CHIP-8 emulator instance.
+func NewEmulator(cfg Config) *Emulator {
+	// The configuration may not have been resolved by the caller
+	cfg.Resolve()
 
-	// DefaultTPS is the default ticks per second of the emulator.
-	DefaultTPS = 60
+	// Initialize audio
+	var beeper Beeper = &NullBeeper{}
+	muted := cfg.NoAudio || cfg.Volume == 0.0
 
-	// DefaultHzVM is the default speed (in hertz) in which to run a
-	// CPU cycle of the CHIP-8 virtual machine.
-	DefaultHzVM = 2 * time.Millisecond
+	// The audio device is opened once the beeper first sounds
+	if !cfg.NoAudio {
+		beeper = newLazyBeeper(cfg)
+	}
 
-	// DefaultHzI is the default speed (in hertz) in which to update
-	// the IO timers and audio.
-	DefaultHzIO = 16 * time.Millisecond
-)
+	// Fall back to a visual bell whenever the beeper can't be heard
+	visualBell := cfg.VisualBell || muted
 
-//=====================================================================
-// Audio
-//=====================================================================
+	// Initialize input
+	keymaps, err := cfg.Keymaps()
+	if err != nil {
+		emuLog.Error(err)
+		keymaps = chip8.Keymaps
+	}
 
-// This struct is taken directly from Ebiten's example code:
-// <https://ebiten.org/examples/sinewave.html>
-type stream struct {
-	frequency  int
-	sampleRate int
-	position   int64
-	remaining  []byte
-}
+	keymap := chip8.FindKeymap(keymaps, cfg.Keymap)
+	if keymap < 0 {
+		keymap = 0
+	}
 
-// Read fills the byte stream with sine wave samples.
-func (s *stream) Read(buf []byte) (int, error) {
-	if len(s.remaining) > 0 {
-		n := copy(buf, s.remaining)
-		s.remaining = s.remaining[n:]
-		return n, nil
+	hotkeys, err := parseHotkeys(cfg.Hotkeys)
+	if err != nil {
+		emuLog.Error(err)
+		hotkeys, _ = parseHotkeys(nil)
 	}
 
-	var origBuf []byte
-	if len(buf)%4 > 0 {
-		origBuf = buf
-		buf = make([]byte, len(origBuf)+4-len(origBuf)%4)
+	gamepad, err := newGamepadMap(cfg.Gamepad, cfg.GamepadThreshold)
+	if err != nil {
+		emuLog.Error(err)
+		gamepad, _ = newGamepadMap(nil, cfg.GamepadThreshold)
 	}
 
-	length := int64(s.sampleRate / s.frequency)
-	p := s.position / 4
-	for i := 0; i < len(buf)/4; i++ {
-		const max = 32767
-		b := int16(math.Sin(2*math.Pi*float64(p)/float64(length)) * max)
-		buf[4*i] = byte(b)
-		buf[4*i+1] = byte(b >> 8)
-		buf[4*i+2] = byte(b)
-		buf[4*i+3] = byte(b >> 8)
-		p++
+	// Initialize graphics
+	palettes, err := cfg.Palettes()
+	if err != nil {
+		emuLog.Error(err)
+		palettes = chip8.Palettes
 	}
 
-	s.position += int64(len(buf))
-	s.position %= length * 4
+	palette := chip8.FindPalette(palettes, cfg.Palette)
+	if palette < 0 {
+		palette = 0
+	}
+
+	spec := cfg.MachineSpec()
+	window := rotateSize(image.Pt(spec.DisplayWidth, spec.DisplayHeight), cfg.Rotation)
+	window = window.Mul(cfg.Scale).Add(image.Pt(2*cfg.Border, 2*cfg.Border))
+
+	// Make room for the compared display beside the display
+	if cfg.CompareQuirks != "" {
+		window.X *= 2
+	}
 
-	if origBuf != nil {
-		n := copy(origBuf, buf)
-		s.remaining = buf[n:]
-		return n, nil
+	// Make room for the touch keypad beside the display
+	if cfg.TouchKeypad {
+		window.X += window.Y
 	}
-	return len(buf), nil
+	if cfg.MenuBar {
+		window.Y += menuBarHeight
+	}
+	ebiten.SetWindowSize(window.X, window.Y)
+	ebiten.SetWindowResizable(true)
+	ebiten.SetMaxTPS(chip8.DefaultTPS)
+	ebiten.SetVsyncEnabled(true)
+	ebiten.SetScreenClearedEveryFrame(false)
+
+	emu := &Emulator{
+		cfg:        cfg,
+		vm:         chip8.NewMachine(spec),
+		beeper:     beeper,
+		keymaps:    keymaps,
+		keymap:     keymap,
+		hotkeys:    hotkeys,
+		gamepad:    gamepad,
+		touch:      touchKeypad{enabled: cfg.TouchKeypad},
+		keypad:     newKeypadOverlay(cfg),
+		menu:       newMenuBar(cfg.MenuBar),
+		registers:  registerOverlay{visible: cfg.Registers},
+		vmChan:     make(chan string),
+		snapshots:  make(chan chip8.Snapshot),
+		commands:   make(chan func()),
+		renderer:   newRenderer(cfg),
+		filter:     newFilter(cfg.Filter),
+		frameGraph: &frameGraph{},
+		palettes:   palettes,
+		palette:    palette,
+		visualBell: visualBell,
+	}
+	emu.vm.Quirks = cfg.MachineQuirks()
+	if cfg.CoveragePath != "" {
+		emu.vm.Coverage = &chip8.Coverage{}
+	}
+	if cfg.SafeMode {
+		emu.safe = &flashLimiter{}
+	}
+	if cfg.TASPath != "" {
+		emu.tas = newTASEditor(cfg.TASPath)
+	}
+	// Persistent memory would make the machines of netplay diverge
+	if cfg.SaveRAM != "" && cfg.NetplayHost == "" && cfg.NetplayJoin == "" {
+		if r, err := chip8.ParseAddressRange(cfg.SaveRAM); err != nil {
+			emuLog.Error(err)
+		} else {
+			emu.save = &saveRAM{r: r}
+		}
+	}
+	if cfg.Debug || len(cfg.Breakpoints) > 0 || len(cfg.Watchpoints) > 0 || len(cfg.Watches) > 0 {
+		flags, err := ParseDebugSession(cfg.Breakpoints, cfg.Watchpoints, cfg.Watches)
+		if err != nil {
+			emuLog.Error(err)
+		}
+		emu.debugger = newDebugger(flags)
+	}
+	if cfg.Education {
+		emu.education = &education{}
+	}
+	if cfg.CompareQuirks != "" {
+		emu.compare = newComparison(cfg)
+	}
+	if cfg.KeyLabels {
+		emu.keyLabels = &keyLabels{labels: map[ebiten.Key]rune{}}
+	}
+	if macros, err := parseMacros(cfg.Macros); err != nil {
+		emuLog.Error(err)
+	} else {
+		emu.macros.macros = macros
+	}
+	emu.inputs = []InputSource{
+		InputFunc(emu.pressKeyboard), emu.gamepad, &emu.touch, &emu.macros,
+	}
+	emu.updateTitle()
+
+	return emu
 }
 
-// Close closes the bye stream.
-func (s *stream) Close() error {
-	return nil
+// AddInput adds a source of key presses of the CHIP-8 keypad, alongside
+// the keyboard, gamepads, and touch keypad.
+func (emu *Emulator) AddInput(src InputSource) {
+	emu.inputs = append(emu.inputs, src)
 }
 
-//=====================================================================
-// Emulator
-//=====================================================================
+// OnFrame adds a function called once per frame with the display of
+// the virtual machine, alongside recordings and streams. It is called
+// from the goroutine updating the emulator, while the virtual machine
+// runs on its own goroutine.
+func (emu *Emulator) OnFrame(fn chip8.FrameFunc) {
+	emu.onFrame = append(emu.onFrame, fn)
+}
 
-const (
-	playEvent  = "play"
-	pauseEvent = "pause"
-	resetEvent = "reset"
-)
+// Start starts the emulator.
+func (emu *Emulator) Start() error {
+	if emu.cfg.WAVPath != "" {
+		wav, err := newWAVRecorder(emu.cfg.WAVPath, emu.cfg.SampleRate)
+		if err != nil {
+			return err
+		}
+		emu.wav = wav
+	}
 
-var (
-	foreground = color.White
-	background = color.Black
+	if emu.cfg.TracePath != "" {
+		trace, err := newTraceRecorder(emu.cfg.TracePath, emu.vm)
+		if err != nil {
+			return err
+		}
+		emu.trace = trace
+	}
 
-	keyHexMap = map[ebiten.Key]uint{
-		ebiten.Key1: 0x0, ebiten.Key2: 0x1, ebiten.Key3: 0x2, ebiten.Key4: 0x3,
-		ebiten.KeyQ: 0x4, ebiten.KeyW: 0x5, ebiten.KeyE: 0x6, ebiten.KeyR: 0x7,
-		ebiten.KeyA: 0x8, ebiten.KeyS: 0x9, ebiten.KeyD: 0xa, ebiten.KeyF: 0xb,
-		ebiten.KeyZ: 0xc, ebiten.KeyX: 0xd, ebiten.KeyC: 0xe, ebiten.KeyV: 0xf,
+	if emu.cfg.Stream != "" {
+		stream, err := startStreamServer(emu.cfg.Stream)
+		if err != nil {
+			return err
+		}
+		emu.stream = stream
+		emu.AddInput(stream)
 	}
-	keyEventMap = map[ebiten.Key]string{
-		ebiten.KeyRightBracket: pauseEvent,
-		ebiten.KeyLeftBracket:  playEvent,
-		ebiten.KeyBackslash:    resetEvent,
+
+	if emu.cfg.InputSocket != "" {
+		socket, err := listenInputSocket(emu.cfg.InputSocket)
+		if err != nil {
+			return err
+		}
+		defer socket.Close()
+		emu.AddInput(socket)
 	}
-)
 
-// Emulator is the CHIP-8 emulator.
-type Emulator struct {
-	vm     *VirtualMachine
-	beeper *audio.Player
-	vmChan chan string
-}
+	// During netplay the virtual machine runs one frame per update
+	if emu.cfg.NetplayHost != "" || emu.cfg.NetplayJoin != "" {
+		np, err := startNetplay(
+			emu.cfg.NetplayHost, emu.cfg.NetplayJoin, emu.vm, emu.cfg.Config,
+		)
+		if err != nil {
+			return err
+		}
+		emu.netplay = np
+		emu.cfg.Speed = np.speed
+		emu.updateTitle()
 
-// NewEmulator creates a new CHIP-8 emulator instance.
-func NewEmulator(scale int, volume float64) *Emulator {
-	// Initialize audio
-	beeper, _ := audio.NewPlayer(
-		audio.NewContext(DefaultSampleRate),
-		&stream{
-			frequency:  DefaultFrequency,
-			sampleRate: DefaultSampleRate,
-		},
-	)
-	beeper.SetVolume(volume)
+		go emu.ignoreVMEvents()
+	} else if emu.tas != nil {
+		// In tool-assisted mode the virtual machine runs one frame per
+		// update, starting paused at the first frame of the movie
+		emu.tas.reset(emu.vm)
+		emu.paused = true
+		emu.updateTitle()
 
-	// Initialize graphics
-	ebiten.SetWindowSize(DisplayWidth*scale, DisplayHeight*scale)
-	ebiten.SetWindowTitle("CHIP-8")
-	ebiten.SetMaxTPS(DefaultTPS)
-	ebiten.SetVsyncEnabled(true)
+		go emu.forwardTASEvents()
+	} else {
+		if emu.cfg.PauseAtStart {
+			emu.paused = true
+			emu.updateTitle()
+		}
 
-	return &Emulator{NewVirtualMachine(), beeper, make(chan string)}
-}
+		go emu.startIO()
+		go emu.startVM()
+		go emu.watchConfig()
+	}
 
-// Start starts the emulator.
-func (emu *Emulator) Start() error {
-	go emu.startIO()
-	go emu.startVM()
+	// The handlers of the control API take snapshots differently during
+	// netplay, so it only starts once netplay is connected
+	if emu.cfg.Control != "" {
+		if err := emu.startControlServer(emu.cfg.Control); err != nil {
+			return err
+		}
+	}
+
+	err := ebiten.RunGame(emu)
+	if err == errQuit {
+		err = nil
+	}
+	emu.stopRecording()
 
-	return ebiten.RunGame(emu)
+	if emu.gif != nil {
+		emu.saveGIF(emu.gif)
+	}
+	if emu.video != nil {
+		emu.stopVideo(emu.video)
+	}
+	emu.beeper.Close()
+
+	if emu.tas != nil {
+		emu.tas.save()
+	}
+	emu.syncSaveRAM(true)
+	emu.saveDebugSession()
+
+	if emu.trace != nil {
+		if err := emu.trace.Close(); err != nil {
+			emuLog.Error(err)
+		}
+	}
+
+	if emu.cfg.CoveragePath != "" {
+		if err := chip8.SaveCoverage(emu.cfg.CoveragePath, emu.vm.Coverage); err != nil {
+			emuLog.Error(err)
+		}
+	}
+
+	return err
 }
 
-// LoadROM loads a CHIP-8 ROM into the virtual machine.
+// LoadROM loads a CHIP-8 ROM into the virtual machine, and adds it to
+// the recently opened ROMs if it is a file. The ROM may also be read
+// from the standard input or a URL, as with ReadROM.
 func (emu *Emulator) LoadROM(path string) error {
-	return emu.vm.LoadROM(path)
+	data, err := chip8.ReadROM(path)
+	if err != nil {
+		return err
+	}
+	if err := emu.LoadBytes(data); err != nil {
+		return err
+	}
+
+	if chip8.LocalROM(path) {
+		emu.addRecentROM(path)
+	}
+	return nil
+}
+
+// LoadBytes loads the bytes of a CHIP-8 ROM into the virtual machine.
+func (emu *Emulator) LoadBytes(data []byte) error {
+	if emu.comparing() {
+		if err := emu.compare.vm.LoadBytes(data); err != nil {
+			return err
+		}
+	}
+
+	emu.syncSaveRAM(true)
+	emu.saveDebugSession()
+	if err := emu.vm.LoadBytes(data); err != nil {
+		return err
+	}
+	emu.romHash = hashROM(data)
+	emu.loadSaveRAM()
+	emu.loadDebugSession()
+	return nil
 }
 
 // Update updates the state of the emulator.
 func (emu *Emulator) Update() error {
-	for key, event := range keyEventMap {
-		if ebiten.IsKeyPressed(key) {
-			emu.vmChan <- event
-			return nil
+	emu.frame++
+	defer frameLog.Span("update", emu.frame).End()
+	if emu.trace != nil {
+		defer emu.trace.complete("update", time.Now())
+		emu.trace.capture(emu.vm)
+	}
+
+	// The recording follows the updates rather than the timers, since
+	// updates run at exactly 60 ticks per second. During netplay and
+	// TAS each frame records its audio once it has run.
+	if emu.netplay == nil && emu.tas == nil {
+		emu.recordAudio(emu.vm.ST > 0x00)
+	}
+
+	// Commands of the control API run even while an overlay captures the
+	// keys
+	emu.runCommands()
+
+	if emu.quit {
+		return errQuit
+	}
+
+	// Every key is captured while keys are being rebound
+	if emu.rebinder.active {
+		emu.setFrameKeys([chip8.NumberOfKeys]bool{})
+		if emu.rebinder.update() {
+			emu.applyKeys(emu.rebinder.keyNames())
+		}
+		return emu.runOverlayFrame()
+	}
+
+	// Every key is also captured while binding a macro
+	if emu.macros.binding {
+		emu.setFrameKeys([chip8.NumberOfKeys]bool{})
+		if emu.bindMacro() {
+			emu.saveMacros()
+		}
+		return emu.runOverlayFrame()
+	}
+
+	// Every key is also captured while the command palette is open
+	if emu.cmdPalette.active {
+		emu.setFrameKeys([chip8.NumberOfKeys]bool{})
+		emu.cmdPalette.update(emu)
+		emu.updateTitle()
+		return emu.runOverlayFrame()
+	}
+
+	// Every key is also captured while the pause menu is open
+	if emu.pauseMenu.active {
+		emu.setFrameKeys([chip8.NumberOfKeys]bool{})
+		emu.pauseMenu.update(emu)
+		emu.updateTitle()
+		return emu.runOverlayFrame()
+	}
+
+	// During netplay the game runs in lockstep with the peer
+	if emu.cfg.PauseOnFocusLoss && emu.netplay == nil {
+		emu.updateFocus()
+	}
+
+	if emu.attract != nil {
+		emu.attract.update(emu)
+	}
+
+	// The virtual machine pauses itself at breakpoints and watchpoints
+	if emu.debugger != nil && atomic.CompareAndSwapInt32(&emu.debugger.hit, 1, 0) {
+		emu.paused = true
+		emu.updateTitle()
+	}
+
+	// It also halts itself when it crashes
+	if atomic.CompareAndSwapInt32(&emu.crashed, 1, 0) {
+		emu.paused = true
+		emu.reportCrash(emu.crash)
+		emu.updateTitle()
+	}
+
+	if emu.menu.update(emu) {
+		emu.updateTitle()
+	}
+
+	// Ctrl+P opens the command palette rather than running the hotkey
+	// of P
+	if ebiten.IsKeyPressed(ebiten.KeyControl) && inpututil.IsKeyJustPressed(ebiten.KeyP) {
+		emu.openCommandPalette()
+	} else {
+		for key, action := range emu.hotkeys {
+			if inpututil.IsKeyJustPressed(key) {
+				action.run(emu)
+				emu.updateTitle()
+			}
+		}
+	}
+
+	emu.hud.update(emu.vm)
+	emu.frameGraph.update()
+	if emu.watchdog.update(emu.vm) {
+		if emu.watchdog.stalled() {
+			emuLog.Warn(emu.watchdog.message())
+		}
+		emu.redraw = true
+	}
+
+	if emu.gif != nil {
+		emu.gif.capture(emu.vm)
+	}
+	if emu.video != nil {
+		emu.video.capture(emu.vm)
+	}
+	if emu.stream != nil {
+		emu.stream.capture(emu.vm, emu.palettes[emu.palette])
+	}
+	for _, fn := range emu.onFrame {
+		fn(&emu.vm.Display, emu.vm.ST > 0x00)
+	}
+
+	if emu.keyLabels != nil {
+		emu.keyLabels.learn()
+	}
+	emu.gamepad.update()
+	if emu.touch.update() {
+		emu.redraw = true
+	}
+
+	// Several sources may hold the same keypad key
+	var keys [chip8.NumberOfKeys]bool
+	for _, src := range emu.inputs {
+		// The virtual machine polls asynchronous sources by itself,
+		// except during netplay and TAS, which run it once per frame
+		if _, ok := src.(AsyncInputSource); ok && emu.netplay == nil && emu.tas == nil {
+			continue
 		}
+		src.Press(&keys)
 	}
+	emu.macros.update(keys)
 
-	for key, hex := range keyHexMap {
-		emu.vm.Keys[hex] = ebiten.IsKeyPressed(key)
+	if emu.netplay != nil {
+		defer emu.frameGraph.measureEmulation(time.Now())
+		return emu.runNetplayFrame(keys)
+	}
+	if emu.tas != nil {
+		defer emu.frameGraph.measureEmulation(time.Now())
+		emu.updateTAS(keys)
+		return nil
 	}
+	emu.setFrameKeys(keys)
 	return nil
 }
 
+// setFrameKeys sets the keys held by the input sources polled once per
+// frame.
+func (emu *Emulator) setFrameKeys(keys [chip8.NumberOfKeys]bool) {
+	emu.keyMutex.Lock()
+	defer emu.keyMutex.Unlock()
+
+	emu.frameKeys = keys
+}
+
+// pollKeys presses the keys held by the asynchronous input sources,
+// along with the keys of the last frame from the other sources. It must
+// be called from the goroutine running the virtual machine.
+func (emu *Emulator) pollKeys() {
+	emu.keyMutex.Lock()
+	keys := emu.frameKeys
+	emu.keyMutex.Unlock()
+
+	for _, src := range emu.inputs {
+		if async, ok := src.(AsyncInputSource); ok {
+			async.Press(&keys)
+		}
+	}
+
+	emu.vm.Keys = keys
+	if emu.comparing() {
+		emu.compare.vm.Keys = keys
+	}
+}
+
+// pressKeyboard presses the keypad keys bound to the keys of the
+// keyboard which are held. Several keys may be bound to the same keypad
+// key.
+func (emu *Emulator) pressKeyboard(keys *[chip8.NumberOfKeys]bool) {
+	pressed := ebiten.IsKeyPressed
+	if emu.keyLabels != nil {
+		pressed = emu.keyLabels.pressed
+	}
+
+	for key, hex := range keymapKeys(emu.keymaps[emu.keymap]) {
+		if pressed(key) {
+			keys[hex] = true
+		}
+	}
+}
+
 // Draw renders the screen of the emulator.
 func (emu *Emulator) Draw(screen *ebiten.Image) {
-	screen.Fill(background)
+	defer frameLog.Span("draw", emu.frame).End()
+	defer emu.frameGraph.measureRender(time.Now())
+	if emu.trace != nil {
+		defer emu.trace.complete("draw", time.Now())
+	}
 
-	for y := 0; y < DisplayHeight; y++ {
-		for x := 0; x < DisplayWidth; x++ {
-			if emu.vm.Display[y][x] {
-				screen.Set(x, y, foreground)
-			}
+	// Overlays change every frame, so the screen is redrawn from scratch
+	if emu.hud.visible || emu.frameGraph.visible || emu.keypad.visible ||
+		emu.touch.enabled || emu.paused || emu.rebinder.active || emu.menu.enabled ||
+		emu.pauseMenu.active || emu.cmdPalette.active ||
+		emu.registers.visible || emu.education != nil || emu.comparing() ||
+		emu.watchdog.stalled() {
+		emu.redraw = true
+	}
+
+	// The display is letterboxed anew when its resolution changes
+	size := screen.Bounds().Size()
+	display := emu.vm.Display.Size()
+	resized := size != emu.screenSize || display != emu.display || emu.redraw
+	emu.screenSize = size
+	emu.display = display
+	emu.redraw = false
+
+	ringing := (emu.visualBell || emu.audioUnavailable()) && emu.vm.ST > 0x00
+	dirty := emu.vm.DisplayChanged() || emu.held
+
+	// In safe mode, flashes over the cap are held back until a later
+	// frame, along with everything else drawn on the screen
+	if emu.safe != nil {
+		emu.held = dirty && !resized && emu.safe.flashes(&emu.vm.Display) && !emu.safe.allow()
+		if emu.held || (ringing != emu.ringing && !emu.safe.allow()) {
+			ringing = emu.ringing
 		}
 	}
 
-	ebiten.SetWindowTitle(
-		fmt.Sprintf("CHIP-8 | FPS: %.2f", ebiten.CurrentFPS()),
-	)
+	changed := !emu.held && (dirty || ringing != emu.ringing || emu.renderer.isFading())
+	emu.ringing = ringing
+
+	// The screen keeps its contents between frames, so there is
+	// nothing to do unless the display, visual bell, or window changed
+	// or pixels are still fading out
+	if changed || resized {
+		palette := emu.palettes[emu.palette]
+		fg, bg := palette.Foreground(), palette.Background()
+		if emu.safe != nil {
+			fg, bg = reduceContrast(fg, bg)
+			emu.safe.show(&emu.vm.Display)
+		}
+
+		// The visual bell flashes the border if there is one, or
+		// otherwise inverts the colors of the display
+		border := int(float64(emu.cfg.Border) * ebiten.DeviceScaleFactor())
+		borderColor := emu.borderColor(palette)
+
+		if ringing && border > 0 {
+			borderColor = fg
+		} else if ringing {
+			fg, bg = bg, fg
+		}
+
+		// Letterbox the display within the window
+		if resized {
+			screen.Fill(color.Black)
+		}
+
+		// The menu bar takes the top of the screen
+		bar := image.Pt(0, emu.menu.height())
+		view, keypad := emu.touch.layout(size.Sub(bar))
+		view, keypad = view.Add(bar), keypad.Add(bar)
+
+		// The compared display takes the right half of the view
+		var compareView image.Rectangle
+		comparing := emu.comparing()
+		if comparing {
+			view, compareView = emu.compare.split(view)
+		}
+
+		rotated := rotateSize(display, emu.cfg.Rotation)
+		margin := image.Pt(border, border)
+		scale, offset := fitDisplay(view.Size().Sub(margin.Mul(2)), rotated)
+		offset = offset.Add(view.Min).Add(margin)
+		area := image.Rectangle{Min: offset, Max: offset.Add(rotated.Mul(scale))}
+
+		if border > 0 {
+			outer := area.Inset(-border)
+			ebitenutil.DrawRect(
+				screen,
+				float64(outer.Min.X), float64(outer.Min.Y),
+				float64(outer.Dx()), float64(outer.Dy()),
+				borderColor,
+			)
+		}
+
+		op := &ebiten.DrawImageOptions{}
+		op.GeoM.Scale(float64(scale), float64(scale))
+		rotateGeoM(&op.GeoM, display.Mul(scale), emu.cfg.Rotation)
+		op.GeoM.Translate(float64(offset.X), float64(offset.Y))
+
+		screen.DrawImage(emu.renderer.render(emu.vm, fg, bg), op)
+		emu.filter.apply(screen, area)
+		emu.grid.draw(screen, rotated, scale, offset)
+		if comparing {
+			emu.compare.draw(screen, emu, view, compareView, fg, bg)
+		}
+		emu.hud.draw(screen, bar, emu.vm, emu.cfg.Speed)
+		emu.frameGraph.draw(screen)
+		emu.watchdog.draw(screen, bar)
+		emu.registers.draw(screen, bar, emu.vm, palette)
+		if emu.education != nil {
+			emu.education.draw(screen)
+		}
+		emu.keypad.draw(screen, bar, emu.vm, palette)
+		emu.touch.draw(screen, keypad, emu.vm, palette)
+
+		emu.rebinder.draw(screen, palette)
+
+		// Inspect pixels under the mouse while the emulation is paused
+		if emu.paused {
+			drawInspector(screen, emu.vm, scale, offset, emu.cfg.Rotation, palette)
+		}
+		emu.pauseMenu.draw(screen, emu)
+		emu.cmdPalette.draw(screen, emu)
+
+		emu.menu.draw(screen, emu)
+	}
 }
 
 // Layout returns the resolution of the emulator's screen.
+//
+// The screen always matches the size of the window in device pixels,
+// and the display is scaled within it by the largest whole factor that
+// fits. This keeps the display sharp on high-DPI monitors, where the
+// window size is given in logical pixels.
 func (emu *Emulator) Layout(outsideWidth, outsideHeight int) (int, int) {
-	return DisplayWidth, DisplayHeight
+	s := ebiten.DeviceScaleFactor()
+	return int(float64(outsideWidth) * s), int(float64(outsideHeight) * s)
+}
+
+// updateTitle sets the title of the window to the ROM being run and
+// the state of the emulator, e.g. "CHIP-8 | Pong.ch8 | 1.5x | Paused".
+func (emu *Emulator) updateTitle() {
+	parts := []string{"CHIP-8"}
+
+	if emu.cfg.ROM != "" {
+		parts = append(parts, emu.cfg.ROM)
+	}
+	parts = append(parts, fmt.Sprintf("%gx", emu.cfg.Speed))
+	if emu.tas != nil {
+		parts = append(parts, emu.tas.status())
+	}
+
+	if emu.paused {
+		parts = append(parts, "Paused")
+	}
+	if atomic.LoadInt32(&emu.turbo) == 1 {
+		parts = append(parts, "Turbo")
+	}
+	if emu.gif != nil || emu.video != nil || emu.cfg.WAVPath != "" {
+		parts = append(parts, "Recording")
+	}
+
+	// Only set the title when it changes, since some platforms redraw
+	// the window decorations every time
+	if title := strings.Join(parts, " | "); title != emu.title {
+		ebiten.SetWindowTitle(title)
+		emu.title = title
+	}
+}
+
+// borderColor returns the color of the border around the display,
+// which defaults to the background of the palette.
+func (emu *Emulator) borderColor(palette chip8.Palette) color.RGBA {
+	if emu.cfg.BorderColor == "" {
+		return palette.Background()
+	}
+
+	c, err := chip8.ParseColor(emu.cfg.BorderColor)
+	if err != nil {
+		return palette.Background()
+	}
+	return c
+}
+
+func (emu *Emulator) toggleHUD() {
+	emu.hud.visible = !emu.hud.visible
+	emu.redraw = true
+}
+
+func (emu *Emulator) toggleKeypad() {
+	emu.keypad.visible = !emu.keypad.visible
+	emu.redraw = true
+}
+
+func (emu *Emulator) cycleGrid() {
+	emu.grid.cycle()
+	emu.redraw = true
+}
+
+// rotate rotates the screen by a quarter turn clockwise, turning the
+// window along with it.
+func (emu *Emulator) rotate() {
+	emu.cfg.Rotation = (emu.cfg.Rotation + 90) % 360
+	emu.redraw = true
+
+	w, h := ebiten.WindowSize()
+	ebiten.SetWindowSize(h, w)
+}
+
+func (emu *Emulator) screenshot() {
+	path, err := emu.takeScreenshot()
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+	emuLog.Info("saved screenshot to", path)
+}
+
+func (emu *Emulator) toggleGIF() {
+	if emu.gif != nil {
+		go emu.saveGIF(emu.gif)
+		emu.gif = nil
+		return
+	}
+
+	if err := os.MkdirAll(emu.cfg.ScreenshotDir, 0755); err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	emu.gif = newGIFRecorder(
+		timestampedPath(emu.cfg.ScreenshotDir, emu.cfg.ROM, ".gif"),
+		emu.palettes[emu.palette],
+		emu.cfg.GIFScale,
+		emu.cfg.GIFDecimation,
+	)
+	emuLog.Info("recording GIF to", emu.gif.path)
+}
+
+func (emu *Emulator) saveGIF(rec *gifRecorder) {
+	if err := rec.save(); err != nil {
+		emuLog.Error(err)
+		return
+	}
+	emuLog.Info("saved GIF to", rec.path)
+}
+
+func (emu *Emulator) toggleVideo() {
+	if emu.video != nil {
+		go emu.stopVideo(emu.video)
+		emu.video = nil
+		return
+	}
+
+	if err := os.MkdirAll(emu.cfg.ScreenshotDir, 0755); err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	path := timestampedPath(
+		emu.cfg.ScreenshotDir, emu.cfg.ROM, "."+emu.cfg.VideoFormat,
+	)
+
+	video, err := startVideoRecorder(emu.cfg, path, emu.palettes[emu.palette])
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	emu.video = video
+	emuLog.Info("recording video to", path)
+}
+
+func (emu *Emulator) stopVideo(rec *videoRecorder) {
+	if err := rec.stop(); err != nil {
+		emuLog.Error(err)
+		return
+	}
+	emuLog.Info("saved video to", rec.path)
+}
+
+func (emu *Emulator) cyclePalette() {
+	emu.stepPalette(1)
+}
+
+// stepPalette switches to the palette a number of steps after the
+// current one, or before it if the number is negative.
+func (emu *Emulator) stepPalette(step int) {
+	n := len(emu.palettes)
+	emu.palette = ((emu.palette+step)%n + n) % n
+	emu.redraw = true
+
+	name := emu.palettes[emu.palette].Name
+	emu.saveROMConfig(func(rom *ROMConfig) {
+		rom.Palette = name
+	})
+}
+
+func (emu *Emulator) cycleKeymap() {
+	emu.keymap = (emu.keymap + 1) % len(emu.keymaps)
+
+	name := emu.keymaps[emu.keymap].Name
+	emuLog.Info("keymap:", name)
+
+	emu.saveROMConfig(func(rom *ROMConfig) {
+		rom.Keymap = name
+	})
+}
+
+func (emu *Emulator) rebindKeys() {
+	emu.rebinder.start(emu.hotkeys)
+	emu.redraw = true
+}
+
+// applyKeys switches to a custom keymap of the given key bindings, and
+// saves it for the running ROM.
+func (emu *Emulator) applyKeys(keys map[string]string) {
+	custom, err := chip8.NewCustomKeymap(keys)
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	i := chip8.FindKeymap(emu.keymaps, chip8.CustomKeymap)
+	if i < 0 {
+		emu.keymaps = append(emu.keymaps, custom)
+		i = len(emu.keymaps) - 1
+	}
+	emu.keymaps[i] = custom
+	emu.keymap = i
+	emu.redraw = true
+
+	emuLog.Info("keymap:", chip8.CustomKeymap)
+
+	emu.saveROMConfig(func(rom *ROMConfig) {
+		rom.Keymap = chip8.CustomKeymap
+		rom.Keys = keys
+	})
+}
+
+// saveROMConfig saves a change to the settings of the running ROM to
+// the configuration file.
+func (emu *Emulator) saveROMConfig(update func(rom *ROMConfig)) {
+	if emu.cfg.Path == "" || emu.cfg.ROM == "" {
+		return
+	}
+
+	err := UpdateConfig(emu.cfg.Path, func(cfg *Config) {
+		if cfg.ROMs == nil {
+			cfg.ROMs = map[string]ROMConfig{}
+		}
+
+		rom := cfg.ROMs[emu.cfg.ROM]
+		update(&rom)
+		cfg.ROMs[emu.cfg.ROM] = rom
+	})
+
+	if err != nil {
+		emuLog.Error(err)
+	}
 }
 
 func (emu *Emulator) startVM() {
-	pause := false
+	pause := emu.cfg.PauseAtStart
+	budget := 0.0
+	speed := emu.vmSpeed()
+	beeping := false
 
-	for range time.Tick(DefaultHzVM) {
+	for range time.Tick(chip8.DefaultHzVM) {
 		select {
 		case event := <-emu.vmChan:
 			switch event {
@@ -216,27 +985,179 @@ func (emu *Emulator) startVM() {
 				pause = true
 			case resetEvent:
 				emu.vm.Reset()
+				if emu.comparing() {
+					emu.compare.vm.Reset()
+				}
+			case saveStateEvent:
+				emu.saveState()
+			case loadStateEvent:
+				emu.loadState()
+			case dumpMemoryEvent:
+				emu.dumpMemory()
+			case bugReportEvent:
+				emu.saveBugReport()
+			case loadROMEvent:
+				emu.syncSaveRAM(true)
+				emu.saveDebugSession()
+				emu.vm.Clear()
+				if err := emu.vm.LoadBytes(emu.nextROM); err != nil {
+					emuLog.Error(err)
+				}
+				emu.loadSaveRAM()
+				emu.loadDebugSession()
+				if emu.comparing() {
+					emu.compare.vm.Clear()
+					if err := emu.compare.vm.LoadBytes(emu.nextROM); err != nil {
+						emu.compare.stop(err)
+					}
+				}
+			case speedEvent:
+				speed = emu.vmSpeed()
+			case quirksEvent:
+				emu.vm.Quirks = emu.cfg.MachineQuirks()
+			case snapshotEvent:
+				emu.snapshots <- emu.vm.Snapshot()
 			}
 		default:
+			emu.pollKeys()
 			if pause {
 				continue
 			}
 
-			if err := emu.vm.RunCycle(); err != nil {
-				log.Println(err)
+			// Run as many cycles as the speed of the emulator allows,
+			// carrying over fractions of a cycle to the next tick
+			cycles := speed
+			if atomic.LoadInt32(&emu.turbo) == 1 {
+				cycles *= DefaultTurbo
+			}
+
+			// Cycles which can't get anywhere aren't run at all, which
+			// keeps the host idle on menus waiting for a key
+			if emu.vm.Idle() && (!emu.comparing() || emu.compare.vm.Idle()) {
+				budget = 0.0
+				continue
+			}
+
+			start := time.Now()
+			budget += cycles
+			for ; budget >= 1.0; budget-- {
+				if emu.debugger != nil && emu.debugger.breaks(emu.vm) {
+					atomic.StoreInt32(&emu.debugger.hit, 1)
+					pause = true
+					budget = 0.0
+					break
+				}
+				pc := emu.vm.PC
+				err := emu.runCycle()
+				emu.history.record(pc, emu.vm.Opcode)
+				if beep := emu.vm.ST > 0x00; beep != beeping {
+					beeping = beep
+					emu.gateBeeper(beep)
+				}
+				if err != nil {
+					emuLog.Error(err)
+					if _, exited := err.(*chip8.ExitError); !exited {
+						emu.crashVM(err)
+						pause = true
+						budget = 0.0
+						break
+					}
+				}
+				if emu.comparing() {
+					emu.compare.runCycle()
+				}
 			}
+			emu.frameGraph.measureEmulation(start)
+			emu.syncSaveRAM(false)
 		}
 	}
 }
 
+// updateFocus pauses and mutes the emulator when its window loses
+// focus, and resumes it when the window is focused again. A game which
+// was already paused stays paused.
+func (emu *Emulator) updateFocus() {
+	focused := ebiten.IsFocused()
+
+	if !focused && !emu.unfocused && !emu.paused {
+		emu.unfocused = true
+		atomic.StoreInt32(&emu.muted, 1)
+		emu.pause()
+		emu.updateTitle()
+	} else if focused && emu.unfocused {
+		emu.unfocused = false
+		atomic.StoreInt32(&emu.muted, 0)
+		emu.play()
+		emu.updateTitle()
+	}
+}
+
+// audioUnavailable reports whether the audio device failed to open, in
+// which case the visual bell stands in for the beeper.
+func (emu *Emulator) audioUnavailable() bool {
+	b, ok := emu.beeper.(*lazyBeeper)
+	return ok && b.unavailable()
+}
+
 func (emu *Emulator) startIO() {
+	// The timers count down at their own rate, carrying over fractions
+	// of a tick to the next update
+	budget := 0.0
+	ticks := emu.cfg.TimerRate * DefaultHzIO.Seconds()
+
 	for range time.Tick(DefaultHzIO) {
-		emu.vm.UpdateTimers()
+		// The beeper sounds for the tick in which the sound timer ran,
+		// even when the timer runs out at the end of it
+		beep := emu.vm.ST > 0x00
+		emu.beeper.SetBeeping(beep && atomic.LoadInt32(&emu.muted) == 0)
 
-		if emu.vm.ST > 0x00 {
-			emu.beeper.Play()
-		} else {
-			emu.beeper.Pause()
+		budget += ticks
+		for ; budget >= 1.0; budget-- {
+			emu.vm.UpdateTimers()
+			if emu.comparing() {
+				emu.compare.vm.UpdateTimers()
+			}
+		}
+		if beep && emu.vm.ST == 0x00 {
+			emu.gateBeeper(false)
 		}
 	}
 }
+
+// gateBeeper turns the beeper on or off as soon as the sound timer
+// starts or stops, rather than at the next update of the timers, if
+// the beeper can be gated.
+func (emu *Emulator) gateBeeper(on bool) {
+	if b, ok := emu.beeper.(GatedBeeper); ok {
+		b.SetGate(on && atomic.LoadInt32(&emu.muted) == 0)
+	}
+}
+
+func (emu *Emulator) recordAudio(beep bool) {
+	emu.wavMutex.Lock()
+	defer emu.wavMutex.Unlock()
+
+	if emu.wav == nil {
+		return
+	}
+
+	if err := emu.wav.WriteFrame(beep); err != nil {
+		emuLog.Error(err)
+		emu.wav.Close()
+		emu.wav = nil
+	}
+}
+
+func (emu *Emulator) stopRecording() {
+	emu.wavMutex.Lock()
+	defer emu.wavMutex.Unlock()
+
+	if emu.wav == nil {
+		return
+	}
+
+	if err := emu.wav.Close(); err != nil {
+		emuLog.Error(err)
+	}
+	emu.wav = nil
+}
@@ -0,0 +1,95 @@
+package ch8
+
+import (
+	"image"
+	"image/color"
+	"math"
+
+	"github.com/hajimehoshi/ebiten/v2"
+)
+
+//=====================================================================
+// Filters
+//=====================================================================
+
+const (
+	// scanlineAlpha is the opacity of the dark lines between
+	// scanlines.
+	scanlineAlpha = 0.3
+
+	// vignetteAlpha is the opacity of the corners of the display.
+	vignetteAlpha = 0.6
+
+	// vignetteRadius is the distance from the center of the display
+	// (relative to its edges) where the vignette starts.
+	vignetteRadius = 0.5
+)
+
+// filter is a retro effect drawn over the scaled display.
+type filter struct {
+	name    string
+	size    image.Point
+	overlay *ebiten.Image
+}
+
+func newFilter(name string) *filter {
+	return &filter{name: name}
+}
+
+// apply draws the filter over the area of the screen covered by the
+// scaled display.
+func (f *filter) apply(screen *ebiten.Image, area image.Rectangle) {
+	if f.name == FilterNone || f.name == "" {
+		return
+	}
+
+	size := area.Size()
+	if size != f.size || f.overlay == nil {
+		if f.overlay != nil {
+			f.overlay.Dispose()
+		}
+		f.overlay = ebiten.NewImageFromImage(f.generate(size))
+		f.size = size
+	}
+
+	op := &ebiten.DrawImageOptions{}
+	op.GeoM.Translate(float64(area.Min.X), float64(area.Min.Y))
+	screen.DrawImage(f.overlay, op)
+}
+
+// generate creates the overlay of the filter for a display of the
+// given size.
+func (f *filter) generate(size image.Point) image.Image {
+	overlay := image.NewRGBA(image.Rectangle{Max: size})
+
+	for y := 0; y < size.Y; y++ {
+		for x := 0; x < size.X; x++ {
+			alpha := 0.0
+
+			if y%2 == 1 {
+				alpha = scanlineAlpha
+			}
+
+			if f.name == FilterCRT {
+				alpha = math.Max(alpha, vignette(x, y, size))
+			}
+
+			overlay.SetRGBA(x, y, color.RGBA{A: uint8(alpha * 0xff)})
+		}
+	}
+
+	return overlay
+}
+
+// vignette returns the opacity of the vignette at a point of the
+// display, which increases towards the corners.
+func vignette(x, y int, size image.Point) float64 {
+	dx := 2*float64(x)/float64(size.X) - 1
+	dy := 2*float64(y)/float64(size.Y) - 1
+	d := math.Sqrt(dx*dx+dy*dy) / math.Sqrt2
+
+	if d < vignetteRadius {
+		return 0.0
+	}
+	return vignetteAlpha * (d - vignetteRadius) / (1 - vignetteRadius)
+}
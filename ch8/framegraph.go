@@ -0,0 +1,131 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+	"sync/atomic"
+	"time"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Frame Timing Graph
+//=====================================================================
+
+const (
+	// frameGraphFrames is the number of frames shown by the frame
+	// timing graph, which is 4 seconds at 60 FPS.
+	frameGraphFrames = 240
+
+	// frameGraphHeight is the height (in pixels) of the bars of the
+	// frame timing graph, and frameGraphScale the time of a pixel.
+	frameGraphHeight = 50
+	frameGraphScale  = time.Millisecond
+)
+
+// frameTiming is the time taken by a frame of the emulator: the time
+// since the frame before it on the host, and how much of it went into
+// running the virtual machine and into drawing the screen.
+type frameTiming struct {
+	host      time.Duration
+	emulation time.Duration
+	render    time.Duration
+}
+
+// frameGraph is an overlay graphing the timing of the last few seconds
+// of frames as bars, one per frame, so stutters and pacing problems
+// stand out. The host time of a frame is drawn in gray, with its
+// emulation and render times stacked over it.
+type frameGraph struct {
+	// emulation is the time (in nanoseconds) spent running the virtual
+	// machine since the last frame, which is added to from the
+	// goroutine running it. It is accessed atomically, so it must stay
+	// 64-bit aligned
+	emulation int64
+
+	visible bool
+	last    time.Time
+	render  time.Duration
+	timings [frameGraphFrames]frameTiming
+	next    int
+}
+
+// measureEmulation adds the time since the virtual machine started
+// running to the emulation time of the frame.
+func (g *frameGraph) measureEmulation(start time.Time) {
+	atomic.AddInt64(&g.emulation, int64(time.Since(start)))
+}
+
+// measureRender sets the render time of the frame to the time since
+// the screen started being drawn.
+func (g *frameGraph) measureRender(start time.Time) {
+	g.render = time.Since(start)
+}
+
+// update records the timing of the frame which just ended.
+func (g *frameGraph) update() {
+	now := time.Now()
+	emulation := time.Duration(atomic.SwapInt64(&g.emulation, 0))
+
+	if !g.last.IsZero() {
+		g.timings[g.next] = frameTiming{now.Sub(g.last), emulation, g.render}
+		g.next = (g.next + 1) % frameGraphFrames
+	}
+	g.last = now
+}
+
+// draw draws the graph in the bottom-left corner of the screen, with
+// the newest frame on the right, along with the average times of the
+// frames shown.
+func (g *frameGraph) draw(screen *ebiten.Image) {
+	if !g.visible {
+		return
+	}
+
+	bottom := screen.Bounds().Dy()
+	top := bottom - frameGraphHeight - menuBarHeight
+	drawRect(screen, image.Rect(0, top, frameGraphFrames, bottom), color.RGBA{0, 0, 0, 0xc0})
+
+	bar := func(x, from int, d time.Duration, c color.Color) int {
+		h := int(d / frameGraphScale)
+		if from+h > frameGraphHeight {
+			h = frameGraphHeight - from
+		}
+		drawRect(screen, image.Rect(x, bottom-from-h, x+1, bottom-from), c)
+		return from + h
+	}
+
+	var total frameTiming
+	for i := 0; i < frameGraphFrames; i++ {
+		t := g.timings[(g.next+i)%frameGraphFrames]
+		total.host += t.host
+		total.emulation += t.emulation
+		total.render += t.render
+
+		bar(i, 0, t.host, color.RGBA{0x60, 0x60, 0x60, 0xff})
+		from := bar(i, 0, t.emulation, color.RGBA{0x40, 0xc0, 0x40, 0xff})
+		bar(i, from, t.render, color.RGBA{0xe0, 0x90, 0x20, 0xff})
+	}
+
+	// The line marks the time of a frame at the target rate
+	target := bottom - int(time.Second/chip8.DefaultTPS/frameGraphScale)
+	drawRect(screen, image.Rect(0, target, frameGraphFrames, target+1), color.RGBA{0xc0, 0x40, 0x40, 0xff})
+
+	ms := func(d time.Duration) float64 {
+		return d.Seconds() * 1000 / frameGraphFrames
+	}
+	text := fmt.Sprintf(
+		"Frame %.1fms Emu %.1fms Draw %.1fms",
+		ms(total.host), ms(total.emulation), ms(total.render),
+	)
+	ebitenutil.DebugPrintAt(screen, text, 0, top)
+}
+
+func (emu *Emulator) toggleFrameGraph() {
+	emu.frameGraph.visible = !emu.frameGraph.visible
+	emu.redraw = true
+}
@@ -0,0 +1,602 @@
+package ch8
+
+import (
+	"fmt"
+	"math/rand"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Differential Fuzzing
+//=====================================================================
+
+// fuzzFrameCycles is the number of cycles run per frame while fuzzing,
+// between which the timers count down and the held keys change.
+const fuzzFrameCycles = 10
+
+// FuzzOptions are the options of a differential fuzzing session.
+type FuzzOptions struct {
+	// Seed seeds the random programs, keys, and numbers of the session,
+	// so a session can be repeated.
+	Seed int64
+
+	// Runs is the number of random programs run.
+	Runs int
+
+	// Size is the largest number of instructions of a random program.
+	Size int
+
+	// Cycles is the number of cycles each random program runs, unless
+	// it stops first.
+	Cycles int
+
+	// Quirks are the quirks both interpreters run the programs with.
+	Quirks chip8.Quirks
+}
+
+// FuzzMismatch is a random program which the virtual machine ran
+// differently from the reference interpreter.
+type FuzzMismatch struct {
+	// Program is the random program.
+	Program []byte
+
+	// Cycle is the cycle after which the interpreters differed.
+	Cycle int
+
+	// Addr and Opcode are the address and opcode of the instruction
+	// run in that cycle.
+	Addr   uint
+	Opcode uint
+
+	// Diff describes how the interpreters differed.
+	Diff string
+}
+
+func (m *FuzzMismatch) Error() string {
+	return fmt.Sprintf(
+		"cycle %d, 0x%.3X %.4X (%s): %s",
+		m.Cycle, m.Addr, m.Opcode, Decode(m.Opcode), m.Diff,
+	)
+}
+
+// Fuzz runs random programs through the virtual machine and through a
+// reference interpreter in lockstep, comparing the registers, memory,
+// and screen of both after every cycle, to catch subtle bugs in the
+// semantics of the virtual machine. It returns the first program run
+// differently, or nil if every program ran the same way.
+//
+// The programs are made of valid CHIP-8 and SUPER-CHIP instructions
+// with random operands, jumping and calling within themselves, and run
+// with random keys held. A program stops at its first error, which must
+// be the same in both interpreters.
+func Fuzz(opts FuzzOptions) *FuzzMismatch {
+	rng := rand.New(rand.NewSource(opts.Seed))
+
+	for run := 0; run < opts.Runs; run++ {
+		program := randomProgram(rng, 1+rng.Intn(opts.Size))
+		if m := fuzzProgram(program, opts.Quirks, opts.Cycles, rng.Int63()); m != nil {
+			return m
+		}
+	}
+	return nil
+}
+
+// fuzzProgram runs a program through both interpreters, with random
+// numbers and keys drawn from a seed.
+func fuzzProgram(program []byte, quirks chip8.Quirks, cycles int, seed int64) *FuzzMismatch {
+	vm := chip8.NewVirtualMachine()
+	vm.Quirks = quirks
+	vm.Seed(seed)
+	if err := vm.LoadBytes(program); err != nil {
+		return &FuzzMismatch{Program: program, Diff: err.Error()}
+	}
+
+	ref := newRefMachine(program, quirks, seed)
+	keys := rand.New(rand.NewSource(seed))
+
+	for cycle := 0; cycle < cycles; cycle++ {
+		if cycle%fuzzFrameCycles == 0 {
+			if cycle > 0 {
+				vm.UpdateTimers()
+				ref.updateTimers()
+			}
+			for k := range ref.keys {
+				ref.keys[k] = keys.Intn(4) == 0
+			}
+			vm.Keys = ref.keys
+		}
+
+		addr := ref.pc
+		opcode := uint(ref.memory[addr%chip8.MemorySize])<<8 | uint(ref.memory[(addr+1)%chip8.MemorySize])
+		mismatch := func(diff string) *FuzzMismatch {
+			return &FuzzMismatch{program, cycle, uint(addr), opcode, diff}
+		}
+
+		panicked, vmErr := runCycleSafely(vm)
+		if panicked != nil {
+			return mismatch(fmt.Sprintf("the virtual machine panicked: %v", panicked))
+		}
+		refErr := ref.runCycle()
+
+		if diff := compareErrors(vmErr, refErr); diff != "" {
+			return mismatch(diff)
+		}
+		if vmErr != nil {
+			return nil
+		}
+		if diff := ref.compare(vm); diff != "" {
+			return mismatch(diff)
+		}
+	}
+	return nil
+}
+
+// runCycleSafely runs a cycle of the virtual machine, recovering from
+// any panic so it can be reported as a mismatch.
+func runCycleSafely(vm *chip8.VirtualMachine) (panicked interface{}, err error) {
+	defer func() {
+		panicked = recover()
+	}()
+	return nil, vm.RunCycle()
+}
+
+// compareErrors describes how the errors of both interpreters differ
+// in a cycle, or returns "" if both failed or exited the same way.
+func compareErrors(vmErr, refErr error) string {
+	switch {
+	case vmErr == nil && refErr == nil:
+		return ""
+	case vmErr == nil:
+		return fmt.Sprintf("the reference failed (%v), but the virtual machine didn't", refErr)
+	case refErr == nil:
+		return fmt.Sprintf("the virtual machine failed (%v), but the reference didn't", vmErr)
+	}
+
+	vmExit, vmExited := vmErr.(*chip8.ExitError)
+	refExit, refExited := refErr.(*chip8.ExitError)
+	if vmExited != refExited || (vmExited && vmExit.Code != refExit.Code) {
+		return fmt.Sprintf("the virtual machine stopped with %q, but the reference with %q", vmErr, refErr)
+	}
+	return ""
+}
+
+// randomProgram generates a program of valid CHIP-8 and SUPER-CHIP
+// instructions, whose jumps and calls stay within it.
+func randomProgram(rng *rand.Rand, size int) []byte {
+	target := func() uint {
+		return uint(chip8.ProgramStartAddress + 2*rng.Intn(size))
+	}
+
+	program := make([]byte, 0, 2*size)
+	for i := 0; i < size; i++ {
+		x, y := uint(rng.Intn(chip8.NumberOfRegisters)), uint(rng.Intn(chip8.NumberOfRegisters))
+		xy := x<<8 | y<<4
+		kk := uint(rng.Intn(0x100))
+
+		var opcode uint
+		switch rng.Intn(17) {
+		case 0:
+			ops := []uint{0x00e0, 0x00ee, 0x00fb, 0x00fc, 0x00fd, 0x00fe, 0x00ff, 0x00c0 | uint(rng.Intn(0x10))}
+			opcode = ops[rng.Intn(len(ops))]
+		case 1:
+			opcode = 0x1000 | target()
+		case 2:
+			opcode = 0x2000 | target()
+		case 3:
+			opcode = 0x3000 | x<<8 | kk
+		case 4:
+			opcode = 0x4000 | x<<8 | kk
+		case 5:
+			opcode = 0x5000 | xy
+		case 6:
+			opcode = 0x6000 | x<<8 | kk
+		case 7:
+			opcode = 0x7000 | x<<8 | kk
+		case 8:
+			opcode = 0x8000 | xy | []uint{0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0xe}[rng.Intn(9)]
+		case 9:
+			opcode = 0x9000 | xy
+		case 10:
+			opcode = 0xa000 | uint(rng.Intn(chip8.MemorySize))
+		case 11:
+			opcode = 0xb000 | target()
+		case 12:
+			opcode = 0xc000 | x<<8 | kk
+		case 13:
+			opcode = 0xd000 | xy | uint(rng.Intn(0x10))
+		case 14:
+			opcode = 0xe000 | x<<8 | []uint{0x9e, 0xa1}[rng.Intn(2)]
+		default:
+			fx := []uint{0x07, 0x0a, 0x15, 0x18, 0x1e, 0x29, 0x30, 0x33, 0x55, 0x65, 0x75, 0x85}
+			opcode = 0xf000 | x<<8 | fx[rng.Intn(len(fx))]
+		}
+		program = append(program, byte(opcode>>8), byte(opcode))
+	}
+	return program
+}
+
+// refMachine is a reference interpreter of CHIP-8 and SUPER-CHIP,
+// written from the specification separately from VirtualMachine, and
+// kept as plain as possible so it is easy to check by reading. It draws
+// random numbers the same way as the virtual machine, so both see the
+// same numbers given the same seed.
+type refMachine struct {
+	memory [chip8.MemorySize]byte
+	v      [chip8.NumberOfRegisters]byte
+	flags  [chip8.NumberOfFlags]byte
+	stack  []uint16
+	i      uint16
+	pc     uint16
+	dt     byte
+	st     byte
+	screen [chip8.HiresHeight][chip8.HiresWidth]bool
+	width  int
+	height int
+	keys   [chip8.NumberOfKeys]bool
+	quirks chip8.Quirks
+	rng    *rand.Rand
+}
+
+func newRefMachine(program []byte, quirks chip8.Quirks, seed int64) *refMachine {
+	m := &refMachine{
+		pc:     chip8.ProgramStartAddress,
+		width:  chip8.DisplayWidth,
+		height: chip8.DisplayHeight,
+		quirks: quirks,
+		rng:    rand.New(rand.NewSource(seed)),
+	}
+
+	// The fonts are those of the machine the fuzzer runs
+	spec := chip8.MachineSpecs[chip8.FindMachine(chip8.DefaultMachine)]
+	for i, b := range spec.Font {
+		m.memory[i] = byte(b)
+	}
+	for i, b := range spec.BigFont {
+		m.memory[chip8.BigFontAddress+i] = byte(b)
+	}
+	copy(m.memory[chip8.ProgramStartAddress:], program)
+	return m
+}
+
+// updateTimers counts the timers down by a frame.
+func (m *refMachine) updateTimers() {
+	if m.dt > 0 {
+		m.dt--
+	}
+	if m.st > 0 {
+		m.st--
+	}
+}
+
+// runCycle runs the instruction at the program counter.
+func (m *refMachine) runCycle() error {
+	// The program counter wraps around to the program once it reaches
+	// the last byte of memory, which can't start an instruction
+	defer func() {
+		if int(m.pc) >= chip8.MemorySize-1 {
+			m.pc = m.pc + chip8.ProgramStartAddress - chip8.MemorySize
+		}
+	}()
+
+	op := uint16(m.memory[m.pc])<<8 | uint16(m.memory[m.pc+1])
+	m.pc += 2
+
+	x, y := (op>>8)&0xf, (op>>4)&0xf
+	n, kk, nnn := op&0xf, byte(op), op&0xfff
+	vx, vy := m.v[x], m.v[y]
+
+	switch op >> 12 {
+	case 0x0:
+		if op&0xfff0 == 0x00c0 {
+			m.scroll(0, int(n))
+			break
+		}
+		switch op {
+		case 0x00e0:
+			m.screen = [chip8.HiresHeight][chip8.HiresWidth]bool{}
+		case 0x00ee:
+			if len(m.stack) == 0 {
+				return fmt.Errorf("returned with an empty stack")
+			}
+			m.pc = m.stack[len(m.stack)-1]
+			m.stack = m.stack[:len(m.stack)-1]
+		case 0x00fb:
+			m.scroll(4, 0)
+		case 0x00fc:
+			m.scroll(-4, 0)
+		case 0x00fd:
+			return &chip8.ExitError{Code: int(m.v[0])}
+		case 0x00fe:
+			m.width, m.height = chip8.DisplayWidth, chip8.DisplayHeight
+			m.screen = [chip8.HiresHeight][chip8.HiresWidth]bool{}
+		case 0x00ff:
+			m.width, m.height = chip8.HiresWidth, chip8.HiresHeight
+			m.screen = [chip8.HiresHeight][chip8.HiresWidth]bool{}
+		default:
+			return fmt.Errorf("unknown instruction")
+		}
+	case 0x1:
+		if !refJumpable(nnn) {
+			return fmt.Errorf("jumped out of the program")
+		}
+		m.pc = nnn
+	case 0x2:
+		if len(m.stack) == chip8.MaxStackDepth || !refJumpable(nnn) {
+			return fmt.Errorf("called out of the program or the stack")
+		}
+		m.stack = append(m.stack, m.pc)
+		m.pc = nnn
+	case 0x3:
+		if vx == kk {
+			m.pc += 2
+		}
+	case 0x4:
+		if vx != kk {
+			m.pc += 2
+		}
+	case 0x5:
+		if vx == vy {
+			m.pc += 2
+		}
+	case 0x6:
+		m.v[x] = kk
+	case 0x7:
+		m.v[x] += kk
+	case 0x8:
+		m.runArithmetic(x, y, n)
+	case 0x9:
+		if vx != vy {
+			m.pc += 2
+		}
+	case 0xa:
+		m.i = nnn
+	case 0xb:
+		addr := nnn + uint16(m.v[0])
+		if m.quirks.JumpVX {
+			addr = nnn + uint16(vx)
+		}
+		addr &= 0xfff
+		if !refJumpable(addr) {
+			return fmt.Errorf("jumped out of the program")
+		}
+		m.pc = addr
+	case 0xc:
+		m.v[x] = byte(m.rng.Int()&0xff) & kk
+	case 0xd:
+		m.draw(vx, vy, n)
+	case 0xe:
+		held := m.keys[vx&0xf]
+		if kk == 0x9e && held || kk == 0xa1 && !held {
+			m.pc += 2
+		}
+	case 0xf:
+		return m.runMisc(x, kk)
+	}
+	return nil
+}
+
+// runArithmetic runs the 8XYN instructions, whose flag is written to VF
+// after their result.
+func (m *refMachine) runArithmetic(x, y, n uint16) {
+	vx, vy := m.v[x], m.v[y]
+	if m.quirks.ShiftVY && (n == 0x6 || n == 0xe) {
+		vx = vy
+	}
+
+	var flag byte
+	switch n {
+	case 0x0:
+		m.v[x] = vy
+		return
+	case 0x1, 0x2, 0x3:
+		switch n {
+		case 0x1:
+			m.v[x] = vx | vy
+		case 0x2:
+			m.v[x] = vx & vy
+		case 0x3:
+			m.v[x] = vx ^ vy
+		}
+		if m.quirks.ResetVF {
+			m.v[0xf] = 0
+		}
+		return
+	case 0x4:
+		m.v[x] = vx + vy
+		if int(vx)+int(vy) > 0xff {
+			flag = 1
+		}
+	case 0x5:
+		m.v[x] = vx - vy
+		if vx >= vy {
+			flag = 1
+		}
+	case 0x6:
+		m.v[x] = vx >> 1
+		flag = vx & 1
+	case 0x7:
+		m.v[x] = vy - vx
+		if vy >= vx {
+			flag = 1
+		}
+	case 0xe:
+		m.v[x] = vx << 1
+		flag = vx >> 7
+	}
+	m.v[0xf] = flag
+}
+
+// draw draws an n-row sprite at I, XORing it onto the screen. A
+// sprite of 0 rows is a 16x16 sprite of two bytes per row.
+func (m *refMachine) draw(vx, vy byte, n uint16) {
+	m.v[0xf] = 0
+	x0, y0 := int(vx)%m.width, int(vy)%m.height
+
+	rows, cols := int(n), 8
+	if n == 0 {
+		rows, cols = 16, 16
+	}
+
+	for row := 0; row < rows; row++ {
+		y := y0 + row
+		if y >= m.height {
+			if m.quirks.Clip {
+				break
+			}
+			y %= m.height
+		}
+
+		bits := uint16(m.memory[(int(m.i)+row)%chip8.MemorySize]) << 8
+		if cols == 16 {
+			bits = uint16(m.memory[(int(m.i)+2*row)%chip8.MemorySize])<<8 |
+				uint16(m.memory[(int(m.i)+2*row+1)%chip8.MemorySize])
+		}
+		for col := 0; col < cols; col++ {
+			if bits&(0x8000>>uint(col)) == 0 {
+				continue
+			}
+
+			x := x0 + col
+			if x >= m.width {
+				if m.quirks.Clip {
+					continue
+				}
+				x %= m.width
+			}
+
+			if m.screen[y][x] {
+				m.v[0xf] = 1
+			}
+			m.screen[y][x] = !m.screen[y][x]
+		}
+	}
+}
+
+// scroll moves the screen by (dx, dy) pixels, turning off the pixels
+// scrolled in from the edges.
+func (m *refMachine) scroll(dx, dy int) {
+	var screen [chip8.HiresHeight][chip8.HiresWidth]bool
+	for y := 0; y < m.height; y++ {
+		for x := 0; x < m.width; x++ {
+			fromX, fromY := x-dx, y-dy
+			if fromX >= 0 && fromX < m.width && fromY >= 0 && fromY < m.height {
+				screen[y][x] = m.screen[fromY][fromX]
+			}
+		}
+	}
+	m.screen = screen
+}
+
+// runMisc runs the FXKK instructions.
+func (m *refMachine) runMisc(x uint16, kk byte) error {
+	switch kk {
+	case 0x07:
+		m.v[x] = m.dt
+	case 0x0a:
+		for k, held := range m.keys {
+			if held {
+				m.v[x] = byte(k)
+				return nil
+			}
+		}
+		m.pc -= 2
+	case 0x15:
+		m.dt = m.v[x]
+	case 0x18:
+		m.st = m.v[x]
+	case 0x1e:
+		m.i = (m.i + uint16(m.v[x])) & 0xfff
+	case 0x29:
+		m.i = uint16(m.v[x]) * chip8.FontSize
+	case 0x30:
+		m.i = chip8.BigFontAddress + uint16(m.v[x]&0xf)*chip8.BigFontSize
+	case 0x33:
+		m.memory[m.i] = m.v[x] / 100
+		m.memory[(m.i+1)%chip8.MemorySize] = m.v[x] / 10 % 10
+		m.memory[(m.i+2)%chip8.MemorySize] = m.v[x] % 10
+	case 0x55, 0x65:
+		for r := uint16(0); r <= x; r++ {
+			addr := (m.i + r) % chip8.MemorySize
+			if kk == 0x55 {
+				m.memory[addr] = m.v[r]
+			} else {
+				m.v[r] = m.memory[addr]
+			}
+		}
+		if m.quirks.IncrementI {
+			m.i = (m.i + x + 1) & 0xfff
+		}
+	case 0x75, 0x85:
+		if x >= chip8.NumberOfFlags {
+			return fmt.Errorf("unknown instruction")
+		}
+		for r := uint16(0); r <= x; r++ {
+			if kk == 0x75 {
+				m.flags[r] = m.v[r]
+			} else {
+				m.v[r] = m.flags[r]
+			}
+		}
+	default:
+		return fmt.Errorf("unknown instruction")
+	}
+	return nil
+}
+
+// refJumpable reports whether a jump or call may go to an address.
+func refJumpable(addr uint16) bool {
+	return addr >= chip8.ProgramStartAddress && addr < chip8.MemorySize-1
+}
+
+// compare describes the first difference between the state of the
+// reference interpreter and the virtual machine, or returns "" if they
+// are the same.
+func (m *refMachine) compare(vm *chip8.VirtualMachine) string {
+	if vm.PC != uint(m.pc) {
+		return fmt.Sprintf("PC is 0x%.3X, but should be 0x%.3X", vm.PC, m.pc)
+	}
+	if vm.I != uint(m.i) {
+		return fmt.Sprintf("I is 0x%.3X, but should be 0x%.3X", vm.I, m.i)
+	}
+	for r, value := range m.v {
+		if vm.V[r] != uint(value) {
+			return fmt.Sprintf("V%X is 0x%.2X, but should be 0x%.2X", r, vm.V[r], value)
+		}
+	}
+	for r, value := range m.flags {
+		if vm.Flags[r] != uint(value) {
+			return fmt.Sprintf("flag %d is 0x%.2X, but should be 0x%.2X", r, vm.Flags[r], value)
+		}
+	}
+	if vm.DT != uint(m.dt) || vm.ST != uint(m.st) {
+		return fmt.Sprintf("the timers are %d and %d, but should be %d and %d", vm.DT, vm.ST, m.dt, m.st)
+	}
+
+	if vm.SP != uint(len(m.stack)) {
+		return fmt.Sprintf("SP is %d, but should be %d", vm.SP, len(m.stack))
+	}
+	for d, addr := range m.stack {
+		if vm.Stack[d] != uint(addr) {
+			return fmt.Sprintf("stack entry %d is 0x%.3X, but should be 0x%.3X", d, vm.Stack[d], addr)
+		}
+	}
+
+	for addr, b := range m.memory {
+		if vm.Memory[addr] != uint(b) {
+			return fmt.Sprintf("memory at 0x%.3X is 0x%.2X, but should be 0x%.2X", addr, vm.Memory[addr], b)
+		}
+	}
+
+	if vm.Display.Width != m.width || vm.Display.Height != m.height {
+		return fmt.Sprintf("the screen is %dx%d, but should be %dx%d", vm.Display.Width, vm.Display.Height, m.width, m.height)
+	}
+	for y, row := range m.screen[:m.height] {
+		for x, lit := range row[:m.width] {
+			if vm.Display.At(x, y) != lit {
+				return fmt.Sprintf("the pixel at (%d, %d) is %t, but should be %t", x, y, vm.Display.At(x, y), lit)
+			}
+		}
+	}
+	return ""
+}
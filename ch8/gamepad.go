@@ -0,0 +1,154 @@
+package ch8
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Gamepads
+//=====================================================================
+
+// gamepadMap maps buttons and axes of gamepads and joysticks to keys of
+// the CHIP-8 keypad.
+//
+// The same bindings apply to every connected gamepad. Gamepads can be
+// connected and disconnected while the emulator runs.
+type gamepadMap struct {
+	buttons   map[ebiten.GamepadButton]uint
+	axes      []axisBinding
+	connected []ebiten.GamepadID
+}
+
+// axisBinding presses a key while an axis is pushed past a threshold
+// in one direction.
+type axisBinding struct {
+	axis      int
+	direction float64
+	threshold float64
+	key       uint
+}
+
+// newGamepadMap creates a gamepad map from bindings of button and axis
+// names (e.g. "button0" or "axis1-") to hexadecimal keypad keys (e.g.
+// "5"). Axes are pressed once pushed past the threshold, unless the
+// binding gives its own threshold (e.g. "axis5+@0.8").
+//
+// The directions of a d-pad or hat are reported as buttons numbered
+// after the other buttons of the gamepad.
+func newGamepadMap(bindings map[string]string, threshold float64) (*gamepadMap, error) {
+	m := &gamepadMap{buttons: map[ebiten.GamepadButton]uint{}}
+
+	for name, hex := range bindings {
+		key, err := chip8.ParseKeypadKey(hex)
+		if err != nil {
+			return nil, err
+		}
+
+		if strings.HasPrefix(strings.ToLower(name), "axis") {
+			axis, err := parseGamepadAxis(name, threshold)
+			if err != nil {
+				return nil, err
+			}
+			axis.key = key
+			m.axes = append(m.axes, axis)
+			continue
+		}
+
+		button, err := parseGamepadButton(name)
+		if err != nil {
+			return nil, err
+		}
+		m.buttons[button] = key
+	}
+
+	return m, nil
+}
+
+// parseGamepadAxis returns the direction of an axis with the given
+// name, such as "axis0+" or "axis2-@0.25".
+func parseGamepadAxis(name string, threshold float64) (axisBinding, error) {
+	invalid := fmt.Errorf("invalid gamepad axis: %s", name)
+	s := strings.TrimPrefix(strings.ToLower(name), "axis")
+
+	if i := strings.Index(s, "@"); i >= 0 {
+		t, err := strconv.ParseFloat(s[i+1:], 64)
+		if err != nil || t <= 0.0 || t > 1.0 {
+			return axisBinding{}, invalid
+		}
+		threshold = t
+		s = s[:i]
+	}
+
+	if len(s) < 2 {
+		return axisBinding{}, invalid
+	}
+
+	direction := 1.0
+	switch s[len(s)-1] {
+	case '+':
+	case '-':
+		direction = -1.0
+	default:
+		return axisBinding{}, invalid
+	}
+
+	axis, err := strconv.Atoi(s[:len(s)-1])
+	if err != nil || axis < 0 {
+		return axisBinding{}, invalid
+	}
+
+	return axisBinding{axis: axis, direction: direction, threshold: threshold}, nil
+}
+
+// parseGamepadButton returns the button of a gamepad with the given
+// name, such as "button3".
+func parseGamepadButton(name string) (ebiten.GamepadButton, error) {
+	n := strings.TrimPrefix(strings.ToLower(name), "button")
+	if v, err := strconv.Atoi(n); err == nil && v >= 0 && v <= int(ebiten.GamepadButtonMax) {
+		return ebiten.GamepadButton(v), nil
+	}
+	return 0, fmt.Errorf("unknown gamepad button: %s", name)
+}
+
+// update logs gamepads which were connected or disconnected.
+func (m *gamepadMap) update() {
+	for _, id := range inpututil.JustConnectedGamepadIDs() {
+		inputLog.Infof("gamepad %d connected: %s", id, ebiten.GamepadName(id))
+	}
+
+	// Disconnected gamepads are no longer listed, so the gamepads of
+	// the previous update are checked
+	for _, id := range m.connected {
+		if inpututil.IsGamepadJustDisconnected(id) {
+			inputLog.Infof("gamepad %d disconnected", id)
+		}
+	}
+	m.connected = ebiten.GamepadIDs()
+}
+
+// Press presses the keypad keys bound to the buttons which are held,
+// and the axes which are pushed, on any connected gamepad.
+func (m *gamepadMap) Press(keys *[chip8.NumberOfKeys]bool) {
+	for _, id := range m.connected {
+		for button, hex := range m.buttons {
+			if ebiten.IsGamepadButtonPressed(id, button) {
+				keys[hex] = true
+			}
+		}
+
+		for _, b := range m.axes {
+			if b.axis >= ebiten.GamepadAxisNum(id) {
+				continue
+			}
+			if ebiten.GamepadAxis(id, b.axis)*b.direction >= b.threshold {
+				keys[b.key] = true
+			}
+		}
+	}
+}
@@ -0,0 +1,95 @@
+package ch8
+
+import (
+	"bytes"
+	"image"
+	"image/color"
+	"image/gif"
+	"os"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// GIF Recording
+//=====================================================================
+
+// gifRecorder captures the display of the virtual machine into an
+// animated GIF.
+type gifRecorder struct {
+	path       string
+	anim       gif.GIF
+	palette    color.Palette
+	scale      int
+	decimation int
+	frames     int
+	last       []byte
+}
+
+func newGIFRecorder(path string, palette chip8.Palette, scale, decimation int) *gifRecorder {
+	return &gifRecorder{
+		path:       path,
+		palette:    color.Palette{palette.Background(), palette.Foreground()},
+		scale:      scale,
+		decimation: decimation,
+	}
+}
+
+// capture records a single frame of the display. Only every n-th
+// frame is captured, where n is the decimation of the recording.
+func (r *gifRecorder) capture(vm *chip8.VirtualMachine) {
+	n := r.frames
+	r.frames++
+
+	if n%r.decimation != 0 {
+		return
+	}
+
+	// GIF delays are in 100ths of a second, so the delays are rounded
+	// such that the recording keeps in sync with the emulator
+	delay := r.centiseconds(n+r.decimation) - r.centiseconds(n)
+
+	img := image.NewPaletted(
+		image.Rect(0, 0, chip8.DisplayWidth*r.scale, chip8.DisplayHeight*r.scale),
+		r.palette,
+	)
+	size := img.Rect.Size()
+	for y := 0; y < size.Y; y++ {
+		for x := 0; x < size.X; x++ {
+			if vm.Display.Sample(x, y, size) {
+				img.SetColorIndex(x, y, 1)
+			}
+		}
+	}
+
+	// Extend the previous frame rather than storing a duplicate
+	if len(r.anim.Image) > 0 && bytes.Equal(img.Pix, r.last) {
+		r.anim.Delay[len(r.anim.Delay)-1] += delay
+		return
+	}
+
+	r.anim.Image = append(r.anim.Image, img)
+	r.anim.Delay = append(r.anim.Delay, delay)
+	r.last = img.Pix
+}
+
+// centiseconds returns the time (in 100ths of a second) when a frame
+// of the recording is shown.
+func (r *gifRecorder) centiseconds(frame int) int {
+	return (frame*100 + chip8.DefaultTPS/2) / chip8.DefaultTPS
+}
+
+// save writes the recording to its GIF file.
+func (r *gifRecorder) save() error {
+	file, err := os.Create(r.path)
+	if err != nil {
+		return err
+	}
+
+	if err := gif.EncodeAll(file, &r.anim); err != nil {
+		file.Close()
+		return err
+	}
+
+	return file.Close()
+}
@@ -0,0 +1,81 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+	"image/draw"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Golden Images
+//=====================================================================
+
+// Colors of the image of the differences between a display and a
+// golden image.
+var (
+	diffLit         = color.RGBA{0x80, 0x80, 0x80, 0xff}
+	diffUnlit       = color.RGBA{0x20, 0x20, 0x20, 0xff}
+	diffOnlyDisplay = color.RGBA{0xff, 0x40, 0x40, 0xff}
+	diffOnlyGolden  = color.RGBA{0x40, 0xa0, 0xff, 0xff}
+)
+
+// CompareImage compares a display with a golden image of it drawn with
+// a palette at any scale, such as a screenshot. It returns the number
+// of CHIP-8 pixels which differ, along with an image of the size of
+// the golden image showing the differences: pixels lit in both are
+// gray, pixels lit only on the display are red, and pixels lit only in
+// the golden image are blue.
+func CompareImage(display *chip8.Display, golden image.Image, palette chip8.Palette) (int, *image.RGBA, error) {
+	bounds := golden.Bounds()
+	size := bounds.Size()
+	scale := size.X / display.Width
+	if scale < 1 || size.X != display.Width*scale || size.Y != display.Height*scale {
+		return 0, nil, fmt.Errorf(
+			"the golden image is %dx%d, which isn't a multiple of the %dx%d display",
+			size.X, size.Y, display.Width, display.Height,
+		)
+	}
+
+	fg, bg := palette.Foreground(), palette.Background()
+	diff := image.NewRGBA(image.Rectangle{Max: size})
+	mismatches := 0
+
+	for y := 0; y < display.Height; y++ {
+		for x := 0; x < display.Width; x++ {
+			// The center of the square of a pixel is away from any
+			// smoothing along its edges
+			c := golden.At(bounds.Min.X+x*scale+scale/2, bounds.Min.Y+y*scale+scale/2)
+			expected := colorDistance(c, fg) < colorDistance(c, bg)
+			lit := display.At(x, y)
+
+			fill := diffUnlit
+			switch {
+			case lit && !expected:
+				fill = diffOnlyDisplay
+			case !lit && expected:
+				fill = diffOnlyGolden
+			case lit:
+				fill = diffLit
+			}
+			if lit != expected {
+				mismatches++
+			}
+
+			r := image.Rect(x*scale, y*scale, (x+1)*scale, (y+1)*scale)
+			draw.Draw(diff, r, image.NewUniform(fill), image.Point{}, draw.Src)
+		}
+	}
+
+	return mismatches, diff, nil
+}
+
+// colorDistance returns the squared distance between two colors.
+func colorDistance(a, b color.Color) int {
+	r1, g1, b1, _ := a.RGBA()
+	r2, g2, b2, _ := b.RGBA()
+	dr, dg, db := int(r1>>8)-int(r2>>8), int(g1>>8)-int(g2>>8), int(b1>>8)-int(b2>>8)
+	return dr*dr + dg*dg + db*db
+}
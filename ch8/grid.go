@@ -0,0 +1,90 @@
+package ch8
+
+import (
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+)
+
+//=====================================================================
+// Grid Overlay
+//=====================================================================
+
+const (
+	gridOff = iota
+	gridPixels
+	gridTiles
+	gridModes
+)
+
+const (
+	// gridMinScale is the smallest scale factor of the display at
+	// which lines are drawn between pixels, since smaller pixels
+	// would be covered by the lines.
+	gridMinScale = 4
+
+	// gridTileSize is the size (in CHIP-8 pixels) of the tiles
+	// outlined by the tile guides, which matches the width of a
+	// sprite.
+	gridTileSize = 8
+)
+
+var (
+	gridPixelColor = color.RGBA{0x40, 0x40, 0x40, 0x80}
+	gridTileColor  = color.RGBA{0xff, 0x40, 0x40, 0xc0}
+)
+
+// gridOverlay draws lines between the pixels of the display, and
+// optionally guides around 8x8 tiles, to help with placing sprites.
+type gridOverlay struct {
+	mode int
+}
+
+// cycle switches between no grid, the pixel grid, and the pixel grid
+// with tile guides.
+func (g *gridOverlay) cycle() {
+	g.mode = (g.mode + 1) % gridModes
+}
+
+// draw draws the grid over a display with the given resolution, scale,
+// and offset on the screen.
+func (g *gridOverlay) draw(
+	screen *ebiten.Image,
+	display image.Point,
+	scale int,
+	offset image.Point,
+) {
+	if g.mode == gridOff {
+		return
+	}
+
+	if scale >= gridMinScale {
+		g.lines(screen, display, scale, offset, 1, gridPixelColor)
+	}
+	if g.mode == gridTiles {
+		g.lines(screen, display, scale, offset, gridTileSize, gridTileColor)
+	}
+}
+
+// lines draws a line every given number of CHIP-8 pixels, both
+// vertically and horizontally.
+func (g *gridOverlay) lines(
+	screen *ebiten.Image,
+	display image.Point,
+	scale int,
+	offset image.Point,
+	every int,
+	c color.Color,
+) {
+	x0, y0 := float64(offset.X), float64(offset.Y)
+	w, h := float64(display.X*scale), float64(display.Y*scale)
+
+	for x := every; x < display.X; x += every {
+		ebitenutil.DrawRect(screen, x0+float64(x*scale), y0, 1, h, c)
+	}
+	for y := every; y < display.Y; y += every {
+		ebitenutil.DrawRect(screen, x0, y0+float64(y*scale), w, 1, c)
+	}
+}
@@ -0,0 +1,63 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+	"time"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// HUD
+//=====================================================================
+
+// hud is an overlay of performance statistics of the emulator.
+type hud struct {
+	visible    bool
+	lastCycles uint64
+	lastTime   time.Time
+	ips        float64
+}
+
+// update measures the instructions per second of the virtual machine
+// about once per second.
+func (h *hud) update(vm *chip8.VirtualMachine) {
+	now := time.Now()
+	elapsed := now.Sub(h.lastTime)
+
+	if elapsed < time.Second {
+		return
+	}
+
+	cycles := vm.Cycles()
+	if !h.lastTime.IsZero() {
+		h.ips = float64(cycles-h.lastCycles) / elapsed.Seconds()
+	}
+
+	h.lastCycles = cycles
+	h.lastTime = now
+}
+
+// draw draws the statistics at the given point of the screen, which is
+// its top-left corner unless the menu bar is shown.
+func (h *hud) draw(screen *ebiten.Image, at image.Point, vm *chip8.VirtualMachine, speed float64) {
+	if !h.visible {
+		return
+	}
+
+	text := fmt.Sprintf(
+		"FPS: %.2f\nIPS: %.0f\nDT: 0x%.2X ST: 0x%.2X\nSpeed: x%.2f",
+		ebiten.CurrentFPS(),
+		h.ips,
+		vm.DT,
+		vm.ST,
+		speed,
+	)
+
+	ebitenutil.DrawRect(screen, float64(at.X), float64(at.Y), 140, 68, color.RGBA{0, 0, 0, 0xc0})
+	ebitenutil.DebugPrintAt(screen, text, at.X, at.Y)
+}
@@ -0,0 +1,90 @@
+package ch8
+
+import (
+	"sync"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Input Sources
+//=====================================================================
+
+// InputSource is a source of key presses of the CHIP-8 keypad, such as
+// the keyboard, a gamepad, or a program driving the emulator.
+//
+// The sources of the emulator are polled once per frame, and a keypad
+// key is held for as long as any source holds it.
+type InputSource interface {
+	// Press presses the keypad keys which are held by the source.
+	Press(keys *[chip8.NumberOfKeys]bool)
+}
+
+// AsyncInputSource is an input source which is safe to poll from any
+// goroutine. The emulator polls it before every batch of instructions
+// rather than once per frame, so its key presses are seen as soon as
+// they happen instead of up to a frame later.
+type AsyncInputSource interface {
+	InputSource
+
+	// Async marks the source as safe to poll from any goroutine.
+	Async()
+}
+
+// InputFunc is a function which is an input source.
+type InputFunc func(keys *[chip8.NumberOfKeys]bool)
+
+// Press calls the function.
+func (f InputFunc) Press(keys *[chip8.NumberOfKeys]bool) {
+	f(keys)
+}
+
+// KeyState is an input source whose keys are held and released by
+// calling its methods, which is useful for feeding the emulator from a
+// GUI, a script, or a replay. It is safe to use from any goroutine.
+//
+// A key which is pressed is held until the source is next polled, even
+// if it is released before then, so fast taps aren't missed.
+type KeyState struct {
+	mutex   sync.Mutex
+	keys    [chip8.NumberOfKeys]bool
+	pressed [chip8.NumberOfKeys]bool
+}
+
+// SetKey holds or releases a keypad key.
+func (s *KeyState) SetKey(key uint, down bool) {
+	if key >= chip8.NumberOfKeys {
+		return
+	}
+
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	s.keys[key] = down
+	s.pressed[key] = s.pressed[key] || down
+}
+
+// SetKeys holds the given keypad keys and releases all the others.
+func (s *KeyState) SetKeys(keys [chip8.NumberOfKeys]bool) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	s.keys = keys
+	for i, down := range keys {
+		s.pressed[i] = s.pressed[i] || down
+	}
+}
+
+// Press presses the keypad keys which are held.
+func (s *KeyState) Press(keys *[chip8.NumberOfKeys]bool) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	for i, down := range s.keys {
+		keys[i] = keys[i] || down || s.pressed[i]
+	}
+	s.pressed = [chip8.NumberOfKeys]bool{}
+}
+
+// Async marks the key state as safe to poll from any goroutine.
+func (s *KeyState) Async() {}
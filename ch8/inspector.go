@@ -0,0 +1,121 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Pixel Inspector
+//=====================================================================
+
+const (
+	// inspectorRadius is the number of CHIP-8 pixels shown around the
+	// hovered pixel in the magnified view.
+	inspectorRadius = 4
+
+	// inspectorZoom is the size (in screen pixels) of a CHIP-8 pixel
+	// in the magnified view.
+	inspectorZoom = 12
+)
+
+var (
+	inspectorBorder  = color.RGBA{0x80, 0x80, 0x80, 0xff}
+	inspectorCursor  = color.RGBA{0xff, 0x40, 0x40, 0xff}
+	inspectorOutside = color.RGBA{0x20, 0x20, 0x20, 0xff}
+)
+
+// drawInspector shows the coordinates and state of the CHIP-8 pixel
+// under the mouse cursor, along with a magnified view of the pixels
+// around it. Nothing is drawn if the cursor is outside the display.
+//
+// The magnified view is shown as the display appears on the screen,
+// while the coordinates are those of the unrotated display.
+func drawInspector(
+	screen *ebiten.Image,
+	vm *chip8.VirtualMachine,
+	scale int,
+	offset image.Point,
+	rotation int,
+	palette chip8.Palette,
+) {
+	cx, cy := ebiten.CursorPosition()
+	x, y := (cx-offset.X)/scale, (cy-offset.Y)/scale
+
+	display := &vm.Display
+	rotated := rotateSize(display.Size(), rotation)
+	if cx < offset.X || cy < offset.Y || x >= rotated.X || y >= rotated.Y {
+		return
+	}
+
+	// lit reports whether a pixel of the rotated display is lit
+	lit := func(x, y int) bool {
+		p := unrotatePoint(image.Pt(x, y), display.Size(), rotation)
+		return display.At(p.X, p.Y)
+	}
+
+	// Keep the magnified view within the screen, next to the cursor
+	size := (2*inspectorRadius + 1) * inspectorZoom
+	bounds := screen.Bounds().Size()
+
+	origin := image.Pt(cx+16, cy+16)
+	if origin.X+size > bounds.X {
+		origin.X = cx - 16 - size
+	}
+	if origin.Y+size+16 > bounds.Y {
+		origin.Y = cy - 32 - size
+	}
+
+	ebitenutil.DrawRect(
+		screen,
+		float64(origin.X-1), float64(origin.Y-1),
+		float64(size+2), float64(size+18),
+		inspectorBorder,
+	)
+
+	for dy := -inspectorRadius; dy <= inspectorRadius; dy++ {
+		for dx := -inspectorRadius; dx <= inspectorRadius; dx++ {
+			px, py := x+dx, y+dy
+
+			c := color.Color(inspectorOutside)
+			if px >= 0 && py >= 0 && px < rotated.X && py < rotated.Y {
+				c = palette.Background()
+				if lit(px, py) {
+					c = palette.Foreground()
+				}
+			}
+
+			left := float64(origin.X + (dx+inspectorRadius)*inspectorZoom)
+			top := float64(origin.Y + (dy+inspectorRadius)*inspectorZoom)
+			zoom := float64(inspectorZoom)
+
+			if dx == 0 && dy == 0 {
+				ebitenutil.DrawRect(screen, left, top, zoom, zoom, inspectorCursor)
+				ebitenutil.DrawRect(screen, left+2, top+2, zoom-4, zoom-4, c)
+			} else {
+				ebitenutil.DrawRect(screen, left, top, zoom, zoom, c)
+			}
+		}
+	}
+
+	state := "off"
+	if lit(x, y) {
+		state = "on"
+	}
+	p := unrotatePoint(image.Pt(x, y), display.Size(), rotation)
+
+	ebitenutil.DrawRect(
+		screen,
+		float64(origin.X), float64(origin.Y+size),
+		float64(size), 16,
+		color.Black,
+	)
+	ebitenutil.DebugPrintAt(
+		screen, fmt.Sprintf("%d,%d %s", p.X, p.Y, state), origin.X+2, origin.Y+size,
+	)
+}
@@ -0,0 +1,112 @@
+package ch8
+
+import (
+	"fmt"
+	"strings"
+	"unicode"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Keymaps
+//=====================================================================
+
+// ParseKey returns the key of the keyboard with the given name, which
+// is not case-sensitive.
+func ParseKey(name string) (ebiten.Key, error) {
+	for key := ebiten.Key(0); key <= ebiten.KeyMax; key++ {
+		if strings.EqualFold(key.String(), name) {
+			return key, nil
+		}
+	}
+	return 0, fmt.Errorf("unknown key: %s", name)
+}
+
+// keymapKeys returns the keys of the keyboard bound by a keymap. Keys
+// with unknown names are left out, since they can't be pressed.
+func keymapKeys(keymap chip8.Keymap) map[ebiten.Key]uint {
+	keys := make(map[ebiten.Key]uint, len(keymap.Keys))
+	for name, hex := range keymap.Keys {
+		if key, err := ParseKey(name); err == nil {
+			keys[key] = hex
+		}
+	}
+	return keys
+}
+
+//=====================================================================
+// Key Labels
+//=====================================================================
+
+// keyLabels matches keys of the keyboard by their label on the keyboard
+// layout rather than their position. The layout isn't known, so the
+// label of each key is learned from the character it types when it is
+// first pressed.
+type keyLabels struct {
+	labels map[ebiten.Key]rune
+}
+
+// learn labels the key which was just pressed with the character it
+// typed. It is called once per frame, before the keys are polled.
+func (l *keyLabels) learn() {
+	chars := ebiten.InputChars()
+	if len(chars) != 1 {
+		return
+	}
+
+	// The character can only be told apart when a single key was
+	// pressed
+	pressed := ebiten.Key(-1)
+	for key := ebiten.Key(0); key <= ebiten.KeyMax; key++ {
+		if inpututil.IsKeyJustPressed(key) {
+			if pressed >= 0 {
+				return
+			}
+			pressed = key
+		}
+	}
+	if pressed >= 0 {
+		l.labels[pressed] = unicode.ToLower(chars[0])
+	}
+}
+
+// pressed reports whether the key with the label of the given key on
+// the US QWERTY layout is held. A key is still matched by its position
+// until its label is learned, or if it doesn't type a letter or digit.
+func (l *keyLabels) pressed(key ebiten.Key) bool {
+	label, ok := keyLabel(key)
+	if !ok {
+		return ebiten.IsKeyPressed(key)
+	}
+
+	for k, r := range l.labels {
+		if r == label && ebiten.IsKeyPressed(k) {
+			return true
+		}
+	}
+
+	// The key in its position is labeled differently on the layout
+	if r, ok := l.labels[key]; ok && isAlphanumeric(r) && r != label {
+		return false
+	}
+	return ebiten.IsKeyPressed(key)
+}
+
+// keyLabel returns the letter or digit which labels a key on the US
+// QWERTY layout, if any.
+func keyLabel(key ebiten.Key) (rune, bool) {
+	name := key.String()
+	if len(name) != 1 || !isAlphanumeric(rune(name[0])) {
+		return 0, false
+	}
+	return unicode.ToLower(rune(name[0])), true
+}
+
+// isAlphanumeric reports whether a character is an ASCII letter or
+// digit, which are what the keys of keymaps are labeled with.
+func isAlphanumeric(r rune) bool {
+	return r < unicode.MaxASCII && (unicode.IsLetter(r) || unicode.IsDigit(r))
+}
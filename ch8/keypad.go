@@ -0,0 +1,122 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Keypad Overlay
+//=====================================================================
+
+// keypadMargin is the distance (in pixels) between the keypad overlay
+// and the edges of the screen.
+const keypadMargin = 8
+
+// keypadLayout is the layout of the hexadecimal keypad of the COSMAC
+// VIP.
+var keypadLayout = [4][4]uint{
+	{0x1, 0x2, 0x3, 0xc},
+	{0x4, 0x5, 0x6, 0xd},
+	{0x7, 0x8, 0x9, 0xe},
+	{0xa, 0x0, 0xb, 0xf},
+}
+
+var (
+	keypadIdle    = color.RGBA{0x20, 0x20, 0x20, 0xc0}
+	keypadWaiting = color.RGBA{0xff, 0xcc, 0x00, 0xff}
+	keypadPanel   = color.RGBA{0x00, 0x00, 0x00, 0x90}
+)
+
+// drawKeypad draws the keypad with its top-left corner at origin,
+// highlighting the keys which are pressed. Every key is outlined while
+// the virtual machine waits for a key press.
+func drawKeypad(
+	screen *ebiten.Image,
+	origin image.Point,
+	cell int,
+	keys [chip8.NumberOfKeys]bool,
+	waiting bool,
+	palette chip8.Palette,
+) {
+	for row, hexes := range keypadLayout {
+		for col, hex := range hexes {
+			x := float64(origin.X + col*cell)
+			y := float64(origin.Y + row*cell)
+			size := float64(cell - 2)
+
+			if waiting {
+				ebitenutil.DrawRect(screen, x-1, y-1, size+2, size+2, keypadWaiting)
+			}
+
+			fill := color.Color(keypadIdle)
+			if keys[hex] {
+				fill = palette.Foreground()
+			}
+			ebitenutil.DrawRect(screen, x, y, size, size, fill)
+
+			ebitenutil.DebugPrintAt(
+				screen,
+				fmt.Sprintf("%X", hex),
+				int(x)+(cell-2)/2-3,
+				int(y)+(cell-2)/2-8,
+			)
+		}
+	}
+}
+
+// keypadOverlay shows the state of the CHIP-8 keypad on a panel in a
+// corner of the screen, so viewers of streams and recordings of the
+// window can follow the input of the player.
+type keypadOverlay struct {
+	visible bool
+	corner  string
+	cell    int
+}
+
+func newKeypadOverlay(cfg Config) keypadOverlay {
+	k := keypadOverlay{
+		visible: cfg.InputDisplay != "",
+		corner:  cfg.InputDisplay,
+		cell:    cfg.InputDisplaySize,
+	}
+	if k.corner == "" {
+		k.corner = CornerBottomRight
+	}
+	if k.cell < MinKeypadCellSize {
+		k.cell = DefaultKeypadCellSize
+	}
+	return k
+}
+
+// draw draws the overlay in its corner of the area of the screen below
+// the menu bar.
+func (k *keypadOverlay) draw(screen *ebiten.Image, bar image.Point, vm *chip8.VirtualMachine, palette chip8.Palette) {
+	if !k.visible {
+		return
+	}
+
+	area := screen.Bounds()
+	area.Min = area.Min.Add(bar)
+	size := 4 * k.cell
+
+	origin := image.Pt(area.Max.X-keypadMargin-size, area.Max.Y-keypadMargin-size)
+	switch k.corner {
+	case CornerTopLeft:
+		origin = area.Min.Add(image.Pt(keypadMargin, keypadMargin))
+	case CornerTopRight:
+		origin.Y = area.Min.Y + keypadMargin
+	case CornerBottomLeft:
+		origin.X = area.Min.X + keypadMargin
+	}
+
+	// The panel keeps the keys readable over any screen
+	panel := image.Rectangle{Min: origin, Max: origin.Add(image.Pt(size, size))}.Inset(-keypadMargin / 2)
+	drawRect(screen, panel, keypadPanel)
+	drawKeypad(screen, origin, k.cell, vm.Keys, vm.WaitingForKey(), palette)
+}
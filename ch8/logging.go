@@ -0,0 +1,21 @@
+package ch8
+
+import "github.com/kevhlee/chip8/chip8"
+
+//=====================================================================
+// Logging
+//=====================================================================
+
+// The modules of the emulator, which can be filtered separately
+var (
+	emuLog     = chip8.NewLogger("emulator")
+	configLog  = chip8.NewLogger("config")
+	stateLog   = chip8.NewLogger("state")
+	inputLog   = chip8.NewLogger("input")
+	netplayLog = chip8.NewLogger("netplay")
+	streamLog  = chip8.NewLogger("stream")
+	controlLog = chip8.NewLogger("control")
+	frameLog   = chip8.NewLogger("frame")
+	debugLog   = chip8.NewLogger("debugger")
+	tasLog     = chip8.NewLogger("tas")
+)
@@ -0,0 +1,192 @@
+package ch8
+
+import (
+	"fmt"
+	"strings"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Input Macros
+//=====================================================================
+
+// parseMacro parses a macro: the keys held in each of its frames,
+// written as with formatKeys and separated by spaces, such as
+// "5 . 5 . 5 .".
+func parseMacro(s string) ([][chip8.NumberOfKeys]bool, error) {
+	var frames [][chip8.NumberOfKeys]bool
+	for _, f := range strings.Fields(s) {
+		keys, err := parseKeys(f)
+		if err != nil {
+			return nil, fmt.Errorf("invalid macro: %s", s)
+		}
+		frames = append(frames, keys)
+	}
+
+	if len(frames) == 0 {
+		return nil, fmt.Errorf("invalid macro: %s", s)
+	}
+	return frames, nil
+}
+
+// formatMacro writes a macro as parsed by parseMacro.
+func formatMacro(frames [][chip8.NumberOfKeys]bool) string {
+	parts := make([]string, len(frames))
+	for i, keys := range frames {
+		parts[i] = formatKeys(keys)
+	}
+	return strings.Join(parts, " ")
+}
+
+// parseMacros parses macros by key name to macro, as written in the
+// configuration file.
+func parseMacros(macros map[string]string) (map[ebiten.Key][][chip8.NumberOfKeys]bool, error) {
+	keys := map[ebiten.Key][][chip8.NumberOfKeys]bool{}
+
+	for name, s := range macros {
+		key, err := ParseKey(name)
+		if err != nil {
+			return nil, err
+		}
+
+		frames, err := parseMacro(s)
+		if err != nil {
+			return nil, err
+		}
+		keys[key] = frames
+	}
+	return keys, nil
+}
+
+// macroPlayer replays short sequences of keypad input, such as a
+// rapid-fire pattern, when the keys they are bound to are pressed
+// during normal play. A macro is recorded from the input of the
+// emulator, and then bound to the next key pressed.
+//
+// It is an input source polled once per frame, which is only used from
+// the goroutine updating the emulator.
+type macroPlayer struct {
+	macros  map[ebiten.Key][][chip8.NumberOfKeys]bool
+	playing [][chip8.NumberOfKeys]bool
+
+	recording bool
+	binding   bool
+	recorded  [][chip8.NumberOfKeys]bool
+}
+
+// Press presses the keys of the next frame of the macro being played.
+func (p *macroPlayer) Press(keys *[chip8.NumberOfKeys]bool) {
+	if len(p.playing) == 0 {
+		return
+	}
+
+	for k, held := range p.playing[0] {
+		keys[k] = keys[k] || held
+	}
+	p.playing = p.playing[1:]
+}
+
+// update plays the macros whose keys were just pressed, and records
+// the keys held in the frame while a macro is being recorded.
+func (p *macroPlayer) update(keys [chip8.NumberOfKeys]bool) {
+	for key, frames := range p.macros {
+		if inpututil.IsKeyJustPressed(key) {
+			p.playing = frames
+		}
+	}
+
+	if p.recording {
+		p.recorded = append(p.recorded, keys)
+	}
+}
+
+// bindMacro binds the recorded macro to the key which was just
+// pressed, and reports whether it was bound. Pressing escape discards
+// the macro. Keys bound to the keypad or to hotkeys can't be bound to
+// macros.
+func (emu *Emulator) bindMacro() bool {
+	p := &emu.macros
+
+	if inpututil.IsKeyJustPressed(ebiten.KeyEscape) {
+		p.binding = false
+		inputLog.Info("discarded the macro")
+		return false
+	}
+
+	for key := ebiten.Key(0); key <= ebiten.KeyMax; key++ {
+		if !inpututil.IsKeyJustPressed(key) {
+			continue
+		}
+
+		if _, ok := keymapKeys(emu.keymaps[emu.keymap])[key]; ok {
+			inputLog.Warn(key, "is bound to the keypad")
+			return false
+		}
+		if a, ok := emu.hotkeys[key]; ok {
+			inputLog.Warn(key, "is the hotkey to", a.name)
+			return false
+		}
+
+		if p.macros == nil {
+			p.macros = map[ebiten.Key][][chip8.NumberOfKeys]bool{}
+		}
+		p.macros[key] = p.recorded
+		p.binding = false
+		inputLog.Infof("bound a macro of %d frames to %s", len(p.recorded), key)
+		return true
+	}
+
+	return false
+}
+
+// toggleMacro starts recording a macro, or stops recording it and
+// waits for the key to bind it to.
+func (emu *Emulator) toggleMacro() {
+	p := &emu.macros
+
+	if !p.recording {
+		p.recording = true
+		p.recorded = nil
+		inputLog.Info("recording a macro")
+		return
+	}
+
+	// The frames before the first key and after the last one are idle
+	p.recording = false
+	none := [chip8.NumberOfKeys]bool{}
+	for len(p.recorded) > 0 && p.recorded[0] == none {
+		p.recorded = p.recorded[1:]
+	}
+	for len(p.recorded) > 0 && p.recorded[len(p.recorded)-1] == none {
+		p.recorded = p.recorded[:len(p.recorded)-1]
+	}
+	if len(p.recorded) == 0 {
+		inputLog.Info("discarded the macro, since no keys were pressed")
+		return
+	}
+	p.binding = true
+	inputLog.Info("press a key to bind the macro to, or Escape to discard it")
+}
+
+// saveMacros saves the macros to the configuration file.
+func (emu *Emulator) saveMacros() {
+	macros := map[string]string{}
+	for key, frames := range emu.macros.macros {
+		macros[key.String()] = formatMacro(frames)
+	}
+	emu.cfg.Macros = macros
+
+	if emu.cfg.Path == "" {
+		return
+	}
+
+	err := UpdateConfig(emu.cfg.Path, func(cfg *Config) {
+		cfg.Macros = macros
+	})
+	if err != nil {
+		emuLog.Error(err)
+	}
+}
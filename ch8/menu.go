@@ -0,0 +1,287 @@
+package ch8
+
+import (
+	"image"
+	"image/color"
+	"io/ioutil"
+	"path/filepath"
+	"strings"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Menu Bar
+//=====================================================================
+
+const (
+	// menuBarHeight is the height (in pixels) of the menu bar and of
+	// each item of its menus, which fits a line of debug text.
+	menuBarHeight = 16
+
+	// menuCharWidth is the width (in pixels) of a character of debug
+	// text.
+	menuCharWidth = 6
+
+	// maxRecentROMs is the number of recently opened ROMs listed in
+	// the File menu.
+	maxRecentROMs = 8
+)
+
+// menus are the menus of the menu bar, by title to the names of the
+// actions they list.
+var menus = []struct {
+	title   string
+	actions []string
+}{
+	{"File", []string{
+		"screenshot", "gif", "video", "save-state", "load-state", "dump-memory",
+		"bug-report",
+	}},
+	{"Emulation", []string{
+		"menu", "play", "pause", "reset", "turbo", "faster", "slower",
+	}},
+	{"Options", []string{
+		"palette", "keymap", "rebind", "macro", "hud", "timing", "keypad", "registers", "grid",
+		"rotate", "fullscreen",
+	}},
+}
+
+// menuItem is an item of a menu, which runs an action when clicked.
+type menuItem struct {
+	label  string
+	hotkey string
+	run    func(emu *Emulator)
+}
+
+// menuBar is a bar of menus along the top of the window, listing the
+// actions of the emulator along with their hotkeys, so they can be
+// clicked rather than memorized.
+type menuBar struct {
+	enabled bool
+	open    int
+}
+
+func newMenuBar(enabled bool) menuBar {
+	return menuBar{enabled: enabled, open: -1}
+}
+
+// height returns the height of the menu bar, which is zero if it is
+// hidden.
+func (m *menuBar) height() int {
+	if !m.enabled {
+		return 0
+	}
+	return menuBarHeight
+}
+
+// titleArea returns the area of the title of a menu on the bar.
+func (m *menuBar) titleArea(menu int) image.Rectangle {
+	x := 0
+	for i := 0; i < menu; i++ {
+		x += (len(menus[i].title) + 2) * menuCharWidth
+	}
+	w := (len(menus[menu].title) + 2) * menuCharWidth
+	return image.Rect(x, 0, x+w, menuBarHeight)
+}
+
+// items returns the items of a menu, where the File menu also lists
+// the recently opened ROMs.
+func (m *menuBar) items(emu *Emulator, menu int) []menuItem {
+	var items []menuItem
+
+	if menus[menu].title == "File" {
+		for _, path := range emu.cfg.RecentROMs {
+			path := path
+			items = append(items, menuItem{
+				label: "Open " + filepath.Base(path),
+				run:   func(emu *Emulator) { emu.openROM(path) },
+			})
+		}
+	}
+
+	keys := emu.hotkeyNames()
+	for _, name := range menus[menu].actions {
+		a := findAction(name)
+		items = append(items, menuItem{a.description, keys[name], a.run})
+	}
+
+	return items
+}
+
+// hotkeyNames returns the names of the hotkeys bound to actions, by
+// action name.
+func (emu *Emulator) hotkeyNames() map[string]string {
+	keys := map[string]string{}
+	for key, a := range emu.hotkeys {
+		keys[a.name] = key.String()
+	}
+	return keys
+}
+
+// itemArea returns the area of an item of a menu, where the widest
+// label of the menu sets the width of every item.
+func (m *menuBar) itemArea(items []menuItem, menu, item int) image.Rectangle {
+	chars := 0
+	for _, it := range items {
+		if n := len(it.label) + len(it.hotkey) + 4; n > chars {
+			chars = n
+		}
+	}
+
+	x := m.titleArea(menu).Min.X
+	y := menuBarHeight * (item + 1)
+	return image.Rect(x, y, x+chars*menuCharWidth, y+menuBarHeight)
+}
+
+// update opens and closes menus and runs the items which are clicked,
+// and reports whether the click was taken by the menu bar.
+func (m *menuBar) update(emu *Emulator) bool {
+	if !m.enabled || !inpututil.IsMouseButtonJustPressed(ebiten.MouseButtonLeft) {
+		return false
+	}
+
+	p := image.Pt(ebiten.CursorPosition())
+	open := m.open
+	m.open = -1
+
+	if open >= 0 {
+		items := m.items(emu, open)
+		for i, item := range items {
+			if p.In(m.itemArea(items, open, i)) {
+				item.run(emu)
+				return true
+			}
+		}
+	}
+
+	for i := range menus {
+		if p.In(m.titleArea(i)) {
+			if i != open {
+				m.open = i
+			}
+			return true
+		}
+	}
+
+	return open >= 0
+}
+
+// draw draws the menu bar and the open menu, highlighting the item
+// under the mouse.
+func (m *menuBar) draw(screen *ebiten.Image, emu *Emulator) {
+	if !m.enabled {
+		return
+	}
+
+	bar := color.RGBA{0x20, 0x20, 0x20, 0xff}
+	highlight := color.RGBA{0x40, 0x40, 0x80, 0xff}
+	cursor := image.Pt(ebiten.CursorPosition())
+
+	w := screen.Bounds().Dx()
+	ebitenutil.DrawRect(screen, 0, 0, float64(w), menuBarHeight, bar)
+
+	for i, menu := range menus {
+		area := m.titleArea(i)
+		if i == m.open {
+			drawRect(screen, area, highlight)
+		}
+		ebitenutil.DebugPrintAt(screen, menu.title, area.Min.X+menuCharWidth, 0)
+	}
+
+	if m.open < 0 {
+		return
+	}
+
+	items := m.items(emu, m.open)
+	for i, item := range items {
+		area := m.itemArea(items, m.open, i)
+
+		c := bar
+		if cursor.In(area) {
+			c = highlight
+		}
+		drawRect(screen, area, c)
+
+		// Hotkeys are right-aligned
+		chars := area.Dx() / menuCharWidth
+		label := item.label
+		if item.hotkey != "" {
+			gap := chars - 2 - len(item.label) - len(item.hotkey)
+			label += strings.Repeat(" ", gap) + item.hotkey
+		}
+		ebitenutil.DebugPrintAt(screen, label, area.Min.X+menuCharWidth, area.Min.Y)
+	}
+}
+
+func drawRect(screen *ebiten.Image, r image.Rectangle, c color.Color) {
+	ebitenutil.DrawRect(
+		screen,
+		float64(r.Min.X), float64(r.Min.Y),
+		float64(r.Dx()), float64(r.Dy()),
+		c,
+	)
+}
+
+// openROM switches to the ROM file at path.
+func (emu *Emulator) openROM(path string) {
+	data, err := ioutil.ReadFile(path)
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	emu.switchROM(filepath.Base(path), data)
+	emu.addRecentROM(path)
+	emuLog.Info("opened", path)
+}
+
+// switchROM switches to another ROM, along with the palette and keymap
+// saved for it.
+func (emu *Emulator) switchROM(name string, data []byte) {
+	if rom, ok := emu.cfg.ROMs[name]; ok {
+		if i := chip8.FindPalette(emu.palettes, rom.Palette); i >= 0 {
+			emu.palette = i
+		}
+		if i := chip8.FindKeymap(emu.keymaps, rom.Keymap); i >= 0 {
+			emu.keymap = i
+		}
+	}
+
+	// The ROM is loaded by the goroutine running the virtual machine
+	emu.cfg.ROM = name
+	emu.nextROM = data
+	emu.romHash = hashROM(data)
+	emu.vmChan <- loadROMEvent
+	emu.redraw = true
+}
+
+// addRecentROM moves a ROM to the top of the recently opened ROMs, and
+// saves them to the configuration file.
+func (emu *Emulator) addRecentROM(path string) {
+	if abs, err := filepath.Abs(path); err == nil {
+		path = abs
+	}
+
+	recent := []string{path}
+	for _, p := range emu.cfg.RecentROMs {
+		if p != path && len(recent) < maxRecentROMs {
+			recent = append(recent, p)
+		}
+	}
+	emu.cfg.RecentROMs = recent
+
+	if emu.cfg.Path == "" {
+		return
+	}
+
+	err := UpdateConfig(emu.cfg.Path, func(cfg *Config) {
+		cfg.RecentROMs = recent
+	})
+	if err != nil {
+		emuLog.Error(err)
+	}
+}
@@ -0,0 +1,12 @@
+package ch8
+
+import "github.com/kevhlee/chip8/chip8"
+
+//=====================================================================
+// Metrics
+//=====================================================================
+
+// Metrics returns the counters of the virtual machine of the emulator.
+func (emu *Emulator) Metrics() chip8.Metrics {
+	return emu.vm.Metrics()
+}
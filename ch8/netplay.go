@@ -0,0 +1,277 @@
+package ch8
+
+import (
+	"bufio"
+	"crypto/sha1"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"net"
+	"strings"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Netplay
+//=====================================================================
+
+const (
+	// netplayVersion is the version of the netplay protocol, which
+	// both players must use.
+	netplayVersion = 2
+
+	// netplayDelay is the number of frames the keys of a player take
+	// to reach the virtual machine, which hides the latency of the
+	// network from both players.
+	netplayDelay = 3
+
+	// netplayTimeout is how long a player waits for the keys of the
+	// other player before giving up.
+	netplayTimeout = 10 * time.Second
+)
+
+var netplayMagic = [4]byte{'C', 'H', '8', 'N'}
+
+// netplayHello is the first message sent by each player. The seed and
+// speed of the host are used by both players, while the machine,
+// quirks, and timer rate must be the same for both.
+type netplayHello struct {
+	Magic     [4]byte
+	Version   uint8
+	Seed      int64
+	Speed     float64
+	TimerRate float64
+	Machine   [16]byte
+	Quirks    chip8.Quirks
+	ROM       [sha1.Size]byte
+}
+
+// netplayFrame is the message of the keys held by a player during a
+// frame, one bit per keypad key.
+type netplayFrame struct {
+	Frame uint32
+	Keys  uint16
+}
+
+// netplay runs the virtual machine in lockstep with another player over
+// TCP, so two people can play the same game from different machines.
+//
+// Both virtual machines start from the same ROM and seed, and run one
+// frame at a time with the combined keys of both players, so they stay
+// identical without sending anything but keys. Each player's keys are
+// delayed by a few frames to give them time to reach the other player.
+type netplay struct {
+	conn      net.Conn
+	reader    *bufio.Reader
+	writer    *bufio.Writer
+	frame     uint32
+	pending   []uint16
+	budget    float64
+	timers    float64
+	speed     float64
+	timerRate float64
+	machine   string
+}
+
+// startNetplay hosts a game at the given address, or joins the game
+// hosted at the given address, and waits for the other player.
+func startNetplay(host, join string, vm *chip8.VirtualMachine, cfg chip8.Config) (*netplay, error) {
+	var conn net.Conn
+
+	if host != "" {
+		listener, err := net.Listen("tcp", host)
+		if err != nil {
+			return nil, err
+		}
+		netplayLog.Info("waiting for a player on", listener.Addr())
+
+		conn, err = listener.Accept()
+		listener.Close()
+		if err != nil {
+			return nil, err
+		}
+	} else {
+		var err error
+		if conn, err = net.DialTimeout("tcp", join, netplayTimeout); err != nil {
+			return nil, err
+		}
+	}
+
+	n := &netplay{
+		conn:      conn,
+		reader:    bufio.NewReader(conn),
+		writer:    bufio.NewWriter(conn),
+		pending:   make([]uint16, netplayDelay),
+		speed:     cfg.Speed,
+		timerRate: cfg.TimerRate,
+		machine:   cfg.Machine,
+	}
+
+	if err := n.handshake(host != "", vm); err != nil {
+		conn.Close()
+		return nil, err
+	}
+
+	netplayLog.Info("playing with", conn.RemoteAddr())
+	return n, nil
+}
+
+// handshake exchanges hellos with the other player, checks that both
+// players run the same ROM on the same machine, and seeds the virtual
+// machine.
+func (n *netplay) handshake(hosting bool, vm *chip8.VirtualMachine) error {
+	hello := netplayHello{
+		Magic:     netplayMagic,
+		Version:   netplayVersion,
+		Seed:      time.Now().UnixNano(),
+		Speed:     n.speed,
+		TimerRate: n.timerRate,
+		Quirks:    vm.Quirks,
+		ROM:       programHash(vm),
+	}
+	copy(hello.Machine[:], n.machine)
+
+	n.conn.SetDeadline(time.Now().Add(netplayTimeout))
+	defer n.conn.SetDeadline(time.Time{})
+
+	if err := binary.Write(n.writer, binary.BigEndian, hello); err != nil {
+		return err
+	}
+	if err := n.writer.Flush(); err != nil {
+		return err
+	}
+
+	var peer netplayHello
+	if err := binary.Read(n.reader, binary.BigEndian, &peer); err != nil {
+		return err
+	}
+
+	switch {
+	case peer.Magic != netplayMagic:
+		return errors.New("netplay: the other player is not a CHIP-8 emulator")
+	case peer.Version != netplayVersion:
+		return fmt.Errorf("netplay: protocol version %d is not supported", peer.Version)
+	case peer.ROM != hello.ROM:
+		return errors.New("netplay: the other player is running a different ROM")
+	case peer.Machine != hello.Machine:
+		machine := strings.TrimRight(string(peer.Machine[:]), "\x00")
+		return fmt.Errorf("netplay: the other player is running the %s machine", machine)
+	case peer.Quirks != hello.Quirks:
+		return errors.New("netplay: the other player is running with different quirks")
+	case peer.TimerRate != hello.TimerRate:
+		return fmt.Errorf("netplay: the other player's timers run at %g Hz", peer.TimerRate)
+	}
+
+	if !hosting {
+		hello.Seed = peer.Seed
+		n.speed = peer.Speed
+	}
+	vm.Seed(hello.Seed)
+
+	return nil
+}
+
+// programHash returns the hash of the program loaded in the virtual
+// machine.
+func programHash(vm *chip8.VirtualMachine) [sha1.Size]byte {
+	program := make([]byte, chip8.ProgramMemorySize)
+	for i := range program {
+		program[i] = byte(vm.Memory[chip8.ProgramStartAddress+i])
+	}
+	return sha1.Sum(program)
+}
+
+// exchange sends the keys held by this player, and returns the keys of
+// both players which are due for the current frame.
+func (n *netplay) exchange(local [chip8.NumberOfKeys]bool) ([chip8.NumberOfKeys]bool, error) {
+	var keys uint16
+	for i, down := range local {
+		if down {
+			keys |= 1 << uint(i)
+		}
+	}
+
+	// Keys are sent for the frame they are due
+	out := netplayFrame{n.frame + netplayDelay, keys}
+	if err := binary.Write(n.writer, binary.BigEndian, out); err != nil {
+		return local, err
+	}
+	if err := n.writer.Flush(); err != nil {
+		return local, err
+	}
+	n.pending = append(n.pending, keys)
+
+	var in netplayFrame
+	if n.frame >= netplayDelay {
+		n.conn.SetReadDeadline(time.Now().Add(netplayTimeout))
+		if err := binary.Read(n.reader, binary.BigEndian, &in); err != nil {
+			return local, fmt.Errorf("netplay: %v", err)
+		}
+		if in.Frame != n.frame {
+			return local, fmt.Errorf("netplay: expected frame %d, got %d", n.frame, in.Frame)
+		}
+	}
+
+	keys = n.pending[0] | in.Keys
+	n.pending = n.pending[1:]
+	n.frame++
+
+	var combined [chip8.NumberOfKeys]bool
+	for i := range combined {
+		combined[i] = keys&(1<<uint(i)) != 0
+	}
+	return combined, nil
+}
+
+// runNetplayFrame runs a single frame of the virtual machine with the
+// keys of both players, in place of the goroutines which run it
+// otherwise.
+func (emu *Emulator) runNetplayFrame(local [chip8.NumberOfKeys]bool) error {
+	keys, err := emu.netplay.exchange(local)
+	if err != nil {
+		return err
+	}
+	emu.vm.Keys = keys
+
+	n := emu.netplay
+	n.budget += chip8.CyclesPerFrame(n.speed)
+	for ; n.budget >= 1.0; n.budget-- {
+		if err := emu.vm.RunCycle(); err != nil {
+			netplayLog.Error(err)
+		}
+	}
+
+	beep := emu.vm.ST > 0x00
+	emu.beeper.SetBeeping(beep)
+	emu.recordAudio(beep)
+
+	n.timers += n.timerRate / chip8.DefaultTPS
+	for ; n.timers >= 1.0; n.timers-- {
+		emu.vm.UpdateTimers()
+	}
+	return nil
+}
+
+// runOverlayFrame keeps netplay in lockstep with the peer while an
+// overlay captures the keys, by running a frame without any local keys
+// held. The peer would stall waiting for the frame otherwise.
+func (emu *Emulator) runOverlayFrame() error {
+	if emu.netplay == nil {
+		return nil
+	}
+
+	defer emu.frameGraph.measureEmulation(time.Now())
+	return emu.runNetplayFrame([chip8.NumberOfKeys]bool{})
+}
+
+// ignoreVMEvents discards the events sent to the virtual machine
+// during netplay, since pausing, resetting, or changing the speed of
+// one player's machine would desynchronize the game. Snapshots are
+// taken by snapshotVM without sending an event.
+func (emu *Emulator) ignoreVMEvents() {
+	for event := range emu.vmChan {
+		netplayLog.Infof("%s is not available", event)
+	}
+}
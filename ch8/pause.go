@@ -0,0 +1,157 @@
+package ch8
+
+import (
+	"errors"
+	"fmt"
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+)
+
+//=====================================================================
+// Pause Menu
+//=====================================================================
+
+// errQuit stops the game loop once quit is chosen from the pause menu.
+var errQuit = errors.New("quit")
+
+// pauseMenuWidth is the width (in characters) of the pause menu.
+const pauseMenuWidth = 32
+
+// pauseItem is an item of the pause menu. Enter chooses an item, and
+// left and right adjust it if it is a setting.
+type pauseItem struct {
+	label  func(emu *Emulator) string
+	choose func(emu *Emulator)
+	adjust func(emu *Emulator, step int)
+}
+
+// pauseItems are the items of the pause menu, from top to bottom.
+var pauseItems = []pauseItem{
+	{
+		label:  func(*Emulator) string { return "Resume" },
+		choose: (*Emulator).closePauseMenu,
+	},
+	{
+		label: func(*Emulator) string { return "Reset" },
+		choose: func(emu *Emulator) {
+			emu.reset()
+			emu.closePauseMenu()
+		},
+	},
+	{
+		label: func(*Emulator) string { return "Save state" },
+		choose: func(emu *Emulator) {
+			emu.requestSaveState()
+			emu.closePauseMenu()
+		},
+	},
+	{
+		label: func(*Emulator) string { return "Load state" },
+		choose: func(emu *Emulator) {
+			emu.requestLoadState()
+			emu.closePauseMenu()
+		},
+	},
+	{
+		label: func(emu *Emulator) string {
+			return fmt.Sprintf("Speed: < %gx >", emu.cfg.Speed)
+		},
+		choose: (*Emulator).faster,
+		adjust: func(emu *Emulator, step int) {
+			if step > 0 {
+				emu.faster()
+			} else {
+				emu.slower()
+			}
+		},
+	},
+	{
+		label: func(emu *Emulator) string {
+			return fmt.Sprintf("Palette: < %s >", emu.palettes[emu.palette].Name)
+		},
+		choose: (*Emulator).cyclePalette,
+		adjust: (*Emulator).stepPalette,
+	},
+	{
+		label:  func(*Emulator) string { return "Quit" },
+		choose: func(emu *Emulator) { emu.quit = true },
+	},
+}
+
+// pauseMenu is a menu shown over the display while the emulator is
+// paused, so that the common actions can be found without knowing any
+// hotkeys or flags. It is navigated with the arrow keys, Enter, and
+// Escape, and takes every key of the keyboard while it is open.
+type pauseMenu struct {
+	active   bool
+	selected int
+}
+
+// update moves the selection and runs the items which are chosen.
+func (m *pauseMenu) update(emu *Emulator) {
+	switch {
+	case inpututil.IsKeyJustPressed(ebiten.KeyEscape):
+		emu.closePauseMenu()
+	case inpututil.IsKeyJustPressed(ebiten.KeyUp):
+		m.selected = (m.selected + len(pauseItems) - 1) % len(pauseItems)
+	case inpututil.IsKeyJustPressed(ebiten.KeyDown):
+		m.selected = (m.selected + 1) % len(pauseItems)
+	case inpututil.IsKeyJustPressed(ebiten.KeyEnter):
+		pauseItems[m.selected].choose(emu)
+	case inpututil.IsKeyJustPressed(ebiten.KeyLeft):
+		if adjust := pauseItems[m.selected].adjust; adjust != nil {
+			adjust(emu, -1)
+		}
+	case inpututil.IsKeyJustPressed(ebiten.KeyRight):
+		if adjust := pauseItems[m.selected].adjust; adjust != nil {
+			adjust(emu, 1)
+		}
+	}
+}
+
+// draw shows the menu in the center of the screen, highlighting the
+// selected item.
+func (m *pauseMenu) draw(screen *ebiten.Image, emu *Emulator) {
+	if !m.active {
+		return
+	}
+
+	background := color.RGBA{0x20, 0x20, 0x20, 0xe0}
+	highlight := color.RGBA{0x40, 0x40, 0x80, 0xff}
+
+	size := screen.Bounds().Size()
+	box := image.Pt(pauseMenuWidth*menuCharWidth, (len(pauseItems)+2)*menuBarHeight)
+	origin := size.Sub(box).Div(2)
+	drawRect(screen, image.Rectangle{Min: origin, Max: origin.Add(box)}, background)
+
+	ebitenutil.DebugPrintAt(screen, "Paused", origin.X+menuCharWidth, origin.Y)
+
+	for i, item := range pauseItems {
+		y := origin.Y + (i+1)*menuBarHeight
+		if i == m.selected {
+			drawRect(screen, image.Rect(origin.X, y, origin.X+box.X, y+menuBarHeight), highlight)
+		}
+		ebitenutil.DebugPrintAt(screen, item.label(emu), origin.X+menuCharWidth, y)
+	}
+
+	hint := "Enter to choose, Esc to resume"
+	ebitenutil.DebugPrintAt(screen, hint, origin.X+menuCharWidth, origin.Y+box.Y-menuBarHeight)
+}
+
+func (emu *Emulator) openPauseMenu() {
+	if !emu.paused {
+		emu.pause()
+	}
+	emu.pauseMenu = pauseMenu{active: true}
+	emu.redraw = true
+}
+
+func (emu *Emulator) closePauseMenu() {
+	emu.pauseMenu.active = false
+	emu.play()
+	emu.redraw = true
+}
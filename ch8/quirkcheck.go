@@ -0,0 +1,250 @@
+package ch8
+
+import (
+	"math/rand"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Quirk Dependence
+//=====================================================================
+
+// The quirks which a program may depend on, in the order of the fields
+// of Quirks.
+const (
+	quirkShiftVY = iota
+	quirkIncrementI
+	quirkJumpVX
+	quirkResetVF
+	quirkClip
+	numberOfQuirks
+)
+
+// quirkBehaviors describe when the behavior of a program depends on
+// each quirk.
+var quirkBehaviors = [numberOfQuirks][2]string{
+	{"ShiftVY", "8XY6 or 8XYE shifted a register other than VY"},
+	{"IncrementI", "I was used after FX55 or FX65"},
+	{"JumpVX", "BNNN jumped with VX other than V0"},
+	{"ResetVF", "VF was used after 8XY1, 8XY2, or 8XY3"},
+	{"Clip", "DXYN drew a sprite past the edge of the screen"},
+}
+
+// QuirkDependence tells how often the behavior of a program depended
+// on a quirk, that is, how many instructions would have behaved
+// differently with the quirk turned the other way.
+type QuirkDependence struct {
+	// Quirk is the name of the field of Quirks, such as "ShiftVY".
+	Quirk string
+
+	// Behavior describes when the program depends on the quirk.
+	Behavior string
+
+	// Count is the number of instructions which depended on the quirk.
+	Count int
+
+	// First is the address of the first instruction which depended on
+	// the quirk.
+	First uint
+}
+
+// quirkDetector watches a virtual machine run, looking for
+// instructions whose behavior depends on a quirk.
+type quirkDetector struct {
+	deps [numberOfQuirks]QuirkDependence
+
+	// A store or load of registers (FX55, FX65) leaves I at one of two
+	// addresses until I is set again
+	staleI     bool
+	staleIAddr uint
+
+	// A logical instruction (8XY1, 8XY2, 8XY3) leaves one of two values
+	// in VF until VF is set again
+	staleVF     bool
+	staleVFAddr uint
+}
+
+func newQuirkDetector() *quirkDetector {
+	d := &quirkDetector{}
+	for i, b := range quirkBehaviors {
+		d.deps[i].Quirk, d.deps[i].Behavior = b[0], b[1]
+	}
+	return d
+}
+
+// hit records that the instruction at an address depended on a quirk.
+func (d *quirkDetector) hit(quirk int, addr uint) {
+	if d.deps[quirk].Count == 0 {
+		d.deps[quirk].First = addr
+	}
+	d.deps[quirk].Count++
+}
+
+// check looks at the instruction which the virtual machine is about to
+// execute.
+func (d *quirkDetector) check(vm *chip8.VirtualMachine) {
+	pc := vm.PC
+	if pc+1 >= chip8.MemorySize {
+		return
+	}
+	in := Decode(vm.Memory[pc]<<8 | vm.Memory[pc+1])
+	x, y := in.X, in.Y
+
+	// The stale value of VF or I only matters once it is used
+	if d.staleVF && in.readsVF() {
+		d.hit(quirkResetVF, d.staleVFAddr)
+		d.staleVF = false
+	} else if d.staleVF && in.writesVF() {
+		d.staleVF = false
+	}
+
+	if d.staleI && in.readsI() {
+		d.hit(quirkIncrementI, d.staleIAddr)
+		d.staleI = false
+	} else if d.staleI && in.writesI() {
+		d.staleI = false
+	}
+
+	switch in.Opcode >> 12 {
+	case 0x8:
+		switch in.N {
+		case 0x1, 0x2, 0x3:
+			if x != 0xf && vm.V[0xf] != 0 {
+				d.staleVF, d.staleVFAddr = true, pc
+			}
+		case 0x6, 0xe:
+			if vm.V[x] != vm.V[y] {
+				d.hit(quirkShiftVY, pc)
+			}
+		}
+	case 0xb:
+		if x != 0x0 && vm.V[x] != vm.V[0x0] {
+			d.hit(quirkJumpVX, pc)
+		}
+	case 0xd:
+		if d.clips(vm, in) {
+			d.hit(quirkClip, pc)
+		}
+	case 0xf:
+		if in.KK == 0x55 || in.KK == 0x65 {
+			d.staleI, d.staleIAddr = true, pc
+		}
+	}
+}
+
+// clips reports whether a sprite has pixels past the edges of the
+// screen, which are either clipped or wrapped around.
+func (d *quirkDetector) clips(vm *chip8.VirtualMachine, in Instruction) bool {
+	width, height := uint(vm.Display.Width), uint(vm.Display.Height)
+	x0, y0 := vm.V[in.X]%width, vm.V[in.Y]%height
+
+	for row := uint(0); row < in.N; row++ {
+		b := vm.Memory[(vm.I+row)%chip8.MemorySize]
+		if b == 0 {
+			continue
+		}
+		if y0+row >= height {
+			return true
+		}
+		for bit := uint(0); bit < SpriteWidth; bit++ {
+			if b&(0x80>>bit) != 0 && x0+bit >= width {
+				return true
+			}
+		}
+	}
+	return false
+}
+
+// readsVF reports whether the instruction uses the value of VF.
+func (in Instruction) readsVF() bool {
+	switch in.Opcode >> 12 {
+	case 0x3, 0x4, 0x7, 0xe:
+		return in.X == 0xf
+	case 0x5, 0x9, 0xd:
+		return in.X == 0xf || in.Y == 0xf
+	case 0x8:
+		return in.Y == 0xf || (in.X == 0xf && in.N != 0x0)
+	case 0xf:
+		switch in.KK {
+		case 0x15, 0x18, 0x1e, 0x29, 0x30, 0x33, 0x55:
+			return in.X == 0xf
+		}
+	}
+	return false
+}
+
+// writesVF reports whether the instruction sets VF.
+func (in Instruction) writesVF() bool {
+	switch in.Opcode >> 12 {
+	case 0x6, 0xc:
+		return in.X == 0xf
+	case 0x8:
+		return in.N >= 0x4 || in.X == 0xf
+	case 0xd:
+		return true
+	case 0xf:
+		switch in.KK {
+		case 0x07, 0x0a, 0x65:
+			return in.X == 0xf
+		}
+	}
+	return false
+}
+
+// readsI reports whether the instruction uses the value of I.
+func (in Instruction) readsI() bool {
+	switch in.Opcode >> 12 {
+	case 0xd:
+		return true
+	case 0xf:
+		switch in.KK {
+		case 0x1e, 0x33, 0x55, 0x65:
+			return true
+		}
+	}
+	return false
+}
+
+// writesI reports whether the instruction sets I.
+func (in Instruction) writesI() bool {
+	return in.Opcode>>12 == 0xa || (in.Opcode>>12 == 0xf && (in.KK == 0x29 || in.KK == 0x30))
+}
+
+// AnalyzeQuirks runs a program for a number of frames with the given
+// quirks, pressing random keys to play it, and reports how often its
+// behavior depended on each quirk.
+//
+// Only the paths which the program took are analyzed, so a program may
+// depend on quirks in code which wasn't run.
+func AnalyzeQuirks(program []byte, quirks chip8.Quirks, frames int) ([]QuirkDependence, error) {
+	vm := chip8.NewVirtualMachine()
+	vm.Quirks = quirks
+	if err := vm.LoadBytes(program); err != nil {
+		return nil, err
+	}
+
+	d := newQuirkDetector()
+	keys := &attractMode{demo: true, rng: rand.New(rand.NewSource(1))}
+	budget := 0.0
+
+	for frame := 0; frame < frames; frame++ {
+		vm.Keys = [chip8.NumberOfKeys]bool{}
+		keys.Press(&vm.Keys)
+
+		budget += chip8.CyclesPerFrame(chip8.DefaultSpeed)
+		for ; budget >= 1.0; budget-- {
+			d.check(vm)
+
+			err := vm.RunCycle()
+			if _, ok := err.(*chip8.ExitError); ok {
+				return d.deps[:], nil
+			} else if err != nil {
+				return d.deps[:], err
+			}
+		}
+		vm.UpdateTimers()
+	}
+
+	return d.deps[:], nil
+}
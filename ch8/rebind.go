@@ -0,0 +1,133 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Key Rebinding
+//=====================================================================
+
+// rebindCellSize is the size (in pixels) of a key of the keypad shown
+// while rebinding keys.
+const rebindCellSize = 32
+
+// rebinder asks for keys of the keyboard for each key of the CHIP-8
+// keypad in turn, in the layout of the COSMAC VIP keypad. Any number of
+// keys can be bound to a keypad key before moving on to the next one.
+type rebinder struct {
+	active  bool
+	next    int
+	bound   int
+	keys    map[ebiten.Key]uint
+	hotkeys map[ebiten.Key]*action
+	status  string
+}
+
+// start begins binding keys from the first key of the keypad. Keys
+// bound to hotkeys can't be bound to the keypad.
+func (r *rebinder) start(hotkeys map[ebiten.Key]*action) {
+	r.active = true
+	r.hotkeys = hotkeys
+	r.next = 0
+	r.bound = 0
+	r.keys = map[ebiten.Key]uint{}
+	r.status = ""
+}
+
+// current returns the keypad key which is being bound.
+func (r *rebinder) current() uint {
+	return keypadLayout[r.next/4][r.next%4]
+}
+
+// update binds the key which was just pressed to the current keypad
+// key, and reports whether every keypad key has been bound. Pressing
+// enter moves on to the next keypad key, and pressing escape cancels
+// the rebinding.
+func (r *rebinder) update() bool {
+	if inpututil.IsKeyJustPressed(ebiten.KeyEscape) {
+		r.active = false
+		return false
+	}
+
+	if inpututil.IsKeyJustPressed(ebiten.KeyEnter) {
+		if r.bound == 0 {
+			r.status = "No keys are bound yet"
+			return false
+		}
+
+		r.next++
+		r.bound = 0
+		r.status = ""
+
+		if r.next == chip8.NumberOfKeys {
+			r.active = false
+			return true
+		}
+		return false
+	}
+
+	for key := ebiten.Key(0); key <= ebiten.KeyMax; key++ {
+		if !inpututil.IsKeyJustPressed(key) {
+			continue
+		}
+
+		if _, ok := r.keys[key]; ok {
+			r.status = fmt.Sprintf("%s is already bound", key)
+			return false
+		}
+		if a, ok := r.hotkeys[key]; ok {
+			r.status = fmt.Sprintf("%s is the hotkey to %s", key, a.name)
+			return false
+		}
+
+		r.keys[key] = r.current()
+		r.bound++
+		r.status = fmt.Sprintf("Bound %s", key)
+		return false
+	}
+
+	return false
+}
+
+// draw shows the keypad in the center of the screen, highlighting the
+// key which is being bound.
+func (r *rebinder) draw(screen *ebiten.Image, palette chip8.Palette) {
+	if !r.active {
+		return
+	}
+
+	size := screen.Bounds().Size()
+	origin := image.Pt(
+		(size.X-4*rebindCellSize)/2,
+		(size.Y-4*rebindCellSize)/2,
+	)
+
+	var keys [chip8.NumberOfKeys]bool
+	keys[r.current()] = true
+	drawKeypad(screen, origin, rebindCellSize, keys, false, palette)
+
+	prompt := fmt.Sprintf(
+		"Press keys for %X, then Enter (Esc to cancel)", r.current(),
+	)
+	if r.status != "" {
+		prompt = r.status + "\n" + prompt
+	}
+	ebitenutil.DebugPrintAt(screen, prompt, origin.X-48, origin.Y+4*rebindCellSize+4)
+}
+
+// keyNames returns the bound keys by name, as written in the
+// configuration file.
+func (r *rebinder) keyNames() map[string]string {
+	names := map[string]string{}
+	for key, hex := range r.keys {
+		names[key.String()] = fmt.Sprintf("%X", hex)
+	}
+	return names
+}
@@ -0,0 +1,97 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Register Overlay
+//=====================================================================
+
+const (
+	// registerBarWidth is the width (in pixels) of the bar of a
+	// register at its largest value.
+	registerBarWidth = 36
+
+	// registerCellWidth is the width (in pixels) of a register, its
+	// value, and its bar.
+	registerCellWidth = 5*menuCharWidth + registerBarWidth + 12
+
+	// registerStackColumns is the number of stack entries listed on
+	// each line.
+	registerStackColumns = 4
+)
+
+var registerBarIdle = color.RGBA{0x40, 0x40, 0x40, 0xff}
+
+// registerOverlay shows the registers and stack of the virtual machine
+// in the top-right corner of the screen as the program runs, with a
+// bar beside each V register in proportion to its value.
+type registerOverlay struct {
+	visible bool
+}
+
+// draw draws the registers below the given point of the screen, which
+// is its top edge unless the menu bar is shown.
+func (r *registerOverlay) draw(screen *ebiten.Image, at image.Point, vm *chip8.VirtualMachine, palette chip8.Palette) {
+	if !r.visible {
+		return
+	}
+
+	sp := vm.SP
+	if sp > chip8.MaxStackDepth {
+		sp = chip8.MaxStackDepth
+	}
+	stackLines := (int(sp) + registerStackColumns - 1) / registerStackColumns
+
+	w := 2*registerCellWidth + 8
+	h := (chip8.NumberOfRegisters/2+2+stackLines)*lessonLineHeight + 4
+	x := screen.Bounds().Dx() - w
+	y := at.Y
+
+	ebitenutil.DrawRect(screen, float64(x), float64(y), float64(w), float64(h), color.RGBA{0, 0, 0, 0xc0})
+	x += 4
+
+	// The V registers are listed in two columns
+	for i, v := range vm.V {
+		cx := x + (i/(chip8.NumberOfRegisters/2))*registerCellWidth
+		cy := y + (i%(chip8.NumberOfRegisters/2))*lessonLineHeight
+
+		ebitenutil.DebugPrintAt(screen, fmt.Sprintf("V%X %.2X", i, v), cx, cy)
+
+		bx := float64(cx + 5*menuCharWidth + 4)
+		by := float64(cy + 5)
+		ebitenutil.DrawRect(screen, bx, by, registerBarWidth, 6, registerBarIdle)
+		ebitenutil.DrawRect(screen, bx, by, registerBarWidth*float64(v&0xff)/0xff, 6, palette.Foreground())
+	}
+	y += chip8.NumberOfRegisters / 2 * lessonLineHeight
+
+	ebitenutil.DebugPrintAt(screen, fmt.Sprintf("I %.3X  PC %.3X  SP %X", vm.I, vm.PC, vm.SP), x, y)
+	y += lessonLineHeight
+
+	// The stack is listed from the bottom up, as it was pushed
+	stack := "Stack: empty"
+	if sp > 0 {
+		stack = "Stack:"
+	}
+	ebitenutil.DebugPrintAt(screen, stack, x, y)
+
+	for i := 0; i < int(sp); i++ {
+		if i%registerStackColumns == 0 {
+			y += lessonLineHeight
+		}
+		cx := x + (i%registerStackColumns)*6*menuCharWidth
+		ebitenutil.DebugPrintAt(screen, fmt.Sprintf("%.3X", vm.Stack[i]), cx, y)
+	}
+}
+
+func (emu *Emulator) toggleRegisters() {
+	emu.registers.visible = !emu.registers.visible
+	emu.redraw = true
+}
@@ -0,0 +1,160 @@
+package ch8
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Regression Tests
+//=====================================================================
+
+// Golden is the expected display of a ROM of a regression corpus, once
+// it has run for a number of frames on a machine.
+type Golden struct {
+	ROM     string
+	Machine string
+	Frames  int
+	Hash    string
+}
+
+// GoldenResult is the outcome of running the ROM of a golden.
+type GoldenResult struct {
+	Golden
+
+	// Actual is the hash of the display the ROM ended with.
+	Actual string
+
+	// Err is the error which stopped the ROM, if any.
+	Err error
+}
+
+// Passed reports whether the ROM ended with the expected display.
+func (r GoldenResult) Passed() bool {
+	return r.Err == nil && r.Actual == r.Hash
+}
+
+// ReadGoldens reads the goldens of a regression corpus, one per line,
+// as the path of the ROM, the machine, the number of frames, and the
+// hash of the display (see Display.Hash), separated by spaces. Blank
+// lines and lines starting with # are skipped.
+func ReadGoldens(r io.Reader) ([]Golden, error) {
+	var goldens []Golden
+
+	scanner := bufio.NewScanner(r)
+	for n := 1; scanner.Scan(); n++ {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" || strings.HasPrefix(line, "#") {
+			continue
+		}
+
+		fields := strings.Fields(line)
+		if len(fields) < 4 {
+			return nil, fmt.Errorf("line %d: expected a ROM, machine, frames, and hash", n)
+		}
+		fields, last := fields[:len(fields)-3], fields[len(fields)-3:]
+
+		frames, err := strconv.Atoi(last[1])
+		if err != nil || frames < 1 {
+			return nil, fmt.Errorf("line %d: invalid frames: %s", n, last[1])
+		}
+		if chip8.FindMachine(last[0]) < 0 {
+			return nil, fmt.Errorf("line %d: unknown machine: %s", n, last[0])
+		}
+
+		goldens = append(goldens, Golden{
+			ROM:     strings.Join(fields, " "),
+			Machine: last[0],
+			Frames:  frames,
+			Hash:    last[2],
+		})
+	}
+
+	return goldens, scanner.Err()
+}
+
+// WriteGoldens writes the goldens of a regression corpus as read by
+// ReadGoldens.
+func WriteGoldens(w io.Writer, goldens []Golden) error {
+	if _, err := fmt.Fprintf(w, "%-24s %-8s %6s  %s\n", "# ROM", "machine", "frames", "display"); err != nil {
+		return err
+	}
+	for _, g := range goldens {
+		if _, err := fmt.Fprintf(w, "%-24s %-8s %6d  %s\n", g.ROM, g.Machine, g.Frames, g.Hash); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// LoadGoldens reads the goldens of a regression corpus from a file.
+func LoadGoldens(path string) ([]Golden, error) {
+	file, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	defer file.Close()
+
+	return ReadGoldens(file)
+}
+
+// SaveGoldens writes the goldens of a regression corpus to a file.
+func SaveGoldens(path string, goldens []Golden) error {
+	file, err := os.Create(path)
+	if err != nil {
+		return err
+	}
+
+	if err := WriteGoldens(file, goldens); err != nil {
+		file.Close()
+		return err
+	}
+	return file.Close()
+}
+
+// RunGoldens runs the ROM of each golden, whose path is relative to the
+// given directory, for its number of frames on its machine, and returns
+// the display it ended with. A ROM which exits (00FD) ends early. The
+// ROMs run on their own cores, all at once.
+func RunGoldens(dir string, goldens []Golden) []GoldenResult {
+	farm := chip8.NewFarm()
+	results := make([]GoldenResult, len(goldens))
+	for i, g := range goldens {
+		spec := chip8.MachineSpecs[0]
+		if m := chip8.FindMachine(g.Machine); m >= 0 {
+			spec = chip8.MachineSpecs[m]
+		}
+
+		core := chip8.NewCore(spec, chip8.DefaultSampleRate)
+		farm.Add(core)
+
+		results[i].Golden = g
+		results[i].Err = core.VM.LoadROM(filepath.Join(dir, g.ROM))
+	}
+
+	farm.Each(func(i int, c *chip8.Core) {
+		r := &results[i]
+		if r.Err != nil {
+			return
+		}
+
+		for frame := 0; frame < r.Frames; frame++ {
+			err := c.RunFrame()
+			if _, ok := err.(*chip8.ExitError); ok {
+				break
+			} else if err != nil {
+				r.Err = err
+				return
+			}
+		}
+		r.Actual = c.VM.Display.Hash()
+	})
+	return results
+}
@@ -0,0 +1,74 @@
+package ch8
+
+import (
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+// corpus is the goldens of the regression corpus checked into the
+// repository.
+const corpus = "../roms/tests/goldens.txt"
+
+func TestReadGoldens(t *testing.T) {
+	tests := []struct {
+		name    string
+		text    string
+		want    []Golden
+		wantErr bool
+	}{
+		{
+			name: "goldens",
+			text: "# ROM machine frames display\n\nfont.ch8 modern 60 d0fa\n",
+			want: []Golden{{ROM: "font.ch8", Machine: "modern", Frames: 60, Hash: "d0fa"}},
+		},
+		{
+			name: "ROM with spaces",
+			text: "my rom.ch8 schip 10 abcd\n",
+			want: []Golden{{ROM: "my rom.ch8", Machine: "schip", Frames: 10, Hash: "abcd"}},
+		},
+		{
+			name:    "missing hash",
+			text:    "font.ch8 modern 60\n",
+			wantErr: true,
+		},
+		{
+			name:    "invalid frames",
+			text:    "font.ch8 modern 0 d0fa\n",
+			wantErr: true,
+		},
+		{
+			name:    "unknown machine",
+			text:    "font.ch8 pdp8 60 d0fa\n",
+			wantErr: true,
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, err := ReadGoldens(strings.NewReader(tt.text))
+			if (err != nil) != tt.wantErr {
+				t.Fatalf("ReadGoldens() error = %v, want error %v", err, tt.wantErr)
+			}
+			if !tt.wantErr && !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("ReadGoldens() = %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestRegressionCorpus(t *testing.T) {
+	goldens, err := LoadGoldens(corpus)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	for _, r := range RunGoldens(filepath.Dir(corpus), goldens) {
+		if r.Err != nil {
+			t.Errorf("%s on %s: %v", r.ROM, r.Machine, r.Err)
+		} else if !r.Passed() {
+			t.Errorf("%s on %s: display %s, want %s", r.ROM, r.Machine, r.Actual, r.Hash)
+		}
+	}
+}
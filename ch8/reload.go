@@ -0,0 +1,104 @@
+package ch8
+
+import (
+	"os"
+	"reflect"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Config Reloading
+//=====================================================================
+
+// configPollInterval is how often the configuration file is checked
+// for changes.
+const configPollInterval = time.Second
+
+// watchConfig checks the configuration file for changes, and applies
+// the settings which were changed while the emulator runs.
+//
+// Only the settings changed in the file are applied, so the flags of
+// the CLI keep their precedence until the file changes the same
+// setting.
+func (emu *Emulator) watchConfig() {
+	path := emu.cfg.Path
+	if path == "" {
+		return
+	}
+
+	file, err := LoadConfig(path)
+	if err != nil {
+		return
+	}
+	file.UseROM(emu.cfg.ROM)
+	file.Resolve()
+
+	var modified time.Time
+	if info, err := os.Stat(path); err == nil {
+		modified = info.ModTime()
+	}
+
+	for range time.Tick(configPollInterval) {
+		info, err := os.Stat(path)
+		if err != nil || info.ModTime().Equal(modified) {
+			continue
+		}
+		modified = info.ModTime()
+
+		next, err := LoadConfig(path)
+		if err == nil {
+			next.UseROM(emu.cfg.ROM)
+			next.Resolve()
+			err = next.Validate()
+		}
+		if err != nil {
+			configLog.Error("can't reload config:", err)
+			continue
+		}
+
+		prev := file
+		file = next
+		emu.do(func() { emu.reloadConfig(prev, next) })
+	}
+}
+
+// reloadConfig applies the settings which differ between the previous
+// and the next contents of the configuration file.
+func (emu *Emulator) reloadConfig(prev, next Config) {
+	if next.Speed != prev.Speed {
+		emu.setSpeed(next.Speed)
+	}
+
+	if next.Quirks != prev.Quirks {
+		emu.cfg.Quirks = next.Quirks
+		emu.vmChan <- quirksEvent
+		configLog.Info("quirks:", next.Quirks)
+	}
+
+	if next.Palette != prev.Palette || !reflect.DeepEqual(next.Colors, prev.Colors) {
+		emu.cfg.Palette, emu.cfg.Colors = next.Palette, next.Colors
+		if palettes, err := emu.cfg.Palettes(); err == nil {
+			emu.palettes = palettes
+			emu.palette = chip8.FindPalette(palettes, emu.cfg.Palette)
+		}
+		if emu.palette < 0 {
+			emu.palette = 0
+		}
+		emu.redraw = true
+		configLog.Info("palette:", emu.palettes[emu.palette].Name)
+	}
+
+	if next.Keymap != prev.Keymap || !reflect.DeepEqual(next.Keys, prev.Keys) {
+		emu.cfg.Keymap, emu.cfg.Keys = next.Keymap, next.Keys
+		if keymaps, err := emu.cfg.Keymaps(); err == nil {
+			emu.keymaps = keymaps
+			emu.keymap = chip8.FindKeymap(keymaps, emu.cfg.Keymap)
+		}
+		if emu.keymap < 0 {
+			emu.keymap = 0
+		}
+		configLog.Info("keymap:", emu.keymaps[emu.keymap].Name)
+	}
+}
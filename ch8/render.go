@@ -0,0 +1,151 @@
+package ch8
+
+import (
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Rendering
+//=====================================================================
+
+// renderer draws the display of the virtual machine into an image,
+// which is then scaled onto the screen.
+//
+// Each pixel has an intensity, which fades out over a few frames once
+// the pixel is turned off when phosphor decay is enabled. This hides
+// the flicker of sprites that are erased and redrawn every frame.
+type renderer struct {
+	software  bool
+	size      image.Point
+	frame     *ebiten.Image
+	pixels    []byte
+	intensity []float64
+	decay     float64
+	fading    bool
+}
+
+func newRenderer(cfg Config) *renderer {
+	// Safe mode always hides flicker
+	frames := cfg.Decay
+	if cfg.SafeMode && frames < safeDecay {
+		frames = safeDecay
+	}
+
+	decay := 1.0
+	if frames > 0 {
+		decay = 1.0 / float64(frames+1)
+	}
+
+	return &renderer{
+		software: cfg.Renderer == RendererSoftware,
+		decay:    decay,
+	}
+}
+
+// resize recreates the frame for a display of the given resolution.
+func (r *renderer) resize(size image.Point) {
+	if r.frame != nil {
+		r.frame.Dispose()
+	}
+
+	r.size = size
+	r.frame = ebiten.NewImage(size.X, size.Y)
+	r.pixels = make([]byte, 4*size.X*size.Y)
+	r.intensity = make([]float64, size.X*size.Y)
+}
+
+// render draws the display of the virtual machine and returns the
+// resulting image.
+func (r *renderer) render(vm *chip8.VirtualMachine, fg, bg color.Color) *ebiten.Image {
+	on, off := rgba(fg), rgba(bg)
+	r.fading = false
+
+	// Pixels don't fade out across changes of resolution
+	if size := vm.Display.Size(); size != r.size {
+		r.resize(size)
+	}
+
+	if r.software {
+		r.frame.Fill(bg)
+	}
+
+	for y := 0; y < r.size.Y; y++ {
+		for x := 0; x < r.size.X; x++ {
+			level := r.updateIntensity(x, y, vm.Display.At(x, y))
+			c := blend(off, on, level)
+
+			if !r.software {
+				i := 4 * (y*r.size.X + x)
+				copy(r.pixels[i:i+4], c[:])
+			} else if level > 0.0 {
+				r.frame.Set(x, y, color.RGBA{c[0], c[1], c[2], c[3]})
+			}
+		}
+	}
+
+	if !r.software {
+		r.frame.ReplacePixels(r.pixels)
+	}
+	return r.frame
+}
+
+// isFading reports whether any pixel was still fading out during the
+// last render, in which case the display must be rendered again.
+func (r *renderer) isFading() bool {
+	return r.fading
+}
+
+func (r *renderer) updateIntensity(x, y int, lit bool) float64 {
+	i := y*r.size.X + x
+
+	switch {
+	case lit:
+		r.intensity[i] = 1.0
+	case r.intensity[i] > r.decay:
+		r.intensity[i] -= r.decay
+		r.fading = true
+	default:
+		r.intensity[i] = 0.0
+	}
+
+	return r.intensity[i]
+}
+
+// blend linearly interpolates between two RGBA colors.
+func blend(from, to [4]byte, t float64) [4]byte {
+	var c [4]byte
+	for i := range c {
+		c[i] = byte(float64(from[i]) + (float64(to[i])-float64(from[i]))*t)
+	}
+	return c
+}
+
+// rgba converts a color into its (alpha-premultiplied) RGBA bytes.
+func rgba(c color.Color) [4]byte {
+	r, g, b, a := c.RGBA()
+	return [4]byte{byte(r >> 8), byte(g >> 8), byte(b >> 8), byte(a >> 8)}
+}
+
+// fitDisplay returns the largest whole scale factor of a display with
+// the given resolution that fits within a screen of the given size, and
+// the offset which centers the scaled display on the screen.
+func fitDisplay(size, display image.Point) (int, image.Point) {
+	scale := size.X / display.X
+	if s := size.Y / display.Y; s < scale {
+		scale = s
+	}
+	if scale < 1 {
+		scale = 1
+	}
+
+	offset := image.Pt(
+		(size.X-display.X*scale)/2,
+		(size.Y-display.Y*scale)/2,
+	)
+
+	return scale, offset
+}
@@ -0,0 +1,55 @@
+package ch8
+
+import (
+	"image"
+	"math"
+
+	"github.com/hajimehoshi/ebiten/v2"
+)
+
+//=====================================================================
+// Rotation
+//=====================================================================
+
+// isQuarterTurn reports whether a rotation swaps the width and height
+// of the display.
+func isQuarterTurn(degrees int) bool {
+	return degrees == 90 || degrees == 270
+}
+
+// rotateSize returns the size of a display once it is rotated.
+func rotateSize(size image.Point, degrees int) image.Point {
+	if isQuarterTurn(degrees) {
+		return image.Pt(size.Y, size.X)
+	}
+	return size
+}
+
+// rotateGeoM rotates a display of the given (scaled) size clockwise,
+// keeping its top-left corner at the origin.
+func rotateGeoM(m *ebiten.GeoM, size image.Point, degrees int) {
+	m.Rotate(float64(degrees) * math.Pi / 180)
+
+	switch degrees {
+	case 90:
+		m.Translate(float64(size.Y), 0)
+	case 180:
+		m.Translate(float64(size.X), float64(size.Y))
+	case 270:
+		m.Translate(0, float64(size.X))
+	}
+}
+
+// unrotatePoint maps a pixel of the rotated display back to the pixel
+// of the display with the given size.
+func unrotatePoint(p, size image.Point, degrees int) image.Point {
+	switch degrees {
+	case 90:
+		return image.Pt(p.Y, size.Y-1-p.X)
+	case 180:
+		return image.Pt(size.X-1-p.X, size.Y-1-p.Y)
+	case 270:
+		return image.Pt(size.X-1-p.Y, p.X)
+	}
+	return p
+}
@@ -0,0 +1,102 @@
+package ch8
+
+import (
+	"image"
+	"image/color"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Photosensitivity
+//=====================================================================
+
+const (
+	// safeDecay is the least number of frames pixels take to fade out
+	// in safe mode.
+	safeDecay = 6
+
+	// safeContrast is how much of the contrast between the colors of
+	// the palette is kept in safe mode.
+	safeContrast = 0.6
+
+	// safeFlashes is the most flashes shown in any second in safe mode,
+	// the threshold of the WCAG guidelines for flashing content.
+	safeFlashes = 3
+
+	// safeFlashArea is how much of the screen must change at once for
+	// the change to count as a flash.
+	safeFlashArea = 0.25
+)
+
+// reduceContrast pulls the foreground and background colors towards
+// each other, so that the screen flashes less brightly.
+func reduceContrast(fg, bg color.RGBA) (color.RGBA, color.RGBA) {
+	mid := blend(rgba(bg), rgba(fg), 0.5)
+	f := blend(mid, rgba(fg), safeContrast)
+	b := blend(mid, rgba(bg), safeContrast)
+	return color.RGBA{f[0], f[1], f[2], f[3]}, color.RGBA{b[0], b[1], b[2], b[3]}
+}
+
+// flashLimiter caps how many times per second the screen may flash,
+// either by the visual bell or by a ROM changing most of its display
+// at once. Flashes over the cap are held back until a later frame.
+//
+// It is only used from the goroutine drawing the emulator.
+type flashLimiter struct {
+	flashes []time.Time
+
+	// shown is the display as it was last drawn
+	size  image.Point
+	shown []bool
+}
+
+// allow reports whether another flash may be shown now, and if so
+// counts it.
+func (l *flashLimiter) allow() bool {
+	now := time.Now()
+	for len(l.flashes) > 0 && now.Sub(l.flashes[0]) >= time.Second {
+		l.flashes = l.flashes[1:]
+	}
+
+	if len(l.flashes) >= safeFlashes {
+		return false
+	}
+	l.flashes = append(l.flashes, now)
+	return true
+}
+
+// flashes reports whether drawing the display would change enough of
+// the screen to count as a flash.
+func (l *flashLimiter) flashes(d *chip8.Display) bool {
+	size := d.Size()
+	if size != l.size {
+		return false
+	}
+
+	changed := 0
+	for y := 0; y < size.Y; y++ {
+		for x := 0; x < size.X; x++ {
+			if d.At(x, y) != l.shown[y*size.X+x] {
+				changed++
+			}
+		}
+	}
+	return float64(changed) >= safeFlashArea*float64(size.X*size.Y)
+}
+
+// show records the display as drawn.
+func (l *flashLimiter) show(d *chip8.Display) {
+	size := d.Size()
+	if size != l.size {
+		l.size = size
+		l.shown = make([]bool, size.X*size.Y)
+	}
+
+	for y := 0; y < size.Y; y++ {
+		for x := 0; x < size.X; x++ {
+			l.shown[y*size.X+x] = d.At(x, y)
+		}
+	}
+}
@@ -0,0 +1,101 @@
+package ch8
+
+import (
+	"bytes"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Persistent Memory
+//=====================================================================
+
+// saveRAMInterval is how often the persistent memory is written to its
+// file while a program changes it.
+const saveRAMInterval = time.Second
+
+// saveRAM is a range of memory kept in a file for each ROM, like the
+// battery-backed memory of a cartridge, so programs can keep high
+// scores or saved games between runs.
+type saveRAM struct {
+	r     chip8.AddressRange
+	path  string
+	saved []byte
+	last  time.Time
+}
+
+// savePath returns the path of the persistent memory of the running
+// ROM.
+func (emu *Emulator) savePath() string {
+	return filepath.Join(emu.cfg.StateDir, emu.romName()+".sav")
+}
+
+// saveRAMBytes returns the bytes of the persistent memory.
+func (emu *Emulator) saveRAMBytes() []byte {
+	data, _ := emu.vm.MemoryRange(int(emu.save.r.Start), int(emu.save.r.End-emu.save.r.Start))
+	return data
+}
+
+// loadSaveRAM loads the persistent memory of the running ROM into the
+// virtual machine. The memory of a ROM without a file is left as the
+// ROM loaded it.
+func (emu *Emulator) loadSaveRAM() {
+	s := emu.save
+	if s == nil {
+		return
+	}
+	s.path = emu.savePath()
+	s.saved = emu.saveRAMBytes()
+
+	data, err := ioutil.ReadFile(s.path)
+	if os.IsNotExist(err) {
+		return
+	} else if err != nil {
+		stateLog.Error(err)
+		return
+	}
+
+	if len(data) != len(s.saved) {
+		stateLog.Warn("ignoring", s.path, "since it doesn't match the size of the persistent memory")
+		return
+	}
+
+	for i, b := range data {
+		emu.vm.Memory[s.r.Start+uint(i)] = uint(b)
+	}
+	s.saved = data
+	stateLog.Info("loaded persistent memory from", s.path)
+}
+
+// syncSaveRAM writes the persistent memory to its file if the program
+// changed it, at most once per saveRAMInterval unless forced. It must
+// be called from the goroutine running the virtual machine.
+func (emu *Emulator) syncSaveRAM(force bool) {
+	s := emu.save
+	if s == nil || s.path == "" {
+		return
+	}
+	if !force && time.Since(s.last) < saveRAMInterval {
+		return
+	}
+	s.last = time.Now()
+
+	data := emu.saveRAMBytes()
+	if bytes.Equal(data, s.saved) {
+		return
+	}
+
+	if err := os.MkdirAll(filepath.Dir(s.path), 0755); err != nil {
+		stateLog.Error(err)
+		return
+	}
+	if err := ioutil.WriteFile(s.path, data, 0644); err != nil {
+		stateLog.Error(err)
+		return
+	}
+	s.saved = data
+}
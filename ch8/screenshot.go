@@ -0,0 +1,93 @@
+package ch8
+
+import (
+	"image"
+	"image/png"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Screenshots
+//=====================================================================
+
+// RenderImage draws the display of the virtual machine into an image
+// using the colors of a palette, where each CHIP-8 pixel is a square of
+// scale by scale pixels.
+func RenderImage(vm *chip8.VirtualMachine, palette chip8.Palette, scale int) *image.RGBA {
+	return RenderDisplay(&vm.Display, palette, scale)
+}
+
+// RenderDisplay draws a display into an image like RenderImage, such as
+// the display of a frame kept from a FrameIterator.
+func RenderDisplay(display *chip8.Display, palette chip8.Palette, scale int) *image.RGBA {
+	return renderDisplay(display, palette, display.Size().Mul(scale))
+}
+
+// RenderImageSize draws the display of the virtual machine into an
+// image of the given size, stretching the display over the image. This
+// keeps the size of the image fixed when the resolution changes.
+func RenderImageSize(vm *chip8.VirtualMachine, palette chip8.Palette, size image.Point) *image.RGBA {
+	return renderDisplay(&vm.Display, palette, size)
+}
+
+func renderDisplay(display *chip8.Display, palette chip8.Palette, size image.Point) *image.RGBA {
+	img := image.NewRGBA(image.Rectangle{Max: size})
+	fg, bg := palette.Foreground(), palette.Background()
+
+	for y := 0; y < size.Y; y++ {
+		for x := 0; x < size.X; x++ {
+			if display.Sample(x, y, size) {
+				img.SetRGBA(x, y, fg)
+			} else {
+				img.SetRGBA(x, y, bg)
+			}
+		}
+	}
+
+	return img
+}
+
+// SavePNG writes an image to a PNG file.
+func SavePNG(path string, img image.Image) error {
+	file, err := os.Create(path)
+	if err != nil {
+		return err
+	}
+
+	if err := png.Encode(file, img); err != nil {
+		file.Close()
+		return err
+	}
+
+	return file.Close()
+}
+
+// timestampedPath returns a path within dir for a file of the running
+// ROM, named after the current time.
+func timestampedPath(dir, rom, ext string) string {
+	name := strings.TrimSuffix(rom, filepath.Ext(rom))
+	if name == "" {
+		name = "ch8"
+	}
+
+	stamp := time.Now().Format("2006-01-02_15-04-05.000")
+	return filepath.Join(dir, name+"_"+stamp+ext)
+}
+
+// takeScreenshot saves the display as a PNG file in the screenshot
+// directory and returns the path of the file.
+func (emu *Emulator) takeScreenshot() (string, error) {
+	if err := os.MkdirAll(emu.cfg.ScreenshotDir, 0755); err != nil {
+		return "", err
+	}
+
+	path := timestampedPath(emu.cfg.ScreenshotDir, emu.cfg.ROM, ".png")
+	img := RenderImage(emu.vm, emu.palettes[emu.palette], emu.cfg.ScreenshotScale)
+
+	return path, SavePNG(path, img)
+}
@@ -0,0 +1,104 @@
+package ch8
+
+import (
+	"fmt"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Memory Search
+//=====================================================================
+
+const (
+	// SearchIncreased keeps the addresses whose value increased.
+	SearchIncreased = "increased"
+
+	// SearchDecreased keeps the addresses whose value decreased.
+	SearchDecreased = "decreased"
+
+	// SearchChanged keeps the addresses whose value changed.
+	SearchChanged = "changed"
+
+	// SearchUnchanged keeps the addresses whose value stayed the same.
+	SearchUnchanged = "unchanged"
+
+	// SearchEqual keeps the addresses whose value is equal to a number.
+	SearchEqual = "equal"
+)
+
+// SearchResult is an address which is still a candidate of a memory
+// search, along with its value at the last snapshot.
+type SearchResult struct {
+	Addr  uint `json:"addr"`
+	Value uint `json:"value"`
+}
+
+// MemorySearch narrows down where a program keeps a value, such as the
+// score or the lives of a game, by taking snapshots of memory while the
+// value changes, and keeping only the addresses which changed the same
+// way between them.
+type MemorySearch struct {
+	last       [chip8.MemorySize]uint
+	candidates []uint
+}
+
+// NewMemorySearch starts a search from a snapshot of memory, with every
+// address as a candidate.
+func NewMemorySearch(memory [chip8.MemorySize]uint) *MemorySearch {
+	s := &MemorySearch{last: memory}
+	for addr := range memory {
+		s.candidates = append(s.candidates, uint(addr))
+	}
+	return s
+}
+
+// Filter takes another snapshot of memory, and keeps the candidates
+// whose value changed since the last one as the filter says. The
+// number is only used by SearchEqual.
+func (s *MemorySearch) Filter(memory [chip8.MemorySize]uint, filter string, n uint) error {
+	var keep func(before, after uint) bool
+
+	switch filter {
+	case SearchIncreased:
+		keep = func(before, after uint) bool { return after > before }
+	case SearchDecreased:
+		keep = func(before, after uint) bool { return after < before }
+	case SearchChanged:
+		keep = func(before, after uint) bool { return after != before }
+	case SearchUnchanged:
+		keep = func(before, after uint) bool { return after == before }
+	case SearchEqual:
+		keep = func(before, after uint) bool { return after == n }
+	default:
+		return fmt.Errorf("unknown search filter: %s", filter)
+	}
+
+	candidates := s.candidates[:0]
+	for _, addr := range s.candidates {
+		if keep(s.last[addr], memory[addr]) {
+			candidates = append(candidates, addr)
+		}
+	}
+
+	s.candidates = candidates
+	s.last = memory
+	return nil
+}
+
+// Results returns the candidates of the search, up to a limit.
+func (s *MemorySearch) Results(limit int) []SearchResult {
+	results := []SearchResult{}
+	for _, addr := range s.candidates {
+		if len(results) == limit {
+			break
+		}
+		results = append(results, SearchResult{addr, s.last[addr]})
+	}
+	return results
+}
+
+// Count returns the number of candidates of the search.
+func (s *MemorySearch) Count() int {
+	return len(s.candidates)
+}
@@ -0,0 +1,113 @@
+package ch8
+
+import (
+	"errors"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Smoke Tests
+//=====================================================================
+
+const (
+	// SmokePassed is the status of a ROM which ran without failing,
+	// exited (00FD), or waited for a key.
+	SmokePassed = "ok"
+
+	// SmokeCrashed is the status of a ROM which halted the virtual
+	// machine with an error, such as an invalid jump or a stack
+	// overflow.
+	SmokeCrashed = "crash"
+
+	// SmokeIllegal is the status of a ROM which ran an invalid opcode.
+	SmokeIllegal = "illegal"
+
+	// SmokeHung is the status of a ROM which kept running without
+	// drawing, reading the keys, or using the timers.
+	SmokeHung = "hang"
+)
+
+// SmokeResult is the outcome of running a ROM on a machine in a smoke
+// test.
+type SmokeResult struct {
+	ROM     string
+	Machine string
+	Status  string
+
+	// Frames is the number of frames the ROM ran before it failed or
+	// exited, or every frame of the test.
+	Frames int
+
+	// Err describes why the ROM failed, if it did.
+	Err error
+}
+
+// SmokeTest runs every ROM (*.ch8) of a directory headlessly on each of
+// the given machines for a number of frames, and reports which ones
+// crash, hang, or run invalid opcodes, which helps to check changes to
+// the emulator against a large library of ROMs.
+//
+// Each ROM runs on its own core, all at once, and the results are in
+// the order of the ROMs, then of the machines.
+func SmokeTest(dir string, machines []chip8.MachineSpec, frames int) ([]SmokeResult, error) {
+	roms, err := chip8.ListROMs(dir)
+	if err != nil {
+		return nil, err
+	}
+
+	farm := chip8.NewFarm()
+	var results []SmokeResult
+	for _, rom := range roms {
+		data, err := chip8.ReadROM(rom)
+		if err != nil {
+			return nil, err
+		}
+
+		for _, spec := range machines {
+			core := chip8.NewCore(spec, chip8.DefaultSampleRate)
+
+			result := SmokeResult{ROM: rom, Machine: spec.Name, Status: SmokePassed}
+			if err := core.VM.LoadBytes(data); err != nil {
+				result.Status, result.Err = SmokeCrashed, err
+			}
+			farm.Add(core)
+			results = append(results, result)
+		}
+	}
+
+	farm.Each(func(i int, c *chip8.Core) {
+		if results[i].Err == nil {
+			smokeTest(c, &results[i], frames)
+		}
+	})
+	return results, nil
+}
+
+// smokeTest runs the ROM loaded by a core until it fails, exits, or the
+// frames are up.
+func smokeTest(c *chip8.Core, result *SmokeResult, frames int) {
+	var watchdog stallWatchdog
+
+	for result.Frames < frames {
+		err := c.RunFrame()
+		result.Frames++
+
+		switch err.(type) {
+		case nil:
+		case *chip8.ExitError:
+			return
+		case *chip8.OpcodeError:
+			result.Status, result.Err = SmokeIllegal, err
+			return
+		default:
+			result.Status, result.Err = SmokeCrashed, err
+			return
+		}
+
+		if watchdog.update(c.VM) && watchdog.stalled() {
+			result.Status, result.Err = SmokeHung, errors.New(watchdog.message())
+			return
+		}
+	}
+}
@@ -0,0 +1,148 @@
+package ch8
+
+import (
+	"bufio"
+	"errors"
+	"fmt"
+	"net"
+	"strconv"
+	"strings"
+	"sync"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Input Socket
+//=====================================================================
+
+// socketTapFrames is the number of frames a tapped key is held by
+// default.
+const socketTapFrames = 6
+
+// socketInput is an input source taking keypad events from clients of
+// a TCP or Unix socket, such as chat bots or scripts. Each line sent to
+// the socket is a command:
+//
+//	down <key>          hold a keypad key, e.g. "down 5"
+//	up <key>            release a keypad key
+//	tap <key> [frames]  hold a keypad key for a few frames
+//	release             release every keypad key
+//
+// Every command is answered with "ok" or an error.
+type socketInput struct {
+	listener net.Listener
+	mutex    sync.Mutex
+	held     [chip8.NumberOfKeys]bool
+	taps     [chip8.NumberOfKeys]int
+}
+
+// listenInputSocket listens for clients at the given address, which is
+// either a path prefixed with "unix:" or a TCP address.
+func listenInputSocket(addr string) (*socketInput, error) {
+	network := "tcp"
+	if strings.HasPrefix(addr, "unix:") {
+		network, addr = "unix", strings.TrimPrefix(addr, "unix:")
+	}
+
+	listener, err := net.Listen(network, addr)
+	if err != nil {
+		return nil, err
+	}
+
+	s := &socketInput{listener: listener}
+	go s.accept()
+
+	inputLog.Info("taking input on", network, listener.Addr())
+	return s, nil
+}
+
+func (s *socketInput) accept() {
+	for {
+		conn, err := s.listener.Accept()
+		if err != nil {
+			return
+		}
+		go s.serve(conn)
+	}
+}
+
+func (s *socketInput) serve(conn net.Conn) {
+	defer conn.Close()
+
+	scanner := bufio.NewScanner(conn)
+	for scanner.Scan() {
+		reply := "ok"
+		if err := s.run(strings.Fields(scanner.Text())); err != nil {
+			reply = "error: " + err.Error()
+		}
+		if _, err := fmt.Fprintln(conn, reply); err != nil {
+			return
+		}
+	}
+}
+
+// run runs a command sent to the socket.
+func (s *socketInput) run(args []string) error {
+	if len(args) == 0 {
+		return errors.New("empty command")
+	}
+
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if args[0] == "release" {
+		s.held = [chip8.NumberOfKeys]bool{}
+		s.taps = [chip8.NumberOfKeys]int{}
+		return nil
+	}
+
+	if len(args) < 2 {
+		return fmt.Errorf("%s needs a key", args[0])
+	}
+
+	key, err := chip8.ParseKeypadKey(args[1])
+	if err != nil {
+		return err
+	}
+
+	switch args[0] {
+	case "down":
+		s.held[key] = true
+	case "up":
+		s.held[key] = false
+		s.taps[key] = 0
+	case "tap":
+		frames := socketTapFrames
+		if len(args) > 2 {
+			if frames, err = strconv.Atoi(args[2]); err != nil || frames < 1 {
+				return fmt.Errorf("invalid number of frames: %s", args[2])
+			}
+		}
+		s.taps[key] = frames
+	default:
+		return fmt.Errorf("unknown command: %s", args[0])
+	}
+
+	return nil
+}
+
+// Press presses the keypad keys which are held or tapped. It is called
+// once per frame, so taps run out after their number of frames.
+func (s *socketInput) Press(keys *[chip8.NumberOfKeys]bool) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	for i := range keys {
+		if s.taps[i] > 0 {
+			s.taps[i]--
+			keys[i] = true
+		}
+		keys[i] = keys[i] || s.held[i]
+	}
+}
+
+// Close stops listening for clients.
+func (s *socketInput) Close() error {
+	return s.listener.Close()
+}
@@ -0,0 +1,165 @@
+package ch8
+
+import (
+	"bufio"
+	"fmt"
+	"image"
+	"image/color"
+	"io"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Sprites
+//=====================================================================
+
+const (
+	// SpriteWidth is the width (in pixels) of a CHIP-8 sprite.
+	SpriteWidth = 8
+
+	// MaxSpriteHeight is the height (in pixels) of the tallest CHIP-8
+	// sprite (DXYN).
+	MaxSpriteHeight = 15
+
+	// spriteSheetColumns is the number of sprites in each row of a
+	// sprite sheet.
+	spriteSheetColumns = 8
+)
+
+// RenderSprites draws the sprites in data, each of which is height
+// bytes tall, into a sheet of up to 8 sprites per row using the colors
+// of a palette. Each pixel of a sprite is a square of scale by scale
+// pixels, and the sprites are separated by transparent gaps.
+func RenderSprites(data []byte, height int, palette chip8.Palette, scale int) *image.RGBA {
+	count := (len(data) + height - 1) / height
+	columns := count
+	if columns > spriteSheetColumns {
+		columns = spriteSheetColumns
+	}
+	rows := (count + columns - 1) / columns
+
+	// Sprites are laid out on a grid of cells with a gap of 1 pixel
+	cell := image.Pt(SpriteWidth+1, height+1).Mul(scale)
+	size := image.Pt(columns*cell.X-scale, rows*cell.Y-scale)
+	img := image.NewRGBA(image.Rectangle{Max: size})
+	fg, bg := palette.Foreground(), palette.Background()
+
+	for i, b := range data {
+		sprite, row := i/height, i%height
+		origin := image.Pt(sprite%columns*cell.X, sprite/columns*cell.Y+row*scale)
+
+		for bit := 0; bit < SpriteWidth; bit++ {
+			c := bg
+			if b&(0x80>>uint(bit)) != 0 {
+				c = fg
+			}
+
+			for dy := 0; dy < scale; dy++ {
+				for dx := 0; dx < scale; dx++ {
+					img.SetRGBA(origin.X+bit*scale+dx, origin.Y+dy, c)
+				}
+			}
+		}
+	}
+
+	return img
+}
+
+const (
+	// SpriteOcto writes sprites as Octo source, a label followed by
+	// the bytes of the sprite.
+	SpriteOcto = "octo"
+
+	// SpriteAsm writes sprites as assembler source, a label followed
+	// by DB directives.
+	SpriteAsm = "asm"
+
+	// wideSpriteSize is the width and height (in pixels) of a 16x16
+	// SUPER-CHIP sprite (DXY0).
+	wideSpriteSize = 16
+)
+
+// ImageSprites cuts an image into CHIP-8 sprites, left to right and
+// top to bottom, each of which is 8 by height pixels, or 16 by 16
+// pixels for SUPER-CHIP if wide. Pixels past the edges of the image are
+// off.
+//
+// Opaque pixels lighter than middle gray are on, or darker ones if
+// invert is set.
+func ImageSprites(img image.Image, height int, wide, invert bool) [][]byte {
+	width := SpriteWidth
+	if wide {
+		width, height = wideSpriteSize, wideSpriteSize
+	}
+
+	bounds := img.Bounds()
+	columns := (bounds.Dx() + width - 1) / width
+	rows := (bounds.Dy() + height - 1) / height
+
+	var sprites [][]byte
+	for row := 0; row < rows; row++ {
+		for column := 0; column < columns; column++ {
+			origin := bounds.Min.Add(image.Pt(column*width, row*height))
+			sprite := make([]byte, 0, height*width/8)
+
+			for y := 0; y < height; y++ {
+				for x := 0; x < width; x += 8 {
+					var b byte
+					for bit := 0; bit < 8; bit++ {
+						p := origin.Add(image.Pt(x+bit, y))
+						if p.In(bounds) && spritePixel(img.At(p.X, p.Y), invert) {
+							b |= 0x80 >> uint(bit)
+						}
+					}
+					sprite = append(sprite, b)
+				}
+			}
+
+			sprites = append(sprites, sprite)
+		}
+	}
+
+	return sprites
+}
+
+// spritePixel reports whether a pixel of an image is on in a sprite.
+func spritePixel(c color.Color, invert bool) bool {
+	if _, _, _, a := c.RGBA(); a < 0x8000 {
+		return false
+	}
+	gray := color.Gray16Model.Convert(c).(color.Gray16)
+	return (gray.Y >= 0x8000) != invert
+}
+
+// WriteSprites writes sprites as source code in the given format,
+// either SpriteOcto or SpriteAsm, labeling them after name, such as:
+//
+//	: ship0
+//		0x18 0x3C 0x7E 0xFF
+func WriteSprites(w io.Writer, sprites [][]byte, name, format string) error {
+	if format != SpriteOcto && format != SpriteAsm {
+		return fmt.Errorf("unknown sprite format: %s", format)
+	}
+
+	b := bufio.NewWriter(w)
+	for i, sprite := range sprites {
+		label := name
+		if len(sprites) > 1 {
+			label = fmt.Sprintf("%s%d", name, i)
+		}
+
+		values := make([]string, len(sprite))
+		for j, v := range sprite {
+			values[j] = fmt.Sprintf("0x%.2X", v)
+		}
+
+		if format == SpriteOcto {
+			fmt.Fprintf(b, ": %s\n\t%s\n", label, strings.Join(values, " "))
+		} else {
+			fmt.Fprintf(b, "%s:\n\tDB %s\n", label, strings.Join(values, ", "))
+		}
+	}
+	return b.Flush()
+}
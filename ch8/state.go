@@ -0,0 +1,56 @@
+package ch8
+
+import (
+	"path/filepath"
+	"strings"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Save States
+//=====================================================================
+
+// romName returns the file name of the running ROM without its
+// extension, which names the files kept for the ROM.
+func (emu *Emulator) romName() string {
+	name := strings.TrimSuffix(emu.cfg.ROM, filepath.Ext(emu.cfg.ROM))
+	if name == "" {
+		name = "ch8"
+	}
+	return name
+}
+
+// statePath returns the path of the save state of the running ROM.
+func (emu *Emulator) statePath() string {
+	return filepath.Join(emu.cfg.StateDir, emu.romName()+".state")
+}
+
+// saveState saves the state of the virtual machine. It must be called
+// from the goroutine running the virtual machine.
+func (emu *Emulator) saveState() {
+	path := emu.statePath()
+	if err := chip8.SaveSnapshot(path, emu.vm.Snapshot()); err != nil {
+		stateLog.Error(err)
+		return
+	}
+	stateLog.Info("saved state to", path)
+}
+
+// loadState restores the saved state of the virtual machine. It must
+// be called from the goroutine running the virtual machine.
+func (emu *Emulator) loadState() {
+	path := emu.statePath()
+
+	s, err := chip8.LoadSnapshot(path)
+	if err != nil {
+		stateLog.Error(err)
+		return
+	}
+
+	if err := emu.vm.Restore(s); err != nil {
+		stateLog.Error(err)
+		return
+	}
+	stateLog.Info("loaded state from", path)
+}
@@ -0,0 +1,264 @@
+package ch8
+
+import (
+	"bytes"
+	"encoding/json"
+	"net"
+	"net/http"
+	"sync"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Streaming
+//=====================================================================
+
+// streamBacklog is the number of frames queued for a client before
+// frames are dropped, so a slow client never stalls the emulator.
+const streamBacklog = 4
+
+// streamServer streams the display of the emulator to web browsers
+// over WebSocket, and takes key presses from them, so the emulator can
+// be watched and played from another machine.
+//
+// Every frame whose display changed is sent as a binary message: the
+// width and height of the display, the foreground and background RGB
+// colors of the palette, and then the pixels row by row, 8 pixels per
+// byte with the leftmost pixel in the high bit. Clients send key
+// presses as JSON text messages, such as {"key": 5, "down": true}.
+type streamServer struct {
+	mutex   sync.Mutex
+	clients map[*streamClient]bool
+	last    []byte
+}
+
+// streamClient is a browser connected to the stream server.
+type streamClient struct {
+	conn   *wsConn
+	frames chan []byte
+	keys   [chip8.NumberOfKeys]bool
+}
+
+// streamKey is a key press sent by a client.
+type streamKey struct {
+	Key  uint `json:"key"`
+	Down bool `json:"down"`
+}
+
+// startStreamServer starts serving the page of the stream and the
+// stream itself at the given address, such as ":8080".
+func startStreamServer(addr string) (*streamServer, error) {
+	listener, err := net.Listen("tcp", addr)
+	if err != nil {
+		return nil, err
+	}
+
+	s := &streamServer{clients: map[*streamClient]bool{}}
+
+	mux := http.NewServeMux()
+	mux.HandleFunc("/", s.servePage)
+	mux.HandleFunc("/stream", s.serveStream)
+
+	go func() {
+		if err := http.Serve(listener, mux); err != nil {
+			streamLog.Error(err)
+		}
+	}()
+
+	streamLog.Info("streaming on", listener.Addr())
+	return s, nil
+}
+
+// capture sends the display to the clients if it changed since the
+// last frame.
+func (s *streamServer) capture(vm *chip8.VirtualMachine, palette chip8.Palette) {
+	frame := encodeStreamFrame(&vm.Display, palette)
+
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	if bytes.Equal(frame, s.last) {
+		return
+	}
+	s.last = frame
+
+	for client := range s.clients {
+		select {
+		case client.frames <- frame:
+		default:
+		}
+	}
+}
+
+// encodeStreamFrame encodes a display as a frame of the stream.
+func encodeStreamFrame(display *chip8.Display, palette chip8.Palette) []byte {
+	fg, bg := palette.Foreground(), palette.Background()
+	frame := []byte{
+		byte(display.Width), byte(display.Height),
+		fg.R, fg.G, fg.B, bg.R, bg.G, bg.B,
+	}
+
+	bits := make([]byte, (display.Width*display.Height+7)/8)
+	for i, on := range display.Pixels {
+		if on {
+			bits[i/8] |= 0x80 >> uint(i%8)
+		}
+	}
+
+	return append(frame, bits...)
+}
+
+// Press presses the keypad keys held by any of the clients.
+func (s *streamServer) Press(keys *[chip8.NumberOfKeys]bool) {
+	s.mutex.Lock()
+	defer s.mutex.Unlock()
+
+	for client := range s.clients {
+		for i, down := range client.keys {
+			keys[i] = keys[i] || down
+		}
+	}
+}
+
+// Async marks the stream as safe to poll from any goroutine.
+func (s *streamServer) Async() {}
+
+func (s *streamServer) servePage(w http.ResponseWriter, r *http.Request) {
+	if r.URL.Path != "/" {
+		http.NotFound(w, r)
+		return
+	}
+
+	w.Header().Set("Content-Type", "text/html; charset=utf-8")
+	w.Write([]byte(streamPage))
+}
+
+func (s *streamServer) serveStream(w http.ResponseWriter, r *http.Request) {
+	conn, err := upgradeWebSocket(w, r)
+	if err != nil {
+		streamLog.Error(err)
+		return
+	}
+	defer conn.Close()
+
+	client := &streamClient{
+		conn:   conn,
+		frames: make(chan []byte, streamBacklog),
+	}
+
+	// New clients start from the last frame
+	s.mutex.Lock()
+	s.clients[client] = true
+	if s.last != nil {
+		client.frames <- s.last
+	}
+	s.mutex.Unlock()
+
+	streamLog.Info("stream client connected:", r.RemoteAddr)
+	defer streamLog.Info("stream client disconnected:", r.RemoteAddr)
+
+	go func() {
+		for frame := range client.frames {
+			if err := conn.writeMessage(wsBinary, frame); err != nil {
+				conn.Close()
+				return
+			}
+		}
+	}()
+
+	for {
+		opcode, data, err := conn.readMessage()
+		if err != nil {
+			break
+		}
+		if opcode != wsText {
+			continue
+		}
+
+		var key streamKey
+		if err := json.Unmarshal(data, &key); err != nil || key.Key >= chip8.NumberOfKeys {
+			continue
+		}
+
+		s.mutex.Lock()
+		client.keys[key.Key] = key.Down
+		s.mutex.Unlock()
+	}
+
+	// The keys of the client are released once it leaves
+	s.mutex.Lock()
+	delete(s.clients, client)
+	close(client.frames)
+	s.mutex.Unlock()
+}
+
+// streamPage is the page which shows the stream in a browser. The keys
+// of the keyboard are mapped to the keypad by their position, as in the
+// positional keymap.
+const streamPage = `<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>CHIP-8</title>
+<style>
+  body { margin: 0; background: #000; color: #ccc; font-family: sans-serif; }
+  canvas { display: block; width: 100vw; max-height: 90vh; object-fit: contain; image-rendering: pixelated; }
+  p { text-align: center; }
+</style>
+</head>
+<body>
+<canvas id="screen" width="64" height="32"></canvas>
+<p id="status">Connecting...</p>
+<script>
+const keys = {
+  Digit1: 0x1, Digit2: 0x2, Digit3: 0x3, Digit4: 0xc,
+  KeyQ: 0x4, KeyW: 0x5, KeyE: 0x6, KeyR: 0xd,
+  KeyA: 0x7, KeyS: 0x8, KeyD: 0x9, KeyF: 0xe,
+  KeyZ: 0xa, KeyX: 0x0, KeyC: 0xb, KeyV: 0xf,
+};
+
+const canvas = document.getElementById("screen");
+const context = canvas.getContext("2d");
+const status = document.getElementById("status");
+
+const scheme = location.protocol === "https:" ? "wss:" : "ws:";
+const socket = new WebSocket(scheme + "//" + location.host + "/stream");
+socket.binaryType = "arraybuffer";
+
+socket.onopen = () => { status.textContent = "Connected"; };
+socket.onclose = () => { status.textContent = "Disconnected"; };
+
+socket.onmessage = (event) => {
+  const frame = new Uint8Array(event.data);
+  const width = frame[0], height = frame[1];
+  if (canvas.width !== width || canvas.height !== height) {
+    canvas.width = width;
+    canvas.height = height;
+  }
+
+  const image = context.createImageData(width, height);
+  for (let i = 0; i < width * height; i++) {
+    const on = frame[8 + (i >> 3)] & (0x80 >> (i & 7));
+    const c = on ? 2 : 5;
+    image.data.set([frame[c], frame[c + 1], frame[c + 2], 0xff], 4 * i);
+  }
+  context.putImageData(image, 0, 0);
+};
+
+function send(event, down) {
+  const key = keys[event.code];
+  if (key === undefined || event.repeat || socket.readyState !== WebSocket.OPEN) {
+    return;
+  }
+  socket.send(JSON.stringify({ key: key, down: down }));
+  event.preventDefault();
+}
+
+document.addEventListener("keydown", (event) => send(event, true));
+document.addEventListener("keyup", (event) => send(event, false));
+</script>
+</body>
+</html>
+`
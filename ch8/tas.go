@@ -0,0 +1,413 @@
+package ch8
+
+import (
+	"bufio"
+	"bytes"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/inpututil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Tool-Assisted Mode
+//=====================================================================
+
+// tasKeyframeInterval is how many frames apart the states kept for
+// seeking through a movie are.
+const tasKeyframeInterval = 60
+
+// Movie is the keypad input of a session: the keys held during each
+// frame, from the start of the ROM.
+type Movie struct {
+	Frames [][chip8.NumberOfKeys]bool
+}
+
+// formatKeys returns the held keys as hexadecimal digits, such as
+// "15C", or "." if no keys are held.
+func formatKeys(keys [chip8.NumberOfKeys]bool) string {
+	var sb strings.Builder
+	for k, held := range keys {
+		if held {
+			fmt.Fprintf(&sb, "%X", k)
+		}
+	}
+
+	if sb.Len() == 0 {
+		return "."
+	}
+	return sb.String()
+}
+
+// parseKeys parses held keys written by formatKeys.
+func parseKeys(s string) ([chip8.NumberOfKeys]bool, error) {
+	var keys [chip8.NumberOfKeys]bool
+	if s == "." {
+		return keys, nil
+	}
+
+	for _, c := range s {
+		k, err := strconv.ParseUint(string(c), 16, 4)
+		if err != nil {
+			return keys, fmt.Errorf("invalid keys: %s", s)
+		}
+		keys[k] = true
+	}
+	return keys, nil
+}
+
+// WriteMovie writes a movie as text, with a line for each frame listing
+// the keys held during it, as with formatKeys.
+func WriteMovie(w io.Writer, m *Movie) error {
+	bw := bufio.NewWriter(w)
+	bw.WriteString("# CHIP-8 movie: the keypad keys held in each frame\n")
+
+	for _, keys := range m.Frames {
+		bw.WriteString(formatKeys(keys))
+		bw.WriteByte('\n')
+	}
+	return bw.Flush()
+}
+
+// ReadMovie reads a movie written by WriteMovie. Empty lines and lines
+// starting with "#" are skipped.
+func ReadMovie(r io.Reader) (*Movie, error) {
+	m := &Movie{}
+
+	scanner := bufio.NewScanner(r)
+	for line := 1; scanner.Scan(); line++ {
+		s := strings.TrimSpace(scanner.Text())
+		if s == "" || strings.HasPrefix(s, "#") {
+			continue
+		}
+
+		keys, err := parseKeys(s)
+		if err != nil {
+			return nil, fmt.Errorf("invalid keys on line %d: %s", line, s)
+		}
+		m.Frames = append(m.Frames, keys)
+	}
+
+	return m, scanner.Err()
+}
+
+// SaveMovie writes a movie to a file.
+func SaveMovie(path string, m *Movie) error {
+	if dir := filepath.Dir(path); dir != "" {
+		if err := os.MkdirAll(dir, 0755); err != nil {
+			return err
+		}
+	}
+
+	file, err := os.Create(path)
+	if err != nil {
+		return err
+	}
+
+	if err := WriteMovie(file, m); err != nil {
+		file.Close()
+		return err
+	}
+	return file.Close()
+}
+
+// LoadMovie reads a movie from a file.
+func LoadMovie(path string) (*Movie, error) {
+	file, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	defer file.Close()
+
+	return ReadMovie(file)
+}
+
+// tasKeyframe is the state of the virtual machine at the start of a
+// frame, which seeking through the movie restores. Long movies keep
+// hundreds of states, so they are kept compressed.
+type tasKeyframe struct {
+	state  []byte
+	budget float64
+}
+
+func newTASKeyframe(vm *chip8.VirtualMachine, budget float64) tasKeyframe {
+	var state bytes.Buffer
+	if err := chip8.WriteSnapshot(&state, vm.Snapshot()); err != nil {
+		tasLog.Error(err)
+	}
+	return tasKeyframe{state.Bytes(), budget}
+}
+
+// tasEditor runs the virtual machine one frame at a time with the input
+// of a movie, which can be edited frame by frame and re-recorded from
+// any frame.
+//
+// It is only used from the goroutine updating the emulator, which runs
+// the virtual machine in tool-assisted mode.
+type tasEditor struct {
+	path      string
+	movie     *Movie
+	frame     int
+	budget    float64
+	keyframes map[int]tasKeyframe
+	bookmark  int
+
+	// While recording, the keys held while running replace the input
+	// of the movie; otherwise the movie is played back as is
+	recording bool
+
+	// events are the events sent to the virtual machine, forwarded to
+	// the goroutine updating the emulator
+	events chan string
+}
+
+func newTASEditor(path string) *tasEditor {
+	t := &tasEditor{
+		path:   path,
+		movie:  &Movie{},
+		events: make(chan string, 16),
+	}
+
+	if m, err := LoadMovie(path); err == nil {
+		t.movie = m
+		tasLog.Infof("loaded %d frames of input from %s", len(m.Frames), path)
+	} else if !os.IsNotExist(err) {
+		tasLog.Error(err)
+	}
+
+	// A new movie is recorded, and an existing one played back
+	t.recording = len(t.movie.Frames) == 0
+	return t
+}
+
+// reset starts the movie over from the current state of the virtual
+// machine, which must be the start of the ROM.
+func (t *tasEditor) reset(vm *chip8.VirtualMachine) {
+	t.frame, t.budget, t.bookmark = 0, 0.0, 0
+	t.keyframes = map[int]tasKeyframe{0: newTASKeyframe(vm, 0.0)}
+}
+
+// input returns the keys held during a frame of the movie.
+func (t *tasEditor) input(frame int) [chip8.NumberOfKeys]bool {
+	if frame < len(t.movie.Frames) {
+		return t.movie.Frames[frame]
+	}
+	return [chip8.NumberOfKeys]bool{}
+}
+
+// setInput changes the keys held during a frame of the movie, which
+// discards the states kept after it.
+func (t *tasEditor) setInput(frame int, keys [chip8.NumberOfKeys]bool) {
+	if keys == t.input(frame) && frame < len(t.movie.Frames) {
+		return
+	}
+
+	for len(t.movie.Frames) <= frame {
+		t.movie.Frames = append(t.movie.Frames, [chip8.NumberOfKeys]bool{})
+	}
+	t.movie.Frames[frame] = keys
+
+	for f := range t.keyframes {
+		if f > frame {
+			delete(t.keyframes, f)
+		}
+	}
+}
+
+// run runs the current frame with its input from the movie, and
+// reports whether the beeper sounded during it.
+func (t *tasEditor) run(vm *chip8.VirtualMachine, speed float64) bool {
+	if _, ok := t.keyframes[t.frame]; !ok && t.frame%tasKeyframeInterval == 0 {
+		t.keyframes[t.frame] = newTASKeyframe(vm, t.budget)
+	}
+
+	// Random numbers are seeded by the frame, so a frame runs the same
+	// way however it was reached
+	vm.Seed(int64(t.frame))
+	vm.Keys = t.input(t.frame)
+
+	t.budget += chip8.CyclesPerFrame(speed)
+	for ; t.budget >= 1.0; t.budget-- {
+		if err := vm.RunCycle(); err != nil {
+			tasLog.Error(err)
+		}
+	}
+
+	beep := vm.ST > 0x00
+	vm.UpdateTimers()
+	t.frame++
+	return beep
+}
+
+// seek returns the virtual machine to the start of a frame, by
+// restoring the closest state kept before it and running the frames in
+// between.
+func (t *tasEditor) seek(vm *chip8.VirtualMachine, frame int, speed float64) {
+	if frame < 0 {
+		frame = 0
+	}
+
+	start := 0
+	for f := range t.keyframes {
+		if f <= frame && f > start {
+			start = f
+		}
+	}
+
+	k := t.keyframes[start]
+	s, err := chip8.ReadSnapshot(bytes.NewReader(k.state))
+	if err != nil {
+		tasLog.Error(err)
+		return
+	}
+	if err := vm.Restore(s); err != nil {
+		tasLog.Error(err)
+		return
+	}
+	t.frame, t.budget = start, k.budget
+
+	for t.frame < frame {
+		t.run(vm, speed)
+	}
+}
+
+// save writes the movie to its file.
+func (t *tasEditor) save() {
+	if err := SaveMovie(t.path, t.movie); err != nil {
+		tasLog.Error(err)
+		return
+	}
+	tasLog.Infof("saved %d frames of input to %s", len(t.movie.Frames), t.path)
+}
+
+// status describes the position in the movie for the window title,
+// such as "Frame 120/600 (recording)".
+func (t *tasEditor) status() string {
+	mode := "read-only"
+	if t.recording {
+		mode = "recording"
+	}
+	return fmt.Sprintf("Frame %d/%d (%s)", t.frame, len(t.movie.Frames), mode)
+}
+
+// forwardTASEvents forwards the events sent to the virtual machine to
+// the goroutine updating the emulator, which runs the virtual machine in
+// tool-assisted mode.
+func (emu *Emulator) forwardTASEvents() {
+	for event := range emu.vmChan {
+		emu.tas.events <- event
+	}
+}
+
+// handleTASEvents handles the events sent to the virtual machine in
+// tool-assisted mode. Resetting returns to the start of the movie, and
+// the save state is a bookmark of a frame of the movie, which loading
+// the state seeks back to.
+func (emu *Emulator) handleTASEvents() {
+	t := emu.tas
+
+	for {
+		select {
+		case event := <-t.events:
+			switch event {
+			case playEvent, pauseEvent, speedEvent:
+				// The emulator and its configuration are read directly
+			case resetEvent:
+				t.seek(emu.vm, 0, emu.cfg.Speed)
+			case saveStateEvent:
+				t.bookmark = t.frame
+				t.save()
+				tasLog.Infof("bookmarked frame %d", t.frame)
+			case loadStateEvent:
+				t.seek(emu.vm, t.bookmark, emu.cfg.Speed)
+				tasLog.Infof("returned to frame %d", t.frame)
+			case dumpMemoryEvent:
+				emu.dumpMemory()
+			case bugReportEvent:
+				emu.saveBugReport()
+			case loadROMEvent:
+				emu.vm.Clear()
+				if err := emu.vm.LoadBytes(emu.nextROM); err != nil {
+					emuLog.Error(err)
+				}
+				t.reset(emu.vm)
+			case quirksEvent:
+				emu.vm.Quirks = emu.cfg.MachineQuirks()
+			case snapshotEvent:
+				emu.snapshots <- emu.vm.Snapshot()
+			default:
+				tasLog.Infof("%s is not available", event)
+			}
+		default:
+			return
+		}
+	}
+}
+
+// updateTAS runs a frame of tool-assisted mode with the keys held on
+// the input sources. Space pauses and resumes the movie, "." advances
+// it by a frame, "," steps it back by a frame, and "/" switches between
+// recording and playing back. While paused, the keys of the keypad
+// toggle the keys held during the next frame.
+func (emu *Emulator) updateTAS(keys [chip8.NumberOfKeys]bool) {
+	t := emu.tas
+	emu.handleTASEvents()
+	defer emu.updateTitle()
+
+	if inpututil.IsKeyJustPressed(ebiten.KeySpace) {
+		emu.paused = !emu.paused
+	}
+	if inpututil.IsKeyJustPressed(ebiten.KeySlash) {
+		t.recording = !t.recording
+	}
+	if inpututil.IsKeyJustPressed(ebiten.KeyComma) {
+		emu.paused = true
+		t.seek(emu.vm, t.frame-1, emu.cfg.Speed)
+	}
+
+	step := inpututil.IsKeyJustPressed(ebiten.KeyPeriod)
+	if emu.paused && !step {
+		input := t.input(t.frame)
+		for key, hex := range keymapKeys(emu.keymaps[emu.keymap]) {
+			if inpututil.IsKeyJustPressed(key) {
+				input[hex] = !input[hex]
+			}
+		}
+		if input != t.input(t.frame) {
+			t.setInput(t.frame, input)
+		}
+
+		emu.beeper.SetBeeping(false)
+		return
+	}
+
+	// Keys held while advancing a frame are added to its input, and
+	// keys held while running replace it
+	if t.recording {
+		input := keys
+		if step {
+			for k, held := range t.input(t.frame) {
+				input[k] = input[k] || held
+			}
+		}
+		t.setInput(t.frame, input)
+	} else if t.frame >= len(t.movie.Frames) {
+		emu.paused = true
+		tasLog.Info("reached the end of the movie")
+		return
+	}
+
+	if step {
+		emu.paused = true
+	}
+
+	beep := t.run(emu.vm, emu.cfg.Speed)
+	emu.beeper.SetBeeping(beep)
+	emu.recordAudio(beep)
+}
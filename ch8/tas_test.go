@@ -0,0 +1,110 @@
+package ch8
+
+import (
+	"bytes"
+	"reflect"
+	"strings"
+	"testing"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+// heldKeys returns the state of the keypad with the given keys held.
+func heldKeys(keys ...uint) [chip8.NumberOfKeys]bool {
+	var held [chip8.NumberOfKeys]bool
+	for _, k := range keys {
+		held[k] = true
+	}
+	return held
+}
+
+func TestParseKeys(t *testing.T) {
+	tests := []struct {
+		s       string
+		want    [chip8.NumberOfKeys]bool
+		wantErr bool
+	}{
+		{s: ".", want: heldKeys()},
+		{s: "5", want: heldKeys(0x5)},
+		{s: "15C", want: heldKeys(0x1, 0x5, 0xc)},
+		{s: "c", want: heldKeys(0xc)},
+		{s: "0123456789ABCDEF", want: heldKeys(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15)},
+		{s: "G", wantErr: true},
+		{s: "1 2", wantErr: true},
+		{s: "..", wantErr: true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.s, func(t *testing.T) {
+			got, err := parseKeys(tt.s)
+			if (err != nil) != tt.wantErr {
+				t.Fatalf("parseKeys(%q) error = %v, want error %v", tt.s, err, tt.wantErr)
+			}
+			if !tt.wantErr && got != tt.want {
+				t.Errorf("parseKeys(%q) = %v, want %v", tt.s, got, tt.want)
+			}
+			if !tt.wantErr && formatKeys(got) != strings.ToUpper(tt.s) {
+				t.Errorf("formatKeys(%v) = %q, want %q", got, formatKeys(got), strings.ToUpper(tt.s))
+			}
+		})
+	}
+}
+
+func TestReadMovie(t *testing.T) {
+	tests := []struct {
+		name    string
+		text    string
+		want    [][chip8.NumberOfKeys]bool
+		wantErr bool
+	}{
+		{
+			name: "frames",
+			text: "# CHIP-8 movie\n.\n5\n15C\n",
+			want: [][chip8.NumberOfKeys]bool{heldKeys(), heldKeys(0x5), heldKeys(0x1, 0x5, 0xc)},
+		},
+		{
+			name: "blank lines and comments",
+			text: "\n# comment\n  5  \n\n# another\n.",
+			want: [][chip8.NumberOfKeys]bool{heldKeys(0x5), heldKeys()},
+		},
+		{
+			name: "empty",
+			text: "",
+		},
+		{
+			name:    "invalid keys",
+			text:    ".\nXYZ\n",
+			wantErr: true,
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			m, err := ReadMovie(strings.NewReader(tt.text))
+			if (err != nil) != tt.wantErr {
+				t.Fatalf("ReadMovie() error = %v, want error %v", err, tt.wantErr)
+			}
+			if !tt.wantErr && !reflect.DeepEqual(m.Frames, tt.want) {
+				t.Errorf("ReadMovie() frames = %v, want %v", m.Frames, tt.want)
+			}
+		})
+	}
+}
+
+func TestMovieRoundTrip(t *testing.T) {
+	m := &Movie{Frames: [][chip8.NumberOfKeys]bool{
+		heldKeys(), heldKeys(0x0), heldKeys(0x4, 0x6), heldKeys(), heldKeys(0xf),
+	}}
+
+	var buf bytes.Buffer
+	if err := WriteMovie(&buf, m); err != nil {
+		t.Fatal(err)
+	}
+	got, err := ReadMovie(&buf)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !reflect.DeepEqual(got, m) {
+		t.Errorf("ReadMovie() = %v, want %v", got.Frames, m.Frames)
+	}
+}
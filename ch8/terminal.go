@@ -0,0 +1,243 @@
+package ch8
+
+import (
+	"bufio"
+	"bytes"
+	"fmt"
+	"image/color"
+	"io"
+	"os"
+	"os/exec"
+	"strings"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Terminal
+//=====================================================================
+
+const (
+	// terminalKeyHold is the number of frames a key stays held after
+	// it is typed, since terminals don't report when keys are
+	// released.
+	terminalKeyHold = 10
+
+	// terminalQuit is the byte typed by Ctrl+C, which quits the
+	// emulator while the terminal is in raw mode.
+	terminalQuit = 0x03
+)
+
+// Terminal runs the CHIP-8 emulator within a terminal, drawing two rows
+// of pixels per line of text with half blocks.
+//
+// Terminals only report keys as they are typed, so each typed key is
+// held for a few frames. The beeper rings the bell of the terminal.
+type Terminal struct {
+	cfg     Config
+	core    *chip8.Core
+	palette chip8.Palette
+	keys    map[byte]uint
+	held    [chip8.NumberOfKeys]int
+	typed   chan byte
+}
+
+// NewTerminal creates a new CHIP-8 emulator running in the terminal.
+func NewTerminal(cfg Config) *Terminal {
+	// The configuration may not have been resolved by the caller
+	cfg.Resolve()
+
+	palettes, err := cfg.Palettes()
+	if err != nil {
+		palettes = chip8.Palettes
+	}
+	palette := chip8.FindPalette(palettes, cfg.Palette)
+	if palette < 0 {
+		palette = 0
+	}
+
+	keymaps, err := cfg.Keymaps()
+	if err != nil {
+		keymaps = chip8.Keymaps
+	}
+	keymap := chip8.FindKeymap(keymaps, cfg.Keymap)
+	if keymap < 0 {
+		keymap = 0
+	}
+
+	// Only keys which type a single character can be used
+	keys := map[byte]uint{}
+	for name, hex := range keymaps[keymap].Keys {
+		if len(name) == 1 {
+			keys[strings.ToLower(name)[0]] = hex
+		}
+	}
+
+	core := chip8.NewCore(cfg.MachineSpec(), cfg.SampleRate)
+	core.Speed = cfg.Speed
+	core.VM.Quirks = cfg.MachineQuirks()
+	core.TimerRate = cfg.TimerRate
+
+	return &Terminal{
+		cfg:     cfg,
+		core:    core,
+		palette: palettes[palette],
+		keys:    keys,
+		typed:   make(chan byte, 64),
+	}
+}
+
+// Metrics returns the counters of the virtual machine.
+func (t *Terminal) Metrics() chip8.Metrics {
+	return t.core.Metrics()
+}
+
+// LoadROM loads a CHIP-8 ROM into the virtual machine.
+func (t *Terminal) LoadROM(path string) error {
+	return t.core.VM.LoadROM(path)
+}
+
+// Run runs the emulator until Ctrl+C is typed.
+func (t *Terminal) Run() error {
+	restore, err := rawTerminal()
+	if err != nil {
+		return err
+	}
+	defer restore()
+
+	out := bufio.NewWriter(os.Stdout)
+	defer out.Flush()
+
+	// Hide the cursor while running, and clear the screen afterwards
+	fmt.Fprint(out, "\x1b[?25l\x1b[2J")
+	defer fmt.Fprint(out, "\x1b[0m\x1b[2J\x1b[H\x1b[?25h")
+
+	go t.readKeys(os.Stdin)
+
+	beeping := false
+
+	for range time.Tick(time.Second / chip8.DefaultTPS) {
+		if quit := t.updateKeys(); quit {
+			return nil
+		}
+
+		if err := t.core.RunFrame(); err != nil {
+			return err
+		}
+
+		if beep := t.core.Beeping(); beep && !beeping {
+			fmt.Fprint(out, "\a")
+		}
+		beeping = t.core.Beeping()
+
+		if t.core.VM.DisplayChanged() {
+			t.draw(out)
+		}
+		if err := out.Flush(); err != nil {
+			return err
+		}
+	}
+
+	return nil
+}
+
+func (t *Terminal) readKeys(in io.Reader) {
+	buf := make([]byte, 64)
+	for {
+		n, err := in.Read(buf)
+		if err != nil {
+			close(t.typed)
+			return
+		}
+		for _, b := range buf[:n] {
+			t.typed <- b
+		}
+	}
+}
+
+// updateKeys holds the keys typed since the last frame, and reports
+// whether the emulator should quit.
+func (t *Terminal) updateKeys() bool {
+	for i := range t.held {
+		if t.held[i] > 0 {
+			t.held[i]--
+		}
+	}
+
+	for typing := true; typing; {
+		select {
+		case b, ok := <-t.typed:
+			if !ok || b == terminalQuit {
+				return true
+			}
+			if b >= 'A' && b <= 'Z' {
+				b += 'a' - 'A'
+			}
+			if hex, ok := t.keys[b]; ok {
+				t.held[hex] = terminalKeyHold
+			}
+		default:
+			typing = false
+		}
+	}
+
+	for i, frames := range t.held {
+		t.core.VM.Keys[i] = frames > 0
+	}
+	return false
+}
+
+// draw draws the display with half blocks, where the top pixel of a
+// character is its foreground and the bottom pixel its background.
+func (t *Terminal) draw(out io.Writer) {
+	var buf bytes.Buffer
+	fg, bg := t.palette.Foreground(), t.palette.Background()
+	display := &t.core.VM.Display
+
+	buf.WriteString("\x1b[H")
+	for y := 0; y < display.Height; y += 2 {
+		for x := 0; x < display.Width; x++ {
+			top, bottom := bg, bg
+			if display.At(x, y) {
+				top = fg
+			}
+			if display.At(x, y+1) {
+				bottom = fg
+			}
+			fmt.Fprintf(&buf, "%s%s▀", ansiColor(38, top), ansiColor(48, bottom))
+		}
+		buf.WriteString("\x1b[0m\r\n")
+	}
+
+	out.Write(buf.Bytes())
+}
+
+// ansiColor returns the escape sequence which sets the foreground (38)
+// or background (48) of the terminal to a 24-bit color.
+func ansiColor(layer int, c color.RGBA) string {
+	return fmt.Sprintf("\x1b[%d;2;%d;%d;%dm", layer, c.R, c.G, c.B)
+}
+
+// rawTerminal puts the terminal into raw mode, so keys are read as
+// they are typed without being echoed, and returns a function which
+// restores the previous mode.
+func rawTerminal() (func(), error) {
+	stty := func(args ...string) (string, error) {
+		cmd := exec.Command("stty", args...)
+		cmd.Stdin = os.Stdin
+		out, err := cmd.Output()
+		return strings.TrimSpace(string(out)), err
+	}
+
+	state, err := stty("-g")
+	if err != nil {
+		return nil, fmt.Errorf("terminal does not support raw mode: %v", err)
+	}
+
+	if _, err := stty("raw", "-echo"); err != nil {
+		return nil, err
+	}
+
+	return func() { stty(state) }, nil
+}
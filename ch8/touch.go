@@ -0,0 +1,99 @@
+package ch8
+
+import (
+	"image"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Touch Keypad
+//=====================================================================
+
+// touchKeypad is a keypad drawn beside or below the display, which can
+// be tapped on a touchscreen or clicked with the mouse.
+//
+// The keypad is shown once the screen is first touched, even if it was
+// not enabled.
+type touchKeypad struct {
+	enabled bool
+	area    image.Rectangle
+}
+
+// layout splits a screen of the given size between the display and the
+// keypad. The keypad is placed beside the display on wide screens and
+// below it on tall screens.
+func (t *touchKeypad) layout(size image.Point) (view, keypad image.Rectangle) {
+	view = image.Rectangle{Max: size}
+	if !t.enabled {
+		return view, image.Rectangle{}
+	}
+
+	if size.X >= 2*size.Y {
+		side := min(size.Y, size.X/3)
+		view.Max.X -= side
+		keypad = image.Rect(view.Max.X, (size.Y-side)/2, size.X, (size.Y+side)/2)
+	} else {
+		side := min(size.X, size.Y/2)
+		view.Max.Y -= side
+		keypad = image.Rect((size.X-side)/2, view.Max.Y, (size.X+side)/2, size.Y)
+	}
+
+	return view, keypad
+}
+
+// update shows the keypad once the screen is touched, and reports
+// whether the keypad was just shown.
+func (t *touchKeypad) update() bool {
+	if !t.enabled && len(ebiten.TouchIDs()) > 0 {
+		t.enabled = true
+		return true
+	}
+	return false
+}
+
+// Press presses the keypad keys which are touched or clicked.
+func (t *touchKeypad) Press(keys *[chip8.NumberOfKeys]bool) {
+	if !t.enabled || t.area.Empty() {
+		return
+	}
+
+	var points []image.Point
+	for _, id := range ebiten.TouchIDs() {
+		points = append(points, image.Pt(ebiten.TouchPosition(id)))
+	}
+	if ebiten.IsMouseButtonPressed(ebiten.MouseButtonLeft) {
+		points = append(points, image.Pt(ebiten.CursorPosition()))
+	}
+
+	cell := t.area.Dx() / 4
+	for _, p := range points {
+		if !p.In(t.area) || cell == 0 {
+			continue
+		}
+
+		p = p.Sub(t.area.Min).Div(cell)
+		if p.X < 4 && p.Y < 4 {
+			keys[keypadLayout[p.Y][p.X]] = true
+		}
+	}
+}
+
+// draw draws the keypad within the given area of the screen,
+// highlighting the keys which are pressed.
+func (t *touchKeypad) draw(screen *ebiten.Image, area image.Rectangle, vm *chip8.VirtualMachine, palette chip8.Palette) {
+	t.area = area
+	if !t.enabled {
+		return
+	}
+
+	drawKeypad(screen, area.Min, area.Dx()/4, vm.Keys, vm.WaitingForKey(), palette)
+}
+
+func min(a, b int) int {
+	if a < b {
+		return a
+	}
+	return b
+}
@@ -0,0 +1,133 @@
+package ch8
+
+import (
+	"bufio"
+	"encoding/json"
+	"os"
+	"time"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Trace Timelines
+//=====================================================================
+
+// traceEvent is an event of the Chrome trace event format, which can
+// be opened in chrome://tracing or Perfetto.
+type traceEvent struct {
+	Name string            `json:"name"`
+	Ph   string            `json:"ph"`
+	TS   int64             `json:"ts"`
+	Dur  int64             `json:"dur,omitempty"`
+	PID  int               `json:"pid"`
+	TID  int               `json:"tid"`
+	Args map[string]uint64 `json:"args,omitempty"`
+}
+
+// traceRecorder writes a timeline of the emulator: how long each frame
+// takes to update and draw on the host, and how many instructions,
+// sprites, and key waits the virtual machine got through in it.
+//
+// It is only used from the goroutine updating the emulator.
+type traceRecorder struct {
+	file   *os.File
+	w      *bufio.Writer
+	start  time.Time
+	events int
+	frame  time.Time
+	last   chip8.Metrics
+}
+
+func newTraceRecorder(path string, vm *chip8.VirtualMachine) (*traceRecorder, error) {
+	file, err := os.Create(path)
+	if err != nil {
+		return nil, err
+	}
+
+	r := &traceRecorder{
+		file:  file,
+		w:     bufio.NewWriter(file),
+		start: time.Now(),
+		last:  vm.Metrics(),
+	}
+	r.w.WriteString(`{"displayTimeUnit":"ms","traceEvents":[`)
+	return r, nil
+}
+
+// write writes an event of the timeline.
+func (r *traceRecorder) write(e traceEvent) {
+	data, err := json.Marshal(e)
+	if err != nil {
+		emuLog.Error(err)
+		return
+	}
+
+	if r.events > 0 {
+		r.w.WriteByte(',')
+	}
+	r.w.WriteString("\n")
+	r.w.Write(data)
+	r.events++
+}
+
+// micros returns the time since the start of the timeline in
+// microseconds, the unit of trace events.
+func (r *traceRecorder) micros(t time.Time) int64 {
+	return int64(t.Sub(r.start) / time.Microsecond)
+}
+
+// complete writes an event which started at the given time and ends
+// now. It is meant to be deferred, such as:
+//
+//	defer emu.trace.complete("update", time.Now())
+func (r *traceRecorder) complete(name string, start time.Time) {
+	r.write(traceEvent{
+		Name: name,
+		Ph:   "X",
+		TS:   r.micros(start),
+		Dur:  int64(time.Since(start) / time.Microsecond),
+		PID:  1,
+		TID:  1,
+	})
+}
+
+// capture writes the host frame which ended, along with the counters of
+// the virtual machine during it.
+func (r *traceRecorder) capture(vm *chip8.VirtualMachine) {
+	now := time.Now()
+	if !r.frame.IsZero() {
+		r.write(traceEvent{
+			Name: "frame",
+			Ph:   "X",
+			TS:   r.micros(r.frame),
+			Dur:  int64(now.Sub(r.frame) / time.Microsecond),
+			PID:  1,
+			TID:  2,
+		})
+	}
+	r.frame = now
+
+	m := vm.Metrics()
+	ts := r.micros(now)
+	r.write(traceEvent{Name: "instructions", Ph: "C", TS: ts, PID: 1, Args: map[string]uint64{
+		"instructions": m.Instructions - r.last.Instructions,
+	}})
+	r.write(traceEvent{Name: "draws", Ph: "C", TS: ts, PID: 1, Args: map[string]uint64{
+		"draws": m.Draws - r.last.Draws,
+	}})
+	r.write(traceEvent{Name: "key waits", Ph: "C", TS: ts, PID: 1, Args: map[string]uint64{
+		"key waits": m.KeyWaits - r.last.KeyWaits,
+	}})
+	r.last = m
+}
+
+// Close finishes the timeline and closes its file.
+func (r *traceRecorder) Close() error {
+	r.w.WriteString("\n]}\n")
+	if err := r.w.Flush(); err != nil {
+		r.file.Close()
+		return err
+	}
+	return r.file.Close()
+}
@@ -0,0 +1,157 @@
+package ch8
+
+import (
+	"encoding/binary"
+	"fmt"
+	"image"
+	"io"
+	"os"
+	"os/exec"
+	"sync"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Video Recording
+//=====================================================================
+
+// videoBacklog is the number of frames which may be queued for ffmpeg
+// before the emulator waits for it to catch up.
+const videoBacklog = 2 * chip8.DefaultTPS
+
+// videoRecorder pipes the display and beeper audio of the emulator
+// into an ffmpeg process, which encodes them into a video file.
+//
+// Raw RGBA frames are written to the standard input of ffmpeg, while
+// raw PCM audio is written to a second pipe.
+type videoRecorder struct {
+	path       string
+	cmd        *exec.Cmd
+	palette    chip8.Palette
+	scale      int
+	sampleRate int
+	oscillator chip8.Oscillator
+	frame      int64
+	samples    int64
+
+	video   chan []byte
+	audio   chan []byte
+	writers sync.WaitGroup
+	errs    chan error
+}
+
+func startVideoRecorder(cfg Config, path string, palette chip8.Palette) (*videoRecorder, error) {
+	width, height := chip8.DisplayWidth*cfg.VideoScale, chip8.DisplayHeight*cfg.VideoScale
+
+	audioIn, audioOut, err := os.Pipe()
+	if err != nil {
+		return nil, err
+	}
+
+	cmd := exec.Command(
+		cfg.FFmpeg, "-y", "-loglevel", "error",
+		"-f", "rawvideo", "-pix_fmt", "rgba",
+		"-s", fmt.Sprintf("%dx%d", width, height),
+		"-r", fmt.Sprint(chip8.DefaultTPS), "-i", "pipe:0",
+		"-f", "s16le", "-ar", fmt.Sprint(cfg.SampleRate), "-ac", "2",
+		"-i", "pipe:3",
+		"-pix_fmt", "yuv420p", "-shortest", path,
+	)
+	cmd.Stdout = os.Stdout
+	cmd.Stderr = os.Stderr
+	cmd.ExtraFiles = []*os.File{audioIn}
+
+	videoOut, err := cmd.StdinPipe()
+	if err != nil {
+		audioIn.Close()
+		audioOut.Close()
+		return nil, err
+	}
+
+	if err := cmd.Start(); err != nil {
+		audioIn.Close()
+		audioOut.Close()
+		return nil, err
+	}
+	audioIn.Close()
+
+	r := &videoRecorder{
+		path:       path,
+		cmd:        cmd,
+		palette:    palette,
+		scale:      cfg.VideoScale,
+		sampleRate: cfg.SampleRate,
+		oscillator: chip8.NewOscillator(chip8.DefaultFrequency, cfg.SampleRate),
+		video:      make(chan []byte, videoBacklog),
+		audio:      make(chan []byte, videoBacklog),
+		errs:       make(chan error, 2),
+	}
+
+	r.writers.Add(2)
+	go r.write(videoOut, r.video)
+	go r.write(audioOut, r.audio)
+
+	return r, nil
+}
+
+// capture records a single frame of the display along with the beeper
+// audio of the frame.
+func (r *videoRecorder) capture(vm *chip8.VirtualMachine) {
+	img := RenderImageSize(vm, r.palette, image.Pt(
+		chip8.DisplayWidth*r.scale, chip8.DisplayHeight*r.scale,
+	))
+	r.video <- img.Pix
+
+	// Each frame spans the same range of samples, as in WAV recordings
+	beep := vm.ST > 0x00
+	end := (r.frame + 1) * int64(r.sampleRate) / chip8.DefaultTPS
+	buf := make([]byte, 0, 4*(end-r.samples))
+
+	for ; r.samples < end; r.samples++ {
+		b := uint16(r.oscillator.Next(beep))
+		buf = append(buf, 0, 0, 0, 0)
+		binary.LittleEndian.PutUint16(buf[len(buf)-4:], b)
+		binary.LittleEndian.PutUint16(buf[len(buf)-2:], b)
+	}
+
+	r.audio <- buf
+	r.frame++
+}
+
+// stop finishes the recording and waits for ffmpeg to exit.
+func (r *videoRecorder) stop() error {
+	close(r.video)
+	close(r.audio)
+	r.writers.Wait()
+
+	err := r.cmd.Wait()
+	close(r.errs)
+
+	for e := range r.errs {
+		if err == nil {
+			err = e
+		}
+	}
+	return err
+}
+
+func (r *videoRecorder) write(w io.WriteCloser, data chan []byte) {
+	defer r.writers.Done()
+
+	var err error
+	for buf := range data {
+		// Keep draining the queue so the emulator never blocks on a
+		// failed recording
+		if err == nil {
+			_, err = w.Write(buf)
+		}
+	}
+
+	if closeErr := w.Close(); err == nil {
+		err = closeErr
+	}
+	if err != nil {
+		r.errs <- err
+	}
+}
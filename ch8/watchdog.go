@@ -0,0 +1,88 @@
+package ch8
+
+import (
+	"fmt"
+	"image"
+	"image/color"
+
+	"github.com/hajimehoshi/ebiten/v2"
+	"github.com/hajimehoshi/ebiten/v2/ebitenutil"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// Stall Watchdog
+//=====================================================================
+
+// stallFrames is the number of frames the virtual machine runs without
+// drawing, reading the keys, or using the timers before the watchdog
+// warns that it is stuck, which is 3 seconds at 60 FPS.
+const stallFrames = 3 * chip8.DefaultTPS
+
+// stallWatchdog notices when the virtual machine keeps running without
+// drawing, reading the keys, or using the timers, which is a likely
+// hang, so a warning can be shown along with where the program loops
+// rather than the emulator appearing frozen.
+//
+// A program which jumps to itself forever, as many programs do once
+// they are done, isn't run at all, so it doesn't count as stuck.
+type stallWatchdog struct {
+	cycles   uint64
+	activity uint64
+	frames   int
+	low      uint
+	high     uint
+}
+
+// update checks the progress of the virtual machine once per frame,
+// sampling its program counter while it makes none, and reports
+// whether the warning appeared or went away.
+func (w *stallWatchdog) update(vm *chip8.VirtualMachine) bool {
+	cycles, activity := vm.Cycles(), vm.Activity()
+	stalled := w.stalled()
+
+	switch {
+	case activity != w.activity:
+		w.frames = 0
+	case cycles != w.cycles:
+		// The range of the loop is only sampled, so it may be wider
+		pc := vm.PC
+		if w.frames == 0 || pc < w.low {
+			w.low = pc
+		}
+		if w.frames == 0 || pc > w.high {
+			w.high = pc
+		}
+		w.frames++
+	}
+	w.cycles, w.activity = cycles, activity
+	return w.stalled() != stalled
+}
+
+// stalled reports whether the virtual machine seems to be stuck.
+func (w *stallWatchdog) stalled() bool {
+	return w.frames >= stallFrames
+}
+
+// message describes where the virtual machine seems to be stuck.
+func (w *stallWatchdog) message() string {
+	at := fmt.Sprintf("0x%.3X", w.low)
+	if w.high != w.low {
+		at = fmt.Sprintf("0x%.3X-0x%.3X", w.low, w.high)
+	}
+
+	seconds := w.frames / chip8.DefaultTPS
+	return fmt.Sprintf("The ROM seems stuck in a loop at %s (no drawing, keys, or timers for %ds)", at, seconds)
+}
+
+// draw draws the warning as a banner across the top of the screen,
+// below the menu bar.
+func (w *stallWatchdog) draw(screen *ebiten.Image, bar image.Point) {
+	if !w.stalled() {
+		return
+	}
+
+	banner := image.Rect(0, bar.Y, screen.Bounds().Dx(), bar.Y+menuBarHeight)
+	drawRect(screen, banner, color.RGBA{0x80, 0x20, 0x20, 0xe0})
+	ebitenutil.DebugPrintAt(screen, w.message(), menuCharWidth, bar.Y)
+}
@@ -0,0 +1,126 @@
+package ch8
+
+import (
+	"bufio"
+	"encoding/binary"
+	"os"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+//=====================================================================
+// WAV Recording
+//=====================================================================
+
+// wavHeaderSize is the size (in bytes) of a canonical PCM WAV header.
+const wavHeaderSize = 44
+
+// wavRecorder writes the beeper audio of a session to a 16-bit stereo
+// PCM WAV file.
+//
+// Audio is written one frame at a time, and each frame always spans
+// the same range of samples, so frame n of the session starts exactly
+// at sample n * SampleRate / DefaultTPS of the file.
+type wavRecorder struct {
+	file       *os.File
+	writer     *bufio.Writer
+	oscillator chip8.Oscillator
+	sampleRate int
+	frame      int64
+	samples    int64
+}
+
+// newWAVRecorder creates the WAV file at path and prepares it for
+// recording.
+func newWAVRecorder(path string, sampleRate int) (*wavRecorder, error) {
+	file, err := os.Create(path)
+	if err != nil {
+		return nil, err
+	}
+
+	r := &wavRecorder{
+		file:       file,
+		writer:     bufio.NewWriter(file),
+		oscillator: chip8.NewOscillator(chip8.DefaultFrequency, sampleRate),
+		sampleRate: sampleRate,
+	}
+
+	// The sizes in the header are filled in once recording stops
+	if err := r.writeHeader(); err != nil {
+		file.Close()
+		return nil, err
+	}
+
+	return r, nil
+}
+
+// WriteFrame appends a single frame of audio to the recording.
+func (r *wavRecorder) WriteFrame(beep bool) error {
+	end := (r.frame + 1) * int64(r.sampleRate) / chip8.DefaultTPS
+
+	var sample [4]byte
+	for ; r.samples < end; r.samples++ {
+		b := r.oscillator.Next(beep)
+		binary.LittleEndian.PutUint16(sample[0:], uint16(b))
+		binary.LittleEndian.PutUint16(sample[2:], uint16(b))
+
+		if _, err := r.writer.Write(sample[:]); err != nil {
+			return err
+		}
+	}
+
+	r.frame++
+	return nil
+}
+
+// Close finalizes the header of the recording and closes the file.
+func (r *wavRecorder) Close() error {
+	defer r.file.Close()
+
+	if err := r.writer.Flush(); err != nil {
+		return err
+	}
+
+	if _, err := r.file.Seek(0, 0); err != nil {
+		return err
+	}
+
+	r.writer.Reset(r.file)
+	if err := r.writeHeader(); err != nil {
+		return err
+	}
+
+	return r.writer.Flush()
+}
+
+func (r *wavRecorder) writeHeader() error {
+	dataSize := uint32(r.samples * 4)
+
+	header := []interface{}{
+		[4]byte{'R', 'I', 'F', 'F'},
+		uint32(wavHeaderSize - 8 + dataSize),
+		[4]byte{'W', 'A', 'V', 'E'},
+
+		// Format chunk
+		[4]byte{'f', 'm', 't', ' '},
+		uint32(16),
+		uint16(1), // PCM
+		uint16(2), // Channels
+		uint32(r.sampleRate),
+		uint32(r.sampleRate * 4), // Byte rate
+		uint16(4),                // Block alignment
+		uint16(16),               // Bits per sample
+
+		// Data chunk
+		[4]byte{'d', 'a', 't', 'a'},
+		dataSize,
+	}
+
+	for _, field := range header {
+		if err := binary.Write(r.writer, binary.LittleEndian, field); err != nil {
+			return err
+		}
+	}
+
+	return nil
+}
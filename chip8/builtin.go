@@ -0,0 +1,143 @@
+package chip8
+
+import (
+	"fmt"
+	"path/filepath"
+	"strings"
+)
+
+//=====================================================================
+// Built-in ROMs
+//=====================================================================
+
+// BuiltinPrefix is the prefix of the paths of the ROMs bundled with the
+// emulator, such as "builtin:maze", so it can be tried out without
+// downloading any ROMs.
+const BuiltinPrefix = "builtin:"
+
+// builtinROM is a public-domain ROM bundled with the emulator.
+type builtinROM struct {
+	// name is the name of the ROM in its path, after BuiltinPrefix.
+	name string
+
+	// file is the file name of the ROM in roms/demos, which names its
+	// settings and the files kept for it, such as save states.
+	file string
+
+	data []byte
+}
+
+// builtinROMs are the ROMs bundled with the emulator. They are copies
+// of the ROMs of the same names in roms/demos.
+var builtinROMs = []builtinROM{
+	// The IBM logo, the classic first test of an emulator
+	{
+		name: "ibm",
+		file: "IBM.ch8",
+		data: []byte{
+			0x00, 0xe0, 0xa2, 0x2a, 0x60, 0x0c, 0x61, 0x08, 0xd0, 0x1f, 0x70, 0x09,
+			0xa2, 0x39, 0xd0, 0x1f, 0xa2, 0x48, 0x70, 0x08, 0xd0, 0x1f, 0x70, 0x04,
+			0xa2, 0x57, 0xd0, 0x1f, 0x70, 0x08, 0xa2, 0x66, 0xd0, 0x1f, 0x70, 0x08,
+			0xa2, 0x75, 0xd0, 0x1f, 0x12, 0x28, 0xff, 0x00, 0xff, 0x00, 0x3c, 0x00,
+			0x3c, 0x00, 0x3c, 0x00, 0x3c, 0x00, 0xff, 0x00, 0xff, 0xff, 0x00, 0xff,
+			0x00, 0x38, 0x00, 0x3f, 0x00, 0x3f, 0x00, 0x38, 0x00, 0xff, 0x00, 0xff,
+			0x80, 0x00, 0xe0, 0x00, 0xe0, 0x00, 0x80, 0x00, 0x80, 0x00, 0xe0, 0x00,
+			0xe0, 0x00, 0x80, 0xf8, 0x00, 0xfc, 0x00, 0x3e, 0x00, 0x3f, 0x00, 0x3b,
+			0x00, 0x39, 0x00, 0xf8, 0x00, 0xf8, 0x03, 0x00, 0x07, 0x00, 0x0f, 0x00,
+			0xbf, 0x00, 0xfb, 0x00, 0xf3, 0x00, 0xe3, 0x00, 0x43, 0xe0, 0x00, 0xe0,
+			0x00, 0x80, 0x00, 0x80, 0x00, 0x80, 0x00, 0x80, 0x00, 0xe0, 0x00, 0xe0,
+		},
+	},
+	// A random maze drawn with diagonal lines, by David Winter
+	{
+		name: "maze",
+		file: "Maze.ch8",
+		data: []byte{
+			0x60, 0x00, 0x61, 0x00, 0xa2, 0x22, 0xc2, 0x01, 0x32, 0x01, 0xa2, 0x1e,
+			0xd0, 0x14, 0x70, 0x04, 0x30, 0x40, 0x12, 0x04, 0x60, 0x00, 0x71, 0x04,
+			0x31, 0x20, 0x12, 0x04, 0x12, 0x1c, 0x80, 0x40, 0x20, 0x10, 0x20, 0x40,
+			0x80, 0x10,
+		},
+	},
+	// A fountain of particles, by zeroZshadow
+	{
+		name: "particles",
+		file: "Particle Demo.ch8",
+		data: []byte{
+			0xa3, 0x21, 0x60, 0x00, 0x61, 0x00, 0x62, 0x08, 0xd0, 0x15, 0xf2, 0x1e,
+			0x80, 0x24, 0xd0, 0x15, 0xf2, 0x1e, 0x80, 0x24, 0xd0, 0x15, 0xf2, 0x1e,
+			0x80, 0x24, 0xd0, 0x15, 0xf2, 0x1e, 0x80, 0x24, 0xd0, 0x15, 0xf2, 0x1e,
+			0x80, 0x24, 0xd0, 0x15, 0xf2, 0x1e, 0x80, 0x24, 0xd0, 0x15, 0xf2, 0x1e,
+			0x80, 0x24, 0xd0, 0x15, 0x66, 0x05, 0x67, 0x02, 0x6a, 0x00, 0x12, 0xb8,
+			0x6b, 0x00, 0x6c, 0x00, 0xa2, 0xd8, 0xfb, 0x1e, 0xf3, 0x65, 0x22, 0xce,
+			0x22, 0x5c, 0x12, 0x62, 0x22, 0xce, 0x22, 0x5c, 0x7b, 0x04, 0x7c, 0x01,
+			0x5c, 0x60, 0x12, 0x40, 0x12, 0x3c, 0x12, 0x00, 0xa3, 0x20, 0xde, 0xd1,
+			0x00, 0xee, 0xa2, 0xd8, 0xfb, 0x1e, 0xf3, 0x65, 0x80, 0x24, 0x81, 0x34,
+			0x8e, 0x00, 0x8d, 0x10, 0x8e, 0xe6, 0x8d, 0xd6, 0x84, 0xe0, 0x65, 0xc2,
+			0x84, 0x54, 0x4f, 0x01, 0x12, 0x92, 0x4d, 0x00, 0x63, 0x01, 0x84, 0xd0,
+			0x65, 0xe1, 0x84, 0x54, 0x4f, 0x01, 0x12, 0x92, 0x33, 0x02, 0x73, 0x01,
+			0x12, 0x94, 0x22, 0x9c, 0xa2, 0xd8, 0xfb, 0x1e, 0xf3, 0x55, 0x12, 0x4c,
+			0xa3, 0x00, 0xfa, 0x1e, 0xf0, 0x65, 0x82, 0x00, 0x7a, 0x01, 0x64, 0x1f,
+			0x8a, 0x42, 0x60, 0x20, 0x61, 0x1e, 0x80, 0x0e, 0x81, 0x1e, 0xc3, 0x03,
+			0x73, 0xf8, 0x00, 0xee, 0x6b, 0x00, 0x6c, 0x00, 0x22, 0x9c, 0xa2, 0xd8,
+			0xfb, 0x1e, 0xf3, 0x55, 0x7b, 0x04, 0x7c, 0x01, 0x5c, 0x60, 0x12, 0xbc,
+			0x12, 0x3c, 0x8e, 0x00, 0x8d, 0x10, 0x8e, 0xe6, 0x8d, 0xd6, 0x00, 0xee,
+			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+			0x00, 0x00, 0x00, 0x00, 0xf8, 0xfa, 0xf9, 0xfe, 0xfb, 0xfc, 0xfd, 0xff,
+			0x02, 0x01, 0x03, 0x05, 0x04, 0x06, 0x07, 0x08, 0x06, 0x07, 0x04, 0x05,
+			0x03, 0x01, 0x02, 0xfe, 0xff, 0xfc, 0xfb, 0xfd, 0xfa, 0xf9, 0xf8, 0xfa,
+			0x80, 0xf7, 0x06, 0x77, 0x06, 0x36, 0x00, 0x00, 0x00, 0xc7, 0x6c, 0xcf,
+			0x0c, 0x0c, 0x00, 0x00, 0x00, 0x9f, 0xd9, 0xdf, 0xd9, 0xd9, 0x00, 0x00,
+			0x00, 0x3f, 0x8c, 0x0c, 0x8c, 0x8c, 0x00, 0x00, 0x00, 0x67, 0x6c, 0x6c,
+			0x6c, 0x67, 0x00, 0x00, 0x00, 0xb0, 0x30, 0x30, 0x30, 0xbe, 0x00, 0x00,
+			0x00, 0xf9, 0xc3, 0xf1, 0xc0, 0xfb, 0x00, 0x00, 0x00, 0xef, 0x00, 0xce,
+			0x60, 0xcc, 0x00, 0x00, 0x00,
+		},
+	},
+}
+
+// BuiltinROMs returns the paths of the ROMs bundled with the emulator.
+func BuiltinROMs() []string {
+	paths := make([]string, len(builtinROMs))
+	for i, rom := range builtinROMs {
+		paths[i] = BuiltinPrefix + rom.name
+	}
+	return paths
+}
+
+// IsBuiltinROM reports whether a path refers to a ROM bundled with the
+// emulator.
+func IsBuiltinROM(path string) bool {
+	return strings.HasPrefix(path, BuiltinPrefix)
+}
+
+// readBuiltinROM returns a copy of the ROM bundled with the emulator
+// at a path.
+func readBuiltinROM(path string) ([]byte, error) {
+	name := strings.TrimPrefix(path, BuiltinPrefix)
+	for _, rom := range builtinROMs {
+		if rom.name == name {
+			return append([]byte(nil), rom.data...), nil
+		}
+	}
+	return nil, fmt.Errorf(
+		"unknown built-in ROM: %s (%s)",
+		name, strings.Join(BuiltinROMs(), ", "),
+	)
+}
+
+// ROMName returns the name of the ROM at a path, which is its file name,
+// and names the settings of the ROM and the files kept for it.
+func ROMName(path string) string {
+	if IsBuiltinROM(path) {
+		name := strings.TrimPrefix(path, BuiltinPrefix)
+		for _, rom := range builtinROMs {
+			if rom.name == name {
+				return rom.file
+			}
+		}
+	}
+	return filepath.Base(path)
+}
@@ -0,0 +1,208 @@
+package chip8
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"strconv"
+	"time"
+)
+
+//=====================================================================
+// Constants
+//=====================================================================
+
+const (
+	// DefaultSpeed is the default speed multiplier of the CHIP-8
+	// virtual machine.
+	DefaultSpeed = 1.0
+
+	// DefaultTimerRate is the default rate (in hertz) at which the
+	// delay and sound timers count down.
+	DefaultTimerRate = 60.0
+
+	// DefaultFrequency is the default frequency of the CHIP-8 beeper.
+	DefaultFrequency = 440
+
+	// DefaultSampleRate is the default sample rate of the CHIP-8
+	// beeper.
+	DefaultSampleRate = 44100
+
+	// DefaultTPS is the default ticks per second of the emulator.
+	DefaultTPS = 60
+
+	// DefaultHzVM is the default speed (in hertz) in which to run a
+	// CPU cycle of the CHIP-8 virtual machine.
+	DefaultHzVM = 2 * time.Millisecond
+)
+
+// EnvPrefix is the prefix of the environment variables which override
+// the configuration file, such as CHIP8_PALETTE.
+const EnvPrefix = "CHIP8_"
+
+//=====================================================================
+// Configuration
+//=====================================================================
+
+// Config is the configuration of the CHIP-8 virtual machine.
+//
+// The frontends of the emulator keep their own settings in the same
+// configuration file, alongside these.
+type Config struct {
+	// Speed is the speed multiplier of the virtual machine, or zero for
+	// the speed of the machine spec.
+	Speed float64 `json:"speed,omitempty"`
+
+	// TimerRate is the rate (in hertz) at which the delay and sound
+	// timers count down.
+	TimerRate float64 `json:"timerRate"`
+
+	// Machine is the name of the machine spec of the virtual machine,
+	// the variant of CHIP-8 which it emulates.
+	Machine string `json:"machine"`
+
+	// Quirks is the name of the quirk profile of the virtual machine,
+	// or empty for the quirks of the machine spec.
+	Quirks string `json:"quirks,omitempty"`
+}
+
+// DefaultConfig returns the default configuration of the virtual
+// machine, whose speed and quirks are those of the machine spec until
+// it is resolved.
+func DefaultConfig() Config {
+	return Config{
+		TimerRate: DefaultTimerRate,
+		Machine:   DefaultMachine,
+	}
+}
+
+// DefaultConfigPath returns the default path of the configuration
+// file, which is CHIP8_CONFIG if it is set, or otherwise within the
+// user's configuration directory.
+func DefaultConfigPath() string {
+	if path, ok := os.LookupEnv(EnvPrefix + "CONFIG"); ok {
+		return path
+	}
+
+	dir, err := os.UserConfigDir()
+	if err != nil {
+		return ""
+	}
+	return filepath.Join(dir, "ch8", "config.json")
+}
+
+// ReadConfig reads the configuration file at path into cfg, which is
+// left as it is if the file does not exist.
+func ReadConfig(path string, cfg interface{}) error {
+	if path == "" {
+		return nil
+	}
+
+	data, err := ioutil.ReadFile(path)
+	if os.IsNotExist(err) {
+		return nil
+	} else if err != nil {
+		return err
+	}
+
+	if err := json.Unmarshal(data, cfg); err != nil {
+		return fmt.Errorf("invalid config %s: %v", path, err)
+	}
+
+	return nil
+}
+
+// ReadEnv reads the settings given by environment variables, keyed by
+// their names without EnvPrefix, into the ints, floats, bools, or
+// strings they point to.
+func ReadEnv(settings map[string]interface{}) error {
+	for name, setting := range settings {
+		s, ok := os.LookupEnv(EnvPrefix + name)
+		if !ok {
+			continue
+		}
+
+		var err error
+		switch v := setting.(type) {
+		case *int:
+			*v, err = strconv.Atoi(s)
+		case *float64:
+			*v, err = strconv.ParseFloat(s, 64)
+		case *bool:
+			*v, err = strconv.ParseBool(s)
+		case *string:
+			*v = s
+		}
+
+		if err != nil {
+			return fmt.Errorf("invalid %s%s: %s", EnvPrefix, name, s)
+		}
+	}
+
+	return nil
+}
+
+// UseEnv applies the settings given by environment variables, such as
+// CHIP8_SPEED=2, which take precedence over the configuration file.
+func (cfg *Config) UseEnv() error {
+	return ReadEnv(map[string]interface{}{
+		"SPEED":   &cfg.Speed,
+		"MACHINE": &cfg.Machine,
+		"QUIRKS":  &cfg.Quirks,
+	})
+}
+
+// Resolve fills in the speed and quirks which weren't set with those of
+// the machine spec. It is called once every source of settings has been
+// applied, so the machine can come from any of them and an explicit
+// speed or quirk profile from any of them still takes precedence.
+func (cfg *Config) Resolve() {
+	spec := cfg.MachineSpec()
+	if cfg.Speed == 0.0 {
+		cfg.Speed = spec.Speed
+	}
+	if cfg.Quirks == "" {
+		cfg.Quirks = spec.Quirks
+	}
+}
+
+// MachineSpec returns the machine spec of the virtual machine.
+func (cfg *Config) MachineSpec() MachineSpec {
+	if i := FindMachine(cfg.Machine); i >= 0 {
+		return MachineSpecs[i]
+	}
+	return MachineSpecs[FindMachine(DefaultMachine)]
+}
+
+// MachineQuirks returns the quirks of the quirk profile of the
+// virtual machine.
+func (cfg *Config) MachineQuirks() Quirks {
+	if i := FindQuirks(cfg.Quirks); i >= 0 {
+		return QuirkProfiles[i].Quirks
+	}
+	return Quirks{}
+}
+
+// Validate checks that the resolved configuration is usable.
+func (cfg *Config) Validate() error {
+	if cfg.Speed <= 0.0 {
+		return errors.New("speed must be positive")
+	}
+
+	if cfg.TimerRate <= 0.0 {
+		return errors.New("timer rate must be positive")
+	}
+
+	if FindMachine(cfg.Machine) < 0 {
+		return fmt.Errorf("unknown machine: %s", cfg.Machine)
+	}
+
+	if FindQuirks(cfg.Quirks) < 0 {
+		return fmt.Errorf("unknown quirks: %s", cfg.Quirks)
+	}
+
+	return nil
+}
@@ -0,0 +1,147 @@
+package chip8
+
+import (
+	"fmt"
+	"time"
+)
+
+//=====================================================================
+// Core
+//=====================================================================
+
+// Core runs a CHIP-8 virtual machine one frame at a time, for
+// frontends which drive the emulator from their own loop rather than
+// from Ebiten's.
+//
+// Every frame runs the cycles allowed by the speed of the core,
+// updates the timers once, and synthesizes the beeper audio of the
+// frame.
+type Core struct {
+	VM    *VirtualMachine
+	Speed float64
+
+	// TimerRate is the rate (in hertz) at which the timers count down,
+	// which is once per frame by default.
+	TimerRate float64
+
+	// OnFrame is called at the end of every frame, if set.
+	OnFrame FrameFunc
+
+	sampleRate int
+	frame      int64
+	samples    int64
+	budget     float64
+	timers     float64
+	oscillator Oscillator
+
+	// gates are whether the beeper was sounding at the start of the
+	// last frame and after each of its cycles, so its audio starts and
+	// stops at the sample where the sound timer changed
+	gates []bool
+}
+
+// FrameFunc is called once per completed frame with the finished
+// display and whether the beeper is sounding, so renderers and encoders
+// can take every frame without polling. The display must not be kept
+// past the call, since the virtual machine goes on drawing to it.
+type FrameFunc func(display *Display, beeping bool)
+
+// NewCore creates a core running a virtual machine of the given spec at
+// the speed which its programs expect, whose audio is synthesized at the
+// given sample rate.
+func NewCore(spec MachineSpec, sampleRate int) *Core {
+	return &Core{
+		VM:         NewMachine(spec),
+		Speed:      spec.Speed,
+		TimerRate:  DefaultTimerRate,
+		sampleRate: sampleRate,
+		oscillator: NewOscillator(DefaultFrequency, sampleRate),
+	}
+}
+
+// RunFrame runs a single frame of the virtual machine.
+func (c *Core) RunFrame() error {
+	// Fractions of a cycle are carried over to the next frame
+	c.budget += CyclesPerFrame(c.Speed)
+	c.gates = append(c.gates[:0], c.Beeping())
+	for ; c.budget >= 1.0; c.budget-- {
+		if c.VM.Idle() {
+			c.budget = 0.0
+			break
+		}
+		if err := c.VM.RunCycle(); err != nil {
+			return err
+		}
+		c.gates = append(c.gates, c.Beeping())
+	}
+
+	c.timers += c.TimerRate / DefaultTPS
+	for ; c.timers >= 1.0; c.timers-- {
+		c.VM.UpdateTimers()
+	}
+
+	if c.OnFrame != nil {
+		c.OnFrame(&c.VM.Display, c.Beeping())
+	}
+	return nil
+}
+
+// RunUntilExit runs frames until the program exits (00FD), and returns
+// its exit code. It gives up once the given number of frames has run,
+// unless the number is 0.
+func (c *Core) RunUntilExit(frames int) (int, error) {
+	for frame := 0; frames == 0 || frame < frames; frame++ {
+		err := c.RunFrame()
+		if exit, ok := err.(*ExitError); ok {
+			return exit.Code, nil
+		}
+		if err != nil {
+			return 0, err
+		}
+	}
+	return 0, fmt.Errorf("program didn't exit within %d frames", frames)
+}
+
+// CyclesPerFrame returns the number of CPU cycles which the virtual
+// machine runs per frame at the given speed.
+func CyclesPerFrame(speed float64) float64 {
+	return speed * float64(time.Second/DefaultTPS) / float64(DefaultHzVM)
+}
+
+// Metrics returns the counters of the virtual machine of the core.
+func (c *Core) Metrics() Metrics {
+	return c.VM.Metrics()
+}
+
+// Beeping reports whether the beeper is sounding.
+func (c *Core) Beeping() bool {
+	return c.VM.ST > 0x00
+}
+
+// Audio returns the beeper audio of the last frame as interleaved
+// stereo samples.
+//
+// Each frame spans the same range of samples, as in WAV recordings, so
+// the audio must be read exactly once per frame to stay in sync. The
+// cycles of the frame are spread evenly over its samples, and the tone
+// starts and stops at the sample of the cycle which changed the sound
+// timer, so even beeps shorter than a frame are heard.
+func (c *Core) Audio() []int16 {
+	end := (c.frame + 1) * int64(c.sampleRate) / DefaultTPS
+	n := end - c.samples
+	buf := make([]int16, 0, 2*n)
+
+	for i := int64(0); i < n; i++ {
+		beep := c.Beeping()
+		if len(c.gates) > 0 {
+			beep = c.gates[i*int64(len(c.gates)-1)/n]
+		}
+
+		b := c.oscillator.Next(beep)
+		buf = append(buf, b, b)
+	}
+
+	c.samples = end
+	c.frame++
+	return buf
+}
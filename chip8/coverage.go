@@ -0,0 +1,151 @@
+package chip8
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"os"
+	"strconv"
+	"strings"
+)
+
+//=====================================================================
+// Coverage
+//=====================================================================
+
+// Coverage marks the addresses of the instructions which a virtual
+// machine has executed, to find the code of a ROM which a session never
+// reached.
+type Coverage [MemorySize]bool
+
+// SaveCoverage writes the executed addresses to a file, one address
+// per line, such as "0x2A4".
+func SaveCoverage(path string, cov *Coverage) error {
+	file, err := os.Create(path)
+	if err != nil {
+		return err
+	}
+
+	w := bufio.NewWriter(file)
+	for addr, executed := range cov {
+		if executed {
+			fmt.Fprintf(w, "0x%.3X\n", addr)
+		}
+	}
+
+	if err := w.Flush(); err != nil {
+		file.Close()
+		return err
+	}
+	return file.Close()
+}
+
+// LoadCoverage reads the executed addresses from a file written by
+// SaveCoverage.
+func LoadCoverage(path string) (*Coverage, error) {
+	file, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	defer file.Close()
+
+	cov := &Coverage{}
+	scanner := bufio.NewScanner(file)
+	for scanner.Scan() {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" {
+			continue
+		}
+
+		addr, err := strconv.ParseUint(line, 0, 16)
+		if err != nil || addr >= MemorySize {
+			return nil, fmt.Errorf("invalid address in %s: %s", path, line)
+		}
+		cov[addr] = true
+	}
+
+	return cov, scanner.Err()
+}
+
+// AddressRange is a range of memory, from Start up to but excluding
+// End.
+type AddressRange struct {
+	Start uint
+	End   uint
+}
+
+// String returns the range along with its size, such as
+// "0x2F0-0x31F (48 bytes)".
+func (r AddressRange) String() string {
+	return fmt.Sprintf("0x%.3X-0x%.3X (%d bytes)", r.Start, r.End-1, r.End-r.Start)
+}
+
+// ParseAddressRange parses a range of memory written as its first and
+// last addresses, such as "0xF00-0xFFF".
+func ParseAddressRange(s string) (AddressRange, error) {
+	parts := strings.Split(s, "-")
+	if len(parts) != 2 {
+		return AddressRange{}, fmt.Errorf("invalid address range: %s", s)
+	}
+
+	start, err := strconv.ParseUint(strings.TrimSpace(parts[0]), 0, 16)
+	if err != nil {
+		return AddressRange{}, fmt.Errorf("invalid address range: %s", s)
+	}
+	last, err := strconv.ParseUint(strings.TrimSpace(parts[1]), 0, 16)
+	if err != nil || last < start || last >= MemorySize {
+		return AddressRange{}, fmt.Errorf("invalid address range: %s", s)
+	}
+
+	return AddressRange{Start: uint(start), End: uint(last) + 1}, nil
+}
+
+// addressRanges returns the ranges of the program where covered is
+// false.
+func addressRanges(covered []bool) []AddressRange {
+	var ranges []AddressRange
+	for i := 0; i < len(covered); i++ {
+		if covered[i] {
+			continue
+		}
+
+		start := i
+		for i < len(covered) && !covered[i] {
+			i++
+		}
+		ranges = append(ranges, AddressRange{
+			Start: uint(ProgramStartAddress + start),
+			End:   uint(ProgramStartAddress + i),
+		})
+	}
+	return ranges
+}
+
+// Unexecuted returns the ranges of a program which were never executed
+// according to the coverage of a session.
+func (cov *Coverage) Unexecuted(program []byte) []AddressRange {
+	executed := make([]bool, len(program))
+	for i := range program {
+		addr := ProgramStartAddress + i
+		if cov[addr] || (addr > 0 && cov[addr-1]) {
+			executed[i] = true
+		}
+	}
+	return addressRanges(executed)
+}
+
+// WriteRanges writes ranges of memory one per line, or "none" if there
+// are none.
+func WriteRanges(w io.Writer, ranges []AddressRange) error {
+	if len(ranges) == 0 {
+		_, err := fmt.Fprintln(w, "none")
+		return err
+	}
+
+	for _, r := range ranges {
+		if _, err := fmt.Fprintln(w, r); err != nil {
+			return err
+		}
+	}
+	return nil
+}
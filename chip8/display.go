@@ -0,0 +1,134 @@
+package chip8
+
+import (
+	"crypto/sha1"
+	"encoding/hex"
+	"fmt"
+	"image"
+)
+
+//=====================================================================
+// Display
+//=====================================================================
+
+const (
+	// HiresWidth is the width (in pixels) of the CHIP-8 display in
+	// high-resolution mode (00FF).
+	HiresWidth = 0x80
+
+	// HiresHeight is the height (in pixels) of the CHIP-8 display in
+	// high-resolution mode (00FF).
+	HiresHeight = 0x40
+)
+
+// Display is the monochrome screen of the CHIP-8 virtual machine.
+//
+// The resolution of the display can change while a program runs, so
+// pixels are stored row by row in a slice rather than a fixed array.
+type Display struct {
+	Width  int
+	Height int
+	Pixels []bool
+}
+
+// NewDisplay creates a blank display with the given resolution.
+func NewDisplay(width, height int) Display {
+	return Display{
+		Width:  width,
+		Height: height,
+		Pixels: make([]bool, width*height),
+	}
+}
+
+// Size returns the resolution of the display.
+func (d *Display) Size() image.Point {
+	return image.Pt(d.Width, d.Height)
+}
+
+// Hires reports whether the display is in high-resolution mode.
+func (d *Display) Hires() bool {
+	return d.Width == HiresWidth && d.Height == HiresHeight
+}
+
+// Resize changes the resolution of the display, which clears it.
+func (d *Display) Resize(width, height int) {
+	if width == d.Width && height == d.Height {
+		d.Clear()
+		return
+	}
+
+	// Replace the pixels before the dimensions, so readers on other
+	// goroutines never index past the end of the pixels
+	pixels := make([]bool, width*height)
+	if len(pixels) > len(d.Pixels) {
+		d.Pixels = pixels
+		d.Width, d.Height = width, height
+	} else {
+		d.Width, d.Height = width, height
+		d.Pixels = pixels
+	}
+}
+
+// Clear turns off every pixel of the display.
+func (d *Display) Clear() {
+	for i := range d.Pixels {
+		d.Pixels[i] = false
+	}
+}
+
+// Scroll moves every pixel of the display by (dx, dy). Pixels moved off
+// the display are lost, and the pixels scrolled in are turned off.
+func (d *Display) Scroll(dx, dy int) {
+	pixels := make([]bool, len(d.Pixels))
+	for y := 0; y < d.Height; y++ {
+		for x := 0; x < d.Width; x++ {
+			pixels[y*d.Width+x] = d.At(x-dx, y-dy)
+		}
+	}
+	copy(d.Pixels, pixels)
+}
+
+// At reports whether the pixel at (x, y) is lit. Pixels outside of the
+// display are never lit.
+func (d *Display) At(x, y int) bool {
+	i := y*d.Width + x
+	if x < 0 || y < 0 || x >= d.Width || i >= len(d.Pixels) {
+		return false
+	}
+	return d.Pixels[i]
+}
+
+// Flip toggles the pixel at (x, y), and reports whether the pixel was
+// turned off.
+func (d *Display) Flip(x, y int) bool {
+	i := y*d.Width + x
+	if x < 0 || y < 0 || x >= d.Width || i >= len(d.Pixels) {
+		return false
+	}
+
+	d.Pixels[i] = !d.Pixels[i]
+	return !d.Pixels[i]
+}
+
+// Hash returns the SHA-1 hash of the resolution and pixels of the
+// display in hexadecimal, which tells whether two displays are the
+// same without keeping their pixels around.
+func (d *Display) Hash() string {
+	data := []byte(fmt.Sprintf("%dx%d\n", d.Width, d.Height))
+	for _, p := range d.Pixels {
+		if p {
+			data = append(data, 1)
+		} else {
+			data = append(data, 0)
+		}
+	}
+
+	sum := sha1.Sum(data)
+	return hex.EncodeToString(sum[:])
+}
+
+// Sample reports whether the pixel at (x, y) of an image with the
+// given size is lit, when the display is stretched over the image.
+func (d *Display) Sample(x, y int, size image.Point) bool {
+	return d.At(x*d.Width/size.X, y*d.Height/size.Y)
+}
@@ -0,0 +1,30 @@
+// +build !nodownload
+
+package chip8
+
+import (
+	"fmt"
+	"net/http"
+	"time"
+)
+
+// downloadTimeout is how long a ROM may take to download.
+const downloadTimeout = 30 * time.Second
+
+// downloadROM downloads a ROM from an http(s) URL. Builds with the
+// nodownload tag leave the downloader out.
+func downloadROM(url string) ([]byte, error) {
+	client := http.Client{Timeout: downloadTimeout}
+
+	resp, err := client.Get(url)
+	if err != nil {
+		return nil, err
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("can't download %s: %s", url, resp.Status)
+	}
+
+	return readROM(resp.Body)
+}
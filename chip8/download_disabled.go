@@ -0,0 +1,11 @@
+// +build nodownload
+
+package chip8
+
+import "fmt"
+
+// downloadROM fails, since the downloader is left out of builds with
+// the nodownload tag.
+func downloadROM(url string) ([]byte, error) {
+	return nil, fmt.Errorf("can't download %s: this build has no downloader", url)
+}
@@ -0,0 +1,88 @@
+package chip8
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+)
+
+//=====================================================================
+// Memory Dumps
+//=====================================================================
+
+const (
+	// DumpBinary writes the bytes of memory as they are.
+	DumpBinary = "bin"
+
+	// DumpHex writes the bytes of memory as a hex dump, 16 bytes per
+	// line along with their address.
+	DumpHex = "hex"
+
+	// dumpLineSize is the number of bytes per line of a hex dump.
+	dumpLineSize = 16
+)
+
+// MemoryRange returns the bytes of memory of the virtual machine from
+// the given address, clamped to the end of memory.
+func (vm *VirtualMachine) MemoryRange(addr, size int) ([]byte, error) {
+	if addr < 0 || addr >= MemorySize {
+		return nil, fmt.Errorf("address out of range: 0x%.3X", addr)
+	}
+	if size < 0 {
+		return nil, fmt.Errorf("invalid size: %d", size)
+	}
+	if addr+size > MemorySize {
+		size = MemorySize - addr
+	}
+
+	data := make([]byte, size)
+	for i := range data {
+		data[i] = byte(vm.Memory[addr+i])
+	}
+	return data, nil
+}
+
+// DumpMemory writes bytes of memory which start at the given address
+// in the given format, either DumpBinary or DumpHex, such as:
+//
+//	0x200  00 E0 A2 2A 60 0C 61 08  D0 1F 70 09 A2 39 D0 1F  ...*`.a...p..9..
+func DumpMemory(w io.Writer, data []byte, addr int, format string) error {
+	switch format {
+	case DumpBinary:
+		_, err := w.Write(data)
+		return err
+	case DumpHex:
+	default:
+		return fmt.Errorf("unknown dump format: %s", format)
+	}
+
+	b := bufio.NewWriter(w)
+	for start := 0; start < len(data); start += dumpLineSize {
+		line := data[start:]
+		if len(line) > dumpLineSize {
+			line = line[:dumpLineSize]
+		}
+
+		fmt.Fprintf(b, "0x%.3X ", addr+start)
+		for i := 0; i < dumpLineSize; i++ {
+			if i == dumpLineSize/2 {
+				b.WriteByte(' ')
+			}
+			if i < len(line) {
+				fmt.Fprintf(b, " %.2X", line[i])
+			} else {
+				b.WriteString("   ")
+			}
+		}
+
+		b.WriteString("  ")
+		for _, c := range line {
+			if c < 0x20 || c > 0x7e {
+				c = '.'
+			}
+			b.WriteByte(c)
+		}
+		b.WriteByte('\n')
+	}
+	return b.Flush()
+}
@@ -0,0 +1,70 @@
+package chip8
+
+import "sync"
+
+//=====================================================================
+// Farms
+//=====================================================================
+
+// Farm runs several cores side by side in one process, such as a set of
+// test ROMs, or the same ROM on several machines to compare them.
+//
+// Each core has its own virtual machine, timers, and random numbers, so
+// the cores of a farm are independent and run on their own goroutines.
+// The package does share its logs and the exported tables of Keymaps,
+// Palettes, MachineSpecs, and QuirkProfiles between them, which must
+// not be changed while a farm is running.
+type Farm struct {
+	Cores []*Core
+}
+
+// NewFarm creates a farm of cores.
+func NewFarm(cores ...*Core) *Farm {
+	return &Farm{Cores: cores}
+}
+
+// Add adds a core to the farm, and returns its index.
+func (f *Farm) Add(c *Core) int {
+	f.Cores = append(f.Cores, c)
+	return len(f.Cores) - 1
+}
+
+// Each runs a function for every core of the farm at the same time,
+// and waits for all of them to return.
+func (f *Farm) Each(fn func(i int, c *Core)) {
+	var wg sync.WaitGroup
+	for i, c := range f.Cores {
+		wg.Add(1)
+		go func(i int, c *Core) {
+			defer wg.Done()
+			fn(i, c)
+		}(i, c)
+	}
+	wg.Wait()
+}
+
+// RunFrames runs a number of frames on every core, and returns the
+// error of each core, which stops at its first error.
+func (f *Farm) RunFrames(frames int) []error {
+	errs := make([]error, len(f.Cores))
+	f.Each(func(i int, c *Core) {
+		for frame := 0; frame < frames; frame++ {
+			if err := c.RunFrame(); err != nil {
+				errs[i] = err
+				return
+			}
+		}
+	})
+	return errs
+}
+
+// RunUntilExit runs every core until its program exits, as with
+// Core.RunUntilExit, and returns the exit code and error of each core.
+func (f *Farm) RunUntilExit(frames int) ([]int, []error) {
+	codes := make([]int, len(f.Cores))
+	errs := make([]error, len(f.Cores))
+	f.Each(func(i int, c *Core) {
+		codes[i], errs[i] = c.RunUntilExit(frames)
+	})
+	return codes, errs
+}
@@ -0,0 +1,101 @@
+package chip8
+
+//=====================================================================
+// Frame Iterators
+//=====================================================================
+
+// Frame is a frame run by a core, as returned by a FrameIterator. It
+// belongs to the caller, so frames can be kept and processed later.
+type Frame struct {
+	// Number is the number of the frame, counting from 0.
+	Number int
+
+	// Display is a copy of the display at the end of the frame.
+	Display Display
+
+	// Beeping is whether the beeper is sounding at the end of the
+	// frame, and Audio the beeper audio of the frame, as returned by
+	// Core.Audio.
+	Beeping bool
+	Audio   []int16
+
+	// Events are the counters of the work done during the frame, such
+	// as the sprites drawn and the sounds started.
+	Events Metrics
+
+	// Exited is whether the program exited (00FD) during the frame,
+	// with ExitCode as its exit code, which ends the iteration.
+	Exited   bool
+	ExitCode int
+}
+
+// FrameIterator runs a core one frame at a time, in the style of a
+// bufio.Scanner, which makes batch tools a short loop:
+//
+//	frames := core.Frames(300)
+//	for frames.Next() {
+//		frame := frames.Frame()
+//		...
+//	}
+//	if err := frames.Err(); err != nil {
+//		...
+//	}
+type FrameIterator struct {
+	core   *Core
+	frames int
+	frame  Frame
+	done   bool
+	err    error
+}
+
+// Frames returns an iterator running the core for the given number of
+// frames, or until the program exits if the number is 0. The audio of
+// the core is read every frame, so it must not be read elsewhere.
+func (c *Core) Frames(frames int) *FrameIterator {
+	return &FrameIterator{core: c, frames: frames, frame: Frame{Number: -1}}
+}
+
+// Next runs the next frame, and reports whether it ran. It returns
+// false once the frames are up, after the program exits, or on an
+// error, which Err returns.
+func (it *FrameIterator) Next() bool {
+	number := it.frame.Number + 1
+	if it.done || (it.frames > 0 && number >= it.frames) {
+		return false
+	}
+
+	c := it.core
+	before := c.Metrics()
+	err := c.RunFrame()
+	exit, exited := err.(*ExitError)
+	if err != nil && !exited {
+		it.err, it.done = err, true
+		return false
+	}
+
+	display := c.VM.Display
+	display.Pixels = append([]bool(nil), c.VM.Display.Pixels...)
+
+	it.frame = Frame{
+		Number:  number,
+		Display: display,
+		Beeping: c.Beeping(),
+		Audio:   c.Audio(),
+		Events:  c.Metrics().sub(before),
+	}
+	if exited {
+		it.frame.Exited, it.frame.ExitCode = true, exit.Code
+		it.done = true
+	}
+	return true
+}
+
+// Frame returns the frame which the last call of Next ran.
+func (it *FrameIterator) Frame() Frame {
+	return it.frame
+}
+
+// Err returns the error which stopped the virtual machine, if any.
+func (it *FrameIterator) Err() error {
+	return it.err
+}
@@ -0,0 +1,142 @@
+package chip8
+
+import (
+	"fmt"
+	"strconv"
+)
+
+//=====================================================================
+// Keymaps
+//=====================================================================
+
+const (
+	// DefaultKeymap is the name of the default keymap.
+	DefaultKeymap = "positional"
+
+	// CustomKeymap is the name of the keymap made of the key bindings
+	// in the configuration file.
+	CustomKeymap = "custom"
+)
+
+// Keymap maps keys of the keyboard to keys of the CHIP-8 keypad.
+//
+// Keys of the keyboard are matched by their position rather than their
+// label, unless key labels are enabled, and are named after the US
+// QWERTY layout, such as "Q" or "Space".
+type Keymap struct {
+	Name string
+	Keys map[string]uint
+}
+
+// Keymaps are the built-in keymaps of the emulator, in the order they
+// are cycled through.
+var Keymaps = []Keymap{
+	// The left side of the keyboard, laid out like the COSMAC VIP
+	{"positional", map[string]uint{
+		"1": 0x1, "2": 0x2, "3": 0x3, "4": 0xc,
+		"Q": 0x4, "W": 0x5, "E": 0x6, "R": 0xd,
+		"A": 0x7, "S": 0x8, "D": 0x9, "F": 0xe,
+		"Z": 0xa, "X": 0x0, "C": 0xb, "V": 0xf,
+	}},
+
+	// The left side of the keyboard, in hexadecimal order
+	{"sequential", map[string]uint{
+		"1": 0x0, "2": 0x1, "3": 0x2, "4": 0x3,
+		"Q": 0x4, "W": 0x5, "E": 0x6, "R": 0x7,
+		"A": 0x8, "S": 0x9, "D": 0xa, "F": 0xb,
+		"Z": 0xc, "X": 0xd, "C": 0xe, "V": 0xf,
+	}},
+
+	// The keys labeled with each hexadecimal digit
+	{"literal", literalKeys("A")},
+
+	// The keys labeled with each hexadecimal digit on AZERTY keyboards,
+	// where A takes the place of Q
+	{"azerty", literalKeys("Q")},
+}
+
+// keymapAliases are other names of the built-in keymaps. QWERTZ
+// keyboards only swap Y and Z, which aren't hexadecimal digits, so the
+// literal keymap already matches them.
+var keymapAliases = map[string]string{
+	"qwertz": "literal",
+}
+
+// literalKeys maps the digit keys and the letter keys A to F to their
+// hexadecimal digits, where the A key may be moved by the layout.
+func literalKeys(a string) map[string]uint {
+	return map[string]uint{
+		"0": 0x0, "1": 0x1, "2": 0x2, "3": 0x3,
+		"4": 0x4, "5": 0x5, "6": 0x6, "7": 0x7,
+		"8": 0x8, "9": 0x9, a: 0xa, "B": 0xb,
+		"C": 0xc, "D": 0xd, "E": 0xe, "F": 0xf,
+	}
+}
+
+// FindKeymap returns the index of the keymap with the given name or
+// alias, or -1 if there is no such keymap.
+func FindKeymap(keymaps []Keymap, name string) int {
+	if alias, ok := keymapAliases[name]; ok {
+		name = alias
+	}
+
+	for i, k := range keymaps {
+		if k.Name == name {
+			return i
+		}
+	}
+	return -1
+}
+
+// KeymapNames returns the names of the built-in keymaps.
+func KeymapNames() []string {
+	names := make([]string, len(Keymaps))
+	for i, k := range Keymaps {
+		names[i] = k.Name
+	}
+	return names
+}
+
+// NewCustomKeymap creates a keymap from bindings of key names (e.g.
+// "Q" or "Space") to hexadecimal keypad keys (e.g. "4"). Only the keypad
+// keys are checked, since the names of the keys of the keyboard are
+// only known to the frontends.
+func NewCustomKeymap(bindings map[string]string) (Keymap, error) {
+	keymap := Keymap{Name: CustomKeymap, Keys: map[string]uint{}}
+
+	for name, hex := range bindings {
+		v, err := ParseKeypadKey(hex)
+		if err != nil {
+			return Keymap{}, err
+		}
+		keymap.Keys[name] = v
+	}
+
+	return keymap, nil
+}
+
+// AvailableKeymaps returns the built-in keymaps, along with the custom
+// keymap made of the given bindings, if there are any.
+func AvailableKeymaps(bindings map[string]string) ([]Keymap, error) {
+	keymaps := append([]Keymap(nil), Keymaps...)
+
+	if len(bindings) > 0 {
+		custom, err := NewCustomKeymap(bindings)
+		if err != nil {
+			return nil, err
+		}
+		keymaps = append(keymaps, custom)
+	}
+
+	return keymaps, nil
+}
+
+// ParseKeypadKey parses a key of the CHIP-8 keypad written as a
+// hexadecimal digit.
+func ParseKeypadKey(hex string) (uint, error) {
+	v, err := strconv.ParseUint(hex, 16, 8)
+	if err != nil || v >= NumberOfKeys {
+		return 0, fmt.Errorf("invalid keypad key: %s", hex)
+	}
+	return uint(v), nil
+}
@@ -0,0 +1,210 @@
+package chip8
+
+import (
+	"fmt"
+	"log"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+//=====================================================================
+// Logging
+//=====================================================================
+
+// DefaultLogLevel is the default level of the emulator's logs.
+const DefaultLogLevel = "info"
+
+// logLevel is the severity of a log message.
+type logLevel int
+
+const (
+	levelTrace logLevel = iota
+	levelDebug
+	levelInfo
+	levelWarn
+	levelError
+	levelOff
+)
+
+var levelNames = []string{"trace", "debug", "info", "warn", "error", "off"}
+
+func (l logLevel) String() string {
+	return levelNames[l]
+}
+
+func parseLogLevel(name string) (logLevel, error) {
+	for i, n := range levelNames {
+		if n == name {
+			return logLevel(i), nil
+		}
+	}
+	return levelOff, fmt.Errorf("unknown log level: %s", name)
+}
+
+// logFilter is the level of logs of each module, parsed from a spec
+// such as "info,netplay=debug".
+type logFilter struct {
+	level   logLevel
+	modules map[string]logLevel
+}
+
+func parseLogFilter(spec string) (logFilter, error) {
+	f := logFilter{level: levelInfo, modules: map[string]logLevel{}}
+
+	for _, part := range strings.Split(spec, ",") {
+		part = strings.TrimSpace(part)
+		if part == "" {
+			continue
+		}
+
+		module, name := "", part
+		if i := strings.IndexByte(part, '='); i >= 0 {
+			module, name = part[:i], part[i+1:]
+		}
+
+		level, err := parseLogLevel(name)
+		if err != nil {
+			return f, err
+		}
+
+		if module == "" {
+			f.level = level
+		} else {
+			f.modules[module] = level
+		}
+	}
+
+	return f, nil
+}
+
+var (
+	logMutex sync.RWMutex
+	logs     = logFilter{level: levelInfo}
+)
+
+// SetLogLevel sets the level of the emulator's logs from a spec such
+// as "info,netplay=debug": a level for every module, followed by the
+// levels of individual modules.
+//
+// The levels are trace, debug, info, warn, error, and off.
+func SetLogLevel(spec string) error {
+	f, err := parseLogFilter(spec)
+	if err != nil {
+		return err
+	}
+
+	logMutex.Lock()
+	defer logMutex.Unlock()
+	logs = f
+	return nil
+}
+
+// CheckLogLevel checks a spec of the levels of the emulator's logs, as
+// taken by SetLogLevel, without setting them.
+func CheckLogLevel(spec string) error {
+	_, err := parseLogFilter(spec)
+	return err
+}
+
+// Logger writes structured logs of a module of the emulator, one line
+// of key=value pairs per message, such as:
+//
+//	level=info module=state msg="saved state to states/Pong.state"
+type Logger struct {
+	module string
+}
+
+// NewLogger creates a logger of a module, whose logs can be filtered
+// separately.
+func NewLogger(module string) *Logger {
+	return &Logger{module}
+}
+
+// enabled reports whether messages of the given level are logged.
+func (l *Logger) enabled(level logLevel) bool {
+	logMutex.RLock()
+	defer logMutex.RUnlock()
+
+	min, ok := logs.modules[l.module]
+	if !ok {
+		min = logs.level
+	}
+	return level >= min && level < levelOff
+}
+
+// log writes a message along with fields given as pairs of keys and
+// values.
+func (l *Logger) log(level logLevel, msg string, fields ...interface{}) {
+	if !l.enabled(level) {
+		return
+	}
+
+	var b strings.Builder
+	fmt.Fprintf(&b, "level=%s module=%s msg=%s", level, l.module, logValue(msg))
+	for i := 0; i+1 < len(fields); i += 2 {
+		fmt.Fprintf(&b, " %v=%s", fields[i], logValue(fmt.Sprint(fields[i+1])))
+	}
+
+	log.Output(3, b.String())
+}
+
+// logValue quotes a value of a log line if it contains spaces or
+// quotes.
+func logValue(s string) string {
+	if s == "" || strings.ContainsAny(s, " \"=") {
+		return strconv.Quote(s)
+	}
+	return s
+}
+
+// sprintln formats its operands like log.Println, without the newline.
+func sprintln(v ...interface{}) string {
+	return strings.TrimSuffix(fmt.Sprintln(v...), "\n")
+}
+
+func (l *Logger) Error(v ...interface{}) {
+	l.log(levelError, sprintln(v...))
+}
+
+func (l *Logger) Errorf(format string, v ...interface{}) {
+	l.log(levelError, fmt.Sprintf(format, v...))
+}
+
+func (l *Logger) Warn(v ...interface{}) {
+	l.log(levelWarn, sprintln(v...))
+}
+
+func (l *Logger) Info(v ...interface{}) {
+	l.log(levelInfo, sprintln(v...))
+}
+
+func (l *Logger) Infof(format string, v ...interface{}) {
+	l.log(levelInfo, fmt.Sprintf(format, v...))
+}
+
+// Span measures how long a part of a frame takes, which is logged at
+// the trace level once it ends.
+type Span struct {
+	log   *Logger
+	name  string
+	frame uint64
+	start time.Time
+}
+
+// Span starts a span of the given frame, unless traces aren't logged.
+func (l *Logger) Span(name string, frame uint64) Span {
+	if !l.enabled(levelTrace) {
+		return Span{}
+	}
+	return Span{l, name, frame, time.Now()}
+}
+
+// End logs the duration of the span.
+func (s Span) End() {
+	if s.log == nil {
+		return
+	}
+	s.log.log(levelTrace, s.name, "frame", s.frame, "duration", time.Since(s.start))
+}
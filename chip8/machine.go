@@ -0,0 +1,911 @@
+package chip8
+
+import (
+	"fmt"
+	"io"
+	"math/rand"
+	"sync/atomic"
+)
+
+//===========================================================================
+// Constants
+//===========================================================================
+
+const (
+	// FontSize is the number of bytes in a CHIP-8 built-in font.
+	FontSize = 0x5
+
+	// MaxStackDepth is the maximum depth of the CHIP-8 virtual
+	// machine's call stack.
+	MaxStackDepth = 0x10
+
+	// MemorySize is the total amount of memory available in the CHIP-8
+	// virtual machine.
+	MemorySize = 0x1000
+
+	// DisplayWidth is the width (in pixels) of the CHIP-8 display in
+	// low-resolution mode.
+	DisplayWidth = 0x40
+
+	// DisplayHeight is the height (in pixels) of the CHIP-8 display in
+	// low-resolution mode.
+	DisplayHeight = 0x20
+
+	// NumberOfKeys is the number of keys in the CHIP-8 keyboard.
+	NumberOfKeys = 0x10
+
+	// NumberOfFonts is the total number of built-in fonts in the
+	// CHIP-8 virtual machine.
+	NumberOfFonts = 0x10
+
+	// BigFontSize is the number of bytes in a SUPER-CHIP large font.
+	BigFontSize = 0xa
+
+	// BigFontAddress is the memory location of the SUPER-CHIP large
+	// fonts, right after the built-in fonts.
+	BigFontAddress = NumberOfFonts * FontSize
+
+	// NumberOfFlags is the number of SUPER-CHIP flag registers, which
+	// FX75 and FX85 save and load registers to and from.
+	NumberOfFlags = 0x8
+
+	// NumberOfPixels is the total number of pixels in the CHIP-8
+	// display in low-resolution mode.
+	NumberOfPixels = DisplayWidth * DisplayHeight
+
+	// NumberOfRegisters is the number of general-purpose registers in
+	// the CHIP-8 virtual machine.
+	NumberOfRegisters = 0x10
+
+	// ProgramStartAddress is the start memory location where programs
+	// are loaded in the virtual machine's memory.
+	ProgramStartAddress = 0x200
+
+	// ProgramMemorySize is the total amount of memory available for
+	// CHIP-8 programs.
+	ProgramMemorySize = MemorySize - ProgramStartAddress
+)
+
+//===========================================================================
+// Errors
+//===========================================================================
+
+// InvalidProgramError is an error that occurs from loading a program.
+func InvalidProgramError(msg string) error {
+	return fmt.Errorf("invalid program: %s", msg)
+}
+
+// InvalidStateError is an error that occurs due to bad state in the
+// virtual machine.
+func InvalidStateError(msg string) error {
+	return fmt.Errorf("invalid state: %s", msg)
+}
+
+// InvalidJumpError is an error caused by a program trying to jump to
+// an invalid memory location.
+func InvalidJumpError(fromAddr, toAddr uint) error {
+	return fmt.Errorf(
+		"invalid jump: Jump from %.3X to %.3X",
+		fromAddr,
+		toAddr,
+	)
+}
+
+// InvalidOpcodeError is an error caused by the CHIP-8 virtual machine
+// trying to run an invalid opcode.
+func InvalidOpcodeError(opcode uint) error {
+	return &OpcodeError{Opcode: opcode}
+}
+
+// OpcodeError is returned by the CHIP-8 virtual machine when it tries
+// to run an invalid opcode, which usually means the program ran into
+// data or was written for another variant of CHIP-8.
+type OpcodeError struct {
+	Opcode uint
+}
+
+func (e *OpcodeError) Error() string {
+	return fmt.Sprintf("invalid opcode: %.4X", e.Opcode)
+}
+
+// ExitError is returned by the CHIP-8 virtual machine when a program
+// exits (00FD), with the value of V0 as its exit code. Test ROMs use it
+// to report whether they passed when run headless.
+type ExitError struct {
+	Code int
+}
+
+func (e *ExitError) Error() string {
+	return fmt.Sprintf("program exited with code %d", e.Code)
+}
+
+//===========================================================================
+// Virtual Machine
+//===========================================================================
+
+// VirtualMachine is the CHIP-8 virtual machine.
+type VirtualMachine struct {
+	// The counters are accessed atomically, so they must stay 64-bit
+	// aligned
+	cycles   uint64
+	frames   uint64
+	draws    uint64
+	keyWaits uint64
+	sounds   uint64
+	activity uint64
+
+	I        uint
+	SP       uint
+	PC       uint
+	DT       uint
+	ST       uint
+	V        [NumberOfRegisters]uint
+	Flags    [NumberOfFlags]uint
+	Stack    [MaxStackDepth]uint
+	Memory   [MemorySize]uint
+	Keys     [NumberOfKeys]bool
+	Display  Display
+	Opcode   uint
+	Quirks   Quirks
+	Spec     MachineSpec
+	Coverage *Coverage
+	opcodeFn map[uint]func() error
+	rng      *rand.Rand
+	dirty    int32
+	waiting  int32
+}
+
+// NewVirtualMachine creates new CHIP-8 virtual machine instance.
+func NewVirtualMachine() *VirtualMachine {
+	return NewMachine(MachineSpecs[FindMachine(DefaultMachine)])
+}
+
+// NewMachine creates a new virtual machine of the variant of CHIP-8
+// described by a machine spec.
+func NewMachine(spec MachineSpec) *VirtualMachine {
+	if spec.MemorySize == 0 || spec.MemorySize > MemorySize {
+		spec.MemorySize = MemorySize
+	}
+
+	vm := &VirtualMachine{
+		PC:      spec.LoadAddress,
+		Stack:   [MaxStackDepth]uint{},
+		V:       [NumberOfRegisters]uint{},
+		Keys:    [NumberOfKeys]bool{},
+		Display: NewDisplay(spec.DisplayWidth, spec.DisplayHeight),
+		Memory:  [MemorySize]uint{},
+		Spec:    spec,
+		rng:     rand.New(rand.NewSource(1)),
+		dirty:   1,
+	}
+
+	if i := FindQuirks(spec.Quirks); i >= 0 {
+		vm.Quirks = QuirkProfiles[i].Quirks
+	}
+
+	for i, b := range spec.Font {
+		vm.Memory[i] = b
+	}
+	for i, b := range spec.BigFont {
+		vm.Memory[BigFontAddress+i] = b
+	}
+
+	vm.opcodeFn = map[uint]func() error{
+		0x0: vm.executeOp0x0, 0x1: vm.executeOp0x1,
+		0x2: vm.executeOp0x2, 0x3: vm.executeOp0x3,
+		0x4: vm.executeOp0x4, 0x5: vm.executeOp0x5,
+		0x6: vm.executeOp0x6, 0x7: vm.executeOp0x7,
+		0x8: vm.executeOp0x8, 0x9: vm.executeOp0x9,
+		0xa: vm.executeOp0xA, 0xb: vm.executeOp0xB,
+		0xc: vm.executeOp0xC, 0xd: vm.executeOp0xD,
+		0xe: vm.executeOp0xE, 0xf: vm.executeOp0xF,
+	}
+
+	return vm
+}
+
+// RunCycle runs a single CPU cycle of the virtual machine.
+func (vm *VirtualMachine) RunCycle() error {
+	atomic.AddUint64(&vm.cycles, 1)
+
+	if vm.Coverage != nil {
+		vm.Coverage[vm.PC] = true
+	}
+
+	// Fetch-decode-execute
+	vm.fetch()
+	execute := vm.decode()
+	err := execute()
+
+	// Keep program counter within range. Instructions are two bytes, so
+	// the last byte of memory can't start one
+	if vm.PC >= vm.Spec.MemorySize-1 {
+		vm.PC = vm.PC + vm.Spec.LoadAddress - vm.Spec.MemorySize
+	}
+
+	return err
+}
+
+// Seed seeds the random numbers of the virtual machine (CXNN), so a
+// program runs the same way every time it is given the same seed and
+// keys.
+func (vm *VirtualMachine) Seed(seed int64) {
+	vm.rng.Seed(seed)
+}
+
+// Cycles returns the number of CPU cycles the virtual machine has run.
+func (vm *VirtualMachine) Cycles() uint64 {
+	return atomic.LoadUint64(&vm.cycles)
+}
+
+// WaitingForKey reports whether the virtual machine is waiting for a
+// key to be pressed (FX0A).
+func (vm *VirtualMachine) WaitingForKey() bool {
+	return atomic.LoadInt32(&vm.waiting) == 1
+}
+
+// Idle reports whether the virtual machine can't get anywhere until a
+// key is pressed, so running it would only burn host CPU. It is idle
+// when it is waiting for a key (FX0A) or polling a key in a loop while
+// no key is held, or when it jumps to itself forever:
+//
+//	loop: SKP V0
+//	      JP loop
+func (vm *VirtualMachine) Idle() bool {
+	pc := vm.PC
+	if pc+3 >= MemorySize {
+		return false
+	}
+	opcode := vm.Memory[pc]<<8 | vm.Memory[pc+1]
+	next := vm.Memory[pc+2]<<8 | vm.Memory[pc+3]
+
+	if opcode>>12 == 0x1 && opcode&0xfff == pc {
+		return true
+	}
+
+	for _, down := range vm.Keys {
+		if down {
+			return false
+		}
+	}
+
+	// Without a key held, SKP never skips the jump back
+	keyLoop := opcode>>12 == 0xe && opcode&0xff == 0x9e &&
+		next>>12 == 0x1 && next&0xfff == pc
+	return vm.WaitingForKey() || keyLoop
+}
+
+// UpdateTimers updates the delay and sound timers, which happens once
+// per frame.
+func (vm *VirtualMachine) UpdateTimers() {
+	atomic.AddUint64(&vm.frames, 1)
+
+	if vm.DT > 0x00 {
+		vm.DT--
+	}
+	if vm.ST > 0x00 {
+		vm.ST--
+	}
+}
+
+// LoadROM reads a CHIP-8 ROM program file (*.ch8) and loads it into
+// memory. The ROM may also be read from the standard input or a URL,
+// as with ReadROM.
+func (vm *VirtualMachine) LoadROM(path string) error {
+	data, err := ReadROM(path)
+	if err != nil {
+		return err
+	}
+
+	return vm.LoadBytes(data)
+}
+
+// LoadBytes loads the bytes of a CHIP-8 program into memory.
+func (vm *VirtualMachine) LoadBytes(data []byte) error {
+	return vm.LoadAt(vm.Spec.LoadAddress, data)
+}
+
+// LoadReader reads a CHIP-8 program from a reader and loads it into
+// memory.
+func (vm *VirtualMachine) LoadReader(r io.Reader) error {
+	data, err := readROM(r)
+	if err != nil {
+		return err
+	}
+
+	return vm.LoadBytes(data)
+}
+
+// LoadAt loads bytes into memory at an address. The bytes must fit
+// within program memory, from the load address of the machine up to
+// the end of its memory, so the built-in fonts can't be overwritten.
+func (vm *VirtualMachine) LoadAt(addr uint, data []byte) error {
+	if err := vm.checkProgramRange(addr, len(data)); err != nil {
+		return err
+	}
+
+	for i, b := range data {
+		vm.Memory[addr+uint(i)] = uint(b)
+	}
+
+	return nil
+}
+
+// checkProgramRange checks that size bytes at an address fit within
+// program memory.
+func (vm *VirtualMachine) checkProgramRange(addr uint, size int) error {
+	if addr < vm.Spec.LoadAddress || addr >= vm.Spec.MemorySize {
+		return InvalidProgramError(fmt.Sprintf("0x%.3X is outside of program memory", addr))
+	}
+	if uint(size) > vm.Spec.MemorySize-addr {
+		return InvalidProgramError(fmt.Sprintf("%d bytes at 0x%.3X don't fit in memory", size, addr))
+	}
+	return nil
+}
+
+// Segment is a block of bytes to be loaded at an address of memory.
+type Segment struct {
+	Addr uint
+	Data []byte
+}
+
+// LoadSegments loads segments into memory, such as a program followed
+// by patches to it. Every segment is validated before any is loaded, so
+// memory is left untouched if one doesn't fit.
+func (vm *VirtualMachine) LoadSegments(segments []Segment) error {
+	for _, seg := range segments {
+		if err := vm.checkProgramRange(seg.Addr, len(seg.Data)); err != nil {
+			return err
+		}
+	}
+
+	for _, seg := range segments {
+		for i, b := range seg.Data {
+			vm.Memory[seg.Addr+uint(i)] = uint(b)
+		}
+	}
+
+	return nil
+}
+
+// LoadOpcodes loads opcodes into the virtual machine's program memory.
+func (vm *VirtualMachine) LoadOpcodes(opcodes []uint) error {
+	if uint(2*len(opcodes)) > vm.Spec.MemorySize-vm.Spec.LoadAddress {
+		return InvalidProgramError("The ROM is too large")
+	}
+
+	i := vm.Spec.LoadAddress
+	for _, opcode := range opcodes {
+		if opcode > 0xffff {
+			return InvalidOpcodeError(vm.Opcode)
+		}
+
+		vm.Memory[i] = opcode >> 8
+		vm.Memory[i+1] = opcode & 0xff
+		i += 0x2
+	}
+
+	return nil
+}
+
+// Reset resets the virtual machine.
+//
+// This preserves the program/opcodes already loaded in memory.
+func (vm *VirtualMachine) Reset() {
+	atomic.StoreInt32(&vm.waiting, 0)
+	vm.ClearRegisters()
+	vm.Display.Resize(vm.Spec.DisplayWidth, vm.Spec.DisplayHeight)
+	vm.ClearDisplay()
+	vm.ClearKeys()
+}
+
+// Clear clears the entire state of the virtual machine.
+func (vm *VirtualMachine) Clear() {
+	vm.Reset()
+	vm.ClearProgram()
+}
+
+// ClearKeys clears the state of the keys.
+func (vm *VirtualMachine) ClearKeys() {
+	for i := 0; i < len(vm.Keys); i++ {
+		vm.Keys[i] = false
+	}
+}
+
+// ClearProgram clears the program loaded in the virtual machine.
+func (vm *VirtualMachine) ClearProgram() {
+	for i := vm.Spec.LoadAddress; i < MemorySize; i++ {
+		vm.Memory[i] = 0x00
+	}
+}
+
+// ClearRegisters clears all the registers, including the program
+// counter, timers, stack pointer.
+func (vm *VirtualMachine) ClearRegisters() {
+	vm.I = 0x000
+	vm.SP = 0x00
+	vm.PC = vm.Spec.LoadAddress
+	vm.DT = 0x00
+	vm.ST = 0x00
+
+	for i := 0; i < len(vm.V); i++ {
+		vm.V[i] = 0x00
+	}
+}
+
+// ClearDisplay clears the state of the display.
+func (vm *VirtualMachine) ClearDisplay() {
+	vm.Display.Clear()
+	atomic.StoreInt32(&vm.dirty, 1)
+}
+
+// DisplayChanged reports whether the display has changed since the
+// last time DisplayChanged was called.
+func (vm *VirtualMachine) DisplayChanged() bool {
+	return atomic.SwapInt32(&vm.dirty, 0) == 1
+}
+
+//=====================================================================
+// CPU Cycle
+//=====================================================================
+
+func (vm *VirtualMachine) fetch() {
+	opcode := (vm.Memory[vm.PC] << 8) | vm.Memory[vm.PC+1]
+	vm.PC += 0x2
+	vm.Opcode = opcode
+}
+
+func (vm *VirtualMachine) decode() func() error {
+	return vm.opcodeFn[vm.decodeOp()]
+}
+
+func (vm *VirtualMachine) decodeX() uint {
+	return (vm.Opcode >> 8) & 0xf
+}
+
+func (vm *VirtualMachine) decodeY() uint {
+	return (vm.Opcode >> 4) & 0xf
+}
+
+func (vm *VirtualMachine) decodeN() uint {
+	return vm.Opcode & 0xf
+}
+
+func (vm *VirtualMachine) decodeOp() uint {
+	return vm.Opcode >> 0xc
+}
+
+func (vm *VirtualMachine) decodeKK() uint {
+	return vm.Opcode & 0xff
+}
+
+func (vm *VirtualMachine) decodeNNN() uint {
+	return vm.Opcode & 0xfff
+}
+
+func (vm *VirtualMachine) executeOp0x0() error {
+	nnn := vm.decodeNNN()
+
+	// 00CN scrolls the display down by N pixels
+	if nnn&0xff0 == 0x0c0 {
+		return vm.scroll(0, int(vm.decodeN()))
+	}
+
+	switch nnn {
+	case 0x0e0:
+		vm.ClearDisplay()
+		vm.touch()
+	case 0x0ee:
+		if vm.SP == 0 {
+			return InvalidStateError("Stack underflow")
+		}
+		vm.SP--
+		vm.PC = vm.Stack[vm.SP]
+	case 0x0fb:
+		return vm.scroll(4, 0)
+	case 0x0fc:
+		return vm.scroll(-4, 0)
+	case 0x0fd:
+		return &ExitError{Code: int(vm.V[0x0])}
+	case 0x0fe:
+		vm.Display.Resize(vm.Spec.DisplayWidth, vm.Spec.DisplayHeight)
+		atomic.StoreInt32(&vm.dirty, 1)
+	case 0x0ff:
+		if !vm.Spec.superChip() {
+			return InvalidOpcodeError(vm.Opcode)
+		}
+		vm.Display.Resize(vm.Spec.HiresWidth, vm.Spec.HiresHeight)
+		atomic.StoreInt32(&vm.dirty, 1)
+	default:
+		return InvalidOpcodeError(vm.Opcode)
+	}
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x1() error {
+	nnn := vm.decodeNNN()
+
+	if !vm.jumpable(nnn) {
+		return InvalidJumpError(vm.PC, nnn)
+	}
+
+	vm.PC = nnn
+
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x2() error {
+	nnn := vm.decodeNNN()
+
+	if vm.SP >= MaxStackDepth {
+		return InvalidStateError("Stack overflow")
+	} else if !vm.jumpable(nnn) {
+		return InvalidJumpError(vm.PC, nnn)
+	}
+
+	vm.Stack[vm.SP] = vm.PC
+	vm.PC = nnn
+	vm.SP++
+
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x3() error {
+	if vm.V[vm.decodeX()] == vm.decodeKK() {
+		vm.PC += 2
+	}
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x4() error {
+	if vm.V[vm.decodeX()] != vm.decodeKK() {
+		vm.PC += 2
+	}
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x5() error {
+	if vm.decodeN() != 0x0 {
+		return InvalidOpcodeError(vm.Opcode)
+	}
+
+	if vm.V[vm.decodeX()] == vm.V[vm.decodeY()] {
+		vm.PC += 2
+	}
+
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x6() error {
+	vm.V[vm.decodeX()] = vm.decodeKK()
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x7() error {
+	x := vm.decodeX()
+	kk := vm.decodeKK()
+	vm.V[x] = (vm.V[x] + kk) & 0xff
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x8() error {
+	x := vm.decodeX()
+	y := vm.decodeY()
+
+	switch vm.decodeN() {
+	case 0x0:
+		vm.V[x] = vm.V[y]
+	case 0x1:
+		vm.V[x] |= vm.V[y]
+		vm.resetVF()
+	case 0x2:
+		vm.V[x] &= vm.V[y]
+		vm.resetVF()
+	case 0x3:
+		vm.V[x] ^= vm.V[y]
+		vm.resetVF()
+	case 0x4:
+		result := vm.V[x] + vm.V[y]
+		vm.V[x] = result & 0xff
+		vm.setFlag(result > 0xff)
+	case 0x5:
+		flag := vm.V[x] >= vm.V[y]
+		vm.V[x] = (vm.V[x] - vm.V[y]) & 0xff
+		vm.setFlag(flag)
+	case 0x6:
+		if vm.Quirks.ShiftVY {
+			vm.V[x] = vm.V[y]
+		}
+		flag := vm.V[x]&0x01 == 0x01
+		vm.V[x] >>= 1
+		vm.setFlag(flag)
+	case 0x7:
+		flag := vm.V[y] >= vm.V[x]
+		vm.V[x] = (vm.V[y] - vm.V[x]) & 0xff
+		vm.setFlag(flag)
+	case 0xe:
+		if vm.Quirks.ShiftVY {
+			vm.V[x] = vm.V[y]
+		}
+		flag := vm.V[x]&0x80 == 0x80
+		vm.V[x] = (vm.V[x] << 1) & 0xff
+		vm.setFlag(flag)
+	default:
+		return InvalidOpcodeError(vm.Opcode)
+	}
+
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0x9() error {
+	if vm.decodeN() != 0x0 {
+		return InvalidOpcodeError(vm.Opcode)
+	}
+
+	if vm.V[vm.decodeX()] != vm.V[vm.decodeY()] {
+		vm.PC += 2
+	}
+
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0xA() error {
+	vm.I = vm.decodeNNN()
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0xB() error {
+	addr := (vm.decodeNNN() + vm.V[0x0]) & 0xfff
+	if vm.Quirks.JumpVX {
+		addr = (vm.decodeNNN() + vm.V[vm.decodeX()]) & 0xfff
+	}
+	if !vm.jumpable(addr) {
+		return InvalidJumpError(vm.PC, addr)
+	}
+
+	vm.PC = addr
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0xC() error {
+	vm.V[vm.decodeX()] = uint(vm.rng.Int()&0xff) & vm.decodeKK()
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0xD() error {
+	atomic.AddUint64(&vm.draws, 1)
+	vm.touch()
+
+	// VF may be a coordinate, so it is read before it is reset
+	vx := vm.V[vm.decodeX()]
+	vy := vm.V[vm.decodeY()]
+	vm.V[0xf] = 0x0
+	width, height := uint(vm.Display.Width), uint(vm.Display.Height)
+
+	// DXY0 draws a 16x16 sprite, two bytes per row, on SUPER-CHIP
+	rows, cols := vm.decodeN(), uint(8)
+	if rows == 0 && vm.Spec.superChip() {
+		rows, cols = 16, 16
+	}
+
+	// Clipped sprites still wrap their starting position
+	clip := vm.Quirks.Clip
+	if clip {
+		vx %= width
+		vy %= height
+	}
+
+	for n := uint(0); n < rows; n++ {
+		y := vy + n
+		if clip && y >= height {
+			break
+		}
+		y %= height
+		sprite := vm.Memory[(vm.I+n)%MemorySize]
+		if cols == 16 {
+			sprite = vm.Memory[(vm.I+2*n)%MemorySize]<<8 | vm.Memory[(vm.I+2*n+1)%MemorySize]
+		}
+
+		for i := int(cols) - 1; sprite > 0x00; i-- {
+			x := vx + uint(i)
+			visible := !clip || x < width
+			x %= width
+
+			if sprite&0x1 == 0x1 && visible && vm.Display.Flip(int(x), int(y)) {
+				vm.V[0xf] = 0x1
+			}
+
+			sprite >>= 1
+		}
+	}
+
+	atomic.StoreInt32(&vm.dirty, 1)
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0xE() error {
+	// Only the lowest nibble of VX names a key
+	vx := vm.V[vm.decodeX()] & 0xf
+	vm.touch()
+
+	switch vm.decodeKK() {
+	case 0x9e:
+		if vm.Keys[vx] {
+			vm.PC += 0x2
+		}
+	case 0xa1:
+		if !vm.Keys[vx] {
+			vm.PC += 0x2
+		}
+	default:
+		return InvalidOpcodeError(vm.Opcode)
+	}
+
+	return nil
+}
+
+func (vm *VirtualMachine) executeOp0xF() error {
+	x := vm.decodeX()
+
+	switch vm.decodeKK() {
+	case 0x07:
+		vm.V[x] = vm.DT
+		vm.touch()
+	case 0x0a:
+		vm.touch()
+		for i, k := range vm.Keys {
+			if k {
+				vm.V[x] = uint(i)
+				atomic.StoreInt32(&vm.waiting, 0)
+				return nil
+			}
+		}
+		vm.PC -= 0x2
+		if atomic.SwapInt32(&vm.waiting, 1) == 0 {
+			atomic.AddUint64(&vm.keyWaits, 1)
+		}
+	case 0x15:
+		vm.DT = vm.V[x]
+		vm.touch()
+	case 0x18:
+		vm.touch()
+		if vm.ST == 0x00 && vm.V[x] > 0x00 {
+			atomic.AddUint64(&vm.sounds, 1)
+		}
+		vm.ST = vm.V[x]
+	case 0x1E:
+		vm.I = (vm.I + vm.V[x]) & 0xfff
+	case 0x29:
+		vm.I = vm.V[x] * FontSize
+	case 0x30:
+		if len(vm.Spec.BigFont) == 0 {
+			return InvalidOpcodeError(vm.Opcode)
+		}
+		vm.I = BigFontAddress + (vm.V[x]&0xf)*BigFontSize
+	case 0x33:
+		vm.Memory[vm.I] = vm.V[x] / 100
+		vm.Memory[(vm.I+1)%MemorySize] = (vm.V[x] % 100) / 10
+		vm.Memory[(vm.I+2)%MemorySize] = vm.V[x] % 10
+	case 0x55:
+		for i := uint(0); i <= x; i++ {
+			vm.Memory[(vm.I+i)%MemorySize] = vm.V[i]
+		}
+		vm.incrementI(x)
+	case 0x65:
+		for i := uint(0); i <= x; i++ {
+			vm.V[i] = vm.Memory[(vm.I+i)%MemorySize]
+		}
+		vm.incrementI(x)
+	case 0x75:
+		if !vm.Spec.superChip() || x >= NumberOfFlags {
+			return InvalidOpcodeError(vm.Opcode)
+		}
+		copy(vm.Flags[:x+1], vm.V[:x+1])
+	case 0x85:
+		if !vm.Spec.superChip() || x >= NumberOfFlags {
+			return InvalidOpcodeError(vm.Opcode)
+		}
+		copy(vm.V[:x+1], vm.Flags[:x+1])
+	default:
+		return InvalidOpcodeError(vm.Opcode)
+	}
+
+	return nil
+}
+
+// scroll scrolls the display by (dx, dy) pixels (00CN, 00FB, 00FC),
+// which only SUPER-CHIP machines can do.
+func (vm *VirtualMachine) scroll(dx, dy int) error {
+	if !vm.Spec.superChip() {
+		return InvalidOpcodeError(vm.Opcode)
+	}
+
+	vm.Display.Scroll(dx, dy)
+	vm.touch()
+	atomic.StoreInt32(&vm.dirty, 1)
+	return nil
+}
+
+// jumpable reports whether a jump or call may go to an address: within
+// the program, and before the last byte of memory, which can't start an
+// instruction.
+func (vm *VirtualMachine) jumpable(addr uint) bool {
+	return addr >= vm.Spec.LoadAddress && addr < vm.Spec.MemorySize-1
+}
+
+// touch counts an instruction which interacts with the outside of the
+// program, by drawing, reading the keys, or using the timers. A program
+// which runs for long without any is likely stuck in a loop.
+func (vm *VirtualMachine) touch() {
+	atomic.AddUint64(&vm.activity, 1)
+}
+
+// Activity returns the number of instructions run which drew on the
+// screen, read the keys, or used the timers.
+func (vm *VirtualMachine) Activity() uint64 {
+	return atomic.LoadUint64(&vm.activity)
+}
+
+// setFlag sets VF to 1 or 0 after an arithmetic instruction. The flag
+// is set after the result, so it wins when VF is also the result.
+func (vm *VirtualMachine) setFlag(flag bool) {
+	if flag {
+		vm.V[0xf] = 0x1
+	} else {
+		vm.V[0xf] = 0x0
+	}
+}
+
+// resetVF resets VF after a logical instruction, if the quirk is on.
+func (vm *VirtualMachine) resetVF() {
+	if vm.Quirks.ResetVF {
+		vm.V[0xf] = 0x0
+	}
+}
+
+// incrementI moves I past the last register stored or loaded by FX55
+// or FX65, if the quirk is on.
+func (vm *VirtualMachine) incrementI(x uint) {
+	if vm.Quirks.IncrementI {
+		vm.I = (vm.I + x + 1) & 0xfff
+	}
+}
+
+//=====================================================================
+// Misc.
+//=====================================================================
+
+// PrintState prints the state of the virtual machine.
+//
+// This function exists primarily for debugging purposes.
+func (vm *VirtualMachine) PrintState() {
+	fmt.Println("----------------")
+
+	// Display special registers
+	fmt.Printf("I:         0x%.3X\n", vm.I)
+	fmt.Printf("SP:        0x%.1X\n", vm.SP)
+	fmt.Printf("PC:        0x%.3X\n", vm.PC)
+	fmt.Printf("Delay:     0x%.2X\n", vm.DT)
+	fmt.Printf("Sound:     0x%.2X\n", vm.ST)
+
+	// Display call stack
+	if vm.SP > 0x0 {
+		fmt.Printf("\nStack:\n")
+
+		for i := int(vm.SP) - 1; i >= 0; i-- {
+			fmt.Printf("  0x%.1X: |0x%.3X|\n", i, vm.Stack[i])
+		}
+	}
+
+	// Display general-purpose registers
+	fmt.Printf("\nRegisters:")
+	for i := 0; i < 4; i++ {
+		fmt.Printf("\n  ")
+
+		for j := 0; j < 4; j++ {
+			fmt.Printf("|0x%.1X: 0x%.2X|", j*4+i, vm.V[j*4+i])
+		}
+	}
+
+	fmt.Println("\n----------------")
+}
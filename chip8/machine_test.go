@@ -0,0 +1,158 @@
+package chip8
+
+import "testing"
+
+func TestOpcodes(t *testing.T) {
+	tests := []struct {
+		name    string
+		opcode  uint
+		setup   func(vm *VirtualMachine)
+		check   func(vm *VirtualMachine) bool
+		wantErr bool
+	}{
+		{
+			name:   "8XY5 of equal registers doesn't borrow",
+			opcode: 0x8125,
+			setup:  func(vm *VirtualMachine) { vm.V[0x1], vm.V[0x2] = 5, 5 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0x1] == 0 && vm.V[0xf] == 1 },
+		},
+		{
+			name:   "8XY5 borrows",
+			opcode: 0x8125,
+			setup:  func(vm *VirtualMachine) { vm.V[0x1], vm.V[0x2] = 1, 2 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0x1] == 0xff && vm.V[0xf] == 0 },
+		},
+		{
+			name:   "8XY7 of equal registers doesn't borrow",
+			opcode: 0x8127,
+			setup:  func(vm *VirtualMachine) { vm.V[0x1], vm.V[0x2] = 5, 5 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0x1] == 0 && vm.V[0xf] == 1 },
+		},
+		{
+			name:   "8XY7 borrows",
+			opcode: 0x8127,
+			setup:  func(vm *VirtualMachine) { vm.V[0x1], vm.V[0x2] = 2, 1 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0x1] == 0xff && vm.V[0xf] == 0 },
+		},
+		{
+			name:   "8XY4 into VF keeps the carry",
+			opcode: 0x8f14,
+			setup:  func(vm *VirtualMachine) { vm.V[0xf], vm.V[0x1] = 0xff, 0x01 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0xf] == 1 },
+		},
+		{
+			name:   "8XY5 into VF keeps the flag",
+			opcode: 0x8f15,
+			setup:  func(vm *VirtualMachine) { vm.V[0xf], vm.V[0x1] = 5, 3 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0xf] == 1 },
+		},
+		{
+			name:   "8XY6 into VF keeps the shifted bit",
+			opcode: 0x8ff6,
+			setup:  func(vm *VirtualMachine) { vm.V[0xf] = 0x02 },
+			check:  func(vm *VirtualMachine) bool { return vm.V[0xf] == 0 },
+		},
+		{
+			name:   "DXYN reads VF as a coordinate before resetting it",
+			opcode: 0xdf01,
+			setup: func(vm *VirtualMachine) {
+				vm.V[0xf], vm.V[0x0] = 8, 0
+				vm.I = 0x300
+				vm.Memory[0x300] = 0x80
+			},
+			check: func(vm *VirtualMachine) bool {
+				return vm.Display.At(8, 0) && !vm.Display.At(0, 0) && vm.V[0xf] == 0
+			},
+		},
+		{
+			name:    "00EE on an empty stack underflows",
+			opcode:  0x00ee,
+			check:   func(vm *VirtualMachine) bool { return vm.SP == 0 },
+			wantErr: true,
+		},
+		{
+			name:   "EX9E only reads the lowest nibble of VX",
+			opcode: 0xe19e,
+			setup:  func(vm *VirtualMachine) { vm.V[0x1], vm.Keys[0x3] = 0x13, true },
+			check:  func(vm *VirtualMachine) bool { return vm.PC == ProgramStartAddress+4 },
+		},
+		{
+			name:   "EXA1 only reads the lowest nibble of VX",
+			opcode: 0xe1a1,
+			setup:  func(vm *VirtualMachine) { vm.V[0x1], vm.Keys[0x3] = 0x13, true },
+			check:  func(vm *VirtualMachine) bool { return vm.PC == ProgramStartAddress+2 },
+		},
+		{
+			name:    "1NNN can't jump to the last byte of memory",
+			opcode:  0x1fff,
+			check:   func(vm *VirtualMachine) bool { return vm.PC == ProgramStartAddress+2 },
+			wantErr: true,
+		},
+		{
+			name:    "2NNN can't call the last byte of memory",
+			opcode:  0x2fff,
+			check:   func(vm *VirtualMachine) bool { return vm.SP == 0 },
+			wantErr: true,
+		},
+		{
+			name:   "the program counter wraps before the last byte of memory",
+			opcode: 0x6001,
+			setup: func(vm *VirtualMachine) {
+				vm.PC = 0xffd
+				vm.Memory[0xffd], vm.Memory[0xffe] = 0x61, 0x2a
+			},
+			check: func(vm *VirtualMachine) bool { return vm.V[0x1] == 0x2a && vm.PC == 0x1ff },
+		},
+		{
+			name:    "unknown FXNN opcodes are invalid",
+			opcode:  0xf1ff,
+			check:   func(vm *VirtualMachine) bool { return true },
+			wantErr: true,
+		},
+		{
+			name:   "FX33 wraps around memory",
+			opcode: 0xf133,
+			setup:  func(vm *VirtualMachine) { vm.I, vm.V[0x1] = 0xffe, 123 },
+			check: func(vm *VirtualMachine) bool {
+				return vm.Memory[0xffe] == 1 && vm.Memory[0xfff] == 2 && vm.Memory[0x000] == 3
+			},
+		},
+		{
+			name:   "FX55 wraps around memory",
+			opcode: 0xf155,
+			setup:  func(vm *VirtualMachine) { vm.I, vm.V[0x0], vm.V[0x1] = 0xfff, 0xaa, 0xbb },
+			check: func(vm *VirtualMachine) bool {
+				return vm.Memory[0xfff] == 0xaa && vm.Memory[0x000] == 0xbb
+			},
+		},
+		{
+			name:   "FX65 wraps around memory",
+			opcode: 0xf165,
+			setup: func(vm *VirtualMachine) {
+				vm.I = 0xfff
+				vm.Memory[0xfff], vm.Memory[0x000] = 0x11, 0x22
+			},
+			check: func(vm *VirtualMachine) bool { return vm.V[0x0] == 0x11 && vm.V[0x1] == 0x22 },
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			vm := NewVirtualMachine()
+			if err := vm.LoadOpcodes([]uint{tt.opcode}); err != nil {
+				t.Fatal(err)
+			}
+			if tt.setup != nil {
+				tt.setup(vm)
+			}
+
+			err := vm.RunCycle()
+			if (err != nil) != tt.wantErr {
+				t.Fatalf("RunCycle() error = %v, want error %v", err, tt.wantErr)
+			}
+			if !tt.check(vm) {
+				t.Errorf("unexpected state after %.4X: V = %X, I = %.3X, PC = %.3X", tt.opcode, vm.V, vm.I, vm.PC)
+			}
+		})
+	}
+}
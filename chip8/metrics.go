@@ -0,0 +1,63 @@
+package chip8
+
+import (
+	"fmt"
+	"sync/atomic"
+)
+
+//=====================================================================
+// Metrics
+//=====================================================================
+
+// Metrics are counters of the work done by a virtual machine since it
+// was created, which stay the same across frontends, so embedders and
+// benchmarks get consistent numbers.
+type Metrics struct {
+	// Instructions is the number of instructions executed.
+	Instructions uint64 `json:"instructions"`
+
+	// Frames is the number of frames emulated, each of which updates
+	// the timers once.
+	Frames uint64 `json:"frames"`
+
+	// Draws is the number of sprites drawn (DXYN).
+	Draws uint64 `json:"draws"`
+
+	// KeyWaits is the number of times the program waited for a key to
+	// be pressed (FX0A).
+	KeyWaits uint64 `json:"keyWaits"`
+
+	// Sounds is the number of times the beeper was started (FX18).
+	Sounds uint64 `json:"sounds"`
+}
+
+// Metrics returns the counters of the virtual machine.
+func (vm *VirtualMachine) Metrics() Metrics {
+	return Metrics{
+		Instructions: atomic.LoadUint64(&vm.cycles),
+		Frames:       atomic.LoadUint64(&vm.frames),
+		Draws:        atomic.LoadUint64(&vm.draws),
+		KeyWaits:     atomic.LoadUint64(&vm.keyWaits),
+		Sounds:       atomic.LoadUint64(&vm.sounds),
+	}
+}
+
+// sub returns the counters of the work done since an earlier reading
+// of the counters.
+func (m Metrics) sub(earlier Metrics) Metrics {
+	return Metrics{
+		Instructions: m.Instructions - earlier.Instructions,
+		Frames:       m.Frames - earlier.Frames,
+		Draws:        m.Draws - earlier.Draws,
+		KeyWaits:     m.KeyWaits - earlier.KeyWaits,
+		Sounds:       m.Sounds - earlier.Sounds,
+	}
+}
+
+// String returns the counters one per line, such as "frames: 600".
+func (m Metrics) String() string {
+	return fmt.Sprintf(
+		"instructions: %d\nframes: %d\ndraws: %d\nkey waits: %d\nsounds: %d",
+		m.Instructions, m.Frames, m.Draws, m.KeyWaits, m.Sounds,
+	)
+}
@@ -0,0 +1,99 @@
+package chip8
+
+import (
+	"math"
+	"time"
+)
+
+//=====================================================================
+// Audio
+//=====================================================================
+
+// envelopeRamp is how long the beeper takes to fade in or out, which
+// keeps the speaker from popping when the tone starts or stops.
+const envelopeRamp = 5 * time.Millisecond
+
+const (
+	envelopeSilent = iota
+	envelopeAttack
+	envelopeSustain
+	envelopeRelease
+)
+
+// envelope is the attack/release envelope of the beeper.
+type envelope struct {
+	state int
+	level float64
+	step  float64
+}
+
+func newEnvelope(sampleRate int) envelope {
+	samples := float64(sampleRate) * envelopeRamp.Seconds()
+	if samples < 1 {
+		samples = 1
+	}
+	return envelope{step: 1 / samples}
+}
+
+// next advances the envelope by a single sample and returns its
+// amplitude within [0.0, 1.0].
+func (e *envelope) next(on bool) float64 {
+	switch e.state {
+	case envelopeSilent:
+		if on {
+			e.state = envelopeAttack
+		}
+	case envelopeAttack:
+		if !on {
+			e.state = envelopeRelease
+			break
+		}
+		e.level += e.step
+		if e.level >= 1.0 {
+			e.level = 1.0
+			e.state = envelopeSustain
+		}
+	case envelopeSustain:
+		if !on {
+			e.state = envelopeRelease
+		}
+	case envelopeRelease:
+		if on {
+			e.state = envelopeAttack
+			break
+		}
+		e.level -= e.step
+		if e.level <= 0.0 {
+			e.level = 0.0
+			e.state = envelopeSilent
+		}
+	}
+	return e.level
+}
+
+// Oscillator synthesizes the sine wave of the beeper, one sample at a
+// time.
+type Oscillator struct {
+	period   int64
+	position int64
+	envelope envelope
+}
+
+// NewOscillator creates an oscillator of a frequency (in hertz) at a
+// sample rate.
+func NewOscillator(frequency, sampleRate int) Oscillator {
+	return Oscillator{
+		period:   int64(sampleRate / frequency),
+		envelope: newEnvelope(sampleRate),
+	}
+}
+
+// Next returns the next sample of the wave, which is only audible
+// while the tone is on.
+func (o *Oscillator) Next(on bool) int16 {
+	const max = 32767
+	level := o.envelope.next(on) * max
+	b := int16(math.Sin(2*math.Pi*float64(o.position)/float64(o.period)) * level)
+	o.position = (o.position + 1) % o.period
+	return b
+}
@@ -0,0 +1,147 @@
+package chip8
+
+import (
+	"fmt"
+	"image/color"
+	"strconv"
+	"strings"
+)
+
+//=====================================================================
+// Palettes
+//=====================================================================
+
+const (
+	// DefaultPalette is the name of the default palette of the CHIP-8
+	// screen.
+	DefaultPalette = "classic"
+
+	// CustomPalette is the name of the palette made of the colors in
+	// the configuration file.
+	CustomPalette = "custom"
+
+	// NumberOfColors is the number of colors in a palette, one for
+	// each combination of the bit planes a pixel is drawn on.
+	NumberOfColors = 4
+)
+
+// Palette is a color scheme of the CHIP-8 screen.
+//
+// The colors are indexed by the bit planes a pixel is drawn on: the
+// background, the first plane, the second plane, and both planes. Only
+// the first plane is used by CHIP-8 programs, while the others are
+// reserved for multi-color extensions.
+type Palette struct {
+	Name   string
+	Colors [NumberOfColors]color.RGBA
+}
+
+// Background returns the color of pixels which are off.
+func (p Palette) Background() color.RGBA {
+	return p.Colors[0]
+}
+
+// Foreground returns the color of pixels which are on.
+func (p Palette) Foreground() color.RGBA {
+	return p.Colors[1]
+}
+
+// Palettes are the built-in palettes of the emulator, in the order
+// they are cycled through.
+var Palettes = []Palette{
+	{"classic", [NumberOfColors]color.RGBA{
+		{0x00, 0x00, 0x00, 0xff}, {0xff, 0xff, 0xff, 0xff},
+		{0xaa, 0xaa, 0xaa, 0xff}, {0x55, 0x55, 0x55, 0xff},
+	}},
+	{"green", [NumberOfColors]color.RGBA{
+		{0x0a, 0x1a, 0x0f, 0xff}, {0x33, 0xff, 0x66, 0xff},
+		{0x1f, 0x99, 0x40, 0xff}, {0x99, 0xff, 0xb3, 0xff},
+	}},
+	{"amber", [NumberOfColors]color.RGBA{
+		{0x1a, 0x10, 0x00, 0xff}, {0xff, 0xb0, 0x00, 0xff},
+		{0x99, 0x6a, 0x00, 0xff}, {0xff, 0xd0, 0x66, 0xff},
+	}},
+	{"lcd", [NumberOfColors]color.RGBA{
+		{0x9b, 0xbc, 0x0f, 0xff}, {0x0f, 0x38, 0x0f, 0xff},
+		{0x8b, 0xac, 0x0f, 0xff}, {0x30, 0x62, 0x30, 0xff},
+	}},
+	{"high-contrast", [NumberOfColors]color.RGBA{
+		{0x00, 0x00, 0x00, 0xff}, {0xff, 0xff, 0x00, 0xff},
+		{0x00, 0xff, 0xff, 0xff}, {0xff, 0xff, 0xff, 0xff},
+	}},
+}
+
+// FindPalette returns the index of the palette with the given name,
+// or -1 if there is no such palette.
+func FindPalette(palettes []Palette, name string) int {
+	for i, p := range palettes {
+		if p.Name == name {
+			return i
+		}
+	}
+	return -1
+}
+
+// PaletteNames returns the names of the built-in palettes.
+func PaletteNames() []string {
+	names := make([]string, len(Palettes))
+	for i, p := range Palettes {
+		names[i] = p.Name
+	}
+	return names
+}
+
+// NewCustomPalette creates a palette from colors written in
+// hexadecimal (e.g. "#33ff66"), in the order of the bit planes. Any
+// missing colors are taken from the default palette.
+func NewCustomPalette(colors []string) (Palette, error) {
+	if len(colors) > NumberOfColors {
+		return Palette{}, fmt.Errorf(
+			"a palette has at most %d colors", NumberOfColors,
+		)
+	}
+
+	palette := Palettes[FindPalette(Palettes, DefaultPalette)]
+	palette.Name = CustomPalette
+
+	for i, s := range colors {
+		c, err := ParseColor(s)
+		if err != nil {
+			return Palette{}, err
+		}
+		palette.Colors[i] = c
+	}
+
+	return palette, nil
+}
+
+// AvailablePalettes returns the built-in palettes, along with the
+// custom palette made of the given colors, if there are any.
+func AvailablePalettes(colors []string) ([]Palette, error) {
+	palettes := append([]Palette(nil), Palettes...)
+
+	if len(colors) > 0 {
+		custom, err := NewCustomPalette(colors)
+		if err != nil {
+			return nil, err
+		}
+		palettes = append(palettes, custom)
+	}
+
+	return palettes, nil
+}
+
+// ParseColor parses a color written in hexadecimal, such as "#33ff66".
+func ParseColor(s string) (color.RGBA, error) {
+	hex := strings.TrimPrefix(s, "#")
+	if len(hex) != 6 {
+		return color.RGBA{}, fmt.Errorf("invalid color: %s", s)
+	}
+
+	v, err := strconv.ParseUint(hex, 16, 32)
+	if err != nil {
+		return color.RGBA{}, fmt.Errorf("invalid color: %s", s)
+	}
+
+	return color.RGBA{uint8(v >> 16), uint8(v >> 8), uint8(v), 0xff}, nil
+}
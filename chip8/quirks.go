@@ -0,0 +1,67 @@
+package chip8
+
+//=====================================================================
+// Quirks
+//=====================================================================
+
+// DefaultQuirks is the name of the default quirk profile of the
+// virtual machine.
+const DefaultQuirks = "modern"
+
+// Quirks are behaviors of instructions which differ between CHIP-8
+// interpreters, and which some programs depend on. The zero value
+// behaves like most modern interpreters.
+type Quirks struct {
+	// ShiftVY shifts VY into VX (8XY6, 8XYE) rather than shifting VX
+	// in place.
+	ShiftVY bool
+
+	// IncrementI leaves I pointing past the last register stored or
+	// loaded (FX55, FX65) rather than leaving it unchanged.
+	IncrementI bool
+
+	// JumpVX jumps to XNN plus VX (BXNN) rather than NNN plus V0.
+	JumpVX bool
+
+	// ResetVF resets VF to 0 after the logical instructions (8XY1,
+	// 8XY2, 8XY3).
+	ResetVF bool
+
+	// Clip clips sprites at the edges of the screen rather than
+	// wrapping them around to the other side.
+	Clip bool
+}
+
+// QuirkProfile is a named set of quirks, matching the behavior of an
+// interpreter.
+type QuirkProfile struct {
+	Name   string
+	Quirks Quirks
+}
+
+// QuirkProfiles are the built-in quirk profiles of the emulator.
+var QuirkProfiles = []QuirkProfile{
+	{"modern", Quirks{}},
+	{"cosmac", Quirks{ShiftVY: true, IncrementI: true, ResetVF: true, Clip: true}},
+	{"schip", Quirks{JumpVX: true, Clip: true}},
+}
+
+// FindQuirks returns the index of the quirk profile with the given
+// name, or -1 if there is no such profile.
+func FindQuirks(name string) int {
+	for i, p := range QuirkProfiles {
+		if p.Name == name {
+			return i
+		}
+	}
+	return -1
+}
+
+// QuirkNames returns the names of the built-in quirk profiles.
+func QuirkNames() []string {
+	names := make([]string, len(QuirkProfiles))
+	for i, p := range QuirkProfiles {
+		names[i] = p.Name
+	}
+	return names
+}
@@ -0,0 +1,78 @@
+package chip8
+
+import (
+	"errors"
+	"io"
+	"io/ioutil"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+//=====================================================================
+// ROMs
+//=====================================================================
+
+// StdinROM is the path of a ROM read from the standard input, so ROMs
+// can be piped from build scripts.
+const StdinROM = "-"
+
+// ReadROM reads a CHIP-8 ROM from a file, from the standard input if
+// the path is StdinROM, from an http(s) URL, or from the ROMs bundled
+// with the emulator if the path starts with BuiltinPrefix.
+func ReadROM(path string) ([]byte, error) {
+	switch {
+	case path == StdinROM:
+		return readROM(os.Stdin)
+	case isURL(path):
+		return downloadROM(path)
+	case IsBuiltinROM(path):
+		return readBuiltinROM(path)
+	}
+	return ioutil.ReadFile(path)
+}
+
+// LocalROM reports whether the path of a ROM is a file, rather than
+// the standard input, a URL, or a built-in ROM.
+func LocalROM(path string) bool {
+	return path != StdinROM && !isURL(path) && !IsBuiltinROM(path)
+}
+
+func isURL(path string) bool {
+	return strings.HasPrefix(path, "http://") || strings.HasPrefix(path, "https://")
+}
+
+// readROM reads a ROM from a stream, stopping once it is too large to
+// fit in memory.
+func readROM(r io.Reader) ([]byte, error) {
+	data, err := ioutil.ReadAll(io.LimitReader(r, ProgramMemorySize+1))
+	if err != nil {
+		return nil, err
+	}
+	if len(data) > ProgramMemorySize {
+		return nil, InvalidProgramError("The ROM is too large")
+	}
+	return data, nil
+}
+
+// ListROMs returns the paths of the ROMs (*.ch8) of a directory, in
+// alphabetical order.
+func ListROMs(dir string) ([]string, error) {
+	files, err := ioutil.ReadDir(dir)
+	if err != nil {
+		return nil, err
+	}
+
+	var roms []string
+	for _, f := range files {
+		if !f.IsDir() && strings.EqualFold(filepath.Ext(f.Name()), ".ch8") {
+			roms = append(roms, filepath.Join(dir, f.Name()))
+		}
+	}
+	if len(roms) == 0 {
+		return nil, errors.New("no ROMs (*.ch8) in " + dir)
+	}
+	sort.Strings(roms)
+	return roms, nil
+}
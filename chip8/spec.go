@@ -0,0 +1,170 @@
+package chip8
+
+//=====================================================================
+// Machine Specs
+//=====================================================================
+
+// DefaultMachine is the name of the default machine spec of the
+// virtual machine.
+const DefaultMachine = "modern"
+
+// MachineSpec describes a variant of the CHIP-8 machine, from which a
+// virtual machine is built.
+type MachineSpec struct {
+	// Name is the name of the machine, such as "schip".
+	Name string
+
+	// MemorySize is the amount of memory of the machine, up to
+	// MemorySize.
+	MemorySize uint
+
+	// LoadAddress is the address where programs are loaded and start
+	// running.
+	LoadAddress uint
+
+	// DisplayWidth and DisplayHeight are the size (in pixels) of the
+	// display in low-resolution mode.
+	DisplayWidth  int
+	DisplayHeight int
+
+	// HiresWidth and HiresHeight are the size (in pixels) of the
+	// display in high-resolution mode (00FF), which is an invalid
+	// instruction if they are zero.
+	HiresWidth  int
+	HiresHeight int
+
+	// Font is the built-in font, FontSize bytes for each of the
+	// NumberOfFonts hexadecimal digits, loaded at the start of memory.
+	Font []uint
+
+	// BigFont is the large font of SUPER-CHIP (FX30), BigFontSize bytes
+	// for each digit, loaded at BigFontAddress.
+	BigFont []uint
+
+	// Quirks is the name of the quirk profile of the machine.
+	Quirks string
+
+	// Speed is the speed multiplier which programs for the machine
+	// expect.
+	Speed float64
+}
+
+// chip8Font is the built-in font of the COSMAC VIP interpreter, which
+// most interpreters since have kept.
+var chip8Font = []uint{
+	0xf0, 0x90, 0x90, 0x90, 0xf0, // 0
+	0x20, 0x60, 0x20, 0x20, 0x70, // 1
+	0xf0, 0x10, 0xf0, 0x80, 0xf0, // 2
+	0xF0, 0x10, 0xF0, 0x10, 0xf0, // 3
+	0x90, 0x90, 0xf0, 0x10, 0x10, // 4
+	0xf0, 0x80, 0xf0, 0x10, 0xf0, // 5
+	0xf0, 0x80, 0xf0, 0x90, 0xf0, // 6
+	0xf0, 0x10, 0x20, 0x40, 0x40, // 7
+	0xf0, 0x90, 0xf0, 0x90, 0xf0, // 8
+	0xf0, 0x90, 0xf0, 0x10, 0xf0, // 9
+	0xf0, 0x90, 0xf0, 0x90, 0x90, // A
+	0xe0, 0x90, 0xe0, 0x90, 0xe0, // B
+	0xf0, 0x80, 0x80, 0x80, 0xf0, // C
+	0xe0, 0x90, 0x90, 0x90, 0xe0, // D
+	0xf0, 0x80, 0xf0, 0x80, 0xf0, // E
+	0xf0, 0x80, 0xf0, 0x80, 0x80, // F
+}
+
+// schipBigFont is the 8x10 font of SUPER-CHIP, which only has the
+// decimal digits, along with the letters A to F of Octo.
+var schipBigFont = []uint{
+	0x3c, 0x7e, 0xe7, 0xc3, 0xc3, 0xc3, 0xc3, 0xe7, 0x7e, 0x3c, // 0
+	0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, // 1
+	0x3e, 0x7f, 0xc3, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xff, 0xff, // 2
+	0x3c, 0x7e, 0xc3, 0x03, 0x0e, 0x0e, 0x03, 0xc3, 0x7e, 0x3c, // 3
+	0x06, 0x0e, 0x1e, 0x36, 0x66, 0xc6, 0xff, 0xff, 0x06, 0x06, // 4
+	0xff, 0xff, 0xc0, 0xc0, 0xfc, 0xfe, 0x03, 0xc3, 0x7e, 0x3c, // 5
+	0x3e, 0x7c, 0xc0, 0xc0, 0xfc, 0xfe, 0xc3, 0xc3, 0x7e, 0x3c, // 6
+	0xff, 0xff, 0x03, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+	0x3c, 0x7e, 0xc3, 0xc3, 0x7e, 0x7e, 0xc3, 0xc3, 0x7e, 0x3c, // 8
+	0x3c, 0x7e, 0xc3, 0xc3, 0x7f, 0x3f, 0x03, 0x03, 0x3e, 0x7c, // 9
+	0x7e, 0xff, 0xc3, 0xc3, 0xc3, 0xff, 0xff, 0xc3, 0xc3, 0xc3, // A
+	0xfc, 0xfc, 0xc3, 0xc3, 0xfc, 0xfc, 0xc3, 0xc3, 0xfc, 0xfc, // B
+	0x3c, 0xff, 0xc3, 0xc0, 0xc0, 0xc0, 0xc0, 0xc3, 0xff, 0x3c, // C
+	0xfc, 0xfe, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xfe, 0xfc, // D
+	0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, // E
+	0xff, 0xff, 0xc0, 0xc0, 0xff, 0xff, 0xc0, 0xc0, 0xc0, 0xc0, // F
+}
+
+// MachineSpecs are the built-in machine specs of the emulator.
+var MachineSpecs = []MachineSpec{
+	{
+		Name:          "modern",
+		MemorySize:    MemorySize,
+		LoadAddress:   ProgramStartAddress,
+		DisplayWidth:  DisplayWidth,
+		DisplayHeight: DisplayHeight,
+		HiresWidth:    HiresWidth,
+		HiresHeight:   HiresHeight,
+		Font:          chip8Font,
+		BigFont:       schipBigFont,
+		Quirks:        "modern",
+		Speed:         DefaultSpeed,
+	},
+	{
+		Name:          "cosmac",
+		MemorySize:    MemorySize,
+		LoadAddress:   ProgramStartAddress,
+		DisplayWidth:  DisplayWidth,
+		DisplayHeight: DisplayHeight,
+		Font:          chip8Font,
+		Quirks:        "cosmac",
+		Speed:         DefaultSpeed,
+	},
+	{
+		Name:          "schip",
+		MemorySize:    MemorySize,
+		LoadAddress:   ProgramStartAddress,
+		DisplayWidth:  DisplayWidth,
+		DisplayHeight: DisplayHeight,
+		HiresWidth:    HiresWidth,
+		HiresHeight:   HiresHeight,
+		Font:          chip8Font,
+		BigFont:       schipBigFont,
+		Quirks:        "schip",
+		Speed:         DefaultSpeed,
+	},
+	{
+		Name:          "eti660",
+		MemorySize:    MemorySize,
+		LoadAddress:   0x600,
+		DisplayWidth:  DisplayWidth,
+		DisplayHeight: 0x30,
+		Font:          chip8Font,
+		Quirks:        "cosmac",
+		Speed:         DefaultSpeed,
+	},
+}
+
+// superChip reports whether the machine has the high-resolution mode of
+// SUPER-CHIP, which comes with its instructions for scrolling the
+// display (00CN, 00FB, 00FC), drawing 16x16 sprites (DXY0), and saving
+// the flag registers (FX75, FX85).
+func (spec MachineSpec) superChip() bool {
+	return spec.HiresWidth != 0 && spec.HiresHeight != 0
+}
+
+// FindMachine returns the index of the machine spec with the given
+// name, or -1 if there is no such spec.
+func FindMachine(name string) int {
+	for i, spec := range MachineSpecs {
+		if spec.Name == name {
+			return i
+		}
+	}
+	return -1
+}
+
+// MachineNames returns the names of the built-in machine specs.
+func MachineNames() []string {
+	names := make([]string, len(MachineSpecs))
+	for i, spec := range MachineSpecs {
+		names[i] = spec.Name
+	}
+	return names
+}
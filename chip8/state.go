@@ -0,0 +1,192 @@
+package chip8
+
+import (
+	"bufio"
+	"compress/gzip"
+	"encoding/gob"
+	"errors"
+	"io"
+	"os"
+	"path/filepath"
+	"sync/atomic"
+)
+
+//=====================================================================
+// Save States
+//=====================================================================
+
+// DefaultStateDir is the default directory where save states are
+// saved.
+const DefaultStateDir = "states"
+
+// Snapshot is the state of the virtual machine at a point in time,
+// which can be restored later. The state of the keys is not included.
+type Snapshot struct {
+	I       uint
+	SP      uint
+	PC      uint
+	DT      uint
+	ST      uint
+	V       [NumberOfRegisters]uint
+	Flags   [NumberOfFlags]uint
+	Stack   [MaxStackDepth]uint
+	Memory  [MemorySize]uint
+	Display Display
+	Cycles  uint64
+}
+
+// Snapshot captures the state of the virtual machine.
+func (vm *VirtualMachine) Snapshot() Snapshot {
+	display := vm.Display
+	display.Pixels = append([]bool(nil), vm.Display.Pixels...)
+
+	return Snapshot{
+		I:       vm.I,
+		SP:      vm.SP,
+		PC:      vm.PC,
+		DT:      vm.DT,
+		ST:      vm.ST,
+		V:       vm.V,
+		Flags:   vm.Flags,
+		Stack:   vm.Stack,
+		Memory:  vm.Memory,
+		Display: display,
+		Cycles:  vm.Cycles(),
+	}
+}
+
+// Restore returns the virtual machine to the state of a snapshot. The
+// snapshot may come from a file, so it is checked first, and the
+// virtual machine is left untouched if the snapshot is invalid.
+func (vm *VirtualMachine) Restore(s Snapshot) error {
+	if err := s.validate(vm.Spec); err != nil {
+		return err
+	}
+
+	vm.I = s.I
+	vm.SP = s.SP
+	vm.PC = s.PC
+	vm.DT = s.DT
+	vm.ST = s.ST
+	vm.V = s.V
+	vm.Flags = s.Flags
+	vm.Stack = s.Stack
+	vm.Memory = s.Memory
+
+	vm.Display.Resize(s.Display.Width, s.Display.Height)
+	copy(vm.Display.Pixels, s.Display.Pixels)
+
+	atomic.StoreUint64(&vm.cycles, s.Cycles)
+	atomic.StoreInt32(&vm.waiting, 0)
+	atomic.StoreInt32(&vm.dirty, 1)
+	return nil
+}
+
+// validate checks that a snapshot can be restored into a virtual
+// machine of the given spec without any instruction running past the
+// end of its memory, stack, or display.
+func (s *Snapshot) validate(spec MachineSpec) error {
+	// Instructions are two bytes, so the last byte can't start one
+	if s.PC >= spec.MemorySize-1 {
+		return errors.New("invalid snapshot: program counter out of range")
+	}
+	if s.I >= MemorySize {
+		return errors.New("invalid snapshot: index register out of range")
+	}
+	if s.SP > MaxStackDepth {
+		return errors.New("invalid snapshot: stack pointer out of range")
+	}
+
+	// A call from the last instruction returns to the end of memory,
+	// from where the program counter wraps around
+	for _, addr := range s.Stack {
+		if addr > spec.MemorySize {
+			return errors.New("invalid snapshot: return address out of range")
+		}
+	}
+	for _, v := range append(s.V[:], s.Flags[:]...) {
+		if v > 0xff {
+			return errors.New("invalid snapshot: register out of range")
+		}
+	}
+	for _, b := range s.Memory {
+		if b > 0xff {
+			return errors.New("invalid snapshot: memory out of range")
+		}
+	}
+
+	d := s.Display
+	lores := d.Width == spec.DisplayWidth && d.Height == spec.DisplayHeight
+	hires := d.Width == spec.HiresWidth && d.Height == spec.HiresHeight
+	if d.Width <= 0 || d.Height <= 0 || !(lores || hires) {
+		return errors.New("invalid snapshot: display size doesn't fit the machine")
+	}
+	if len(d.Pixels) != d.Width*d.Height {
+		return errors.New("invalid snapshot: pixels don't match the display size")
+	}
+
+	return nil
+}
+
+// SaveSnapshot writes a snapshot to a file.
+func SaveSnapshot(path string, s Snapshot) error {
+	if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+		return err
+	}
+
+	file, err := os.Create(path)
+	if err != nil {
+		return err
+	}
+
+	if err := WriteSnapshot(file, s); err != nil {
+		file.Close()
+		return err
+	}
+
+	return file.Close()
+}
+
+// WriteSnapshot encodes a snapshot to a writer, compressed with gzip.
+// Most of the memory of a snapshot is usually empty, so a compressed
+// snapshot is a few kilobytes rather than tens.
+func WriteSnapshot(w io.Writer, s Snapshot) error {
+	zw := gzip.NewWriter(w)
+	if err := gob.NewEncoder(zw).Encode(s); err != nil {
+		return err
+	}
+	return zw.Close()
+}
+
+// ReadSnapshot decodes a snapshot from a reader, which may also be an
+// uncompressed snapshot saved by older versions of the emulator.
+func ReadSnapshot(r io.Reader) (Snapshot, error) {
+	br := bufio.NewReader(r)
+
+	// Uncompressed snapshots don't start with the magic number of gzip
+	var s Snapshot
+	if magic, err := br.Peek(2); err != nil || magic[0] != 0x1f || magic[1] != 0x8b {
+		err := gob.NewDecoder(br).Decode(&s)
+		return s, err
+	}
+
+	zr, err := gzip.NewReader(br)
+	if err != nil {
+		return Snapshot{}, err
+	}
+	defer zr.Close()
+
+	err = gob.NewDecoder(zr).Decode(&s)
+	return s, err
+}
+
+// LoadSnapshot reads a snapshot from a file.
+func LoadSnapshot(path string) (Snapshot, error) {
+	file, err := os.Open(path)
+	if err != nil {
+		return Snapshot{}, err
+	}
+	defer file.Close()
+
+	return ReadSnapshot(file)
+}
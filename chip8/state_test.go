@@ -0,0 +1,89 @@
+package chip8
+
+import (
+	"bytes"
+	"encoding/gob"
+	"reflect"
+	"testing"
+)
+
+// testSnapshot returns the snapshot of a virtual machine which has run
+// a few instructions.
+func testSnapshot(t *testing.T) Snapshot {
+	vm := NewVirtualMachine()
+	opcodes := []uint{0x6105, 0xa300, 0xf133, 0xd015, 0x2208}
+	if err := vm.LoadOpcodes(opcodes); err != nil {
+		t.Fatal(err)
+	}
+	for range opcodes {
+		if err := vm.RunCycle(); err != nil {
+			t.Fatal(err)
+		}
+	}
+	return vm.Snapshot()
+}
+
+func TestSnapshotRoundTrip(t *testing.T) {
+	s := testSnapshot(t)
+
+	var buf bytes.Buffer
+	if err := WriteSnapshot(&buf, s); err != nil {
+		t.Fatal(err)
+	}
+	got, err := ReadSnapshot(&buf)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !reflect.DeepEqual(got, s) {
+		t.Errorf("ReadSnapshot() = %+v, want %+v", got, s)
+	}
+}
+
+func TestReadUncompressedSnapshot(t *testing.T) {
+	s := testSnapshot(t)
+
+	var buf bytes.Buffer
+	if err := gob.NewEncoder(&buf).Encode(s); err != nil {
+		t.Fatal(err)
+	}
+	got, err := ReadSnapshot(&buf)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !reflect.DeepEqual(got, s) {
+		t.Errorf("ReadSnapshot() = %+v, want %+v", got, s)
+	}
+}
+
+func TestRestoreInvalidSnapshot(t *testing.T) {
+	tests := []struct {
+		name   string
+		modify func(s *Snapshot)
+	}{
+		{"program counter", func(s *Snapshot) { s.PC = MemorySize - 1 }},
+		{"index register", func(s *Snapshot) { s.I = MemorySize }},
+		{"stack pointer", func(s *Snapshot) { s.SP = MaxStackDepth + 1 }},
+		{"return address", func(s *Snapshot) { s.Stack[0] = MemorySize + 1 }},
+		{"register", func(s *Snapshot) { s.V[0x3] = 0x100 }},
+		{"flag register", func(s *Snapshot) { s.Flags[0x0] = 0x100 }},
+		{"memory", func(s *Snapshot) { s.Memory[0x200] = 0x100 }},
+		{"display size", func(s *Snapshot) { s.Display.Width = 100 }},
+		{"pixels", func(s *Snapshot) { s.Display.Pixels = s.Display.Pixels[1:] }},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			s := testSnapshot(t)
+			tt.modify(&s)
+
+			vm := NewVirtualMachine()
+			before := vm.Snapshot()
+			if err := vm.Restore(s); err == nil {
+				t.Fatal("Restore() accepted an invalid snapshot")
+			}
+			if !reflect.DeepEqual(vm.Snapshot(), before) {
+				t.Error("Restore() changed the virtual machine after rejecting a snapshot")
+			}
+		})
+	}
+}
@@ -0,0 +1,231 @@
+// Command ch8-ffi exposes the CHIP-8 virtual machine through a C API,
+// so it can be embedded in C/C++ programs and called from other
+// languages.
+//
+// The library is built as a shared library along with its header:
+//
+//	go build -buildmode=c-shared -o libch8.so ./cmd/ch8-ffi
+//
+// Go values can't be held by C, so each virtual machine is referred to
+// by a handle. Functions which can fail return 0 on success and -1 on
+// failure, after logging the error.
+package main
+
+/*
+#include <stddef.h>
+#include <stdint.h>
+*/
+import "C"
+
+import (
+	"log"
+	"sync"
+	"unsafe"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+var (
+	mutex   sync.Mutex
+	cores   = map[C.int]*chip8.Core{}
+	handles C.int
+)
+
+func main() {}
+
+// find returns the core of a handle, or nil if there is no such core.
+func find(handle C.int) *chip8.Core {
+	mutex.Lock()
+	defer mutex.Unlock()
+
+	return cores[handle]
+}
+
+// ch8_create creates a virtual machine running at the given speed
+// multiplier (1.0 is normal speed), and returns its handle.
+//
+//export ch8_create
+func ch8_create(speed C.double) C.int {
+	mutex.Lock()
+	defer mutex.Unlock()
+
+	handles++
+	cores[handles] = chip8.NewCore(chip8.MachineSpecs[chip8.FindMachine(chip8.DefaultMachine)], chip8.DefaultSampleRate)
+	cores[handles].Speed = float64(speed)
+	return handles
+}
+
+// ch8_destroy releases a virtual machine.
+//
+//export ch8_destroy
+func ch8_destroy(handle C.int) {
+	mutex.Lock()
+	defer mutex.Unlock()
+
+	delete(cores, handle)
+}
+
+// ch8_load_rom loads the bytes of a ROM into a virtual machine.
+//
+//export ch8_load_rom
+func ch8_load_rom(handle C.int, data *C.uint8_t, size C.size_t) C.int {
+	core := find(handle)
+	if core == nil || data == nil {
+		return -1
+	}
+
+	if err := core.VM.LoadBytes(C.GoBytes(unsafe.Pointer(data), C.int(size))); err != nil {
+		log.Println(err)
+		return -1
+	}
+	return 0
+}
+
+// ch8_reset resets a virtual machine, keeping the loaded ROM.
+//
+//export ch8_reset
+func ch8_reset(handle C.int) {
+	if core := find(handle); core != nil {
+		core.VM.Reset()
+	}
+}
+
+// ch8_step_frame runs a single frame (1/60 of a second) of a virtual
+// machine.
+//
+//export ch8_step_frame
+func ch8_step_frame(handle C.int) C.int {
+	core := find(handle)
+	if core == nil {
+		return -1
+	}
+
+	if err := core.RunFrame(); err != nil {
+		log.Println(err)
+		return -1
+	}
+	return 0
+}
+
+// ch8_display_width returns the width (in pixels) of the display of a
+// virtual machine, which changes with its resolution.
+//
+//export ch8_display_width
+func ch8_display_width(handle C.int) C.int {
+	core := find(handle)
+	if core == nil {
+		return 0
+	}
+	return C.int(core.VM.Display.Width)
+}
+
+// ch8_display_height returns the height (in pixels) of the display of
+// a virtual machine, which changes with its resolution.
+//
+//export ch8_display_height
+func ch8_display_height(handle C.int) C.int {
+	core := find(handle)
+	if core == nil {
+		return 0
+	}
+	return C.int(core.VM.Display.Height)
+}
+
+// ch8_get_framebuffer copies the display of a virtual machine into
+// buf, one byte per pixel row by row, where 1 is on and 0 is off. It
+// returns the number of pixels copied, which is at most size.
+//
+//export ch8_get_framebuffer
+func ch8_get_framebuffer(handle C.int, buf *C.uint8_t, size C.size_t) C.size_t {
+	core := find(handle)
+	if core == nil || buf == nil {
+		return 0
+	}
+
+	pixels := core.VM.Display.Pixels
+	if len(pixels) > int(size) {
+		pixels = pixels[:size]
+	}
+
+	out := (*[1 << 30]C.uint8_t)(unsafe.Pointer(buf))[:len(pixels):len(pixels)]
+	for i, on := range pixels {
+		out[i] = 0
+		if on {
+			out[i] = 1
+		}
+	}
+	return C.size_t(len(pixels))
+}
+
+// ch8_set_key holds (down is nonzero) or releases a keypad key (0x0 to
+// 0xF) of a virtual machine.
+//
+//export ch8_set_key
+func ch8_set_key(handle C.int, key C.int, down C.int) {
+	core := find(handle)
+	if core == nil || key < 0 || key >= chip8.NumberOfKeys {
+		return
+	}
+	core.VM.Keys[key] = down != 0
+}
+
+// ch8_get_register returns the value of a register of a virtual
+// machine: V0 to VF (0x0 to 0xF), I (0x10), PC (0x11), DT (0x12), or ST
+// (0x13).
+//
+//export ch8_get_register
+func ch8_get_register(handle C.int, index C.int) C.int {
+	core := find(handle)
+	if core == nil {
+		return -1
+	}
+
+	vm := core.VM
+	switch {
+	case index >= 0 && index < chip8.NumberOfRegisters:
+		return C.int(vm.V[index])
+	case index == chip8.NumberOfRegisters:
+		return C.int(vm.I)
+	case index == chip8.NumberOfRegisters+1:
+		return C.int(vm.PC)
+	case index == chip8.NumberOfRegisters+2:
+		return C.int(vm.DT)
+	case index == chip8.NumberOfRegisters+3:
+		return C.int(vm.ST)
+	}
+	return -1
+}
+
+// ch8_read_memory copies the memory of a virtual machine starting at
+// addr into buf, and returns the number of bytes copied, which is at
+// most size.
+//
+//export ch8_read_memory
+func ch8_read_memory(handle C.int, addr C.int, buf *C.uint8_t, size C.size_t) C.size_t {
+	core := find(handle)
+	if core == nil || buf == nil || addr < 0 || addr >= chip8.MemorySize {
+		return 0
+	}
+
+	memory := core.VM.Memory[addr:]
+	if len(memory) > int(size) {
+		memory = memory[:size]
+	}
+
+	out := (*[1 << 30]C.uint8_t)(unsafe.Pointer(buf))[:len(memory):len(memory)]
+	for i, b := range memory {
+		out[i] = C.uint8_t(b)
+	}
+	return C.size_t(len(memory))
+}
+
+// ch8_beeping returns 1 while the beeper of a virtual machine is
+// sounding, and 0 otherwise.
+//
+//export ch8_beeping
+func ch8_beeping(handle C.int) C.int {
+	if core := find(handle); core != nil && core.Beeping() {
+		return 1
+	}
+	return 0
+}
@@ -0,0 +1,477 @@
+// Command ch8-libretro is a libretro core of the CHIP-8 emulator, so
+// ROMs can be run in RetroArch and other libretro frontends.
+//
+// The core is built as a shared library:
+//
+//	go build -buildmode=c-shared -o ch8_libretro.so ./cmd/ch8-libretro
+//
+// The palette, speed, and keymap are read from the configuration file
+// of the emulator, including the settings of the ROM.
+package main
+
+/*
+#include "libretro.h"
+*/
+import "C"
+
+import (
+	"bytes"
+	"encoding/gob"
+	"errors"
+	"image/color"
+	"log"
+	"path/filepath"
+	"strings"
+	"unsafe"
+
+	"github.com/kevhlee/chip8/chip8"
+)
+
+var (
+	libraryName     = C.CString("CHIP-8")
+	libraryVersion  = C.CString("1.0")
+	validExtensions = C.CString("ch8")
+)
+
+// joypadKeys maps the buttons of the RetroPad to keypad keys, with the
+// D-pad on the keys most CHIP-8 games use as arrows.
+var joypadKeys = map[C.uint]uint{
+	C.RETRO_DEVICE_ID_JOYPAD_UP:    0x2,
+	C.RETRO_DEVICE_ID_JOYPAD_LEFT:  0x4,
+	C.RETRO_DEVICE_ID_JOYPAD_RIGHT: 0x6,
+	C.RETRO_DEVICE_ID_JOYPAD_DOWN:  0x8,
+	C.RETRO_DEVICE_ID_JOYPAD_A:     0x5,
+	C.RETRO_DEVICE_ID_JOYPAD_B:     0x0,
+}
+
+var (
+	environment  C.retro_environment_t
+	videoRefresh C.retro_video_refresh_t
+	audioBatch   C.retro_audio_sample_batch_t
+	inputPoll    C.retro_input_poll_t
+	inputState   C.retro_input_state_t
+)
+
+var (
+	cfg          = defaultConfig()
+	core         *chip8.Core
+	palette      chip8.Palette
+	keyboardKeys map[C.uint]uint
+	frame        []uint32
+)
+
+func main() {}
+
+//export retro_set_environment
+func retro_set_environment(cb C.retro_environment_t) {
+	environment = cb
+}
+
+//export retro_set_video_refresh
+func retro_set_video_refresh(cb C.retro_video_refresh_t) {
+	videoRefresh = cb
+}
+
+//export retro_set_audio_sample
+func retro_set_audio_sample(cb C.retro_audio_sample_t) {
+	// The audio of a frame is sent in a single batch
+}
+
+//export retro_set_audio_sample_batch
+func retro_set_audio_sample_batch(cb C.retro_audio_sample_batch_t) {
+	audioBatch = cb
+}
+
+//export retro_set_input_poll
+func retro_set_input_poll(cb C.retro_input_poll_t) {
+	inputPoll = cb
+}
+
+//export retro_set_input_state
+func retro_set_input_state(cb C.retro_input_state_t) {
+	inputState = cb
+}
+
+//export retro_init
+func retro_init() {}
+
+//export retro_deinit
+func retro_deinit() {
+	core = nil
+}
+
+//export retro_api_version
+func retro_api_version() C.uint {
+	return C.RETRO_API_VERSION
+}
+
+//export retro_get_system_info
+func retro_get_system_info(info *C.struct_retro_system_info) {
+	info.library_name = libraryName
+	info.library_version = libraryVersion
+	info.valid_extensions = validExtensions
+	info.need_fullpath = false
+	info.block_extract = false
+}
+
+//export retro_get_system_av_info
+func retro_get_system_av_info(info *C.struct_retro_system_av_info) {
+	info.geometry.base_width = chip8.DisplayWidth
+	info.geometry.base_height = chip8.DisplayHeight
+	info.geometry.max_width = chip8.HiresWidth
+	info.geometry.max_height = chip8.HiresHeight
+	info.geometry.aspect_ratio = 2.0
+
+	info.timing.fps = chip8.DefaultTPS
+	info.timing.sample_rate = C.double(cfg.SampleRate)
+}
+
+//export retro_set_controller_port_device
+func retro_set_controller_port_device(port, device C.uint) {}
+
+//export retro_reset
+func retro_reset() {
+	if core != nil {
+		core.VM.Reset()
+	}
+}
+
+//export retro_run
+func retro_run() {
+	if core == nil {
+		return
+	}
+
+	C.call_input_poll(inputPoll)
+	pressKeys()
+
+	// Errors are logged and the program keeps running, as in the
+	// emulator
+	if err := core.RunFrame(); err != nil {
+		log.Println(err)
+	}
+
+	drawFrame()
+
+	samples := core.Audio()
+	if len(samples) > 0 {
+		C.call_audio_sample_batch(
+			audioBatch,
+			(*C.int16_t)(unsafe.Pointer(&samples[0])),
+			C.size_t(len(samples)/2),
+		)
+	}
+}
+
+// pressKeys reads the keys held on the RetroPad and keyboard of the
+// first player.
+func pressKeys() {
+	var keys [chip8.NumberOfKeys]bool
+
+	for button, key := range joypadKeys {
+		if C.call_input_state(inputState, 0, C.RETRO_DEVICE_JOYPAD, 0, button) != 0 {
+			keys[key] = true
+		}
+	}
+	for code, key := range keyboardKeys {
+		if C.call_input_state(inputState, 0, C.RETRO_DEVICE_KEYBOARD, 0, code) != 0 {
+			keys[key] = true
+		}
+	}
+
+	core.VM.Keys = keys
+}
+
+// drawFrame sends the display to the frontend in the XRGB8888 format.
+func drawFrame() {
+	display := &core.VM.Display
+	if len(frame) != display.Width*display.Height {
+		frame = make([]uint32, display.Width*display.Height)
+	}
+
+	fg, bg := xrgb(palette.Foreground()), xrgb(palette.Background())
+	for y := 0; y < display.Height; y++ {
+		for x := 0; x < display.Width; x++ {
+			if display.At(x, y) {
+				frame[y*display.Width+x] = fg
+			} else {
+				frame[y*display.Width+x] = bg
+			}
+		}
+	}
+
+	C.call_video_refresh(
+		videoRefresh,
+		unsafe.Pointer(&frame[0]),
+		C.uint(display.Width),
+		C.uint(display.Height),
+		C.size_t(4*display.Width),
+	)
+}
+
+func xrgb(c color.RGBA) uint32 {
+	return uint32(c.R)<<16 | uint32(c.G)<<8 | uint32(c.B)
+}
+
+//export retro_serialize_size
+func retro_serialize_size() C.size_t {
+	return C.size_t(maxStateSize())
+}
+
+//export retro_serialize
+func retro_serialize(data unsafe.Pointer, size C.size_t) C.bool {
+	if core == nil {
+		return false
+	}
+
+	// Save states are left uncompressed, so they all have the same size
+	var buf bytes.Buffer
+	if err := gob.NewEncoder(&buf).Encode(core.VM.Snapshot()); err != nil {
+		log.Println(err)
+		return false
+	}
+	if buf.Len() > int(size) {
+		return false
+	}
+
+	copy((*[1 << 30]byte)(data)[:size:size], buf.Bytes())
+	return true
+}
+
+//export retro_unserialize
+func retro_unserialize(data unsafe.Pointer, size C.size_t) C.bool {
+	if core == nil {
+		return false
+	}
+
+	s, err := chip8.ReadSnapshot(bytes.NewReader(C.GoBytes(data, C.int(size))))
+	if err != nil {
+		log.Println(err)
+		return false
+	}
+
+	if err := core.VM.Restore(s); err != nil {
+		log.Println(err)
+		return false
+	}
+	return true
+}
+
+// maxStateSize returns the size of the largest encoded snapshot, since
+// frontends expect every save state of a game to have the same size.
+func maxStateSize() int {
+	vm := chip8.NewVirtualMachine()
+	vm.Display.Resize(chip8.HiresWidth, chip8.HiresHeight)
+
+	s := vm.Snapshot()
+	s.I, s.SP, s.PC, s.DT, s.ST = 0xffff, 0xffff, 0xffff, 0xff, 0xff
+	for i := range s.V {
+		s.V[i] = 0xff
+	}
+	for i := range s.Flags {
+		s.Flags[i] = 0xff
+	}
+	for i := range s.Stack {
+		s.Stack[i] = 0xffff
+	}
+	for i := range s.Memory {
+		s.Memory[i] = 0xff
+	}
+	for i := range s.Display.Pixels {
+		s.Display.Pixels[i] = true
+	}
+	s.Cycles = ^uint64(0)
+
+	var buf bytes.Buffer
+	gob.NewEncoder(&buf).Encode(s)
+	return buf.Len()
+}
+
+//export retro_cheat_reset
+func retro_cheat_reset() {}
+
+//export retro_cheat_set
+func retro_cheat_set(index C.uint, enabled C.bool, code *C.char) {}
+
+//export retro_load_game
+func retro_load_game(game *C.struct_retro_game_info) C.bool {
+	if game == nil || game.data == nil {
+		return false
+	}
+
+	format := C.enum_retro_pixel_format(C.RETRO_PIXEL_FORMAT_XRGB8888)
+	if !C.call_environment(environment, C.RETRO_ENVIRONMENT_SET_PIXEL_FORMAT, unsafe.Pointer(&format)) {
+		log.Println("the frontend does not support the XRGB8888 format")
+		return false
+	}
+
+	loadConfig(game)
+
+	core = chip8.NewCore(cfg.MachineSpec(), cfg.SampleRate)
+	core.Speed = cfg.Speed
+	core.VM.Quirks = cfg.MachineQuirks()
+	core.TimerRate = cfg.TimerRate
+	if err := core.VM.LoadBytes(C.GoBytes(game.data, C.int(game.size))); err != nil {
+		log.Println(err)
+		core = nil
+		return false
+	}
+
+	return true
+}
+
+// config is the part of the configuration file of the emulator which
+// the core uses. The rest of the settings belong to the window.
+type config struct {
+	chip8.Config
+
+	Palette    string               `json:"palette"`
+	Colors     []string             `json:"colors,omitempty"`
+	Keymap     string               `json:"keymap"`
+	Keys       map[string]string    `json:"keys,omitempty"`
+	SampleRate int                  `json:"sampleRate"`
+	LogLevel   string               `json:"logLevel"`
+	ROMs       map[string]romConfig `json:"roms,omitempty"`
+}
+
+// romConfig is the part of the settings of an individual ROM which the
+// core uses.
+type romConfig struct {
+	Quirks  string            `json:"quirks,omitempty"`
+	Palette string            `json:"palette,omitempty"`
+	Colors  []string          `json:"colors,omitempty"`
+	Keymap  string            `json:"keymap,omitempty"`
+	Keys    map[string]string `json:"keys,omitempty"`
+}
+
+func defaultConfig() config {
+	return config{
+		Config:     chip8.DefaultConfig(),
+		Palette:    chip8.DefaultPalette,
+		Keymap:     chip8.DefaultKeymap,
+		SampleRate: chip8.DefaultSampleRate,
+		LogLevel:   chip8.DefaultLogLevel,
+	}
+}
+
+// useROM applies the settings of a ROM from the configuration file.
+func (cfg *config) useROM(name string) {
+	rom, ok := cfg.ROMs[name]
+	if !ok {
+		return
+	}
+
+	if rom.Quirks != "" {
+		cfg.Quirks = rom.Quirks
+	}
+	if rom.Palette != "" {
+		cfg.Palette = rom.Palette
+	}
+	if len(rom.Colors) > 0 {
+		cfg.Colors = rom.Colors
+	}
+	if rom.Keymap != "" {
+		cfg.Keymap = rom.Keymap
+	}
+	if len(rom.Keys) > 0 {
+		cfg.Keys = rom.Keys
+	}
+}
+
+// validate checks that the settings the core uses are usable.
+func (cfg *config) validate() error {
+	if err := cfg.Config.Validate(); err != nil {
+		return err
+	}
+	if cfg.SampleRate < chip8.DefaultFrequency*2 {
+		return errors.New("sample rate is too low for the beeper")
+	}
+	return chip8.CheckLogLevel(cfg.LogLevel)
+}
+
+// loadConfig reads the configuration file and the settings of the ROM,
+// falling back to the defaults if the file is invalid.
+func loadConfig(game *C.struct_retro_game_info) {
+	cfg = defaultConfig()
+	if err := chip8.ReadConfig(chip8.DefaultConfigPath(), &cfg); err != nil {
+		log.Println(err)
+		cfg = defaultConfig()
+	}
+
+	if game.path != nil {
+		cfg.useROM(filepath.Base(C.GoString(game.path)))
+	}
+	err := chip8.ReadEnv(map[string]interface{}{
+		"PALETTE":   &cfg.Palette,
+		"KEYMAP":    &cfg.Keymap,
+		"LOG_LEVEL": &cfg.LogLevel,
+	})
+	if err == nil {
+		err = cfg.UseEnv()
+	}
+	if err != nil {
+		log.Println(err)
+	}
+	cfg.Resolve()
+	if err := cfg.validate(); err != nil {
+		log.Println(err)
+		cfg = defaultConfig()
+		cfg.Resolve()
+	}
+	chip8.SetLogLevel(cfg.LogLevel)
+
+	palettes, err := chip8.AvailablePalettes(cfg.Colors)
+	if err != nil {
+		palettes = chip8.Palettes
+	}
+	i := chip8.FindPalette(palettes, cfg.Palette)
+	if i < 0 {
+		i = 0
+	}
+	palette = palettes[i]
+
+	keymaps, err := chip8.AvailableKeymaps(cfg.Keys)
+	if err != nil {
+		keymaps = chip8.Keymaps
+	}
+	i = chip8.FindKeymap(keymaps, cfg.Keymap)
+	if i < 0 {
+		i = 0
+	}
+
+	// The keys of letters and digits are their lowercase characters
+	// in libretro, as in the terminal frontend
+	keyboardKeys = map[C.uint]uint{}
+	for name, hex := range keymaps[i].Keys {
+		if len(name) == 1 {
+			keyboardKeys[C.uint(strings.ToLower(name)[0])] = hex
+		}
+	}
+}
+
+//export retro_load_game_special
+func retro_load_game_special(kind C.uint, info *C.struct_retro_game_info, num C.size_t) C.bool {
+	return false
+}
+
+//export retro_unload_game
+func retro_unload_game() {
+	core = nil
+}
+
+//export retro_get_region
+func retro_get_region() C.uint {
+	return C.RETRO_REGION_NTSC
+}
+
+//export retro_get_memory_data
+func retro_get_memory_data(id C.uint) unsafe.Pointer {
+	// The memory of the virtual machine isn't stored as bytes, so it
+	// can't be shared with the frontend
+	return nil
+}
+
+//export retro_get_memory_size
+func retro_get_memory_size(id C.uint) C.size_t {
+	return 0
+}
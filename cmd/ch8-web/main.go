@@ -0,0 +1,54 @@
+// +build js,wasm
+
+// Command ch8-web runs the CHIP-8 emulator in a web browser.
+//
+// The page hosting the emulator passes the ROM to run through the
+// global variables ch8ROM (a Uint8Array of the ROM) and ch8ROMName (the
+// file name of the ROM), which must be set before the program starts.
+// A built-in ROM is run by setting ch8ROMName alone to its path, such
+// as "builtin:maze".
+package main
+
+import (
+	"log"
+	"syscall/js"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+)
+
+func main() {
+	name := js.Global().Get("ch8ROMName")
+
+	var data []byte
+	if rom := js.Global().Get("ch8ROM"); !rom.IsUndefined() && !rom.IsNull() {
+		data = make([]byte, rom.Get("length").Int())
+		js.CopyBytesToGo(data, rom)
+	} else if name.Type() == js.TypeString && chip8.IsBuiltinROM(name.String()) {
+		builtin, err := chip8.ReadROM(name.String())
+		if err != nil {
+			log.Fatal(err)
+		}
+		data = builtin
+	} else {
+		log.Fatal("no ROM was given to the emulator")
+	}
+
+	// Settings can't be saved in the browser, so the configuration is
+	// never read from or written to a file
+	cfg := ch8.DefaultConfig()
+	cfg.Path = ""
+
+	if name.Type() == js.TypeString {
+		cfg.UseROM(chip8.ROMName(name.String()))
+	}
+
+	emu := ch8.NewEmulator(cfg)
+	if err := emu.LoadBytes(data); err != nil {
+		log.Fatal(err)
+	}
+
+	if err := emu.Start(); err != nil {
+		log.Fatal(err)
+	}
+}
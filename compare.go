@@ -0,0 +1,142 @@
+package main
+
+import (
+	"fmt"
+	"image"
+	_ "image/png"
+	"os"
+	"strings"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newCompareCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "compare",
+		Short:   "Compare the screen of a CHIP-8 ROM after running it with a golden image",
+		Example: "$ ch8 compare --frames 600 --golden shot.png roms/games/Pong.ch8",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			frames, _ := flags.GetInt("frames")
+			golden, _ := flags.GetString("golden")
+			diff, _ := flags.GetString("diff")
+			machine, _ := flags.GetString("machine")
+			name, _ := flags.GetString("palette")
+			scale, _ := flags.GetInt("scale")
+			update, _ := flags.GetBool("update")
+
+			if golden == "" {
+				return fmt.Errorf("--golden is required")
+			}
+			if frames < 1 {
+				return fmt.Errorf("--frames must be positive")
+			}
+			spec := chip8.FindMachine(machine)
+			if spec < 0 {
+				return fmt.Errorf("unknown machine: %s", machine)
+			}
+			i := chip8.FindPalette(chip8.Palettes, name)
+			if i < 0 {
+				return fmt.Errorf("unknown palette: %s", name)
+			}
+			palette := chip8.Palettes[i]
+
+			core := chip8.NewCore(chip8.MachineSpecs[spec], chip8.DefaultSampleRate)
+			if err := core.VM.LoadROM(args[0]); err != nil {
+				return err
+			}
+
+			// The program may exit before the frames are up
+			it := core.Frames(frames)
+			for it.Next() {
+			}
+			if err := it.Err(); err != nil {
+				return err
+			}
+
+			if update {
+				return ch8.SavePNG(golden, ch8.RenderImage(core.VM, palette, scale))
+			}
+
+			file, err := os.Open(golden)
+			if err != nil {
+				return err
+			}
+			img, _, err := image.Decode(file)
+			file.Close()
+			if err != nil {
+				return err
+			}
+
+			mismatches, diffImg, err := ch8.CompareImage(&core.VM.Display, img, palette)
+			if err != nil {
+				return err
+			}
+			if mismatches == 0 {
+				fmt.Println("The screen matches the golden image.")
+				return nil
+			}
+
+			if diff == "" {
+				diff = strings.TrimSuffix(golden, ".png") + ".diff.png"
+			}
+			if err := ch8.SavePNG(diff, diffImg); err != nil {
+				return err
+			}
+			return fmt.Errorf("%d pixels differ from the golden image, see %s", mismatches, diff)
+		},
+	}
+
+	cmd.Flags().String(
+		"diff",
+		"",
+		"set the path of the image of the differences (default: the golden image with .diff.png)",
+	)
+
+	cmd.Flags().Int(
+		"frames",
+		600,
+		"set the number of frames the ROM runs before its screen is compared",
+	)
+
+	cmd.Flags().String(
+		"golden",
+		"",
+		"set the PNG image the screen should match, such as a screenshot",
+	)
+
+	cmd.Flags().String(
+		"machine",
+		chip8.DefaultMachine,
+		fmt.Sprintf(
+			"set the variant of CHIP-8 which the ROM runs on (%s)",
+			strings.Join(chip8.MachineNames(), ", "),
+		),
+	)
+
+	cmd.Flags().String(
+		"palette",
+		chip8.DefaultPalette,
+		fmt.Sprintf(
+			"set the colors the golden image is drawn with (%s)",
+			strings.Join(chip8.PaletteNames(), ", "),
+		),
+	)
+
+	cmd.Flags().Int(
+		"scale",
+		4,
+		"set the scale factor of the golden image written by --update",
+	)
+
+	cmd.Flags().Bool(
+		"update",
+		false,
+		"write the screen to the golden image instead of comparing it",
+	)
+
+	return cmd
+}
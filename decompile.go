@@ -0,0 +1,26 @@
+package main
+
+import (
+	"os"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newDecompileCommand() *cobra.Command {
+	return &cobra.Command{
+		Use:     "decompile",
+		Short:   "Decompile a CHIP-8 ROM into Octo source",
+		Example: "$ ch8 decompile roms/games/Pong.ch8 > Pong.8o",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			data, err := chip8.ReadROM(args[0])
+			if err != nil {
+				return err
+			}
+
+			return ch8.Decompile(os.Stdout, data)
+		},
+	}
+}
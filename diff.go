@@ -0,0 +1,42 @@
+package main
+
+import (
+	"fmt"
+	"os"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newDiffCommand() *cobra.Command {
+	return &cobra.Command{
+		Use:     "diff",
+		Short:   "Compare two versions of a CHIP-8 ROM",
+		Example: "$ ch8 diff Pong.ch8 Pong-patched.ch8",
+		Args:    cobra.ExactArgs(2),
+		RunE: func(cmd *cobra.Command, args []string) error {
+			a, err := chip8.ReadROM(args[0])
+			if err != nil {
+				return err
+			}
+			b, err := chip8.ReadROM(args[1])
+			if err != nil {
+				return err
+			}
+
+			fmt.Printf("--- %s (%d bytes)\n+++ %s (%d bytes)\n", args[0], len(a), args[1], len(b))
+			diffs, err := ch8.DiffROMs(os.Stdout, a, b)
+			if err != nil {
+				return err
+			}
+
+			size := len(a)
+			if len(b) > size {
+				size = len(b)
+			}
+			fmt.Printf("%d of %d bytes differ\n", diffs, size)
+			return nil
+		},
+	}
+}
@@ -0,0 +1,60 @@
+package main
+
+import (
+	"os"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newDisasmCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "disasm",
+		Short:   "Disassemble a CHIP-8 ROM",
+		Example: "$ ch8 disasm --pseudocode roms/Logo.ch8",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			data, err := chip8.ReadROM(args[0])
+			if err != nil {
+				return err
+			}
+
+			pseudocode, _ := cmd.Flags().GetBool("pseudocode")
+			xrefs, _ := cmd.Flags().GetBool("xrefs")
+
+			var cov *chip8.Coverage
+			if path, _ := cmd.Flags().GetString("coverage"); path != "" {
+				if cov, err = chip8.LoadCoverage(path); err != nil {
+					return err
+				}
+			}
+
+			return ch8.Disassemble(os.Stdout, data, ch8.DisasmOptions{
+				Pseudocode: pseudocode,
+				XRefs:      xrefs,
+				Coverage:   cov,
+			})
+		},
+	}
+
+	cmd.Flags().String(
+		"coverage",
+		"",
+		"list the bytes never executed in a session recorded with --coverage as data",
+	)
+
+	cmd.Flags().Bool(
+		"pseudocode",
+		false,
+		"append a pseudocode comment to each instruction (e.g. ; V1 |= V2)",
+	)
+
+	cmd.Flags().Bool(
+		"xrefs",
+		false,
+		"list the instructions which jump to, call, or point I at each address",
+	)
+
+	return cmd
+}
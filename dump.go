@@ -0,0 +1,108 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"os"
+
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newDumpCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "dump",
+		Short:   "Dump the memory of a CHIP-8 ROM after running it",
+		Example: "$ ch8 dump --frames 120 --addr 0x300 --size 64 roms/Logo.ch8",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			frames, _ := flags.GetInt("frames")
+			addr, _ := flags.GetInt("addr")
+			size, _ := flags.GetInt("size")
+			format, _ := flags.GetString("format")
+			output, _ := flags.GetString("output")
+
+			cfg := chip8.DefaultConfig()
+			cfg.Quirks, _ = flags.GetString("quirks")
+			if chip8.FindQuirks(cfg.Quirks) < 0 {
+				return fmt.Errorf("unknown quirk profile: %s", cfg.Quirks)
+			}
+			cfg.Resolve()
+
+			core := chip8.NewCore(cfg.MachineSpec(), chip8.DefaultSampleRate)
+			core.Speed = cfg.Speed
+			core.VM.Quirks = cfg.MachineQuirks()
+			core.TimerRate = cfg.TimerRate
+			if err := core.VM.LoadROM(args[0]); err != nil {
+				return err
+			}
+
+			// The program may exit before the frames are up
+			for frame := 0; frame < frames; frame++ {
+				err := core.RunFrame()
+				if _, ok := err.(*chip8.ExitError); ok {
+					break
+				} else if err != nil {
+					return err
+				}
+			}
+
+			data, err := core.VM.MemoryRange(addr, size)
+			if err != nil {
+				return err
+			}
+
+			var w io.Writer = os.Stdout
+			if output != "" {
+				file, err := os.Create(output)
+				if err != nil {
+					return err
+				}
+				defer file.Close()
+				w = file
+			}
+
+			return chip8.DumpMemory(w, data, addr, format)
+		},
+	}
+
+	cmd.Flags().Int(
+		"frames",
+		60,
+		"set the number of frames the ROM runs before its memory is dumped",
+	)
+
+	cmd.Flags().Int(
+		"addr",
+		0,
+		"set the address where the dump starts (e.g. 0x200)",
+	)
+
+	cmd.Flags().Int(
+		"size",
+		chip8.MemorySize,
+		"set the number of bytes dumped",
+	)
+
+	cmd.Flags().String(
+		"format",
+		chip8.DumpHex,
+		"set the format of the dump (bin, hex)",
+	)
+
+	cmd.Flags().StringP(
+		"output",
+		"o",
+		"",
+		"write the dump to a file rather than stdout",
+	)
+
+	cmd.Flags().String(
+		"quirks",
+		chip8.DefaultQuirks,
+		"set the quirk profile of the CHIP-8 virtual machine (modern, cosmac, schip)",
+	)
+
+	return cmd
+}
@@ -0,0 +1,94 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"strings"
+	"time"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newFuzzCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "fuzz",
+		Short:   "Compare the virtual machine with a reference interpreter on random programs",
+		Example: "$ ch8 fuzz --runs 100000 --quirks cosmac",
+		Args:    cobra.NoArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			runs, _ := flags.GetInt("runs")
+			size, _ := flags.GetInt("size")
+			cycles, _ := flags.GetInt("cycles")
+			seed, _ := flags.GetInt64("seed")
+			name, _ := flags.GetString("quirks")
+
+			profile := chip8.FindQuirks(name)
+			if profile < 0 {
+				return fmt.Errorf("unknown quirks: %s", name)
+			}
+			if runs < 1 || size < 1 || cycles < 1 {
+				return fmt.Errorf("--runs, --size, and --cycles must be positive")
+			}
+			if !flags.Changed("seed") {
+				seed = time.Now().UnixNano()
+			}
+
+			fmt.Printf("Fuzzing %d programs with seed %d\n", runs, seed)
+			m := ch8.Fuzz(ch8.FuzzOptions{
+				Seed:   seed,
+				Runs:   runs,
+				Size:   size,
+				Cycles: cycles,
+				Quirks: chip8.QuirkProfiles[profile].Quirks,
+			})
+			if m == nil {
+				fmt.Println("The virtual machine ran every program like the reference.")
+				return nil
+			}
+
+			fmt.Printf("\nMismatch after %s\n\n", m.Error())
+			if err := ch8.Disassemble(os.Stdout, m.Program, ch8.DisasmOptions{}); err != nil {
+				return err
+			}
+			return fmt.Errorf("the virtual machine differs from the reference")
+		},
+	}
+
+	cmd.Flags().Int(
+		"cycles",
+		1000,
+		"set the number of cycles each random program runs",
+	)
+
+	cmd.Flags().String(
+		"quirks",
+		chip8.DefaultQuirks,
+		fmt.Sprintf(
+			"set the quirk profile the programs run with (%s)",
+			strings.Join(chip8.QuirkNames(), ", "),
+		),
+	)
+
+	cmd.Flags().Int(
+		"runs",
+		10000,
+		"set the number of random programs run",
+	)
+
+	cmd.Flags().Int64(
+		"seed",
+		0,
+		"set the seed of the random programs, to repeat a session (default: the current time)",
+	)
+
+	cmd.Flags().Int(
+		"size",
+		64,
+		"set the largest number of instructions of a random program",
+	)
+
+	return cmd
+}
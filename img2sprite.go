@@ -0,0 +1,86 @@
+package main
+
+import (
+	"fmt"
+	"image"
+	_ "image/png"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/spf13/cobra"
+)
+
+func newImg2SpriteCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "img2sprite",
+		Short:   "Convert a PNG image into CHIP-8 sprites",
+		Example: "$ ch8 img2sprite --height 4 ship.png",
+		Args:    cobra.ExactArgs(1),
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			height, _ := flags.GetInt("height")
+			wide, _ := flags.GetBool("wide")
+			invert, _ := flags.GetBool("invert")
+			format, _ := flags.GetString("format")
+			name, _ := flags.GetString("name")
+
+			if !wide && (height < 1 || height > ch8.MaxSpriteHeight) {
+				return fmt.Errorf("height must be within [1, %d]", ch8.MaxSpriteHeight)
+			}
+
+			file, err := os.Open(args[0])
+			if err != nil {
+				return err
+			}
+			defer file.Close()
+
+			img, _, err := image.Decode(file)
+			if err != nil {
+				return err
+			}
+
+			// Sprites are named after the image by default
+			if name == "" {
+				base := filepath.Base(args[0])
+				name = strings.TrimSuffix(base, filepath.Ext(base))
+			}
+
+			sprites := ch8.ImageSprites(img, height, wide, invert)
+			return ch8.WriteSprites(os.Stdout, sprites, name, format)
+		},
+	}
+
+	cmd.Flags().Int(
+		"height",
+		8,
+		"set the height (in pixels) of each sprite",
+	)
+
+	cmd.Flags().Bool(
+		"wide",
+		false,
+		"cut the image into 16x16 SUPER-CHIP sprites",
+	)
+
+	cmd.Flags().Bool(
+		"invert",
+		false,
+		"turn on dark pixels rather than light ones",
+	)
+
+	cmd.Flags().String(
+		"format",
+		ch8.SpriteOcto,
+		"set the format of the source code (octo, asm)",
+	)
+
+	cmd.Flags().String(
+		"name",
+		"",
+		"set the label of the sprites (default the name of the image)",
+	)
+
+	return cmd
+}
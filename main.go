@@ -1,11 +1,12 @@
 package main
 
 import (
-	"errors"
 	"fmt"
 	"os"
+	"strings"
 
 	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
 	"github.com/spf13/cobra"
 )
 
@@ -16,27 +17,79 @@ func main() {
 		Long:    "A CHIP-8 emulator written in Go.",
 		Args:    checkArgs,
 		RunE: func(cmd *cobra.Command, args []string) error {
-			scale, _ := cmd.Flags().GetInt("scale")
-			volume, _ := cmd.Flags().GetFloat64("volume")
+			cfg, err := loadConfig(cmd, args[0])
+			if err != nil {
+				return err
+			}
+			chip8.SetLogLevel(cfg.LogLevel)
+
+			if headless, _ := cmd.Flags().GetBool("headless"); headless {
+				return runHeadless(cmd, cfg, args[0])
+			}
 
-			emu := ch8.NewEmulator(scale, volume)
+			if terminal, _ := cmd.Flags().GetBool("terminal"); terminal {
+				if args[0] == chip8.StdinROM {
+					return fmt.Errorf("the terminal can't read the ROM from stdin")
+				}
 
-			if err := emu.LoadROM(args[0]); err != nil {
+				term := ch8.NewTerminal(cfg)
+
+				if err := term.LoadROM(args[0]); err != nil {
+					return err
+				}
+
+				err := term.Run()
+				printMetrics(cmd, term.Metrics())
 				return err
 			}
 
-			return emu.Start()
+			emu := ch8.NewEmulator(cfg)
+
+			// In attract mode the path is a directory of ROMs
+			if cfg.Attract > 0 {
+				if err := emu.Attract(args[0]); err != nil {
+					return err
+				}
+			} else if err := emu.LoadROM(args[0]); err != nil {
+				return err
+			}
+
+			err = emu.Start()
+			printMetrics(cmd, emu.Metrics())
+			return err
 		},
 	}
 
 	addFlags(cli)
+	cli.AddCommand(newDiffCommand())
+	cli.AddCommand(newAsmCommand())
+	cli.AddCommand(newCallGraphCommand())
+	cli.AddCommand(newCompareCommand())
+	cli.AddCommand(newDecompileCommand())
+	cli.AddCommand(newDisasmCommand())
+	cli.AddCommand(newDumpCommand())
+	cli.AddCommand(newFuzzCommand())
+	cli.AddCommand(newImg2SpriteCommand())
+	cli.AddCommand(newQuirksCommand())
+	cli.AddCommand(newRegressCommand())
+	cli.AddCommand(newSmokeCommand())
+	cli.AddCommand(newSpritesCommand())
+	cli.AddCommand(newUnreachableCommand())
 
 	if err := cli.Execute(); err != nil {
 		fmt.Println(err)
+		os.Exit(1)
 	}
 }
 
 func addFlags(cli *cobra.Command) {
+	cli.Flags().StringP(
+		"config",
+		"c",
+		chip8.DefaultConfigPath(),
+		"set the path of the configuration file",
+	)
+
 	cli.Flags().IntP(
 		"scale",
 		"s",
@@ -44,36 +97,590 @@ func addFlags(cli *cobra.Command) {
 		"set the scale factor of the CHIP-8 screen",
 	)
 
+	cli.Flags().Float64(
+		"speed",
+		0.0,
+		"set the speed multiplier of the CHIP-8 virtual machine, which defaults to that of the machine",
+	)
+
+	cli.Flags().Float64(
+		"timer-rate",
+		chip8.DefaultTimerRate,
+		"set the rate (in hertz) at which the delay and sound timers count down",
+	)
+
+	cli.Flags().String(
+		"machine",
+		chip8.DefaultMachine,
+		fmt.Sprintf(
+			"set the variant of CHIP-8 which the virtual machine emulates, along with its quirks and speed (%s)",
+			strings.Join(chip8.MachineNames(), ", "),
+		),
+	)
+
+	cli.Flags().String(
+		"quirks",
+		"",
+		fmt.Sprintf(
+			"set the quirk profile of the CHIP-8 virtual machine, which defaults to that of the machine (%s)",
+			strings.Join(chip8.QuirkNames(), ", "),
+		),
+	)
+
+	cli.Flags().String(
+		"coverage",
+		"",
+		"write the addresses of the instructions executed during the session to a file on exit",
+	)
+
+	cli.Flags().String(
+		"trace",
+		"",
+		"write a timeline of the session to a file in Chrome trace format",
+	)
+
+	cli.Flags().String(
+		"tas",
+		"",
+		"edit the input of a movie file frame by frame, re-recording from any frame",
+	)
+
+	cli.Flags().Bool(
+		"pause-at-start",
+		false,
+		"pause the CHIP-8 emulator before the first instruction of the ROM runs",
+	)
+
+	cli.Flags().Bool(
+		"debug",
+		false,
+		"restore the breakpoints, watchpoints, and watches of the ROM's last debugging session",
+	)
+
+	cli.Flags().StringArray(
+		"break",
+		nil,
+		"pause before the instruction at an address runs (e.g. 0x2A4)",
+	)
+
+	cli.Flags().StringArray(
+		"watchpoint",
+		nil,
+		"pause once an instruction changes the byte of memory at an address",
+	)
+
+	cli.Flags().StringArray(
+		"watch",
+		nil,
+		"log the value of an expression (e.g. V3, I, or [0x300]) at breakpoints and watchpoints",
+	)
+
+	cli.Flags().String(
+		"compare-quirks",
+		"",
+		"run the ROM side by side with another quirk profile to compare them",
+	)
+
+	cli.Flags().String(
+		"palette",
+		chip8.DefaultPalette,
+		fmt.Sprintf(
+			"set the colors of the CHIP-8 screen (%s)",
+			strings.Join(chip8.PaletteNames(), ", "),
+		),
+	)
+
+	cli.Flags().String(
+		"keymap",
+		chip8.DefaultKeymap,
+		fmt.Sprintf(
+			"set the keys bound to the CHIP-8 keypad (%s)",
+			strings.Join(chip8.KeymapNames(), ", "),
+		),
+	)
+
+	cli.Flags().Bool(
+		"key-labels",
+		false,
+		"match keys by their label on the keyboard layout rather than their position",
+	)
+
+	cli.Flags().Bool(
+		"touch-keypad",
+		false,
+		"show a keypad beside the CHIP-8 screen for touchscreens and mice",
+	)
+
+	cli.Flags().Bool(
+		"menu-bar",
+		false,
+		"show a menu bar of the actions of the CHIP-8 emulator above the screen",
+	)
+
+	cli.Flags().Bool(
+		"registers",
+		false,
+		"show the registers and stack of the CHIP-8 virtual machine beside the screen",
+	)
+
+	cli.Flags().String(
+		"input-display",
+		"",
+		"show the held CHIP-8 keys in a corner of the screen for viewers of streams (top-left, top-right, bottom-left, bottom-right)",
+	)
+
+	cli.Flags().Int(
+		"input-display-size",
+		ch8.DefaultKeypadCellSize,
+		"set the size (in pixels) of a key of the input display",
+	)
+
+	cli.Flags().Bool(
+		"pause-on-focus-loss",
+		false,
+		"pause and mute the CHIP-8 emulator while its window isn't focused",
+	)
+
+	cli.Flags().Bool(
+		"education",
+		false,
+		"explain each instruction the CHIP-8 virtual machine executes, slowed down to follow along",
+	)
+
+	cli.Flags().Float64(
+		"education-rate",
+		ch8.DefaultEducationRate,
+		"set the number of instructions per second run in education mode",
+	)
+
+	cli.Flags().Int(
+		"decay",
+		0,
+		"set the number of frames a CHIP-8 pixel takes to fade out",
+	)
+
+	cli.Flags().Int(
+		"rotation",
+		0,
+		"set the rotation (in degrees clockwise) of the CHIP-8 screen (0, 90, 180, 270)",
+	)
+
+	cli.Flags().Int(
+		"border",
+		0,
+		"set the thickness (in pixels) of the margin around the CHIP-8 screen",
+	)
+
+	cli.Flags().String(
+		"border-color",
+		"",
+		"set the color of the margin around the CHIP-8 screen (e.g. #202020)",
+	)
+
+	cli.Flags().String(
+		"filter",
+		ch8.FilterNone,
+		"set the retro effect of the CHIP-8 screen (none, scanlines, crt)",
+	)
+
+	cli.Flags().String(
+		"renderer",
+		ch8.RendererAccelerated,
+		"set how the CHIP-8 screen is sent to the GPU, as a whole texture or one pixel at a time (accelerated, software)",
+	)
+
 	cli.Flags().Float64P(
 		"volume",
 		"v",
 		ch8.DefaultVolume,
 		"set the volume of the CHIP-8 emulator",
 	)
+
+	cli.Flags().Int(
+		"sample-rate",
+		chip8.DefaultSampleRate,
+		"set the sample rate (in hertz) of the CHIP-8 beeper",
+	)
+
+	cli.Flags().Duration(
+		"audio-buffer",
+		ch8.DefaultAudioBuffer,
+		"set how much audio the CHIP-8 beeper buffers ahead of playback",
+	)
+
+	cli.Flags().Bool(
+		"no-audio",
+		false,
+		"disable audio output of the CHIP-8 emulator",
+	)
+
+	cli.Flags().Bool(
+		"visual-bell",
+		false,
+		"flash the CHIP-8 screen while the beeper is sounding",
+	)
+
+	cli.Flags().Bool(
+		"safe-mode",
+		false,
+		"reduce flicker, contrast, and flashes of the CHIP-8 screen for photosensitive players",
+	)
+
+	cli.Flags().String(
+		"crash-dir",
+		ch8.DefaultCrashDir,
+		"set the directory where reports are saved when the ROM crashes (empty to save none)",
+	)
+
+	cli.Flags().String(
+		"screenshot-dir",
+		ch8.DefaultScreenshotDir,
+		"set the directory where screenshots are saved",
+	)
+
+	cli.Flags().Int(
+		"screenshot-scale",
+		1,
+		"set the scale factor of screenshots",
+	)
+
+	cli.Flags().Int(
+		"gif-scale",
+		2,
+		"set the scale factor of GIF recordings",
+	)
+
+	cli.Flags().Int(
+		"gif-decimation",
+		ch8.DefaultGIFDecimation,
+		"set the number of frames per captured frame of GIF recordings",
+	)
+
+	cli.Flags().String(
+		"state-dir",
+		chip8.DefaultStateDir,
+		"set the directory where save states are saved",
+	)
+
+	cli.Flags().String(
+		"save-ram",
+		"",
+		"keep a range of memory (e.g. 0xF00-0xFFF) in a file for each ROM between runs",
+	)
+
+	cli.Flags().String(
+		"ffmpeg",
+		"ffmpeg",
+		"set the ffmpeg executable used to record videos",
+	)
+
+	cli.Flags().String(
+		"video-format",
+		"mp4",
+		"set the file format of video recordings (mp4, webm, ...)",
+	)
+
+	cli.Flags().Int(
+		"video-scale",
+		ch8.DefaultScale,
+		"set the scale factor of video recordings",
+	)
+
+	cli.Flags().String(
+		"control",
+		"",
+		"serve an HTTP API to control the CHIP-8 emulator at an address (e.g. localhost:8081)",
+	)
+
+	cli.Flags().String(
+		"stream",
+		"",
+		"stream the CHIP-8 screen to web browsers at an address (e.g. :8080)",
+	)
+
+	cli.Flags().String(
+		"input-socket",
+		"",
+		"take keypad events from a socket at an address (e.g. localhost:9000 or unix:/tmp/ch8.sock)",
+	)
+
+	cli.Flags().String(
+		"netplay-host",
+		"",
+		"host a game of netplay at an address (e.g. :7000)",
+	)
+
+	cli.Flags().String(
+		"netplay-join",
+		"",
+		"join a game of netplay hosted at an address (e.g. example.com:7000)",
+	)
+
+	cli.Flags().Duration(
+		"attract",
+		0,
+		"run each ROM of a directory for a while, cycling forever (e.g. 30s)",
+	)
+
+	cli.Flags().String(
+		"attract-input",
+		ch8.AttractInputRandom,
+		"set the input of attract mode (random, none)",
+	)
+
+	cli.Flags().Bool(
+		"headless",
+		false,
+		"run the CHIP-8 emulator without a window until the ROM exits (00FD), exiting with V0",
+	)
+
+	cli.Flags().Int(
+		"headless-frames",
+		3600,
+		"set the number of frames a headless run waits for the ROM to exit (0 for no limit)",
+	)
+
+	cli.Flags().String(
+		"log-level",
+		chip8.DefaultLogLevel,
+		"set the level of logs, optionally per module (e.g. info,netplay=debug)",
+	)
+
+	cli.Flags().Bool(
+		"metrics",
+		false,
+		"print the counters of the CHIP-8 virtual machine on exit",
+	)
+
+	cli.Flags().Bool(
+		"terminal",
+		false,
+		"run the CHIP-8 emulator in the terminal instead of a window",
+	)
+
+	cli.Flags().String(
+		"wav",
+		"",
+		"record the audio of the CHIP-8 beeper to a WAV file",
+	)
+}
+
+// printMetrics prints the counters of the virtual machine to stderr if
+// they were asked for.
+func printMetrics(cli *cobra.Command, m chip8.Metrics) {
+	if metrics, _ := cli.Flags().GetBool("metrics"); metrics {
+		fmt.Fprintln(os.Stderr, m)
+	}
+}
+
+// runHeadless runs the ROM without a window as fast as possible, and
+// exits with the exit code of the ROM, or 1 if the ROM crashed or
+// didn't exit in time.
+func runHeadless(cli *cobra.Command, cfg ch8.Config, rom string) error {
+	frames, _ := cli.Flags().GetInt("headless-frames")
+
+	core := chip8.NewCore(cfg.MachineSpec(), cfg.SampleRate)
+	core.Speed = cfg.Speed
+	core.VM.Quirks = cfg.MachineQuirks()
+	core.TimerRate = cfg.TimerRate
+	if cfg.CoveragePath != "" {
+		core.VM.Coverage = &chip8.Coverage{}
+	}
+	if err := core.VM.LoadROM(rom); err != nil {
+		return err
+	}
+
+	code, err := core.RunUntilExit(frames)
+	printMetrics(cli, core.Metrics())
+	if cfg.CoveragePath != "" {
+		if err := chip8.SaveCoverage(cfg.CoveragePath, core.VM.Coverage); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+		}
+	}
+	if err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+
+	os.Exit(code)
+	return nil
 }
 
 func checkArgs(cli *cobra.Command, args []string) error {
 	if len(args) < 1 {
-		return fmt.Errorf("input a path to a CHIP-8 ROM file")
+		return fmt.Errorf(
+			"input a path to a CHIP-8 ROM file, or a built-in ROM (%s)",
+			strings.Join(chip8.BuiltinROMs(), ", "),
+		)
+	}
+
+	// The standard input, URLs, and built-in ROMs are read once the
+	// emulator starts
+	if !chip8.LocalROM(args[0]) {
+		return nil
 	}
 
 	if _, err := os.Stat(args[0]); os.IsNotExist(err) {
 		return err
 	}
 
-	scale, err := cli.Flags().GetInt("scale")
+	return nil
+}
+
+// loadConfig reads the configuration file and the settings of the ROM,
+// and then applies the environment variables and the flags which were
+// set on the command line.
+func loadConfig(cli *cobra.Command, rom string) (ch8.Config, error) {
+	flags := cli.Flags()
+
+	path, _ := flags.GetString("config")
+	cfg, err := ch8.LoadConfig(path)
 	if err != nil {
-		return err
-	} else if scale < 1 {
-		return errors.New("scale factor must be positive")
+		return cfg, err
 	}
 
-	volume, err := cli.Flags().GetFloat64("volume")
-	if err != nil {
-		return err
-	} else if volume < 0.0 || volume > 1.0 {
-		return errors.New("volume must be between [0, 1]")
+	cfg.UseROM(chip8.ROMName(rom))
+	if err := cfg.UseEnv(); err != nil {
+		return cfg, err
 	}
 
-	return nil
+	if flags.Changed("scale") {
+		cfg.Scale, _ = flags.GetInt("scale")
+	}
+	if flags.Changed("speed") {
+		cfg.Speed, _ = flags.GetFloat64("speed")
+	}
+	if flags.Changed("timer-rate") {
+		cfg.TimerRate, _ = flags.GetFloat64("timer-rate")
+	}
+	if flags.Changed("machine") {
+		cfg.Machine, _ = flags.GetString("machine")
+	}
+	if flags.Changed("quirks") {
+		cfg.Quirks, _ = flags.GetString("quirks")
+	}
+	if flags.Changed("palette") {
+		cfg.Palette, _ = flags.GetString("palette")
+	}
+	if flags.Changed("keymap") {
+		cfg.Keymap, _ = flags.GetString("keymap")
+	}
+	if flags.Changed("key-labels") {
+		cfg.KeyLabels, _ = flags.GetBool("key-labels")
+	}
+	if flags.Changed("touch-keypad") {
+		cfg.TouchKeypad, _ = flags.GetBool("touch-keypad")
+	}
+	if flags.Changed("menu-bar") {
+		cfg.MenuBar, _ = flags.GetBool("menu-bar")
+	}
+	if flags.Changed("registers") {
+		cfg.Registers, _ = flags.GetBool("registers")
+	}
+	if flags.Changed("input-display") {
+		cfg.InputDisplay, _ = flags.GetString("input-display")
+	}
+	if flags.Changed("input-display-size") {
+		cfg.InputDisplaySize, _ = flags.GetInt("input-display-size")
+	}
+	if flags.Changed("pause-on-focus-loss") {
+		cfg.PauseOnFocusLoss, _ = flags.GetBool("pause-on-focus-loss")
+	}
+	if flags.Changed("education") {
+		cfg.Education, _ = flags.GetBool("education")
+	}
+	if flags.Changed("education-rate") {
+		cfg.EducationRate, _ = flags.GetFloat64("education-rate")
+	}
+	if flags.Changed("log-level") {
+		cfg.LogLevel, _ = flags.GetString("log-level")
+	}
+	if flags.Changed("decay") {
+		cfg.Decay, _ = flags.GetInt("decay")
+	}
+	if flags.Changed("rotation") {
+		cfg.Rotation, _ = flags.GetInt("rotation")
+	}
+	if flags.Changed("border") {
+		cfg.Border, _ = flags.GetInt("border")
+	}
+	if flags.Changed("border-color") {
+		cfg.BorderColor, _ = flags.GetString("border-color")
+	}
+	if flags.Changed("filter") {
+		cfg.Filter, _ = flags.GetString("filter")
+	}
+	if flags.Changed("renderer") {
+		cfg.Renderer, _ = flags.GetString("renderer")
+	}
+	if flags.Changed("volume") {
+		cfg.Volume, _ = flags.GetFloat64("volume")
+	}
+	if flags.Changed("sample-rate") {
+		cfg.SampleRate, _ = flags.GetInt("sample-rate")
+	}
+	if flags.Changed("audio-buffer") {
+		cfg.AudioBuffer, _ = flags.GetDuration("audio-buffer")
+	}
+	if flags.Changed("no-audio") {
+		cfg.NoAudio, _ = flags.GetBool("no-audio")
+	}
+	if flags.Changed("visual-bell") {
+		cfg.VisualBell, _ = flags.GetBool("visual-bell")
+	}
+	if flags.Changed("safe-mode") {
+		cfg.SafeMode, _ = flags.GetBool("safe-mode")
+	}
+	if flags.Changed("crash-dir") {
+		cfg.CrashDir, _ = flags.GetString("crash-dir")
+	}
+	if flags.Changed("screenshot-dir") {
+		cfg.ScreenshotDir, _ = flags.GetString("screenshot-dir")
+	}
+	if flags.Changed("screenshot-scale") {
+		cfg.ScreenshotScale, _ = flags.GetInt("screenshot-scale")
+	}
+	if flags.Changed("gif-scale") {
+		cfg.GIFScale, _ = flags.GetInt("gif-scale")
+	}
+	if flags.Changed("gif-decimation") {
+		cfg.GIFDecimation, _ = flags.GetInt("gif-decimation")
+	}
+	if flags.Changed("state-dir") {
+		cfg.StateDir, _ = flags.GetString("state-dir")
+	}
+	if flags.Changed("save-ram") {
+		cfg.SaveRAM, _ = flags.GetString("save-ram")
+	}
+	if flags.Changed("ffmpeg") {
+		cfg.FFmpeg, _ = flags.GetString("ffmpeg")
+	}
+	if flags.Changed("video-format") {
+		cfg.VideoFormat, _ = flags.GetString("video-format")
+	}
+	if flags.Changed("video-scale") {
+		cfg.VideoScale, _ = flags.GetInt("video-scale")
+	}
+	cfg.WAVPath, _ = flags.GetString("wav")
+	cfg.Stream, _ = flags.GetString("stream")
+	cfg.Control, _ = flags.GetString("control")
+	cfg.InputSocket, _ = flags.GetString("input-socket")
+	cfg.CompareQuirks, _ = flags.GetString("compare-quirks")
+	cfg.CoveragePath, _ = flags.GetString("coverage")
+	cfg.TracePath, _ = flags.GetString("trace")
+	cfg.TASPath, _ = flags.GetString("tas")
+	cfg.PauseAtStart, _ = flags.GetBool("pause-at-start")
+	cfg.Debug, _ = flags.GetBool("debug")
+	cfg.Breakpoints, _ = flags.GetStringArray("break")
+	cfg.Watchpoints, _ = flags.GetStringArray("watchpoint")
+	cfg.Watches, _ = flags.GetStringArray("watch")
+	cfg.Attract, _ = flags.GetDuration("attract")
+	cfg.AttractInput, _ = flags.GetString("attract-input")
+	cfg.NetplayHost, _ = flags.GetString("netplay-host")
+	cfg.NetplayJoin, _ = flags.GetString("netplay-join")
+
+	// The machine may have come from any of the sources above
+	cfg.Resolve()
+
+	return cfg, cfg.Validate()
 }
@@ -0,0 +1,140 @@
+"""Python bindings of the CHIP-8 virtual machine.
+
+The bindings wrap the C API of the emulator (see `make ffi`) with
+ctypes, so the emulator can be scripted from Python and notebooks:
+
+    from ch8 import Chip8
+
+    vm = Chip8()
+    vm.load_rom("roms/games/Pong.ch8")
+    vm.press(0x1)
+    vm.step(60)
+    print(vm.registers["PC"], vm.screen_text())
+
+The library is looked up in the `bin` directory of the repository, or
+at the path in the CH8_LIBRARY environment variable.
+"""
+
+import ctypes
+import os
+
+NUMBER_OF_KEYS = 0x10
+NUMBER_OF_REGISTERS = 0x10
+MEMORY_SIZE = 0x1000
+
+_REGISTERS = ["V%X" % i for i in range(NUMBER_OF_REGISTERS)] + [
+    "I",
+    "PC",
+    "DT",
+    "ST",
+]
+
+
+def _load_library():
+    path = os.environ.get("CH8_LIBRARY") or os.path.join(
+        os.path.dirname(os.path.abspath(__file__)), "..", "bin", "libch8.so"
+    )
+    lib = ctypes.CDLL(path)
+
+    lib.ch8_create.argtypes = [ctypes.c_double]
+    lib.ch8_create.restype = ctypes.c_int
+    lib.ch8_destroy.argtypes = [ctypes.c_int]
+    lib.ch8_load_rom.argtypes = [ctypes.c_int, ctypes.c_char_p, ctypes.c_size_t]
+    lib.ch8_load_rom.restype = ctypes.c_int
+    lib.ch8_reset.argtypes = [ctypes.c_int]
+    lib.ch8_step_frame.argtypes = [ctypes.c_int]
+    lib.ch8_step_frame.restype = ctypes.c_int
+    lib.ch8_display_width.argtypes = [ctypes.c_int]
+    lib.ch8_display_width.restype = ctypes.c_int
+    lib.ch8_display_height.argtypes = [ctypes.c_int]
+    lib.ch8_display_height.restype = ctypes.c_int
+    lib.ch8_get_framebuffer.argtypes = [ctypes.c_int, ctypes.c_char_p, ctypes.c_size_t]
+    lib.ch8_get_framebuffer.restype = ctypes.c_size_t
+    lib.ch8_set_key.argtypes = [ctypes.c_int, ctypes.c_int, ctypes.c_int]
+    lib.ch8_get_register.argtypes = [ctypes.c_int, ctypes.c_int]
+    lib.ch8_get_register.restype = ctypes.c_int
+    lib.ch8_read_memory.argtypes = [ctypes.c_int, ctypes.c_int, ctypes.c_char_p, ctypes.c_size_t]
+    lib.ch8_read_memory.restype = ctypes.c_size_t
+    lib.ch8_beeping.argtypes = [ctypes.c_int]
+    lib.ch8_beeping.restype = ctypes.c_int
+
+    return lib
+
+
+_lib = _load_library()
+
+
+class Chip8:
+    """A CHIP-8 virtual machine running at the given speed multiplier."""
+
+    def __init__(self, speed=1.0):
+        self._handle = _lib.ch8_create(speed)
+
+    def __del__(self):
+        if _lib is not None:
+            _lib.ch8_destroy(self._handle)
+
+    def load_rom(self, rom):
+        """Loads a ROM from a path or from bytes."""
+        if isinstance(rom, (str, os.PathLike)):
+            with open(rom, "rb") as f:
+                rom = f.read()
+        if _lib.ch8_load_rom(self._handle, rom, len(rom)) != 0:
+            raise ValueError("invalid ROM")
+
+    def reset(self):
+        """Resets the virtual machine, keeping the loaded ROM."""
+        _lib.ch8_reset(self._handle)
+
+    def step(self, frames=1):
+        """Runs a number of frames (1/60 of a second each)."""
+        for _ in range(frames):
+            if _lib.ch8_step_frame(self._handle) != 0:
+                raise RuntimeError("the virtual machine stopped with an error")
+
+    def press(self, key):
+        """Holds a keypad key (0x0 to 0xF)."""
+        _lib.ch8_set_key(self._handle, key, 1)
+
+    def release(self, key):
+        """Releases a keypad key (0x0 to 0xF)."""
+        _lib.ch8_set_key(self._handle, key, 0)
+
+    @property
+    def registers(self):
+        """The registers of the virtual machine, by name."""
+        return {
+            name: _lib.ch8_get_register(self._handle, i)
+            for i, name in enumerate(_REGISTERS)
+        }
+
+    def memory(self, addr=0, size=MEMORY_SIZE):
+        """Returns the bytes of memory starting at an address."""
+        buf = ctypes.create_string_buffer(size)
+        n = _lib.ch8_read_memory(self._handle, addr, buf, size)
+        return buf.raw[:n]
+
+    @property
+    def beeping(self):
+        """Whether the beeper is sounding."""
+        return _lib.ch8_beeping(self._handle) == 1
+
+    @property
+    def screen(self):
+        """The pixels of the display as rows of booleans."""
+        width = _lib.ch8_display_width(self._handle)
+        height = _lib.ch8_display_height(self._handle)
+
+        buf = ctypes.create_string_buffer(width * height)
+        _lib.ch8_get_framebuffer(self._handle, buf, width * height)
+
+        return [
+            [buf.raw[y * width + x] == 1 for x in range(width)]
+            for y in range(height)
+        ]
+
+    def screen_text(self, on="#", off="."):
+        """Returns the display as text, one line per row of pixels."""
+        return "\n".join(
+            "".join(on if pixel else off for pixel in row) for row in self.screen
+        )
@@ -0,0 +1,73 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newQuirksCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "quirks",
+		Short:   "Report which quirks a CHIP-8 ROM depends on",
+		Example: "$ ch8 quirks --frames 3600 roms/games/Tetris.ch8",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			frames, _ := flags.GetInt("frames")
+			name, _ := flags.GetString("quirks")
+
+			profile := chip8.FindQuirks(name)
+			if profile < 0 {
+				return fmt.Errorf("unknown quirks: %s", name)
+			}
+
+			data, err := chip8.ReadROM(args[0])
+			if err != nil {
+				return err
+			}
+
+			deps, err := ch8.AnalyzeQuirks(data, chip8.QuirkProfiles[profile].Quirks, frames)
+			if err != nil {
+				return err
+			}
+
+			var used []string
+			for _, d := range deps {
+				if d.Count == 0 {
+					fmt.Printf("%-12s %6s  %-5s  %s\n", d.Quirk, "-", "", d.Behavior)
+					continue
+				}
+				fmt.Printf("%-12s %6d  0x%.3X  %s\n", d.Quirk, d.Count, d.First, d.Behavior)
+				used = append(used, d.Quirk)
+			}
+
+			if len(used) == 0 {
+				fmt.Println("\nThe ROM didn't depend on any quirk while it ran.")
+			} else {
+				fmt.Printf("\nThe ROM depends on: %s\n", strings.Join(used, ", "))
+			}
+			return nil
+		},
+	}
+
+	cmd.Flags().Int(
+		"frames",
+		3600,
+		"set the number of frames the ROM runs while it is analyzed",
+	)
+
+	cmd.Flags().String(
+		"quirks",
+		chip8.DefaultQuirks,
+		fmt.Sprintf(
+			"set the quirk profile the ROM runs with (%s)",
+			strings.Join(chip8.QuirkNames(), ", "),
+		),
+	)
+
+	return cmd
+}
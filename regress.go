@@ -0,0 +1,61 @@
+package main
+
+import (
+	"fmt"
+	"path/filepath"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/spf13/cobra"
+)
+
+func newRegressCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "regress",
+		Short:   "Check the displays of a corpus of CHIP-8 ROMs against their goldens",
+		Example: "$ ch8 regress roms/tests/goldens.txt",
+		Args:    cobra.ExactArgs(1),
+		RunE: func(cmd *cobra.Command, args []string) error {
+			update, _ := cmd.Flags().GetBool("update")
+
+			goldens, err := ch8.LoadGoldens(args[0])
+			if err != nil {
+				return err
+			}
+
+			failed := 0
+			results := ch8.RunGoldens(filepath.Dir(args[0]), goldens)
+			for i, r := range results {
+				switch {
+				case r.Passed():
+					fmt.Printf("ok    %-24s %s\n", r.ROM, r.Machine)
+					continue
+				case r.Err != nil:
+					fmt.Printf("FAIL  %-24s %-8s %v\n", r.ROM, r.Machine, r.Err)
+				default:
+					fmt.Printf("FAIL  %-24s %-8s display %s, expected %s\n", r.ROM, r.Machine, r.Actual, r.Hash)
+				}
+				failed++
+
+				if update && r.Err == nil {
+					goldens[i].Hash = r.Actual
+				}
+			}
+
+			if update {
+				return ch8.SaveGoldens(args[0], goldens)
+			}
+			if failed > 0 {
+				return fmt.Errorf("%d of %d goldens failed", failed, len(results))
+			}
+			return nil
+		},
+	}
+
+	cmd.Flags().Bool(
+		"update",
+		false,
+		"replace the goldens with the displays the ROMs ended with",
+	)
+
+	return cmd
+}
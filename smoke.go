@@ -0,0 +1,83 @@
+package main
+
+import (
+	"fmt"
+	"path/filepath"
+	"strings"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newSmokeCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "smoke",
+		Short:   "Run every CHIP-8 ROM of a directory headless and report the ones which fail",
+		Example: "$ ch8 smoke --frames 1200 --machines modern,schip roms/games",
+		Args:    cobra.ExactArgs(1),
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			frames, _ := flags.GetInt("frames")
+			names, _ := flags.GetStringSlice("machines")
+
+			if frames < 1 {
+				return fmt.Errorf("--frames must be positive")
+			}
+
+			var machines []chip8.MachineSpec
+			for _, name := range names {
+				i := chip8.FindMachine(name)
+				if i < 0 {
+					return fmt.Errorf("unknown machine: %s", name)
+				}
+				machines = append(machines, chip8.MachineSpecs[i])
+			}
+
+			results, err := ch8.SmokeTest(args[0], machines, frames)
+			if err != nil {
+				return err
+			}
+
+			counts := map[string]int{}
+			for _, r := range results {
+				counts[r.Status]++
+				if r.Status == ch8.SmokePassed {
+					continue
+				}
+				fmt.Printf(
+					"%-7s %-24s %-7s frame %-5d %v\n",
+					r.Status, filepath.Base(r.ROM), r.Machine, r.Frames, r.Err,
+				)
+			}
+
+			failed := len(results) - counts[ch8.SmokePassed]
+			fmt.Printf(
+				"\n%d of %d runs failed (%d crashed, %d hung, %d ran invalid opcodes)\n",
+				failed, len(results),
+				counts[ch8.SmokeCrashed], counts[ch8.SmokeHung], counts[ch8.SmokeIllegal],
+			)
+			if failed > 0 {
+				return fmt.Errorf("%d runs failed", failed)
+			}
+			return nil
+		},
+	}
+
+	cmd.Flags().Int(
+		"frames",
+		600,
+		"set the number of frames each ROM runs on each machine",
+	)
+
+	cmd.Flags().StringSlice(
+		"machines",
+		chip8.MachineNames(),
+		fmt.Sprintf(
+			"set the machines each ROM runs on (%s)",
+			strings.Join(chip8.MachineNames(), ", "),
+		),
+	)
+
+	return cmd
+}
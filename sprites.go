@@ -0,0 +1,100 @@
+package main
+
+import (
+	"fmt"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newSpritesCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "sprites",
+		Short:   "Extract the sprites of a CHIP-8 ROM as a PNG image",
+		Example: "$ ch8 sprites --at 0x2A0 --count 12 roms/games/Pong.ch8",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			flags := cmd.Flags()
+			at, _ := flags.GetInt("at")
+			count, _ := flags.GetInt("count")
+			height, _ := flags.GetInt("height")
+			scale, _ := flags.GetInt("scale")
+			output, _ := flags.GetString("output")
+			name, _ := flags.GetString("palette")
+
+			if count < 1 {
+				return fmt.Errorf("count must be positive")
+			}
+			if height < 1 || height > ch8.MaxSpriteHeight {
+				return fmt.Errorf("height must be within [1, %d]", ch8.MaxSpriteHeight)
+			}
+			if scale < 1 {
+				return fmt.Errorf("scale factor must be positive")
+			}
+
+			palette := chip8.FindPalette(chip8.Palettes, name)
+			if palette < 0 {
+				return fmt.Errorf("unknown palette: %s", name)
+			}
+
+			// Addresses are those of memory, where the ROM starts at 0x200
+			vm := chip8.NewVirtualMachine()
+			if err := vm.LoadROM(args[0]); err != nil {
+				return err
+			}
+
+			data, err := vm.MemoryRange(at, count*height)
+			if err != nil {
+				return err
+			}
+
+			img := ch8.RenderSprites(data, height, chip8.Palettes[palette], scale)
+			if err := ch8.SavePNG(output, img); err != nil {
+				return err
+			}
+
+			fmt.Println("saved", (len(data)+height-1)/height, "sprites to", output)
+			return nil
+		},
+	}
+
+	cmd.Flags().Int(
+		"at",
+		chip8.ProgramStartAddress,
+		"set the address of the first sprite (e.g. 0x2A0)",
+	)
+
+	cmd.Flags().Int(
+		"count",
+		1,
+		"set the number of sprites extracted",
+	)
+
+	cmd.Flags().Int(
+		"height",
+		8,
+		"set the height (in pixels) of each sprite",
+	)
+
+	cmd.Flags().Int(
+		"scale",
+		8,
+		"set the scale factor of the sprites",
+	)
+
+	cmd.Flags().StringP(
+		"output",
+		"o",
+		"sprites.png",
+		"set the path of the PNG image of the sprites",
+	)
+
+	cmd.Flags().String(
+		"palette",
+		chip8.DefaultPalette,
+		"set the colors of the sprites",
+	)
+
+	return cmd
+}
@@ -0,0 +1,51 @@
+package main
+
+import (
+	"fmt"
+	"os"
+
+	"github.com/kevhlee/chip8/ch8"
+	"github.com/kevhlee/chip8/chip8"
+	"github.com/spf13/cobra"
+)
+
+func newUnreachableCommand() *cobra.Command {
+	cmd := &cobra.Command{
+		Use:     "unreachable",
+		Short:   "List the parts of a CHIP-8 ROM which are never reached",
+		Example: "$ ch8 unreachable --coverage pong.cov roms/games/Pong.ch8",
+		Args:    checkArgs,
+		RunE: func(cmd *cobra.Command, args []string) error {
+			data, err := chip8.ReadROM(args[0])
+			if err != nil {
+				return err
+			}
+
+			fmt.Println("Unreachable by control flow:")
+			if err := chip8.WriteRanges(os.Stdout, ch8.Unreachable(data)); err != nil {
+				return err
+			}
+
+			path, _ := cmd.Flags().GetString("coverage")
+			if path == "" {
+				return nil
+			}
+
+			cov, err := chip8.LoadCoverage(path)
+			if err != nil {
+				return err
+			}
+
+			fmt.Println("\nNever executed:")
+			return chip8.WriteRanges(os.Stdout, cov.Unexecuted(data))
+		},
+	}
+
+	cmd.Flags().String(
+		"coverage",
+		"",
+		"also list the parts never executed in a session recorded with --coverage",
+	)
+
+	return cmd
+}